@@ -0,0 +1,54 @@
+//! Example: Assembling a Config from Layers
+//!
+//! Run with: cargo run --example builder
+
+use cosy::from_str;
+use cosy::load::ConfigBuilder;
+use serde::Deserialize;
+use std::fs;
+use tempfile::tempdir;
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    server: Server,
+    debug: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let system_path = dir.path().join("system.cosy");
+    let user_path = dir.path().join("user.cosy");
+
+    // The system file moves the server; the user file doesn't exist
+    fs::write(&system_path, r#"{ server: { host: "0.0.0.0" } }"#)?;
+
+    // Environment variables override both files...
+    unsafe {
+        std::env::set_var("BUILDER_DEMO_SERVER_PORT", "9000");
+    }
+    // ...and explicit overrides, such as command-line flags, come last
+    let cli = from_str("{ debug: true }")?;
+
+    let config: Config = ConfigBuilder::new()
+        .add_defaults_str(r#"{ server: { host: "localhost", port: 8080 }, debug: false }"#)
+        .add_file(&system_path)
+        .add_file_optional(&user_path)
+        .add_env_prefix("BUILDER_DEMO_")
+        .add_overrides(cli)
+        .with_schema(from_str(
+            r#"{ server: { host: "string", port: "port" }, debug: "boolean" }"#,
+        )?)
+        .build_into()?;
+
+    println!("--- Layered Configuration ---");
+    println!("Server: {}:{}", config.server.host, config.server.port);
+    println!("Debug Mode: {}", config.debug);
+
+    Ok(())
+}
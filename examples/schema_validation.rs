@@ -28,8 +28,13 @@ fn main() {
 
     println!("\n--- Validating Correct Config ---");
     match schema::validate(&config, &schema) {
-        Ok(_) => println!("✅ Validation passed!"),
-        Err(e) => println!("❌ Validation failed: {}", e),
+        Ok(report) if report.is_valid() => println!("✅ Validation passed!"),
+        Ok(report) => {
+            for item in report.errors() {
+                println!("❌ Validation failed: {}", item);
+            }
+        }
+        Err(e) => println!("❌ Invalid schema: {}", e),
     }
 
     // 3. Invalid Configuration (Type Mismatch)
@@ -45,7 +50,12 @@ fn main() {
 
     println!("\n--- Validating Incorrect Config ---");
     match schema::validate(&bad_config, &schema) {
-        Ok(_) => println!("✅ Validation passed!"),
-        Err(e) => println!("❌ Validation failed: {}", e),
+        Ok(report) if report.is_valid() => println!("✅ Validation passed!"),
+        Ok(report) => {
+            for item in report.errors() {
+                println!("❌ Validation failed: {}", item);
+            }
+        }
+        Err(e) => println!("❌ Invalid schema: {}", e),
     }
 }
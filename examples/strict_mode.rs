@@ -2,7 +2,7 @@
 //!
 //! Run with: cargo run --example strict_mode
 
-use cosy::schema::ValidationLevel;
+use cosy::schema::{ValidationErrorKind, ValidationItem};
 use cosy::{from_str, schema};
 
 fn main() {
@@ -32,24 +32,43 @@ fn main() {
 
     println!("--- Validating Config ---");
 
-    // 3. Validate
+    // 3. Validate, then print errors and warnings separately
     match schema::validate(&config, &schema) {
         Ok(report) => {
             if report.is_empty() {
                 println!("✅ Config is valid!");
-            } else {
-                println!("⚠️  Validation found issues:");
-                for item in report {
-                    match item.level {
-                        ValidationLevel::Error => {
-                            println!("❌ Error: {} (at {})", item.message, item.path)
-                        }
-                        ValidationLevel::Warning => {
-                            println!("🔸 Warning: {} (at {})", item.message, item.path)
-                        }
+                return;
+            }
+            let location = |item: &ValidationItem| match item.position {
+                Some(pos) => format!("config:{}:{}", pos.line, pos.column),
+                None => item.path.clone(),
+            };
+
+            if report.has_errors() {
+                println!("❌ Errors:");
+                for item in report.errors() {
+                    println!("   {}: {} (at {})", location(item), item.message, item.path);
+                    if let ValidationErrorKind::UnknownField {
+                        suggestion: Some(s),
+                        ..
+                    } = &item.kind
+                    {
+                        println!("      hint: rename the field to '{}'", s);
                     }
                 }
             }
+
+            let warnings: Vec<_> = report.warnings().collect();
+            if !warnings.is_empty() {
+                println!("🔸 Warnings:");
+                for item in warnings {
+                    println!("   {}: {} (at {})", location(item), item.message, item.path);
+                }
+            }
+
+            if report.has_errors() {
+                println!("{} error(s) must be fixed", report.errors().count());
+            }
         }
         Err(e) => {
             // Validation itself failed (critical error, e.g. invalid schema format)
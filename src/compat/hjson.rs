@@ -0,0 +1,427 @@
+//! HJSON (Human JSON) input support.
+//!
+//! Supports the subset of HJSON needed to migrate typical config files:
+//! `#`, `//` and `/* */` comments, quoted (`"`/`'`) and quoteless keys,
+//! `'''` multiline strings, quoteless string values, optional commas,
+//! trailing commas, and a root object without braces.
+
+use crate::error::CosynError;
+use crate::syntax::lexer::Position;
+use crate::syntax::parser::ParseError;
+use crate::value::{Comment, Value, ValueKind};
+use indexmap::IndexMap;
+
+/// Parse an HJSON document into a COSY `Value`.
+///
+/// Quoteless values run until the end of the line or a `,` and are
+/// trimmed; if the result is a number, `true`, `false` or `null`, it is
+/// converted to that type, otherwise it is kept as a string.
+pub fn from_hjson_str(input: &str) -> Result<Value, CosynError> {
+    let mut parser = HjsonParser::new(input);
+    Ok(parser.parse_document()?)
+}
+
+struct HjsonParser {
+    input: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+impl HjsonParser {
+    fn new(input: &str) -> Self {
+        HjsonParser {
+            input: input.chars().collect(),
+            position: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Value, ParseError> {
+        let comments = self.skip_whitespace_and_comments()?;
+
+        let value = match self.current_char() {
+            Some('{') | Some('[') => self.parse_value(comments)?,
+            // Root object without braces
+            Some(_) => {
+                let pos = self.pos();
+                let map = self.parse_members(None)?;
+                Value::with_comments(ValueKind::Object(map), comments).at(pos)
+            }
+            None => return Err(self.error("Empty HJSON document".to_string())),
+        };
+
+        self.skip_whitespace_and_comments()?;
+        if let Some(ch) = self.current_char() {
+            return Err(self.error(format!("Unexpected character after value: '{}'", ch)));
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self, comments: Vec<Comment>) -> Result<Value, ParseError> {
+        let pos = self.pos();
+        let kind = match self.current_char() {
+            Some('{') => {
+                self.advance();
+                ValueKind::Object(self.parse_members(Some('}'))?)
+            }
+            Some('[') => {
+                self.advance();
+                ValueKind::Array(self.parse_elements()?)
+            }
+            Some('\'') if self.starts_with("'''") => ValueKind::String(self.parse_multiline()?),
+            Some(q @ ('"' | '\'')) => ValueKind::String(self.parse_quoted(q)?),
+            Some(_) => self.parse_quoteless()?,
+            None => return Err(self.error("Expected value, found EOF".to_string())),
+        };
+        Ok(Value::with_comments(kind, comments).at(pos))
+    }
+
+    /// Parse `key: value` pairs until `close` (or EOF for a braceless root).
+    fn parse_members(
+        &mut self,
+        close: Option<char>,
+    ) -> Result<IndexMap<String, Value>, ParseError> {
+        let mut map = IndexMap::new();
+
+        loop {
+            let comments = self.skip_separators()?;
+            match (self.current_char(), close) {
+                (None, None) => return Ok(map),
+                (None, Some(c)) => {
+                    return Err(self.error(format!("Expected '{}', found EOF", c)));
+                }
+                (Some(ch), Some(c)) if ch == c => {
+                    self.advance();
+                    return Ok(map);
+                }
+                _ => {}
+            }
+
+            let key = self.parse_key()?;
+            self.skip_inline_whitespace();
+            if self.current_char() != Some(':') {
+                return Err(self.error(format!("Expected ':' after key '{}'", key)));
+            }
+            self.advance();
+            let more = self.skip_whitespace_and_comments()?;
+
+            let mut value_comments = comments;
+            value_comments.extend(more);
+            let value = self.parse_value(value_comments)?;
+            map.insert(key, value);
+        }
+    }
+
+    fn parse_elements(&mut self) -> Result<Vec<Value>, ParseError> {
+        let mut arr = Vec::new();
+
+        loop {
+            let comments = self.skip_separators()?;
+            match self.current_char() {
+                None => return Err(self.error("Expected ']', found EOF".to_string())),
+                Some(']') => {
+                    self.advance();
+                    return Ok(arr);
+                }
+                _ => arr.push(self.parse_value(comments)?),
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, ParseError> {
+        match self.current_char() {
+            Some(q @ ('"' | '\'')) => self.parse_quoted(q),
+            _ => {
+                let start = self.position;
+                while let Some(ch) = self.current_char() {
+                    if ch.is_whitespace() || matches!(ch, ':' | ',' | '{' | '}' | '[' | ']') {
+                        break;
+                    }
+                    self.advance();
+                }
+                if start == self.position {
+                    return Err(self.error("Expected object key".to_string()));
+                }
+                Ok(self.input[start..self.position].iter().collect())
+            }
+        }
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String, ParseError> {
+        self.advance(); // opening quote
+        let mut result = String::new();
+
+        loop {
+            match self.current_char() {
+                None => return Err(self.error("Unterminated string".to_string())),
+                Some(ch) if ch == quote => {
+                    self.advance();
+                    return Ok(result);
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match self.current_char() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('u') => {
+                            self.advance();
+                            let hex: String = self.input
+                                [self.position..(self.position + 4).min(self.input.len())]
+                                .iter()
+                                .collect();
+                            let ch = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| {
+                                    self.error(format!("Invalid unicode escape: \\u{}", hex))
+                                })?;
+                            for _ in 0..3 {
+                                self.advance();
+                            }
+                            ch
+                        }
+                        Some(other) => {
+                            return Err(self.error(format!("Invalid escape sequence: \\{}", other)));
+                        }
+                        None => return Err(self.error("Unterminated string".to_string())),
+                    };
+                    result.push(escaped);
+                    self.advance();
+                }
+                Some(ch) => {
+                    result.push(ch);
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Parse a `'''` string, stripping the indentation of the opening quotes.
+    fn parse_multiline(&mut self) -> Result<String, ParseError> {
+        let indent = self.column - 1;
+        for _ in 0..3 {
+            self.advance();
+        }
+
+        // Skip the rest of the opening line if it only holds whitespace
+        self.skip_inline_whitespace();
+        if self.current_char() == Some('\n') {
+            self.advance();
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+        loop {
+            if self.starts_with("'''") {
+                for _ in 0..3 {
+                    self.advance();
+                }
+                break;
+            }
+            match self.current_char() {
+                None => return Err(self.error("Unterminated multiline string".to_string())),
+                Some('\n') => {
+                    lines.push(std::mem::take(&mut current));
+                    self.advance();
+                }
+                Some('\r') => self.advance(),
+                Some(ch) => {
+                    current.push(ch);
+                    self.advance();
+                }
+            }
+        }
+
+        // The closing quotes usually sit on their own indented line
+        if !current.trim().is_empty() {
+            lines.push(current);
+        }
+
+        let stripped: Vec<String> = lines
+            .into_iter()
+            .map(|line| {
+                let skip = line
+                    .chars()
+                    .take(indent)
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .count();
+                line.chars().skip(skip).collect()
+            })
+            .collect();
+        Ok(stripped.join("\n"))
+    }
+
+    fn parse_quoteless(&mut self) -> Result<ValueKind, ParseError> {
+        if let Some(kind) = self.try_parse_literal() {
+            return Ok(kind);
+        }
+
+        let start = self.position;
+        while let Some(ch) = self.current_char() {
+            if ch == '\n' || ch == ',' {
+                break;
+            }
+            self.advance();
+        }
+        let raw: String = self.input[start..self.position].iter().collect();
+        let text = raw.trim();
+
+        if let Some(ch @ ('}' | ']' | ':')) = text.chars().next() {
+            return Err(self.error(format!("Unexpected '{}'", ch)));
+        }
+        Ok(ValueKind::String(text.to_string()))
+    }
+
+    /// Parse a number, `true`, `false` or `null` if the word at the cursor is
+    /// one and is followed by a delimiter. Leaves the cursor untouched otherwise.
+    fn try_parse_literal(&mut self) -> Option<ValueKind> {
+        let end = self.input[self.position..]
+            .iter()
+            .position(|c| c.is_whitespace() || matches!(c, ',' | ']' | '}'))
+            .map_or(self.input.len(), |n| self.position + n);
+        let word: String = self.input[self.position..end].iter().collect();
+
+        let kind = match word.as_str() {
+            "true" => ValueKind::Bool(true),
+            "false" => ValueKind::Bool(false),
+            "null" => ValueKind::Null,
+            _ => {
+                if let Ok(i) = word.parse::<i64>() {
+                    ValueKind::Integer(i)
+                } else if looks_numeric(&word)
+                    && let Ok(f) = word.parse::<f64>()
+                {
+                    ValueKind::Float(f)
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        // Only a literal if nothing but a delimiter or comment follows it
+        let mut after = end;
+        while matches!(self.input.get(after), Some(' ' | '\t' | '\r')) {
+            after += 1;
+        }
+        let delimited = match self.input.get(after) {
+            None | Some('\n' | ',' | ']' | '}' | '#') => true,
+            Some('/') => matches!(self.input.get(after + 1), Some('/' | '*')),
+            _ => false,
+        };
+        if !delimited {
+            return None;
+        }
+
+        while self.position < end {
+            self.advance();
+        }
+        Some(kind)
+    }
+
+    /// Skip whitespace, newlines and comments; returns the line comments seen.
+    fn skip_whitespace_and_comments(&mut self) -> Result<Vec<Comment>, ParseError> {
+        let mut comments = Vec::new();
+        loop {
+            match self.current_char() {
+                Some(ch) if ch.is_whitespace() => self.advance(),
+                Some('#') => comments.push(self.read_line_comment(1)),
+                Some('/') if self.starts_with("//") => comments.push(self.read_line_comment(2)),
+                Some('/') if self.starts_with("/*") => {
+                    let start = (self.line, self.column);
+                    self.advance();
+                    self.advance();
+                    while !self.starts_with("*/") {
+                        if self.current_char().is_none() {
+                            return Err(ParseError {
+                                message: "Unterminated block comment".to_string(),
+                                line: start.0,
+                                column: start.1,
+                            });
+                        }
+                        self.advance();
+                    }
+                    self.advance();
+                    self.advance();
+                }
+                _ => return Ok(comments),
+            }
+        }
+    }
+
+    /// Like `skip_whitespace_and_comments`, but also skips commas.
+    fn skip_separators(&mut self) -> Result<Vec<Comment>, ParseError> {
+        let mut comments = self.skip_whitespace_and_comments()?;
+        while self.current_char() == Some(',') {
+            self.advance();
+            comments.extend(self.skip_whitespace_and_comments()?);
+        }
+        Ok(comments)
+    }
+
+    fn read_line_comment(&mut self, prefix_len: usize) -> Comment {
+        for _ in 0..prefix_len {
+            self.advance();
+        }
+        let start = self.position;
+        while !matches!(self.current_char(), None | Some('\n')) {
+            self.advance();
+        }
+        let comment: String = self.input[start..self.position].iter().collect();
+        Comment::line(comment.trim())
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while matches!(self.current_char(), Some(' ' | '\t' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, ch)| self.input.get(self.position + i) == Some(&ch))
+    }
+
+    fn current_char(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn advance(&mut self) {
+        if let Some(ch) = self.current_char() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn pos(&self) -> Position {
+        Position::new(self.line, self.column)
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            message,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+/// Guard against `f64::from_str` accepting words like `inf` or `NaN`.
+fn looks_numeric(text: &str) -> bool {
+    let body = text.strip_prefix('-').unwrap_or(text);
+    body.starts_with(|c: char| c.is_ascii_digit())
+}
@@ -0,0 +1,318 @@
+//! Plain JSON input support.
+//!
+//! JSON is nearly a subset of COSY, but differs in ways that matter for
+//! config fragments: `\u` escapes, and `${...}` in strings being literal
+//! text rather than an environment variable. This reader follows RFC 8259
+//! and records positions like the COSY parser does.
+
+use crate::error::CosynError;
+use crate::syntax::lexer::Position;
+use crate::syntax::parser::ParseError;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// Parse a JSON document into a COSY `Value`.
+///
+/// Numbers without a fraction or exponent that fit in an `i64` become
+/// integers; other numbers become floats. A repeated key keeps the last value.
+pub fn from_json_str(input: &str) -> Result<Value, CosynError> {
+    let mut parser = JsonParser::new(input);
+    Ok(parser.parse_document()?)
+}
+
+struct JsonParser {
+    input: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+impl JsonParser {
+    fn new(input: &str) -> Self {
+        JsonParser {
+            input: input.chars().collect(),
+            position: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        if self.current_char().is_none() {
+            return Err(self.error("Empty JSON document".to_string()));
+        }
+        let value = self.parse_value()?;
+
+        self.skip_whitespace();
+        if let Some(ch) = self.current_char() {
+            return Err(self.error(format!("Unexpected character after value: '{}'", ch)));
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseError> {
+        let pos = self.pos();
+        let kind = match self.current_char() {
+            Some('{') => self.parse_object()?,
+            Some('[') => self.parse_array()?,
+            Some('"') => ValueKind::String(self.parse_string()?),
+            Some('-' | '0'..='9') => self.parse_number()?,
+            Some(_) if self.eat_word("true") => ValueKind::Bool(true),
+            Some(_) if self.eat_word("false") => ValueKind::Bool(false),
+            Some(_) if self.eat_word("null") => ValueKind::Null,
+            Some(ch) => return Err(self.error(format!("Expected value, found '{}'", ch))),
+            None => return Err(self.error("Expected value, found end of input".to_string())),
+        };
+        Ok(Value::from(kind).at(pos))
+    }
+
+    fn parse_object(&mut self) -> Result<ValueKind, ParseError> {
+        self.advance(); // {
+        let mut map = IndexMap::new();
+
+        self.skip_whitespace();
+        if self.current_char() == Some('}') {
+            self.advance();
+            return Ok(ValueKind::Object(map));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.current_char() != Some('"') {
+                return Err(self.error("Expected string key".to_string()));
+            }
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            let value = self.parse_value()?;
+            map.insert(key, value);
+
+            self.skip_whitespace();
+            match self.current_char() {
+                Some(',') => self.advance(),
+                Some('}') => {
+                    self.advance();
+                    return Ok(ValueKind::Object(map));
+                }
+                _ => return Err(self.error("Expected ',' or '}' in object".to_string())),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<ValueKind, ParseError> {
+        self.advance(); // [
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.current_char() == Some(']') {
+            self.advance();
+            return Ok(ValueKind::Array(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            items.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.current_char() {
+                Some(',') => self.advance(),
+                Some(']') => {
+                    self.advance();
+                    return Ok(ValueKind::Array(items));
+                }
+                _ => return Err(self.error("Expected ',' or ']' in array".to_string())),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.advance(); // opening quote
+        let mut result = String::new();
+
+        loop {
+            match self.current_char() {
+                None | Some('\n') => return Err(self.error("Unterminated string".to_string())),
+                Some('"') => {
+                    self.advance();
+                    return Ok(result);
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match self.current_char() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('u') => self.parse_unicode_escape()?,
+                        Some(other) => {
+                            return Err(self.error(format!("Invalid escape sequence: \\{}", other)));
+                        }
+                        None => return Err(self.error("Unterminated string".to_string())),
+                    };
+                    result.push(escaped);
+                    self.advance();
+                }
+                Some(ch) if ch < ' ' => {
+                    return Err(self.error("Control character in string".to_string()));
+                }
+                Some(ch) => {
+                    result.push(ch);
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Parse the `XXXX` after `\u`, combining a surrogate pair if present.
+    /// Leaves the parser on the last hex digit.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.parse_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high)
+                .ok_or_else(|| self.error(format!("Invalid unicode escape: \\u{:04x}", high)));
+        }
+
+        self.advance();
+        if !(self.eat_char('\\') && self.current_char() == Some('u')) {
+            return Err(self.error("Unpaired surrogate in unicode escape".to_string()));
+        }
+        let low = self.parse_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("Unpaired surrogate in unicode escape".to_string()));
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        char::from_u32(code)
+            .ok_or_else(|| self.error(format!("Invalid unicode escape: \\u{:04x}", code)))
+    }
+
+    /// Read four hex digits following the current `u`.
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            self.advance();
+            let digit = self
+                .current_char()
+                .and_then(|ch| ch.to_digit(16))
+                .ok_or_else(|| self.error("Invalid unicode escape".to_string()))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<ValueKind, ParseError> {
+        let start = self.position;
+        let mut is_float = false;
+
+        self.eat_char('-');
+        match self.current_char() {
+            Some('0') => self.advance(),
+            Some('1'..='9') => self.skip_digits(),
+            _ => return Err(self.error("Expected digit in number".to_string())),
+        }
+        if self.eat_char('.') {
+            is_float = true;
+            self.expect_digits()?;
+        }
+        if matches!(self.current_char(), Some('e' | 'E')) {
+            is_float = true;
+            self.advance();
+            if matches!(self.current_char(), Some('+' | '-')) {
+                self.advance();
+            }
+            self.expect_digits()?;
+        }
+
+        let text: String = self.input[start..self.position].iter().collect();
+        if !is_float && let Ok(i) = text.parse::<i64>() {
+            return Ok(ValueKind::Integer(i));
+        }
+        text.parse::<f64>()
+            .map(ValueKind::Float)
+            .map_err(|_| self.error(format!("Invalid number: {}", text)))
+    }
+
+    fn expect_digits(&mut self) -> Result<(), ParseError> {
+        if !matches!(self.current_char(), Some('0'..='9')) {
+            return Err(self.error("Expected digit in number".to_string()));
+        }
+        self.skip_digits();
+        Ok(())
+    }
+
+    fn skip_digits(&mut self) {
+        while matches!(self.current_char(), Some('0'..='9')) {
+            self.advance();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.current_char(), Some(' ' | '\t' | '\r' | '\n')) {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        if self.eat_char(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn eat_char(&mut self, expected: char) -> bool {
+        if self.current_char() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let matches = word
+            .chars()
+            .enumerate()
+            .all(|(i, ch)| self.input.get(self.position + i) == Some(&ch));
+        if matches {
+            for _ in 0..word.len() {
+                self.advance();
+            }
+        }
+        matches
+    }
+
+    fn current_char(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn advance(&mut self) {
+        if let Some(ch) = self.current_char() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn pos(&self) -> Position {
+        Position::new(self.line, self.column)
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            message,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! Parsers for formats that can be loaded into a COSY `Value`.
+
+#[cfg(feature = "hjson-compat")]
+pub mod hjson;
+pub mod json;
+
+#[cfg(feature = "hjson-compat")]
+pub use hjson::from_hjson_str;
+pub use json::from_json_str;
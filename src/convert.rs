@@ -0,0 +1,553 @@
+//! Conversions between a COSY `Value` and other formats.
+//!
+//! Comments are dropped by every conversion.
+
+pub use env::{EnvBlockOptions, from_env_block, to_env_block};
+pub use properties::{ConvertError, from_properties_str, to_properties_string};
+#[cfg(feature = "yaml-compat")]
+pub use yaml::{YamlError, from_yaml_str, to_yaml_string};
+
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// Insert `value` into nested objects under `root` along `segments`.
+///
+/// `key` names the entry in conflict messages, for when a segment is already
+/// a scalar or the final one is already an object.
+fn insert_path<'a>(
+    root: &mut Value,
+    key: &str,
+    segments: impl Iterator<Item = &'a str>,
+    value: Value,
+) -> Result<(), String> {
+    let mut node = root;
+    let mut segments = segments.peekable();
+    while let Some(segment) = segments.next() {
+        let ValueKind::Object(map) = &mut node.kind else {
+            return Err(format!("Key '{}' conflicts with a shorter key", key));
+        };
+        if segments.peek().is_none() {
+            if matches!(map.get(segment), Some(v) if matches!(v.kind, ValueKind::Object(_))) {
+                return Err(format!("Key '{}' conflicts with a longer key", key));
+            }
+            map.insert(segment.to_string(), value);
+            return Ok(());
+        }
+        node = map
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::object(IndexMap::new()));
+    }
+    Ok(())
+}
+
+/// Turn every object whose keys are exactly `0`, `1`, ... into an array.
+fn collect_arrays(root: &mut Value) {
+    root.apply_transform(&mut |node: &mut Value| {
+        if let ValueKind::Object(map) = &node.kind
+            && !map.is_empty()
+            && map.keys().enumerate().all(|(i, k)| *k == i.to_string())
+        {
+            let ValueKind::Object(map) = std::mem::replace(&mut node.kind, ValueKind::Null) else {
+                unreachable!()
+            };
+            node.kind = ValueKind::Array(map.into_values().collect());
+        }
+    });
+}
+
+#[cfg(feature = "yaml-compat")]
+mod yaml {
+    use crate::CosynError;
+    use crate::syntax::parser::ParseError;
+    use crate::value::{Value, ValueKind};
+    use indexmap::IndexMap;
+    use serde_yaml::{Mapping, Number};
+
+    /// Error produced when writing YAML.
+    pub type YamlError = serde_yaml::Error;
+
+    impl From<&Value> for serde_yaml::Value {
+        fn from(value: &Value) -> Self {
+            match &value.kind {
+                ValueKind::Null => serde_yaml::Value::Null,
+                ValueKind::Bool(b) => serde_yaml::Value::Bool(*b),
+                ValueKind::Integer(i) => serde_yaml::Value::Number(Number::from(*i)),
+                ValueKind::Float(f) => serde_yaml::Value::Number(Number::from(*f)),
+                ValueKind::String(s) => serde_yaml::Value::String(s.clone()),
+                ValueKind::Array(arr) => {
+                    serde_yaml::Value::Sequence(arr.iter().map(Into::into).collect())
+                }
+                ValueKind::Object(obj) => {
+                    let mut mapping = Mapping::new();
+                    for (k, v) in obj {
+                        mapping.insert(serde_yaml::Value::String(k.clone()), v.into());
+                    }
+                    serde_yaml::Value::Mapping(mapping)
+                }
+            }
+        }
+    }
+
+    /// Serialize a value as a YAML document.
+    pub fn to_yaml_string(value: &Value) -> Result<String, YamlError> {
+        serde_yaml::to_string(&serde_yaml::Value::from(value))
+    }
+
+    /// Parse a YAML document into a `Value`.
+    ///
+    /// Tags are ignored (`!custom 5` reads as `5`). Scalar mapping keys are
+    /// converted to strings; sequence or mapping keys are an error.
+    pub fn from_yaml_str(input: &str) -> Result<Value, CosynError> {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(input).map_err(|e| {
+            let (line, column) = e.location().map_or((0, 0), |l| (l.line(), l.column()));
+            CosynError::Parse(ParseError {
+                message: e.to_string(),
+                line,
+                column,
+            })
+        })?;
+        from_yaml_value(yaml)
+    }
+
+    fn from_yaml_value(yaml: serde_yaml::Value) -> Result<Value, CosynError> {
+        Ok(match yaml {
+            serde_yaml::Value::Null => Value::null(),
+            serde_yaml::Value::Bool(b) => Value::boolean(b),
+            serde_yaml::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::integer(i),
+                None => Value::float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_yaml::Value::String(s) => Value::string(s),
+            serde_yaml::Value::Sequence(seq) => Value::array(
+                seq.into_iter()
+                    .map(from_yaml_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            serde_yaml::Value::Mapping(mapping) => {
+                let mut obj = IndexMap::new();
+                for (k, v) in mapping {
+                    obj.insert(yaml_key(k)?, from_yaml_value(v)?);
+                }
+                Value::object(obj)
+            }
+            serde_yaml::Value::Tagged(tagged) => from_yaml_value(tagged.value)?,
+        })
+    }
+
+    fn yaml_key(key: serde_yaml::Value) -> Result<String, CosynError> {
+        match key {
+            serde_yaml::Value::String(s) => Ok(s),
+            serde_yaml::Value::Bool(b) => Ok(b.to_string()),
+            serde_yaml::Value::Number(n) => Ok(n.to_string()),
+            serde_yaml::Value::Null => Ok("null".to_string()),
+            serde_yaml::Value::Tagged(tagged) => yaml_key(tagged.value),
+            other => Err(CosynError::Parse(ParseError {
+                message: format!("Unsupported YAML mapping key: {:?}", other),
+                line: 0,
+                column: 0,
+            })),
+        }
+    }
+}
+
+mod properties {
+    use super::{collect_arrays, insert_path};
+    use crate::CosynError;
+    use crate::syntax::parser::ParseError;
+    use crate::value::{Value, ValueKind};
+    use indexmap::IndexMap;
+    use std::fmt;
+
+    /// Error produced when a value has no representation in the target format.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ConvertError {
+        /// The value at `key` is not a scalar and has no leaves to flatten
+        /// into (an empty array or object, or a scalar root)
+        CannotFlatten { key: String, found: &'static str },
+    }
+
+    impl fmt::Display for ConvertError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ConvertError::CannotFlatten { key, found } if key.is_empty() => {
+                    write!(f, "Cannot flatten a root {} into properties", found)
+                }
+                ConvertError::CannotFlatten { key, found } => {
+                    write!(f, "Cannot flatten {} at '{}' into a property", found, key)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for ConvertError {}
+
+    /// Serialize an object as a Java-style `.properties` document.
+    ///
+    /// Nested keys are joined with `.` and array items use their index
+    /// (`hosts.0=a`). Strings are written unquoted, `null` as an empty value.
+    pub fn to_properties_string(value: &Value) -> Result<String, ConvertError> {
+        if !matches!(value.kind, ValueKind::Object(_)) {
+            return Err(ConvertError::CannotFlatten {
+                key: String::new(),
+                found: value.type_name(),
+            });
+        }
+
+        let mut out = String::new();
+        for (key, leaf) in value.flatten(".") {
+            let text = match &leaf.kind {
+                ValueKind::Null => String::new(),
+                ValueKind::String(s) => escape(s, false),
+                ValueKind::Array(_) | ValueKind::Object(_) => {
+                    return Err(ConvertError::CannotFlatten {
+                        key,
+                        found: leaf.type_name(),
+                    });
+                }
+                scalar => scalar.to_string(),
+            };
+            out.push_str(&escape(&key, true));
+            out.push('=');
+            out.push_str(&text);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Parse a `.properties` document into a nested `Value`.
+    ///
+    /// Keys are split on `.` into nested objects, and an object whose keys
+    /// are exactly `0`, `1`, ... becomes an array. Every value is a string.
+    pub fn from_properties_str(input: &str) -> Result<Value, CosynError> {
+        let mut root = Value::object(IndexMap::new());
+
+        for (line_no, line) in logical_lines(input) {
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                continue;
+            }
+            let (raw_key, raw_value) = split_entry(line);
+            let key = unescape(raw_key);
+            insert_path(
+                &mut root,
+                &key,
+                key.split('.'),
+                Value::string(unescape(raw_value)),
+            )
+            .map_err(|message| {
+                CosynError::Parse(ParseError {
+                    message,
+                    line: line_no,
+                    column: 1,
+                })
+            })?;
+        }
+
+        collect_arrays(&mut root);
+        Ok(root)
+    }
+
+    /// Join continuation lines (ending in an odd number of backslashes),
+    /// yielding each logical line with its starting line number.
+    fn logical_lines(input: &str) -> Vec<(usize, String)> {
+        let mut lines = Vec::new();
+        let mut pending: Option<(usize, String)> = None;
+        for (i, line) in input.lines().enumerate() {
+            let (start, mut text) = match pending.take() {
+                Some((start, mut text)) => {
+                    text.push_str(line.trim_start());
+                    (start, text)
+                }
+                None => (i + 1, line.to_string()),
+            };
+            let trailing = text.chars().rev().take_while(|c| *c == '\\').count();
+            let is_comment = text.trim_start().starts_with(['#', '!']);
+            if trailing % 2 == 1 && !is_comment {
+                text.pop();
+                pending = Some((start, text));
+            } else {
+                lines.push((start, text));
+            }
+        }
+        lines.extend(pending);
+        lines
+    }
+
+    /// Split a line at the first unescaped `=`, `:` or whitespace.
+    fn split_entry(line: &str) -> (&str, &str) {
+        let mut chars = line.char_indices();
+        let mut end = line.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '=' | ':' | ' ' | '\t' | '\x0c' => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let key = &line[..end];
+        let rest = line[end..].trim_start_matches([' ', '\t', '\x0c']);
+        let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest);
+        (key, rest.trim_start_matches([' ', '\t', '\x0c']))
+    }
+
+    fn unescape(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('t') => out.push('\t'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('f') => out.push('\x0c'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        Some(decoded) => out.push(decoded),
+                        None => {
+                            out.push_str("\\u");
+                            out.push_str(&hex);
+                        }
+                    }
+                }
+                Some(other) => out.push(other),
+                None => {}
+            }
+        }
+        out
+    }
+
+    fn escape(s: &str, is_key: bool) -> String {
+        let mut out = String::with_capacity(s.len());
+        for (i, c) in s.chars().enumerate() {
+            match c {
+                '\\' | ':' | '=' => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                '\x0c' => out.push_str("\\f"),
+                // Whitespace ends a key, and is trimmed from the start of a value
+                ' ' if is_key || i == 0 => out.push_str("\\ "),
+                '#' | '!' if is_key && i == 0 => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+mod env {
+    use super::{ConvertError, collect_arrays, insert_path};
+    use crate::CosynError;
+    use crate::syntax::parser::ParseError;
+    use crate::value::{Value, ValueKind};
+    use indexmap::IndexMap;
+
+    /// Options for [`to_env_block`].
+    #[derive(Debug, Clone)]
+    pub struct EnvBlockOptions {
+        /// Wrap values in double quotes; otherwise shell-special characters
+        /// are backslash-escaped (default: true)
+        pub quote: bool,
+        /// Placed between the prefix and each path segment (default: `_`)
+        pub separator: char,
+        /// Start the block with a `#!/bin/sh` line (default: false)
+        pub header: bool,
+    }
+
+    impl Default for EnvBlockOptions {
+        fn default() -> Self {
+            EnvBlockOptions {
+                quote: true,
+                separator: '_',
+                header: false,
+            }
+        }
+    }
+
+    /// Write an object as shell `export` statements.
+    ///
+    /// Each leaf becomes `export PREFIX_PATH_TO_KEY="value"`: the path is
+    /// joined with the separator and upper-cased, and array items use their
+    /// index (`PREFIX_HOSTS_0`). Other characters that can't appear in a
+    /// variable name become `_`. `null` is written as an empty value.
+    pub fn to_env_block(
+        value: &Value,
+        prefix: &str,
+        options: &EnvBlockOptions,
+    ) -> Result<String, ConvertError> {
+        if !matches!(value.kind, ValueKind::Object(_)) {
+            return Err(ConvertError::CannotFlatten {
+                key: String::new(),
+                found: value.type_name(),
+            });
+        }
+
+        let separator = options.separator.to_string();
+        let mut out = String::new();
+        if options.header {
+            out.push_str("#!/bin/sh\n");
+        }
+        for (path, leaf) in value.flatten(&separator) {
+            let text = match &leaf.kind {
+                ValueKind::Null => String::new(),
+                ValueKind::String(s) => s.clone(),
+                ValueKind::Array(_) | ValueKind::Object(_) => {
+                    return Err(ConvertError::CannotFlatten {
+                        key: path,
+                        found: leaf.type_name(),
+                    });
+                }
+                scalar => scalar.to_string(),
+            };
+            let name = if prefix.is_empty() {
+                path
+            } else {
+                format!("{}{}{}", prefix, separator, path)
+            };
+            let text = if options.quote {
+                quote(&text)
+            } else {
+                escape(&text)
+            };
+            out.push_str(&format!(
+                "export {}={}\n",
+                variable_name(&name, options.separator),
+                text
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Parse a block of `export KEY=value` lines into a nested `Value`.
+    ///
+    /// Names are lower-cased and split on `_` into nested objects, and an
+    /// object whose keys are exactly `0`, `1`, ... becomes an array. Values
+    /// may be double-quoted, single-quoted or backslash-escaped; every value
+    /// is a string. Blank lines and `#` comments are skipped, and `export` is
+    /// optional.
+    pub fn from_env_block(input: &str) -> Result<Value, CosynError> {
+        let mut root = Value::object(IndexMap::new());
+
+        for (i, line) in input.lines().enumerate() {
+            let error = |message: String| {
+                CosynError::Parse(ParseError {
+                    message,
+                    line: i + 1,
+                    column: 1,
+                })
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+            let Some((name, raw)) = line.split_once('=') else {
+                return Err(error(format!("Expected NAME=value, found '{}'", line)));
+            };
+            let value = unquote(raw).map_err(error)?;
+            let key = name.to_lowercase();
+            insert_path(&mut root, name, key.split('_'), Value::string(value)).map_err(error)?;
+        }
+
+        collect_arrays(&mut root);
+        Ok(root)
+    }
+
+    fn variable_name(path: &str, separator: char) -> String {
+        path.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == separator {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    fn quote(s: &str) -> String {
+        let mut out = String::with_capacity(s.len() + 2);
+        out.push('"');
+        for c in s.chars() {
+            if matches!(c, '"' | '\\' | '$' | '`') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('"');
+        out
+    }
+
+    fn escape(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            if !(c.is_ascii_alphanumeric() || "_-./:,@%+=".contains(c)) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    fn unquote(raw: &str) -> Result<String, String> {
+        if let Some(inner) = raw.strip_prefix('\'') {
+            return inner
+                .strip_suffix('\'')
+                .map(str::to_string)
+                .ok_or_else(|| "Unterminated single-quoted value".to_string());
+        }
+        let (body, quoted) = match raw.strip_prefix('"') {
+            Some(inner) => (inner, true),
+            None => (raw, false),
+        };
+
+        let mut out = String::with_capacity(body.len());
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    return if chars.as_str().is_empty() {
+                        Ok(out)
+                    } else {
+                        Err(format!(
+                            "Unexpected text after closing quote: '{}'",
+                            chars.as_str()
+                        ))
+                    };
+                }
+                // Inside double quotes only these characters are escapable
+                '\\' => match chars.next() {
+                    Some(next) if !quoted || matches!(next, '"' | '\\' | '$' | '`') => {
+                        out.push(next)
+                    }
+                    Some(next) => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                    None => out.push('\\'),
+                },
+                _ => out.push(c),
+            }
+        }
+        if quoted {
+            Err("Unterminated double-quoted value".to_string())
+        } else {
+            Ok(out)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::path::{self, join_key};
+use crate::value::{Value, ValueKind};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// A single difference between two values, keyed by path.
+///
+/// Paths use dotted notation for object keys and brackets for array
+/// indices (e.g. `server.port`, `tags[2]`). The root is the empty path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Present only in `after`.
+    Added(String, Value),
+    /// Present only in `before`.
+    Removed(String, Value),
+    /// Present in both with different values: `(path, before, after)`.
+    Modified(String, Value, Value),
+    /// Present in both with the same value.
+    Unchanged(String, Value),
+}
+
+impl Change {
+    /// The path this change applies to.
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added(p, _)
+            | Change::Removed(p, _)
+            | Change::Modified(p, _, _)
+            | Change::Unchanged(p, _) => p,
+        }
+    }
+}
+
+/// Compute a flat list of changes between `before` and `after`.
+///
+/// Objects are compared key by key and arrays index by index; anything else
+/// (including a change of type) is compared as a leaf. Comments are ignored.
+pub fn compute_diff(before: &Value, after: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_recursive(before, after, "", &mut changes);
+    changes
+}
+
+fn diff_recursive(before: &Value, after: &Value, path: &str, changes: &mut Vec<Change>) {
+    match (&before.kind, &after.kind) {
+        (ValueKind::Object(b), ValueKind::Object(a)) => {
+            for (key, b_val) in b {
+                let child = join_key(path, key);
+                match a.get(key) {
+                    Some(a_val) => diff_recursive(b_val, a_val, &child, changes),
+                    None => changes.push(Change::Removed(child, b_val.clone())),
+                }
+            }
+            for (key, a_val) in a {
+                if !b.contains_key(key) {
+                    changes.push(Change::Added(join_key(path, key), a_val.clone()));
+                }
+            }
+        }
+        (ValueKind::Array(b), ValueKind::Array(a)) => {
+            for i in 0..b.len().max(a.len()) {
+                let child = path::join_index(path, i);
+                match (b.get(i), a.get(i)) {
+                    (Some(b_val), Some(a_val)) => diff_recursive(b_val, a_val, &child, changes),
+                    (Some(b_val), None) => changes.push(Change::Removed(child, b_val.clone())),
+                    (None, Some(a_val)) => changes.push(Change::Added(child, a_val.clone())),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (b, a) if b == a => changes.push(Change::Unchanged(path.to_string(), after.clone())),
+        _ => changes.push(Change::Modified(
+            path.to_string(),
+            before.clone(),
+            after.clone(),
+        )),
+    }
+}
+
+/// Render changes in a unified-diff-like format.
+///
+/// Added lines are prefixed with `+`, removed with `-`, unchanged with a
+/// space. A modification renders as a `-` line followed by a `+` line.
+pub fn render_diff(changes: &[Change]) -> String {
+    render(changes, false)
+}
+
+/// Like [`render_diff`], but with ANSI colors: green for additions and red
+/// for removals.
+pub fn render_diff_colored(changes: &[Change]) -> String {
+    render(changes, true)
+}
+
+fn render(changes: &[Change], color: bool) -> String {
+    let mut out = String::new();
+    for change in changes {
+        match change {
+            Change::Added(p, v) => push_line(&mut out, '+', p, v, color.then_some(GREEN)),
+            Change::Removed(p, v) => push_line(&mut out, '-', p, v, color.then_some(RED)),
+            Change::Modified(p, old, new) => {
+                push_line(&mut out, '-', p, old, color.then_some(RED));
+                push_line(&mut out, '+', p, new, color.then_some(GREEN));
+            }
+            Change::Unchanged(p, v) => push_line(&mut out, ' ', p, v, None),
+        }
+    }
+    out
+}
+
+fn push_line(out: &mut String, prefix: char, path: &str, value: &Value, color: Option<&str>) {
+    let path = if path.is_empty() { "$" } else { path };
+    let line = format!("{} {}: {}", prefix, path, value.kind);
+    match color {
+        Some(c) => out.push_str(&format!("{}{}{}\n", c, line, RESET)),
+        None => {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+}
+
+/// `true` if every change is `Unchanged`.
+pub fn diff_is_empty(changes: &[Change]) -> bool {
+    changes.iter().all(|c| matches!(c, Change::Unchanged(..)))
+}
@@ -15,6 +15,14 @@ pub enum CosynError {
     Io(String),
     /// An error occurred during include resolution
     Include(String),
+    /// The schema used for validation is invalid
+    Schema(String),
+    /// Merging the loaded files failed, e.g. on a type conflict
+    Merge(String),
+    /// Rendering a template failed, e.g. on an undefined variable
+    Template(String),
+    /// A config does not match its schema
+    Validation(String),
 }
 
 impl fmt::Display for CosynError {
@@ -24,6 +32,10 @@ impl fmt::Display for CosynError {
             CosynError::Parse(e) => write!(f, "{}", e),
             CosynError::Io(e) => write!(f, "IO error: {}", e),
             CosynError::Include(msg) => write!(f, "Include error: {}", msg),
+            CosynError::Schema(msg) => write!(f, "Schema error: {}", msg),
+            CosynError::Merge(msg) => write!(f, "Merge error: {}", msg),
+            CosynError::Template(msg) => write!(f, "Template error: {}", msg),
+            CosynError::Validation(msg) => write!(f, "Validation error: {}", msg),
         }
     }
 }
@@ -49,6 +61,48 @@ impl From<parser::ParseError> for CosynError {
 }
 
 impl CosynError {
+    /// A [`CosynError::Parse`] at `line` and `column`, for loaders built on
+    /// COSY that report their own problems in the same form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cosy::CosynError;
+    ///
+    /// let err = CosynError::parse("Expected a table name", 3, 7);
+    /// assert_eq!(err.to_string(), "Parse error at line 3, column 7: Expected a table name");
+    /// ```
+    pub fn parse(message: impl Into<String>, line: usize, column: usize) -> Self {
+        CosynError::Parse(parser::ParseError::new(message, line, column))
+    }
+
+    /// A [`CosynError::Lex`] at `line` and `column`.
+    pub fn lex(message: impl Into<String>, line: usize, column: usize) -> Self {
+        CosynError::Lex(lexer::LexError::new(message, line, column))
+    }
+
+    /// Whether reading a file failed. Unlike the other errors, this may go
+    /// away on retry.
+    pub fn is_io(&self) -> bool {
+        matches!(self, CosynError::Io(_))
+    }
+
+    /// Whether the input is not valid COSY (a lex or parse error).
+    pub fn is_syntax(&self) -> bool {
+        matches!(self, CosynError::Lex(_) | CosynError::Parse(_))
+    }
+
+    /// Whether resolving `include`/`extends` failed.
+    pub fn is_include(&self) -> bool {
+        matches!(self, CosynError::Include(_))
+    }
+
+    /// Whether retrying the same operation could succeed; currently the same
+    /// as [`is_io`](Self::is_io).
+    pub fn is_recoverable(&self) -> bool {
+        self.is_io()
+    }
+
     /// Get the line number where the error occurred.
     pub fn line(&self) -> usize {
         match self {
@@ -74,6 +128,10 @@ impl CosynError {
             CosynError::Parse(e) => e.message.clone(),
             CosynError::Io(e) => e.to_string(),
             CosynError::Include(msg) => msg.clone(),
+            CosynError::Schema(msg) => msg.clone(),
+            CosynError::Merge(msg) => msg.clone(),
+            CosynError::Template(msg) => msg.clone(),
+            CosynError::Validation(msg) => msg.clone(),
         }
     }
 }
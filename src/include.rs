@@ -1,34 +1,118 @@
-use crate::merge;
-use crate::syntax::parser;
+use crate::merge::{self, MergeOptions, TypeConflict};
+use crate::path::{self, PathSegment, ValuePath};
+use crate::schema::{ValidationErrorKind, ValidationItem};
+use crate::syntax::parser::{self, ParseOptions};
 use crate::value::{Value, ValueKind};
 use indexmap::IndexMap;
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+mod source;
+
+use source::{Disk, FileSource};
 
 /// Errors that can occur during config inclusion
 #[derive(Debug)]
 pub enum IncludeError {
     IoError(std::io::Error),
-    ParseError(crate::error::CosynError),
-    InvalidIncludePath { path: String, message: String },
-    RecursionLimitExceeded,
+    /// An included or extended file could not be parsed. `chain` lists the
+    /// files that led to it, nearest first.
+    ParseError {
+        path: PathBuf,
+        chain: Vec<PathBuf>,
+        error: crate::error::CosynError,
+    },
+    InvalidIncludePath {
+        path: String,
+        message: String,
+    },
+    /// Includes nested deeper than the configured limit
+    RecursionLimitExceeded {
+        max_depth: usize,
+    },
+    /// A resolved include path lies outside the configured root directory
+    PathEscapesRoot {
+        path: PathBuf,
+    },
+    /// A file includes itself, directly or through other files. The chain
+    /// starts and ends with the repeated file.
+    CycleDetected {
+        chain: Vec<PathBuf>,
+    },
+    /// A file includes or extends itself directly
+    CircularInclude {
+        path: PathBuf,
+    },
     InvalidIncludeTarget(String),
+    /// An included or extended file's top-level value is not an object
+    IncludedNotObject {
+        path: PathBuf,
+        found: &'static str,
+    },
+    /// A value has a different type than the one it overrides, with
+    /// [`TypeConflict::Error`](merge::TypeConflict::Error). `file` is the
+    /// file the overriding value came from, when known.
+    MergeConflict {
+        file: Option<PathBuf>,
+        error: merge::MergeError,
+    },
 }
 
 impl fmt::Display for IncludeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             IncludeError::IoError(e) => write!(f, "IO error during include: {}", e),
-            IncludeError::ParseError(e) => write!(f, "Parse error in included file: {}", e),
+            IncludeError::ParseError { path, chain, error } => {
+                write!(f, "error in \"{}\"", path.display())?;
+                if !chain.is_empty() {
+                    let chain: Vec<String> = chain
+                        .iter()
+                        .map(|p| format!("\"{}\"", p.display()))
+                        .collect();
+                    write!(f, " (included from {})", chain.join(" ← "))?;
+                }
+                write!(f, ": {}", error)
+            }
             IncludeError::InvalidIncludePath { path, message } => {
                 write!(f, "Invalid include path '{}': {}", path, message)
             }
-            IncludeError::RecursionLimitExceeded => {
-                write!(f, "Recursion limit exceeded (max 10 depth)")
+            IncludeError::RecursionLimitExceeded { max_depth } => {
+                write!(f, "Recursion limit exceeded (max {} depth)", max_depth)
+            }
+            IncludeError::PathEscapesRoot { path } => {
+                write!(
+                    f,
+                    "Include path '{}' resolves outside the root directory",
+                    path.display()
+                )
+            }
+            IncludeError::CycleDetected { chain } => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Include cycle detected: {}", chain.join(" -> "))
+            }
+            IncludeError::CircularInclude { path } => {
+                write!(
+                    f,
+                    "Circular include: \"{}\" includes itself",
+                    path.display()
+                )
             }
             IncludeError::InvalidIncludeTarget(msg) => write!(f, "Invalid include usage: {}", msg),
+            IncludeError::IncludedNotObject { path, found } => write!(
+                f,
+                "Invalid include usage: Included/Extended file '{}' must be an Object, found {}",
+                path.display(),
+                found
+            ),
+            IncludeError::MergeConflict { file, error } => {
+                write!(f, "{}", error)?;
+                if let Some(file) = file {
+                    write!(f, " (overridden in \"{}\")", file.display())?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -41,34 +125,451 @@ impl From<std::io::Error> for IncludeError {
     }
 }
 
-impl From<crate::error::CosynError> for IncludeError {
-    fn from(err: crate::error::CosynError) -> Self {
-        IncludeError::ParseError(err)
-    }
-}
-
 /// Recursively resolve "include" keys in a Value.
 ///
 /// If a `Value::Object` contains a key "include" with a string value,
-/// that file is loaded, parsed, and merged into the current object.
+/// that file is loaded, parsed, and merged into the current object. An array
+/// of strings includes each file in order, later files overriding earlier
+/// ones; local keys override them all. A path may use `*` and `?` in its file
+/// name (`conf.d/*.cosy`) to include every matching file in name order.
+/// `extends` accepts the same forms. An empty array is the same as leaving
+/// the directive out.
+///
+/// Paths are interpolated like any other string, so `include:
+/// "${COSY_ENV}/overrides.cosy"` picks a per-environment file, and `\${`
+/// keeps a literal `${`. This happens when a file is parsed, in included
+/// files too; a missing variable in an included file is reported as
+/// [`IncludeError::ParseError`] naming that file.
+///
+/// Interpolation therefore runs before merging: every `${VAR}` in an
+/// included file is replaced, from the same environment as the including
+/// document, as that file is parsed. A base can default `port: ${PORT}` and
+/// the value survives the merge unless a higher layer sets `port` itself.
+/// JSON includes are not interpolated.
+///
+/// When an object has both, the layers are merged from lowest to highest
+/// precedence: `extends` bases in order, then `include` mixins in order,
+/// then the object's own keys. So local > includes > later extends >
+/// earlier extends.
+///
+/// Files ending in `.json` are read as JSON. An entry may also be an object,
+/// `{ path: "limits.txt", format: "json" }`, to choose the format explicitly.
+/// With `into: "db.queries"`, the file's value is mounted at that dotted key
+/// path (creating intermediate objects) instead of merged at the current
+/// level; the file may then hold an array or scalar rather than an object.
 ///
 /// - `value`: The configuration value to process (mutable).
 /// - `base_path`: The base directory to resolve relative paths against.
 pub fn resolve(value: &mut Value, base_path: &Path) -> Result<(), IncludeError> {
-    resolve_recursive(value, base_path, 0)
+    resolve_with_max_depth(value, base_path, DEFAULT_MAX_DEPTH)
 }
 
-const MAX_DEPTH: usize = 10;
+/// Default limit on how deeply included files may nest.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
 
-fn resolve_recursive(
+/// Like [`resolve`], but with a custom limit on include nesting.
+///
+/// Cycles are detected regardless of the limit, which only guards against
+/// runaway chains of distinct files.
+pub fn resolve_with_max_depth(
     value: &mut Value,
     base_path: &Path,
-    depth: usize,
+    max_depth: usize,
 ) -> Result<(), IncludeError> {
-    if depth > MAX_DEPTH {
-        return Err(IncludeError::RecursionLimitExceeded);
+    let options = ResolveOptions {
+        max_depth,
+        ..ResolveOptions::default()
+    };
+    resolve_with_options(value, base_path, &options)
+}
+
+/// Options for [`resolve_with_options`].
+#[derive(Debug, Clone)]
+pub struct ResolveOptions {
+    /// If set, every included file must resolve (following symlinks) to a
+    /// path inside this directory (default: unrestricted)
+    pub root: Option<PathBuf>,
+    /// Whether include paths may be absolute (default: true)
+    pub allow_absolute: bool,
+    /// Limit on how deeply included files may nest (default: [`DEFAULT_MAX_DEPTH`])
+    pub max_depth: usize,
+    /// Options for parsing included files (default: [`ParseOptions::default`])
+    pub parse_options: ParseOptions,
+    /// How included files are merged with each other and the including
+    /// document (default: [`MergeOptions::default`])
+    pub merge_options: MergeOptions,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        ResolveOptions {
+            root: None,
+            allow_absolute: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+            parse_options: ParseOptions::default(),
+            merge_options: MergeOptions::default(),
+        }
+    }
+}
+
+/// Like [`resolve`], but also returns every file that was loaded:
+/// canonicalized, without duplicates, in the order they were first read.
+///
+/// Useful for watching all the files a config was built from.
+pub fn resolve_collecting(
+    value: &mut Value,
+    base_path: &Path,
+) -> Result<Vec<PathBuf>, IncludeError> {
+    let files = resolve_with_options_from(value, base_path, &ResolveOptions::default(), None)?;
+    merge::clear_directives(value);
+    Ok(files)
+}
+
+/// Like [`resolve`], with control over which files may be included.
+///
+/// Use a `root` when config files come from untrusted sources, so that
+/// `include: "../../etc/passwd"` fails with [`IncludeError::PathEscapesRoot`].
+pub fn resolve_with_options(
+    value: &mut Value,
+    base_path: &Path,
+    options: &ResolveOptions,
+) -> Result<(), IncludeError> {
+    resolve_with_options_from(value, base_path, options, None)?;
+    merge::clear_directives(value);
+    Ok(())
+}
+
+/// Like [`resolve`], also warning about every value that an included file
+/// or a local key overrides with a value of another type, such as a base
+/// `port: 8080` overridden by `port: "auto"`.
+///
+/// Each warning is a [`ValidationErrorKind::TypeChanged`] item at the path
+/// of the value in the resolved document.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// let mut config = cosy::from_str(r#"{ extends: "base.cosy", port: "auto" }"#).unwrap();
+/// let warnings = cosy::include::resolve_with_warnings(&mut config, Path::new(".")).unwrap();
+/// for warning in &warnings {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+pub fn resolve_with_warnings(
+    value: &mut Value,
+    base_path: &Path,
+) -> Result<Vec<ValidationItem>, IncludeError> {
+    let options = ResolveOptions::default();
+    let mut stack = IncludeStack {
+        files: Vec::new(),
+        root: None,
+        options: &options,
+        source: &Disk,
+        tracing: false,
+        loaded: Vec::new(),
+        location: "$".to_string(),
+        type_changes: Some(Vec::new()),
+    };
+    resolve_recursive(value, base_path, &mut stack)?;
+    merge::clear_directives(value);
+    Ok(stack.type_changes.unwrap_or_default())
+}
+
+/// Resolve with options, treating the document itself as `file` so errors
+/// in included files name it at the end of their include chain. Returns the
+/// files loaded, as [`resolve_collecting`] does.
+pub(crate) fn resolve_with_options_from(
+    value: &mut Value,
+    base_path: &Path,
+    options: &ResolveOptions,
+    file: Option<&Path>,
+) -> Result<Vec<PathBuf>, IncludeError> {
+    resolve_from_source(value, base_path, options, file, &Disk)
+}
+
+/// Resolve with options, reading files from `source`.
+fn resolve_from_source(
+    value: &mut Value,
+    base_path: &Path,
+    options: &ResolveOptions,
+    file: Option<&Path>,
+    source: &dyn FileSource,
+) -> Result<Vec<PathBuf>, IncludeError> {
+    let root = match &options.root {
+        Some(root) => Some(source.canonicalize(root)?),
+        None => None,
+    };
+    let mut stack = IncludeStack {
+        files: file
+            .map(|file| canonical_or_self(source, file))
+            .into_iter()
+            .collect(),
+        root,
+        options,
+        source,
+        tracing: false,
+        loaded: Vec::new(),
+        location: "$".to_string(),
+        type_changes: None,
+    };
+    resolve_recursive(value, base_path, &mut stack)?;
+    Ok(stack.loaded)
+}
+
+/// Like [`resolve`], but reads files with `tokio::fs` so the runtime is not
+/// blocked, e.g. when configs sit on a network filesystem.
+///
+/// Requires the `async` feature.
+#[cfg(feature = "async")]
+pub async fn resolve_async(value: &mut Value, base_path: &Path) -> Result<(), IncludeError> {
+    resolve_async_from(value, base_path, &ResolveOptions::default(), None).await?;
+    merge::clear_directives(value);
+    Ok(())
+}
+
+/// Async form of [`resolve_with_options_from`].
+///
+/// Every file reachable through `include`/`extends` is first read and
+/// parsed ahead, following the directives in each file it reads. Then the
+/// regular resolver runs against those files. A pass that still needed a
+/// file not read yet is discarded; the file is read and the pass repeated.
+#[cfg(feature = "async")]
+pub(crate) async fn resolve_async_from(
+    value: &mut Value,
+    base_path: &Path,
+    options: &ResolveOptions,
+    file: Option<&Path>,
+) -> Result<Vec<PathBuf>, IncludeError> {
+    let source = source::Prefetched::new(options.parse_options.max_input_bytes);
+    prefetch_includes(value, base_path, options, &source).await;
+    loop {
+        let mut attempt = value.clone();
+        let result = resolve_from_source(&mut attempt, base_path, options, file, &source);
+        let missing = source.take_missing();
+        if missing.is_empty() {
+            *value = attempt;
+            return result;
+        }
+        for needed in missing {
+            source.fetch(needed).await;
+        }
+    }
+}
+
+/// Read and parse, into `source`, every file reachable from the directives
+/// in `value`. Problems are left for the resolver to report.
+#[cfg(feature = "async")]
+async fn prefetch_includes(
+    value: &Value,
+    base_path: &Path,
+    options: &ResolveOptions,
+    source: &source::Prefetched,
+) {
+    let root = match &options.root {
+        Some(root) => match tokio::fs::canonicalize(root).await {
+            Ok(root) => Some(root),
+            Err(_) => return,
+        },
+        None => None,
+    };
+    let mut pending = vec![(value.clone(), base_path.to_path_buf())];
+    while let Some((document, base)) = pending.pop() {
+        let mut directives = Vec::new();
+        collect_directives(&document, &mut directives);
+        for (directive, val) in directives {
+            // A glob needs its directory listed before it expands
+            let targets = loop {
+                let targets = directive_paths(val.clone(), directive, &base, source);
+                let missing = source.take_missing();
+                if missing.is_empty() {
+                    break targets;
+                }
+                for needed in missing {
+                    source.fetch(needed).await;
+                }
+            };
+            for target in targets.into_iter().flatten() {
+                if !options.allow_absolute && Path::new(&target.path).is_absolute() {
+                    continue;
+                }
+                let path = base.join(&target.path);
+                if source.has_file(&path) {
+                    continue;
+                }
+                // Don't read ahead what the resolver would refuse to read
+                if let Some(root) = &root {
+                    match tokio::fs::canonicalize(&path).await {
+                        Ok(canonical) if canonical.starts_with(root) => {}
+                        _ => continue,
+                    }
+                }
+                source.fetch(source::Missing::File(path.clone())).await;
+                let Ok(content) = source.read_to_string(&path) else {
+                    continue;
+                };
+                if let Ok(parsed) = parse_include(&content, target.format, options) {
+                    source.store_parsed(&path, target.format, &parsed);
+                    let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+                    pending.push((parsed, dir));
+                }
+            }
+        }
+    }
+}
+
+/// The `extends` and `include` values anywhere in `value`.
+#[cfg(feature = "async")]
+fn collect_directives(value: &Value, out: &mut Vec<(&'static str, Value)>) {
+    match &value.kind {
+        ValueKind::Object(map) => {
+            for (key, v) in map {
+                match key.as_str() {
+                    "extends" => out.push(("Extends", v.clone())),
+                    "include" => out.push(("Include", v.clone())),
+                    _ => collect_directives(v, out),
+                }
+            }
+        }
+        ValueKind::Array(items) => items.iter().for_each(|v| collect_directives(v, out)),
+        _ => {}
+    }
+}
+
+/// Where a resolved value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// The (canonical) file the value was written in, or `None` for values
+    /// written in the document passed to [`resolve_traced`] itself
+    pub file: Option<PathBuf>,
+    /// The line the value starts on, if known
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}", file.display())?,
+            None => write!(f, "<input>")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The [`Provenance`] of each leaf of a resolved value, keyed by its
+/// dot-separated path (e.g. `"server.port"`). Arrays count as leaves, since
+/// they are replaced rather than merged.
+pub type Trace = IndexMap<String, Provenance>;
+
+/// Like [`resolve`], but also records which file each leaf value came from.
+///
+/// Useful for answering "why is `server.port` 9000?" when a config is spread
+/// across several `extends`/`include` layers.
+pub fn resolve_traced(value: &mut Value, base_path: &Path) -> Result<Trace, IncludeError> {
+    let trace = resolve_traced_from(value, base_path, None)?;
+    merge::clear_directives(value);
+    Ok(trace)
+}
+
+/// Resolve with tracing, attributing values of the document itself to `file`.
+pub(crate) fn resolve_traced_from(
+    value: &mut Value,
+    base_path: &Path,
+    file: Option<&Path>,
+) -> Result<Trace, IncludeError> {
+    let options = ResolveOptions::default();
+    let mut stack = IncludeStack {
+        files: file
+            .map(|file| canonical_or_self(&Disk, file))
+            .into_iter()
+            .collect(),
+        root: None,
+        options: &options,
+        source: &Disk,
+        tracing: true,
+        loaded: Vec::new(),
+        location: "$".to_string(),
+        type_changes: None,
+    };
+    let mut trace = resolve_recursive(value, base_path, &mut stack)?;
+    retain_leaves(&mut trace, value);
+    Ok(trace)
+}
+
+fn canonical_or_self(source: &dyn FileSource, path: &Path) -> PathBuf {
+    source
+        .canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Drop entries for paths that are no longer leaves of `value`, such as a
+/// base file's `db.host` after a later layer replaced `db` with a string.
+pub(crate) fn retain_leaves(trace: &mut Trace, value: &Value) {
+    fn collect(value: &Value, path: String, leaves: &mut HashSet<String>) {
+        match &value.kind {
+            ValueKind::Object(map) => {
+                for (key, v) in map {
+                    collect(v, path::join_key(&path, key), leaves);
+                }
+            }
+            _ => {
+                leaves.insert(path);
+            }
+        }
     }
 
+    let mut leaves = HashSet::new();
+    collect(value, String::new(), &mut leaves);
+    trace.retain(|path, _| leaves.contains(path));
+}
+
+/// The files currently being resolved, outermost first, and the rules they
+/// must follow.
+struct IncludeStack<'a> {
+    files: Vec<PathBuf>,
+    /// Canonical form of `options.root`
+    root: Option<PathBuf>,
+    options: &'a ResolveOptions,
+    source: &'a dyn FileSource,
+    /// Whether to record a [`Trace`]
+    tracing: bool,
+    /// Every file read so far, canonicalized, in load order
+    loaded: Vec<PathBuf>,
+    /// Path of the value being resolved, e.g. `$.server`
+    location: String,
+    /// Type changes found while merging, if they are being recorded
+    type_changes: Option<Vec<ValidationItem>>,
+}
+
+impl IncludeStack<'_> {
+    /// Move to `location`, returning the previous one to restore.
+    fn enter(&mut self, location: String) -> String {
+        std::mem::replace(&mut self.location, location)
+    }
+
+    /// The trace of a leaf value in the file being resolved.
+    fn leaf(&self, value: &Value) -> Trace {
+        let mut trace = Trace::new();
+        if self.tracing {
+            let provenance = Provenance {
+                file: self.files.last().cloned(),
+                line: value.position.map(|p| p.line),
+            };
+            trace.insert(String::new(), provenance);
+        }
+        trace
+    }
+}
+
+fn resolve_recursive(
+    value: &mut Value,
+    base_path: &Path,
+    stack: &mut IncludeStack<'_>,
+) -> Result<Trace, IncludeError> {
     match &mut value.kind {
         ValueKind::Object(map) => {
             // 1. Identify and remove directives
@@ -76,79 +577,477 @@ fn resolve_recursive(
             let include_val = map.shift_remove("include");
 
             // 2. Resolve local fields (FIX for bug where local includes were ignored)
-            for (_, v) in map.iter_mut() {
-                resolve_recursive(v, base_path, depth)?;
-            }
-
-            // 3. Prepare Base (from `extends`)
-            let mut base_config = if let Some(val) = extends_val {
-                let path_str = if let ValueKind::String(s) = val.kind {
-                    s
-                } else {
-                    return Err(IncludeError::InvalidIncludeTarget(format!(
-                        "Extends value must be a string, found {}",
-                        val.type_name()
-                    )));
-                };
-                load_and_resolve(&path_str, base_path, depth)?
-            } else {
-                Value::object(IndexMap::new())
-            };
+            let mut local_trace = Trace::new();
+            for (key, v) in map.iter_mut() {
+                let parent = stack.enter(path::join_key(&stack.location, key));
+                let field_trace = resolve_recursive(v, base_path, stack);
+                stack.location = parent;
+                let field_trace = field_trace?;
+                local_trace.extend(field_trace.into_iter().map(|(rest, provenance)| {
+                    (path::join_path(&path::join_key("", key), &rest), provenance)
+                }));
+            }
 
-            // 4. Prepare Mixin (from `include`) and merge into Base
+            // Layers are traced in merge order, so later layers win; entries
+            // a later layer replaced wholesale are dropped by `retain_leaves`
+            let mut trace = Trace::new();
+
+            // 3. Prepare Base (from `extends`), later entries overriding earlier ones
+            let mut base_config = Value::object(IndexMap::new());
+            if let Some(val) = extends_val {
+                for target in directive_paths(val, "Extends", base_path, stack.source)? {
+                    let (extended, extended_trace) = load_and_resolve(&target, base_path, stack)?;
+                    let file = Some(base_path.join(&target.path));
+                    merge_layer(&mut base_config, extended, file, stack)?;
+                    trace.extend(extended_trace);
+                }
+            }
+
+            // 4. Prepare Mixins (from `include`) and merge into Base in order
             if let Some(val) = include_val {
-                let path_str = if let ValueKind::String(s) = val.kind {
-                    s
-                } else {
-                    return Err(IncludeError::InvalidIncludeTarget(format!(
-                        "Include value must be a string, found {}",
-                        val.type_name()
-                    )));
-                };
-                let mixin_config = load_and_resolve(&path_str, base_path, depth)?;
+                for target in directive_paths(val, "Include", base_path, stack.source)? {
+                    let (mixin_config, mixin_trace) = load_and_resolve(&target, base_path, stack)?;
 
-                // Merge Mixin INTO Base (Mixin overrides Base)
-                // Note: Standard `include` might expect to override `extends`?
-                // Yes, extends is deepest base. Include is like a trait/mixin on top.
-                merge::merge(&mut base_config, mixin_config);
+                    // Merge Mixin INTO Base (Mixin overrides Base)
+                    // Note: Standard `include` might expect to override `extends`?
+                    // Yes, extends is deepest base. Include is like a trait/mixin on top.
+                    let file = Some(base_path.join(&target.path));
+                    merge_layer(&mut base_config, mixin_config, file, stack)?;
+                    trace.extend(mixin_trace);
+                }
             }
 
             // 5. Merge Local (current map) INTO Base (Local overrides Base+Mixin)
             // We take the local map out, wrap it in a Value, merge it into base_config.
             let local_overrides = Value::from(ValueKind::Object(std::mem::take(map)));
-            merge::merge(&mut base_config, local_overrides);
+            let file = stack.files.last().cloned();
+            merge_layer(&mut base_config, local_overrides, file, stack)?;
+            trace.extend(local_trace);
 
             // 6. Put the result back into `value`
             if let ValueKind::Object(merged_map) = base_config.kind {
                 *map = merged_map;
             }
+            return Ok(trace);
         }
         ValueKind::Array(arr) => {
-            for v in arr {
-                resolve_recursive(v, base_path, depth)?;
+            for (i, v) in arr.iter_mut().enumerate() {
+                let parent = stack.enter(path::join_index(&stack.location, i));
+                let result = resolve_recursive(v, base_path, stack);
+                stack.location = parent;
+                result?;
             }
         }
         _ => {}
     }
 
-    Ok(())
+    Ok(stack.leaf(value))
+}
+
+/// Merge one layer of a file, naming `file` in a type conflict or change.
+fn merge_layer(
+    base: &mut Value,
+    layer: Value,
+    file: Option<PathBuf>,
+    stack: &mut IncludeStack<'_>,
+) -> Result<(), IncludeError> {
+    let options = &stack.options.merge_options;
+    let location = &stack.location;
+    let type_changes = &mut stack.type_changes;
+    let mut on_type_change = |conflict: &merge::MergeError| {
+        // Only a value that is actually replaced has changed type
+        if let Some(changes) = type_changes.as_mut()
+            && options.on_type_conflict == TypeConflict::Override
+        {
+            changes.push(type_change(location, conflict, file.as_deref()));
+        }
+    };
+    merge::merge_with_callback(base, layer, options, &mut on_type_change)
+        .map(drop)
+        .map_err(|error| IncludeError::MergeConflict { file, error })
+}
+
+/// The warning for a value whose type an override changed, at `location`
+/// joined with the conflict's path within the object being merged.
+fn type_change(
+    location: &str,
+    conflict: &merge::MergeError,
+    file: Option<&Path>,
+) -> ValidationItem {
+    let path = match ValuePath::parse(&conflict.path) {
+        Some(relative) => relative
+            .segments
+            .iter()
+            .fold(location.to_string(), |path, segment| match segment {
+                PathSegment::Key(key) => path::join_key(&path, key),
+                PathSegment::Index(index) => path::join_index(&path, *index),
+            }),
+        None => path::join_path(location, &conflict.path),
+    };
+    ValidationItem::warning(
+        &path,
+        ValidationErrorKind::TypeChanged {
+            from: conflict.base_type.to_string(),
+            to: conflict.override_type.to_string(),
+            file: file.map(|file| file.display().to_string()),
+        },
+    )
+}
+
+/// The format of an included file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum IncludeFormat {
+    Cosy,
+    Json,
+}
+
+impl IncludeFormat {
+    fn from_name(name: &str) -> Result<Self, IncludeError> {
+        match name {
+            "cosy" => Ok(IncludeFormat::Cosy),
+            "json" => Ok(IncludeFormat::Json),
+            other => Err(IncludeError::InvalidIncludeTarget(format!(
+                "Unknown include format '{}'; expected 'cosy' or 'json'",
+                other
+            ))),
+        }
+    }
+
+    /// `.json` files are JSON; anything else is COSY.
+    fn detect(path: &str) -> Self {
+        match Path::new(path).extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => IncludeFormat::Json,
+            _ => IncludeFormat::Cosy,
+        }
+    }
+}
+
+/// A file named by an `include`/`extends` value.
+struct IncludeTarget {
+    path: String,
+    format: IncludeFormat,
+    /// Dotted key path to mount the file's value under
+    into: Option<String>,
+}
+
+/// One entry of an `include`/`extends` value, before glob expansion.
+struct DirectiveEntry {
+    pattern: String,
+    format: Option<IncludeFormat>,
+    into: Option<String>,
+}
+
+/// The files named by an `include`/`extends` value: a string, an object
+/// with a `path` and optional `format` and `into`, or an array of those,
+/// with glob patterns expanded.
+fn directive_paths(
+    val: Value,
+    directive: &str,
+    base_path: &Path,
+    source: &dyn FileSource,
+) -> Result<Vec<IncludeTarget>, IncludeError> {
+    let entries = match val.kind {
+        ValueKind::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| directive_entry(item, directive, Some(i)))
+            .collect::<Result<_, _>>()?,
+        _ => vec![directive_entry(val, directive, None)?],
+    };
+
+    let mut targets = Vec::new();
+    for entry in entries {
+        for path in expand_glob(&entry.pattern, base_path, source)? {
+            let format = entry.format.unwrap_or_else(|| IncludeFormat::detect(&path));
+            targets.push(IncludeTarget {
+                path,
+                format,
+                into: entry.into.clone(),
+            });
+        }
+    }
+    Ok(targets)
 }
 
-fn load_and_resolve(path_str: &str, base_path: &Path, depth: usize) -> Result<Value, IncludeError> {
+/// Parse one entry of an `include`/`extends` value; `index` is its
+/// position in an array.
+fn directive_entry(
+    entry: Value,
+    directive: &str,
+    index: Option<usize>,
+) -> Result<DirectiveEntry, IncludeError> {
+    let mut map = match entry.kind {
+        ValueKind::String(pattern) => {
+            return Ok(DirectiveEntry {
+                pattern,
+                format: None,
+                into: None,
+            });
+        }
+        ValueKind::Object(map) => map,
+        other => {
+            let message = match index {
+                Some(i) => format!(
+                    "{} value at index {} must be a string or an object, found {}",
+                    directive,
+                    i,
+                    other.type_name()
+                ),
+                None => format!(
+                    "{} value must be a string, an object or an array, found {}",
+                    directive,
+                    other.type_name()
+                ),
+            };
+            return Err(IncludeError::InvalidIncludeTarget(message));
+        }
+    };
+
+    let pattern = match map.shift_remove("path").map(|v| v.kind) {
+        Some(ValueKind::String(pattern)) => pattern,
+        _ => {
+            return Err(IncludeError::InvalidIncludeTarget(format!(
+                "{} object must have a string 'path'",
+                directive
+            )));
+        }
+    };
+    let format = match map.shift_remove("format").map(|v| v.kind) {
+        None => None,
+        Some(ValueKind::String(name)) => Some(IncludeFormat::from_name(&name)?),
+        Some(other) => {
+            return Err(IncludeError::InvalidIncludeTarget(format!(
+                "{} 'format' must be a string, found {}",
+                directive,
+                other.type_name()
+            )));
+        }
+    };
+    let into = match map.shift_remove("into").map(|v| v.kind) {
+        None => None,
+        Some(ValueKind::String(into)) if into.split('.').all(|s| !s.is_empty()) => Some(into),
+        Some(ValueKind::String(into)) => {
+            return Err(IncludeError::InvalidIncludeTarget(format!(
+                "{} 'into' must be a dotted key path, found '{}'",
+                directive, into
+            )));
+        }
+        Some(other) => {
+            return Err(IncludeError::InvalidIncludeTarget(format!(
+                "{} 'into' must be a string, found {}",
+                directive,
+                other.type_name()
+            )));
+        }
+    };
+    if let Some(key) = map.keys().next() {
+        return Err(IncludeError::InvalidIncludeTarget(format!(
+            "Unknown key '{}' in {} object; expected 'path', 'format' or 'into'",
+            key, directive
+        )));
+    }
+    Ok(DirectiveEntry {
+        pattern,
+        format,
+        into,
+    })
+}
+
+/// Nest `value` under the dotted key path `into`, prefixing its trace.
+fn mount(value: Value, trace: Trace, into: &str) -> (Value, Trace) {
+    let mounted = into.rsplit('.').fold(value, |inner, key| {
+        let mut map = IndexMap::new();
+        map.insert(key.to_string(), inner);
+        Value::object(map)
+    });
+    let prefix = into
+        .split('.')
+        .fold(String::new(), |prefix, key| path::join_key(&prefix, key));
+    let trace = trace
+        .into_iter()
+        .map(|(rest, provenance)| (path::join_path(&prefix, &rest), provenance))
+        .collect();
+    (mounted, trace)
+}
+
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Expand `*` and `?` in the file name of `pattern` to the matching files,
+/// sorted by name. A pattern without wildcards is returned unchanged, and one
+/// that matches nothing (or names a missing directory) expands to nothing.
+///
+/// Wildcards are only allowed in the last path component, so matches always
+/// come from the directory the pattern names. Names starting with `.` only
+/// match a pattern that does too.
+fn expand_glob(
+    pattern: &str,
+    base_path: &Path,
+    source: &dyn FileSource,
+) -> Result<Vec<String>, IncludeError> {
+    if !has_wildcard(pattern) {
+        return Ok(vec![pattern.to_string()]);
+    }
+
+    let (dir, file_pattern) = match pattern.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, pattern),
+    };
+    if dir.is_some_and(has_wildcard) {
+        return Err(IncludeError::InvalidIncludePath {
+            path: pattern.to_string(),
+            message: "wildcards are only supported in the file name".to_string(),
+        });
+    }
+
+    let mut names = match source.file_names(&base_path.join(dir.unwrap_or("."))) {
+        Ok(names) => names,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    names.retain(|name| {
+        (!name.starts_with('.') || file_pattern.starts_with('.'))
+            && wildcard_match(file_pattern, name)
+    });
+    names.sort();
+
+    Ok(names
+        .into_iter()
+        .map(|name| match dir {
+            Some(dir) => format!("{}/{}", dir, name),
+            None => name,
+        })
+        .collect())
+}
+
+/// Match `name` against a pattern where `*` is any run of characters and `?`
+/// is exactly one, as in `include: "conf.d/*.cosy"`. `*` also matches `/`.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the most recent `*`: (pattern index, name index)
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    p = bp;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Parse an included file's content. `${VAR}`s are interpolated while
+/// parsing, before any merge.
+fn parse_include(
+    content: &str,
+    format: IncludeFormat,
+    options: &ResolveOptions,
+) -> Result<Value, crate::error::CosynError> {
+    match format {
+        IncludeFormat::Cosy => {
+            parser::from_str_with_options(content, options.parse_options.clone())
+        }
+        IncludeFormat::Json => crate::compat::from_json_str(content),
+    }
+}
+
+fn load_and_resolve(
+    target: &IncludeTarget,
+    base_path: &Path,
+    stack: &mut IncludeStack<'_>,
+) -> Result<(Value, Trace), IncludeError> {
+    let path_str = target.path.as_str();
+    if !stack.options.allow_absolute && Path::new(path_str).is_absolute() {
+        return Err(IncludeError::InvalidIncludePath {
+            path: path_str.to_string(),
+            message: "absolute include paths are not allowed".to_string(),
+        });
+    }
+
     let include_path = base_path.join(path_str);
-    let file_content = fs::read_to_string(&include_path)?;
-    let mut loaded_value = parser::from_str(&file_content)?;
+    let canonical = match &stack.root {
+        Some(root) => {
+            let canonical = stack.source.canonicalize(&include_path)?;
+            if !canonical.starts_with(root) {
+                return Err(IncludeError::PathEscapesRoot { path: include_path });
+            }
+            canonical
+        }
+        None => canonical_or_self(stack.source, &include_path),
+    };
+
+    if stack.files.last() == Some(&canonical) {
+        return Err(IncludeError::CircularInclude { path: canonical });
+    }
+    if let Some(start) = stack.files.iter().position(|p| *p == canonical) {
+        let mut chain = stack.files[start..].to_vec();
+        chain.push(canonical);
+        return Err(IncludeError::CycleDetected { chain });
+    }
+    if stack.files.len() >= stack.options.max_depth {
+        return Err(IncludeError::RecursionLimitExceeded {
+            max_depth: stack.options.max_depth,
+        });
+    }
+
+    let parse_error = |error: crate::error::CosynError| IncludeError::ParseError {
+        path: include_path.clone(),
+        chain: stack.files.iter().rev().cloned().collect(),
+        error,
+    };
+    let size = stack.source.file_size(&include_path)?;
+    parser::check_input_size(size, &stack.options.parse_options)
+        .map_err(|e| parse_error(e.into()))?;
+
+    let mut loaded_value = match stack.source.parsed(&include_path, target.format) {
+        Some(parsed) => parsed,
+        None => {
+            let file_content = stack.source.read_to_string(&include_path)?;
+            let parsed =
+                parse_include(&file_content, target.format, stack.options).map_err(parse_error)?;
+            stack
+                .source
+                .store_parsed(&include_path, target.format, &parsed);
+            parsed
+        }
+    };
+
+    if !stack.loaded.contains(&canonical) {
+        stack.loaded.push(canonical.clone());
+    }
 
     let new_base = include_path.parent().unwrap_or(Path::new("."));
-    resolve_recursive(&mut loaded_value, new_base, depth + 1)?;
+    stack.files.push(canonical);
+    let resolved = resolve_recursive(&mut loaded_value, new_base, stack);
+    stack.files.pop();
+    let trace = resolved?;
 
-    if let ValueKind::Object(_) = loaded_value.kind {
-        Ok(loaded_value)
+    if let Some(into) = &target.into {
+        // The destination is explicit, so any kind of value can be mounted
+        Ok(mount(loaded_value, trace, into))
+    } else if let ValueKind::Object(_) = loaded_value.kind {
+        Ok((loaded_value, trace))
     } else {
-        Err(IncludeError::InvalidIncludeTarget(format!(
-            "Included/Extended file '{}' must be an Object, found {}",
-            path_str,
-            loaded_value.type_name()
-        )))
+        Err(IncludeError::IncludedNotObject {
+            path: PathBuf::from(path_str),
+            found: loaded_value.type_name(),
+        })
     }
 }
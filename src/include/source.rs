@@ -0,0 +1,225 @@
+//! Where the resolver reads files from.
+//!
+//! The resolver only touches the filesystem through [`FileSource`], so the
+//! same merge and precedence logic serves both [`resolve`](super::resolve),
+//! which reads from [`Disk`], and the async resolver, which reads ahead with
+//! `tokio::fs` into [`Prefetched`] and repeats a pass until nothing is
+//! missing.
+
+use super::IncludeFormat;
+use crate::value::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The filesystem operations include resolution needs.
+pub(crate) trait FileSource {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    fn file_size(&self, path: &Path) -> io::Result<u64>;
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// Names of the regular files in `dir`, skipping names that are not
+    /// valid UTF-8.
+    fn file_names(&self, dir: &Path) -> io::Result<Vec<String>>;
+
+    /// An earlier parse of the file at `path`, if the source keeps them.
+    fn parsed(&self, _path: &Path, _format: IncludeFormat) -> Option<Value> {
+        None
+    }
+
+    /// Keep the parse of the file at `path` for [`parsed`](Self::parsed).
+    fn store_parsed(&self, _path: &Path, _format: IncludeFormat, _value: &Value) {}
+}
+
+/// Blocking reads from the real filesystem.
+pub(crate) struct Disk;
+
+impl FileSource for Disk {
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        Ok(fs::metadata(path)?.len())
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn file_names(&self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let Ok(name) = entry.file_name().into_string() {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) use prefetch::{Missing, Prefetched};
+
+#[cfg(feature = "async")]
+mod prefetch {
+    use super::{FileSource, IncludeFormat};
+    use crate::value::Value;
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    /// The outcome of a read, kept so it can be replayed on later passes.
+    type Stored<T> = Result<T, (io::ErrorKind, String)>;
+
+    fn store<T>(result: io::Result<T>) -> Stored<T> {
+        result.map_err(|e| (e.kind(), e.to_string()))
+    }
+
+    fn replay<T: Clone>(stored: &Stored<T>) -> io::Result<T> {
+        stored
+            .clone()
+            .map_err(|(kind, message)| io::Error::new(kind, message))
+    }
+
+    struct FileEntry {
+        canonical: Stored<PathBuf>,
+        size: Stored<u64>,
+        /// Not read when the file is over the size limit
+        content: Option<Stored<String>>,
+    }
+
+    /// Something a pass needed that has not been read yet.
+    pub(crate) enum Missing {
+        File(PathBuf),
+        Dir(PathBuf),
+    }
+
+    /// Files read ahead of a resolution pass with `tokio::fs`, and their
+    /// parsed documents.
+    ///
+    /// A lookup that misses records what was needed and fails; the caller
+    /// fetches everything recorded and runs the pass again. Parses are kept
+    /// across passes, so each file is parsed once.
+    #[derive(Default)]
+    pub(crate) struct Prefetched {
+        max_input_bytes: Option<usize>,
+        files: Mutex<HashMap<PathBuf, FileEntry>>,
+        dirs: Mutex<HashMap<PathBuf, Stored<Vec<String>>>>,
+        parsed: Mutex<HashMap<(PathBuf, IncludeFormat), Value>>,
+        missing: Mutex<Vec<Missing>>,
+    }
+
+    impl Prefetched {
+        /// Files larger than `max_input_bytes` are sized but not read.
+        pub(crate) fn new(max_input_bytes: Option<usize>) -> Self {
+            Prefetched {
+                max_input_bytes,
+                ..Prefetched::default()
+            }
+        }
+
+        /// Whether `path` has been fetched.
+        pub(crate) fn has_file(&self, path: &Path) -> bool {
+            self.files.lock().unwrap().contains_key(path)
+        }
+
+        /// What the last pass needed but could not find.
+        pub(crate) fn take_missing(&self) -> Vec<Missing> {
+            std::mem::take(&mut self.missing.lock().unwrap())
+        }
+
+        pub(crate) async fn fetch(&self, missing: Missing) {
+            match missing {
+                Missing::File(path) => {
+                    let canonical = tokio::fs::canonicalize(&path).await;
+                    let size = tokio::fs::metadata(&path).await.map(|m| m.len());
+                    let content = match &size {
+                        Ok(size) if self.max_input_bytes.is_none_or(|max| *size <= max as u64) => {
+                            Some(store(tokio::fs::read_to_string(&path).await))
+                        }
+                        _ => None,
+                    };
+                    let entry = FileEntry {
+                        canonical: store(canonical),
+                        size: store(size),
+                        content,
+                    };
+                    self.files.lock().unwrap().insert(path, entry);
+                }
+                Missing::Dir(dir) => {
+                    let names = store(read_file_names(&dir).await);
+                    self.dirs.lock().unwrap().insert(dir, names);
+                }
+            }
+        }
+
+        fn file<T: Clone>(
+            &self,
+            path: &Path,
+            field: impl FnOnce(&FileEntry) -> Option<&Stored<T>>,
+        ) -> io::Result<T> {
+            let files = self.files.lock().unwrap();
+            match files.get(path).map(field) {
+                Some(Some(stored)) => replay(stored),
+                Some(None) => Err(io::Error::other("not read: over the size limit")),
+                None => Err(self.miss(Missing::File(path.to_path_buf()))),
+            }
+        }
+
+        fn miss(&self, missing: Missing) -> io::Error {
+            self.missing.lock().unwrap().push(missing);
+            io::Error::other("not read yet")
+        }
+    }
+
+    impl FileSource for Prefetched {
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.file(path, |entry| Some(&entry.canonical))
+        }
+
+        fn file_size(&self, path: &Path) -> io::Result<u64> {
+            self.file(path, |entry| Some(&entry.size))
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.file(path, |entry| entry.content.as_ref())
+        }
+
+        fn file_names(&self, dir: &Path) -> io::Result<Vec<String>> {
+            let dirs = self.dirs.lock().unwrap();
+            match dirs.get(dir) {
+                Some(stored) => replay(stored),
+                None => Err(self.miss(Missing::Dir(dir.to_path_buf()))),
+            }
+        }
+
+        fn parsed(&self, path: &Path, format: IncludeFormat) -> Option<Value> {
+            let parsed = self.parsed.lock().unwrap();
+            parsed.get(&(path.to_path_buf(), format)).cloned()
+        }
+
+        fn store_parsed(&self, path: &Path, format: IncludeFormat, value: &Value) {
+            let key = (path.to_path_buf(), format);
+            self.parsed.lock().unwrap().insert(key, value.clone());
+        }
+    }
+
+    async fn read_file_names(dir: &Path) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_file() {
+                continue;
+            }
+            if let Ok(name) = entry.file_name().into_string() {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+}
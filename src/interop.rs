@@ -0,0 +1,82 @@
+//! Conversions between a COSY `Value` and `serde_json::Value`.
+//!
+//! Kinds map directly, without going through text. Comments and positions
+//! are dropped; values converted from JSON have none. Non-finite floats,
+//! which JSON cannot represent, become `null`.
+
+use crate::value::{Value, ValueKind};
+use serde_json::{Number, Value as Json};
+
+impl From<Value> for Json {
+    fn from(value: Value) -> Self {
+        match value.kind {
+            ValueKind::Null => Json::Null,
+            ValueKind::Bool(b) => Json::Bool(b),
+            ValueKind::Integer(i) => Json::from(i),
+            ValueKind::Float(f) => Number::from_f64(f).map_or(Json::Null, Json::Number),
+            ValueKind::String(s) => Json::String(s),
+            ValueKind::Array(arr) => Json::Array(arr.into_iter().map(Json::from).collect()),
+            ValueKind::Object(obj) => {
+                Json::Object(obj.into_iter().map(|(k, v)| (k, Json::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<&Value> for Json {
+    fn from(value: &Value) -> Self {
+        match &value.kind {
+            ValueKind::Null => Json::Null,
+            ValueKind::Bool(b) => Json::Bool(*b),
+            ValueKind::Integer(i) => Json::from(*i),
+            ValueKind::Float(f) => Number::from_f64(*f).map_or(Json::Null, Json::Number),
+            ValueKind::String(s) => Json::String(s.clone()),
+            ValueKind::Array(arr) => Json::Array(arr.iter().map(Json::from).collect()),
+            ValueKind::Object(obj) => Json::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), Json::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Numbers that fit in an `i64` become integers; others become floats.
+impl From<Json> for Value {
+    fn from(json: Json) -> Self {
+        match json {
+            Json::Null => Value::null(),
+            Json::Bool(b) => Value::boolean(b),
+            Json::Number(n) => number_to_value(&n),
+            Json::String(s) => Value::string(s),
+            Json::Array(arr) => Value::array(arr.into_iter().map(Value::from).collect()),
+            Json::Object(obj) => {
+                Value::object(obj.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<&Json> for Value {
+    fn from(json: &Json) -> Self {
+        match json {
+            Json::Null => Value::null(),
+            Json::Bool(b) => Value::boolean(*b),
+            Json::Number(n) => number_to_value(n),
+            Json::String(s) => Value::from(s.as_str()),
+            Json::Array(arr) => Value::array(arr.iter().map(Value::from).collect()),
+            Json::Object(obj) => Value::object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn number_to_value(n: &Number) -> Value {
+    match n.as_i64() {
+        Some(i) => Value::integer(i),
+        None => Value::float(n.as_f64().unwrap_or(f64::NAN)),
+    }
+}
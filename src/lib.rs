@@ -34,13 +34,21 @@
 
 // --- Modules ---
 
+pub mod compat;
+pub mod convert;
+pub mod diff;
 pub mod error;
 pub mod include;
+#[cfg(feature = "json")]
+pub mod interop;
 pub mod load;
 pub mod merge;
+pub mod path;
 pub mod schema;
 pub mod serde;
 pub mod syntax;
+pub mod template;
+pub mod testing;
 pub mod value;
 
 // --- Prelude / Re-exports ---
@@ -50,14 +58,24 @@ pub use error::CosynError;
 pub use value::Value;
 
 // Parsing
-pub use syntax::parser::{ParseError, from_str};
+pub use syntax::parser::{
+    DuplicateKeyPolicy, EmptyPolicy, ParseError, ParseOptions, ParseStats, from_str,
+    from_str_with_options, from_str_with_stats,
+};
+
+// Tokens (for tooling)
+pub use syntax::lexer::{Position, Token, TokenWithPos, tokenize};
 
 // Convenience utilities
 pub use load::load_and_merge;
-pub use serde::serializer::{SerializeOptions, to_string, to_string_with_options};
+pub use serde::serializer::{
+    CommentMarker, LineEnding, ScientificPolicy, SerializeOptions, to_string,
+    to_string_with_options,
+};
 
 // Feature re-exports
+pub use diff::compute_diff;
 pub use include::resolve as resolve_includes;
-pub use merge::merge;
-pub use schema::validate;
+pub use merge::{merge, merge_all};
+pub use schema::{DescribeFormat, Schema, describe, validate};
 pub use serde::from_value;
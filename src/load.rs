@@ -1,12 +1,26 @@
 use crate::error::CosynError;
+use crate::include::{IncludeError, ResolveOptions, Trace};
+use crate::merge::{MergeOptions, MergeReport};
+use crate::schema::{self, ValidationReport};
+use crate::syntax::lexer;
+use crate::syntax::parser::{self, ParseOptions};
 use crate::value::{Value, ValueKind};
-use std::path::Path;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
 
 /// Load and merge multiple configuration files.
 ///
 /// The files are loaded in order. Subsequent files override previous ones.
 /// Deep merging is performed using `deep_merge`.
 ///
+/// `include`/`extends` directives in each file are resolved relative to that
+/// file's directory before it is merged; see [`load_and_merge_resolved`].
+///
 /// # Example
 ///
 /// ```no_run
@@ -20,21 +34,779 @@ use std::path::Path;
 /// let config = load_and_merge(&paths).unwrap();
 /// ```
 pub fn load_and_merge(paths: &[&Path]) -> Result<Value, CosynError> {
-    let mut merged = Value::from(ValueKind::Object(indexmap::IndexMap::new()));
+    load_and_merge_resolved(paths)
+}
+
+/// Like [`load_and_merge`], combining arrays according to `options` both
+/// between the files and within their includes.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_and_merge_with_options;
+/// use cosy::merge::{ArrayStrategy, MergeOptions};
+/// use std::path::Path;
+///
+/// let options = MergeOptions {
+///     array_strategy: ArrayStrategy::Append,
+///     ..MergeOptions::default()
+/// };
+/// let paths = [Path::new("base.cosy"), Path::new("local.cosy")];
+/// let config = load_and_merge_with_options(&paths, &options).unwrap();
+/// ```
+pub fn load_and_merge_with_options(
+    paths: &[&Path],
+    options: &MergeOptions,
+) -> Result<Value, CosynError> {
+    let (merged, _) = LoadConfig::new()
+        .with_merge_options(options.clone())
+        .load_files(paths)?;
+    Ok(merged)
+}
+
+/// Load, resolve includes in, and merge multiple configuration files.
+///
+/// Like [`load_and_merge`], but accepts any path-like type. Each file is
+/// parsed and has its `include`/`extends` directives resolved against its
+/// parent directory *before* it is merged on top of the previous files.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_and_merge_resolved;
+///
+/// let config = load_and_merge_resolved(&["base.cosy", "local.cosy"]).unwrap();
+/// ```
+pub fn load_and_merge_resolved<P: AsRef<Path>>(paths: &[P]) -> Result<Value, CosynError> {
+    let (merged, _) = LoadConfig::new().load_files(paths)?;
+    Ok(merged)
+}
+
+/// Like [`load_and_merge_resolved`], but also returns every file the config
+/// was built from: `paths` themselves and the files they include or extend,
+/// canonicalized, without duplicates, in the order they were loaded.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_and_merge_collecting;
+///
+/// let (config, files) = load_and_merge_collecting(&["base.cosy", "local.cosy"]).unwrap();
+/// for file in &files {
+///     println!("watching {}", file.display());
+/// }
+/// ```
+pub fn load_and_merge_collecting<P: AsRef<Path>>(
+    paths: &[P],
+) -> Result<(Value, Vec<PathBuf>), CosynError> {
+    let (merged, _, files) = LoadConfig::new().load(paths, None)?;
+    Ok((merged, files))
+}
+
+/// Like [`load_and_merge_resolved`], but also returns what each file changed
+/// when it was merged over the files before it, for auditing overrides.
+///
+/// The first file's report lists everything it set as added.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_and_merge_reported;
+///
+/// let (config, layers) = load_and_merge_reported(&["base.cosy", "local.cosy"]).unwrap();
+/// for (path, report) in &layers[1..] {
+///     println!("{}:\n{}", path.display(), report);
+/// }
+/// ```
+pub fn load_and_merge_reported<P: AsRef<Path>>(
+    paths: &[P],
+) -> Result<(Value, Vec<(PathBuf, MergeReport)>), CosynError> {
+    let mut layers = Vec::new();
+    let (merged, _, _) = LoadConfig::new().load(paths, Some(&mut layers))?;
+    Ok((merged, layers))
+}
+
+/// Like [`load_and_merge_resolved`], but reads every file, including
+/// included ones, with `tokio::fs` so the runtime is not blocked.
+///
+/// Requires the `async` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() {
+/// let config = cosy::load::load_and_merge_async(&["base.cosy", "local.cosy"])
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn load_and_merge_async<P: AsRef<Path>>(paths: &[P]) -> Result<Value, CosynError> {
+    let options = ResolveOptions::default();
+    let mut layers = Vec::new();
+
+    for path in paths {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| CosynError::Io(e.to_string()))?;
+
+        let mut current = parser::from_str_with_options(&content, options.parse_options.clone())?;
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        crate::include::resolve_async_from(&mut current, base_dir, &options, Some(path))
+            .await
+            .map_err(|e| include_error(e, path))?;
+
+        layers.push(current);
+    }
+    let mut merged = crate::merge::merge_all(layers);
+    crate::merge::clear_directives(&mut merged);
+    Ok(merged)
+}
+
+/// Configuration for the full load pipeline: parse each file, resolve its
+/// includes, merge the files in order, and validate the result.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::LoadConfig;
+///
+/// let schema = cosy::from_str(r#"{ port: "port" }"#).unwrap();
+/// let (config, report) = LoadConfig::new()
+///     .with_schema(schema)
+///     .load_files(&["base.cosy", "local.cosy"])
+///     .unwrap();
+/// if report.has_errors() {
+///     eprintln!("{:?}", report);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadConfig {
+    parse_options: ParseOptions,
+    include_options: ResolveOptions,
+    resolve_includes: bool,
+    merge_options: MergeOptions,
+    env_prefix: Option<String>,
+    schema: Option<Value>,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        LoadConfig {
+            parse_options: ParseOptions::default(),
+            include_options: ResolveOptions::default(),
+            resolve_includes: true,
+            merge_options: MergeOptions::default(),
+            env_prefix: None,
+            schema: None,
+        }
+    }
+}
+
+impl LoadConfig {
+    /// Default options and no schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options for parsing each file, including included ones.
+    ///
+    /// These replace `parse_options` in the include options.
+    pub fn with_parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse_options = options;
+        self
+    }
+
+    /// Options for resolving `include`/`extends`, such as a sandbox `root`.
+    pub fn with_include_options(mut self, options: ResolveOptions) -> Self {
+        self.include_options = options;
+        self
+    }
+
+    /// Whether to resolve `include`/`extends` in each file (default: true).
+    ///
+    /// When `false`, the directives are merged as ordinary keys.
+    pub fn with_resolve_includes(mut self, resolve: bool) -> Self {
+        self.resolve_includes = resolve;
+        self
+    }
+
+    /// How the files are merged, and how included files are merged.
+    ///
+    /// These replace `merge_options` in the include options.
+    pub fn with_merge_options(mut self, options: MergeOptions) -> Self {
+        self.merge_options = options;
+        self
+    }
+
+    /// Override the merged files with [`env_overrides`] for `prefix`, before
+    /// validating.
+    pub fn with_env_overrides(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Validate the merged config against `schema`.
+    pub fn with_schema(mut self, schema: Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Load a single file; see [`load_files`](Self::load_files).
+    pub fn load_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Value, ValidationReport), CosynError> {
+        self.load_files(&[path])
+    }
+
+    /// Load and merge `paths` in order, later files overriding earlier ones.
+    ///
+    /// The report is empty without a schema. Problems with the schema itself
+    /// are reported as `InvalidSchema` items rather than returned as errors.
+    pub fn load_files<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+    ) -> Result<(Value, ValidationReport), CosynError> {
+        let (merged, report, _) = self.load(paths, None)?;
+        Ok((merged, report))
+    }
+
+    /// [`load_files`](Self::load_files), also returning the files loaded and,
+    /// if `layers` is given, recording what each file changed.
+    fn load<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        mut layers: Option<&mut Vec<(PathBuf, MergeReport)>>,
+    ) -> Result<(Value, ValidationReport, Vec<PathBuf>), CosynError> {
+        let include_options = ResolveOptions {
+            parse_options: self.parse_options.clone(),
+            merge_options: self.merge_options.clone(),
+            ..self.include_options.clone()
+        };
+        let mut merged = Value::from(ValueKind::Object(indexmap::IndexMap::new()));
+        let mut files: Vec<PathBuf> = Vec::new();
+
+        for path in paths {
+            let path = path.as_ref();
+            let size = fs::metadata(path)
+                .map_err(|e| CosynError::Io(e.to_string()))?
+                .len();
+            parser::check_input_size(size, &self.parse_options)?;
+            let content =
+                std::fs::read_to_string(path).map_err(|e| CosynError::Io(e.to_string()))?;
+            let canonical = fs::canonicalize(path).map_err(|e| CosynError::Io(e.to_string()))?;
+            if !files.contains(&canonical) {
+                files.push(canonical);
+            }
+
+            let mut current = parser::from_str_with_options(&content, self.parse_options.clone())?;
+
+            // Resolve includes for this file *before* merging it into the main config.
+            if self.resolve_includes {
+                let base_dir = path.parent().unwrap_or(Path::new("."));
+                let included = crate::include::resolve_with_options_from(
+                    &mut current,
+                    base_dir,
+                    &include_options,
+                    Some(path),
+                )
+                .map_err(|e| include_error(e, path))?;
+                for file in included {
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+            }
+
+            let before = layers.is_some().then(|| merged.clone());
+            crate::merge::merge_with_options(&mut merged, current, &self.merge_options).map_err(
+                |e| CosynError::Merge(format!("{} (overridden in \"{}\")", e, path.display())),
+            )?;
+            if let (Some(layers), Some(before)) = (layers.as_deref_mut(), before) {
+                layers.push((path.to_path_buf(), MergeReport::between(&before, &merged)));
+            }
+        }
+        if let Some(prefix) = &self.env_prefix {
+            crate::merge::merge(&mut merged, env_overrides(prefix));
+        }
+        crate::merge::clear_directives(&mut merged);
+
+        let report = match &self.schema {
+            Some(schema) => schema::validate_permissive(&merged, schema),
+            None => ValidationReport::new(),
+        };
+        Ok((merged, report, files))
+    }
+}
+
+/// A [`CosynError::Include`] naming the listed file being loaded, unless
+/// the error already lists the files that led to it.
+fn include_error(error: IncludeError, path: &Path) -> CosynError {
+    match error {
+        IncludeError::ParseError { ref chain, .. } if !chain.is_empty() => {
+            CosynError::Include(error.to_string())
+        }
+        _ => CosynError::Include(format!("{} (loading \"{}\")", error, path.display())),
+    }
+}
+
+/// Like [`load_and_merge_resolved`], but also records which file each leaf
+/// value came from, including files pulled in by `include`/`extends`.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_and_merge_traced;
+///
+/// let (config, trace) = load_and_merge_traced(&["base.cosy", "local.cosy"]).unwrap();
+/// if let Some(source) = trace.get("server.port") {
+///     println!("server.port set at {}", source);
+/// }
+/// ```
+pub fn load_and_merge_traced<P: AsRef<Path>>(paths: &[P]) -> Result<(Value, Trace), CosynError> {
+    let mut layers = Vec::new();
+    let mut trace = Trace::new();
 
     for path in paths {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path).map_err(|e| CosynError::Io(e.to_string()))?;
 
         let mut current = crate::syntax::parser::from_str(&content)?;
 
-        // Resolve includes for this file *before* merging it into the main config.
         let base_dir = path.parent().unwrap_or(Path::new("."));
+        let file_trace = crate::include::resolve_traced_from(&mut current, base_dir, Some(path))
+            .map_err(|e| include_error(e, path))?;
 
-        crate::include::resolve(&mut current, base_dir)
-            .map_err(|e| CosynError::Include(e.to_string()))?;
+        layers.push(current);
+        trace.extend(file_trace);
+    }
+    let mut merged = crate::merge::merge_all(layers);
+    crate::merge::clear_directives(&mut merged);
 
-        crate::merge::merge(&mut merged, current);
+    crate::include::retain_leaves(&mut trace, &merged);
+    Ok((merged, trace))
+}
+
+/// Options for [`load_with_env_overlay_options`].
+#[derive(Debug, Clone)]
+pub struct EnvOverlayOptions {
+    /// Match the prefix exactly and keep the case of each key; otherwise
+    /// the prefix matches in any case and keys are lower-cased (default: false)
+    pub case_sensitive: bool,
+    /// Placed between the prefix and each path segment (default: `_`)
+    pub separator: char,
+}
+
+impl Default for EnvOverlayOptions {
+    fn default() -> Self {
+        EnvOverlayOptions {
+            case_sensitive: false,
+            separator: '_',
+        }
     }
+}
 
-    Ok(merged)
+/// Load `path` (resolving its includes) and override it with environment
+/// variables named `PREFIX_PATH_TO_KEY`.
+///
+/// `APP_SERVER_PORT=9000` sets `server.port` to `9000` for the prefix
+/// `APP`. Values are inferred as for [`env_overrides`]: `true`, `false`,
+/// `null`, integers and floats, or else strings taken as they are.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::load_with_env_overlay;
+///
+/// // With APP_SERVER_PORT=9000 set
+/// let config = load_with_env_overlay("app.cosy", "APP").unwrap();
+/// ```
+pub fn load_with_env_overlay<P: AsRef<Path>>(path: P, prefix: &str) -> Result<Value, CosynError> {
+    load_with_env_overlay_options(path, prefix, &EnvOverlayOptions::default())
+}
+
+/// Like [`load_with_env_overlay`], with control over how variable names map
+/// to keys.
+///
+/// Variables are applied in name order, so a variable naming a whole object
+/// (`APP_SERVER`) is overridden by more specific ones (`APP_SERVER_PORT`).
+pub fn load_with_env_overlay_options<P: AsRef<Path>>(
+    path: P,
+    prefix: &str,
+    options: &EnvOverlayOptions,
+) -> Result<Value, CosynError> {
+    let (config, _) = LoadConfig::new().load_file(path)?;
+    let layers = env_layers(prefix, options);
+    Ok(crate::merge::merge_all(
+        std::iter::once(config).chain(layers),
+    ))
+}
+
+/// One overlay per environment variable with the prefix, in name order.
+fn env_layers(prefix: &str, options: &EnvOverlayOptions) -> Vec<Value> {
+    let separator = options.separator.to_string();
+    prefixed_env_vars(prefix, &separator, options.case_sensitive)
+        .into_iter()
+        .map(|(path, raw)| nest(&path, Value::new(lexer::infer_env_value(raw))))
+        .collect()
+}
+
+/// Override keys from environment variables such as
+/// `MYAPP_SERVER__PORT=9000`, which sets `server.port`.
+///
+/// The variables starting with `prefix` become one object, ready to merge
+/// over the rest of the config. `__` separates nested keys, so single
+/// underscores stay in key names (`MYAPP_LOG_LEVEL` sets `log_level`), and
+/// keys are lower-cased. Values are inferred as for `${VAR}` outside a
+/// string: `true`, `false`, `null`, integers and floats, or else strings.
+///
+/// A variable naming a whole object (`MYAPP_SERVER`) is overridden by more
+/// specific ones (`MYAPP_SERVER__PORT`).
+pub fn env_overrides(prefix: &str) -> Value {
+    env_overrides_with_separator(prefix, "__")
+}
+
+/// Like [`env_overrides`], splitting keys on `separator` instead of `__`.
+pub fn env_overrides_with_separator(prefix: &str, separator: &str) -> Value {
+    let layers = prefixed_env_vars(prefix, separator, false)
+        .into_iter()
+        .map(|(path, raw)| nest(&path, Value::new(lexer::infer_env_value(raw))));
+    crate::merge::merge_all(std::iter::once(Value::object(indexmap::IndexMap::new())).chain(layers))
+}
+
+/// The environment variables starting with `prefix`, in name order, as the
+/// keys they set and their value.
+fn prefixed_env_vars(
+    prefix: &str,
+    separator: &str,
+    case_sensitive: bool,
+) -> Vec<(Vec<String>, String)> {
+    let mut variables: Vec<_> = std::env::vars().collect();
+    variables.sort();
+    variables
+        .into_iter()
+        .filter_map(|(name, raw)| {
+            Some((env_key_path(&name, prefix, separator, case_sensitive)?, raw))
+        })
+        .collect()
+}
+
+/// The keys a variable sets, if its name has the prefix.
+///
+/// The prefix is followed by the separator or `_`, and may end with either,
+/// so `APP` and `APP_` both match `APP_PORT`.
+fn env_key_path(
+    name: &str,
+    prefix: &str,
+    separator: &str,
+    case_sensitive: bool,
+) -> Option<Vec<String>> {
+    let prefix = prefix
+        .strip_suffix(separator)
+        .or_else(|| prefix.strip_suffix('_'))
+        .unwrap_or(prefix);
+    let (head, rest) = name.split_at_checked(prefix.len())?;
+    let matches = if case_sensitive {
+        head == prefix
+    } else {
+        head.eq_ignore_ascii_case(prefix)
+    };
+    if !matches {
+        return None;
+    }
+    let rest = rest
+        .strip_prefix(separator)
+        .or_else(|| rest.strip_prefix('_'))?;
+    let rest = if case_sensitive {
+        rest.to_string()
+    } else {
+        rest.to_lowercase()
+    };
+    let segments: Vec<String> = rest.split(separator).map(str::to_string).collect();
+    (!segments.iter().any(String::is_empty)).then_some(segments)
+}
+
+/// `value` nested in objects under `path`.
+fn nest(path: &[String], value: Value) -> Value {
+    path.iter().rev().fold(value, |value, key| {
+        Value::from_iter([(key.as_str(), value)])
+    })
+}
+
+/// Assembles a config from layers, each overriding the ones added before
+/// it: typically defaults, then system and user files, then environment
+/// variables, then command-line overrides.
+///
+/// Files have their includes resolved as with [`LoadConfig`]; the merged
+/// result is checked against the schema, if one is set.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::ConfigBuilder;
+///
+/// let config = ConfigBuilder::new()
+///     .add_defaults_str(r#"{ server: { host: "localhost", port: 8080 } }"#)
+///     .add_file("/etc/myapp/config.cosy")
+///     .add_file_optional("myapp.local.cosy")
+///     .add_env_prefix("MYAPP_")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    sources: Vec<ConfigSource>,
+    loader: LoadConfig,
+    schema: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+enum ConfigSource {
+    Value(Value),
+    Text(String),
+    File {
+        path: PathBuf,
+        optional: bool,
+    },
+    Env {
+        prefix: String,
+        options: EnvOverlayOptions,
+    },
+    EnvOverrides(String),
+}
+
+impl ConfigBuilder {
+    /// A builder with no sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer of built-in defaults.
+    pub fn add_defaults(self, defaults: Value) -> Self {
+        self.add(ConfigSource::Value(defaults))
+    }
+
+    /// Add a layer of defaults written in COSY, parsed by [`build`](Self::build).
+    pub fn add_defaults_str(self, defaults: &str) -> Self {
+        self.add(ConfigSource::Text(defaults.to_string()))
+    }
+
+    /// Add a file that must exist.
+    pub fn add_file(self, path: impl AsRef<Path>) -> Self {
+        self.add(ConfigSource::File {
+            path: path.as_ref().to_path_buf(),
+            optional: false,
+        })
+    }
+
+    /// Add a file that is skipped if it does not exist.
+    pub fn add_file_optional(self, path: impl AsRef<Path>) -> Self {
+        self.add(ConfigSource::File {
+            path: path.as_ref().to_path_buf(),
+            optional: true,
+        })
+    }
+
+    /// Add the environment variables starting with `prefix`, as in
+    /// [`load_with_env_overlay`]. They are read by [`build`](Self::build).
+    pub fn add_env_prefix(self, prefix: &str) -> Self {
+        self.add_env_prefix_with_options(prefix, EnvOverlayOptions::default())
+    }
+
+    /// Like [`add_env_prefix`](Self::add_env_prefix), mapping names to keys
+    /// according to `options`.
+    pub fn add_env_prefix_with_options(self, prefix: &str, options: EnvOverlayOptions) -> Self {
+        self.add(ConfigSource::Env {
+            prefix: prefix.to_string(),
+            options,
+        })
+    }
+
+    /// Add the environment variables starting with `prefix`, nested on `__`
+    /// as in [`env_overrides`]. They are read by [`build`](Self::build).
+    pub fn add_env_overrides(self, prefix: &str) -> Self {
+        self.add(ConfigSource::EnvOverrides(prefix.to_string()))
+    }
+
+    /// Add a layer of explicit overrides, such as command-line flags.
+    pub fn add_overrides(self, overrides: Value) -> Self {
+        self.add(ConfigSource::Value(overrides))
+    }
+
+    /// Options for parsing files and COSY defaults.
+    pub fn with_parse_options(mut self, options: ParseOptions) -> Self {
+        self.loader = self.loader.with_parse_options(options);
+        self
+    }
+
+    /// Options for resolving `include`/`extends` in files.
+    pub fn with_include_options(mut self, options: ResolveOptions) -> Self {
+        self.loader = self.loader.with_include_options(options);
+        self
+    }
+
+    /// Whether to resolve `include`/`extends` in files (default: true).
+    pub fn with_resolve_includes(mut self, resolve: bool) -> Self {
+        self.loader = self.loader.with_resolve_includes(resolve);
+        self
+    }
+
+    /// Fail the build if the merged config does not match `schema`.
+    pub fn with_schema(mut self, schema: Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    fn add(mut self, source: ConfigSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Read every source and merge them in order.
+    ///
+    /// Schema errors are returned as a [`CosynError::Validation`] listing
+    /// them; warnings are ignored.
+    pub fn build(&self) -> Result<Value, CosynError> {
+        let mut layers = Vec::new();
+        for source in &self.sources {
+            match source {
+                ConfigSource::Value(value) => layers.push(value.clone()),
+                ConfigSource::Text(text) => layers.push(parser::from_str_with_options(
+                    text,
+                    self.loader.parse_options.clone(),
+                )?),
+                ConfigSource::File { path, optional } => {
+                    if *optional && !path.exists() {
+                        continue;
+                    }
+                    layers.push(self.loader.load_file(path)?.0);
+                }
+                ConfigSource::Env { prefix, options } => {
+                    layers.extend(env_layers(prefix, options));
+                }
+                ConfigSource::EnvOverrides(prefix) => layers.push(env_overrides(prefix)),
+            }
+        }
+        let mut config = crate::merge::merge_all(layers);
+        crate::merge::clear_directives(&mut config);
+
+        if let Some(schema) = &self.schema {
+            let report = schema::validate_permissive(&config, schema);
+            let errors: Vec<_> = report.errors().map(ToString::to_string).collect();
+            if !errors.is_empty() {
+                return Err(CosynError::Validation(errors.join("; ")));
+            }
+        }
+        Ok(config)
+    }
+
+    /// [`build`](Self::build), then deserialize the config into `T`.
+    pub fn build_into<T: DeserializeOwned>(&self) -> Result<T, CosynError> {
+        crate::serde::from_value(self.build()?)
+    }
+}
+
+/// A background thread started by [`watch_and_reload`].
+///
+/// The thread stops when [`stop`](Self::stop) is called or the watcher is
+/// dropped.
+#[derive(Debug)]
+pub struct PollingWatcher {
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PollingWatcher {
+    /// Stop polling and wait for the thread to finish.
+    pub fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for PollingWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Longest sleep between checks for [`PollingWatcher::stop`], so that
+/// stopping doesn't wait out a long `interval`.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Reload `paths` whenever one of them changes, without the `notify` crate.
+///
+/// A background thread checks the files' modification times every
+/// `interval`. When any of them differs from the last check (including a
+/// file appearing or disappearing), the files are reloaded with
+/// [`load_and_merge_resolved`] and the result is passed to `callback`.
+/// Files pulled in by `include`/`extends` are not watched.
+///
+/// # Example
+///
+/// ```no_run
+/// use cosy::load::watch_and_reload;
+/// use std::time::Duration;
+///
+/// let _watcher = watch_and_reload(&["app.cosy"], Duration::from_secs(1), |result| {
+///     match result {
+///         Ok(config) => println!("reloaded: {}", config),
+///         Err(e) => eprintln!("reload failed: {}", e),
+///     }
+/// });
+/// ```
+pub fn watch_and_reload<P, F>(paths: &[P], interval: Duration, callback: F) -> PollingWatcher
+where
+    P: AsRef<Path>,
+    F: Fn(Result<Value, CosynError>) + Send + 'static,
+{
+    let paths: Vec<PathBuf> = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    // Read the starting times before returning, so changes made right after
+    // this call are not missed
+    let mut last_modified = modification_times(&paths);
+    let thread_stopped = Arc::clone(&stopped);
+    let handle = thread::spawn(move || {
+        while sleep_unless_stopped(interval, &thread_stopped) {
+            let modified = modification_times(&paths);
+            if modified != last_modified {
+                last_modified = modified;
+                callback(load_and_merge_resolved(&paths));
+            }
+        }
+    });
+
+    PollingWatcher {
+        stopped,
+        handle: Some(handle),
+    }
+}
+
+fn modification_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Sleep for `duration`, returning `false` early if `stopped` is set.
+fn sleep_unless_stopped(duration: Duration, stopped: &AtomicBool) -> bool {
+    let mut remaining = duration;
+    loop {
+        if stopped.load(Ordering::Relaxed) {
+            return false;
+        }
+        if remaining.is_zero() {
+            return true;
+        }
+        let step = remaining.min(STOP_CHECK_INTERVAL);
+        thread::sleep(step);
+        remaining -= step;
+    }
 }
@@ -1,5 +1,7 @@
 use std::env;
 use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
 use std::process;
 
 fn main() {
@@ -12,12 +14,113 @@ fn main() {
 
     match args[1].as_str() {
         "check" => {
-            if args.len() < 3 {
+            let mut recursive = false;
+            let mut exclude = Vec::new();
+            let mut path = None;
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--recursive" | "-r" => recursive = true,
+                    "--exclude" => match rest.next() {
+                        Some(glob) => exclude.push(glob.clone()),
+                        None => {
+                            eprintln!("Error: '--exclude' requires a glob pattern.");
+                            process::exit(1);
+                        }
+                    },
+                    _ => path = Some(arg.as_str()),
+                }
+            }
+            let Some(path) = path else {
                 eprintln!("Error: Missing file path for 'check' command.");
                 print_usage();
                 process::exit(1);
+            };
+            if Path::new(path).is_dir() {
+                let (total, errors) = check_directory(Path::new(path), recursive, &exclude);
+                println!("Checked {} files, {} errors", total, errors);
+                if errors > 0 {
+                    process::exit(1);
+                }
+            } else {
+                check_file(path);
+            }
+        }
+        "diff" => {
+            if args.len() < 4 {
+                eprintln!("Error: 'diff' requires two file paths.");
+                print_usage();
+                process::exit(1);
+            }
+            diff_files(&args[2], &args[3]);
+        }
+        "validate" => {
+            if args.len() < 4 {
+                eprintln!("Error: 'validate' requires a file and a schema path.");
+                print_usage();
+                process::exit(1);
             }
-            check_file(&args[2]);
+            let show_info = args[4..].iter().any(|a| a == "--show-info");
+            validate_file(&args[2], &args[3], show_info);
+        }
+        "init" => {
+            let mut template = "basic";
+            let mut output = "config.cosy";
+            let mut force = false;
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--force" => force = true,
+                    flag @ ("--template" | "--output") => {
+                        let Some(value) = rest.next() else {
+                            eprintln!("Error: '{}' requires a value.", flag);
+                            process::exit(1);
+                        };
+                        if flag == "--template" {
+                            template = value;
+                        } else {
+                            output = value;
+                        }
+                    }
+                    other => {
+                        eprintln!("Error: Unknown option '{}' for 'init' command.", other);
+                        print_usage();
+                        process::exit(1);
+                    }
+                }
+            }
+            init_config(template, output, force);
+        }
+        "describe" => {
+            let mut schema = None;
+            let mut format = "markdown";
+            let mut rest = args[2..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    flag @ ("--schema" | "--format") => {
+                        let Some(value) = rest.next() else {
+                            eprintln!("Error: '{}' requires a value.", flag);
+                            process::exit(1);
+                        };
+                        if flag == "--schema" {
+                            schema = Some(value.as_str());
+                        } else {
+                            format = value;
+                        }
+                    }
+                    other => {
+                        eprintln!("Error: Unknown option '{}' for 'describe' command.", other);
+                        print_usage();
+                        process::exit(1);
+                    }
+                }
+            }
+            let Some(schema) = schema else {
+                eprintln!("Error: 'describe' requires '--schema <file>'.");
+                print_usage();
+                process::exit(1);
+            };
+            describe_schema(schema, format);
         }
         "help" | "--help" | "-h" => {
             print_usage();
@@ -34,19 +137,132 @@ fn print_usage() {
     println!("COSY - Comfortable Object Syntax, Yay!");
     println!("\nUsage:");
     println!("  cosy check <file>   Parse and validate a file syntax");
+    println!("  cosy check <dir> [--recursive|-r] [--exclude <glob>]...");
+    println!("                      Check every .cosy file in a directory");
+    println!("  cosy diff <a> <b>   Show differences between two files");
+    println!("  cosy validate <file> <schema> [--show-info]");
+    println!("                      Validate a file against a schema");
+    println!("                      (--show-info also prints informational notes)");
+    println!("  cosy init [--template basic|server|database|all] [--output <file>] [--force]");
+    println!("                      Write a commented starter config");
+    println!("                      (default: basic template to ./config.cosy)");
+    println!("  cosy describe --schema <file> [--format markdown|text|html]");
+    println!("                      Document a schema's fields (default: markdown)");
     println!("  cosy help           Show this help message");
 }
 
 fn check_file(path: &str) {
     println!("Checking '{}'...", path);
+    if !check_syntax(path) {
+        process::exit(1);
+    }
+}
 
+/// Parse `path`, printing the outcome. Returns whether it parsed.
+fn check_syntax(path: &str) -> bool {
     match fs::read_to_string(path) {
         Ok(content) => match cosy::from_str(&content) {
             Ok(_) => {
                 println!("✅ Syntax OK");
+                true
             }
             Err(e) => {
                 eprintln!("❌ Parse Error: {}", e);
+                false
+            }
+        },
+        Err(e) => {
+            eprintln!("❌ IO Error: Failed to read file '{}': {}", path, e);
+            false
+        }
+    }
+}
+
+/// Check every `.cosy` file in `dir`, descending into subdirectories when
+/// `recursive`, and skipping paths relative to `dir` that match an `exclude`
+/// glob. Returns the number of files checked and how many failed.
+#[cfg(feature = "cli")]
+fn check_directory(dir: &Path, recursive: bool, exclude: &[String]) -> (usize, usize) {
+    let walker = walkdir::WalkDir::new(dir)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .sort_by_file_name();
+    let (mut total, mut errors) = (0, 0);
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("❌ IO Error: {}", e);
+                errors += 1;
+                continue;
+            }
+        };
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "cosy") {
+            continue;
+        }
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if exclude
+            .iter()
+            .any(|glob| cosy::include::wildcard_match(glob, &relative))
+        {
+            continue;
+        }
+
+        total += 1;
+        let path = path.to_string_lossy();
+        println!("Checking '{}'...", path);
+        if !check_syntax(&path) {
+            errors += 1;
+        }
+    }
+    (total, errors)
+}
+
+#[cfg(not(feature = "cli"))]
+fn check_directory(_dir: &Path, _recursive: bool, _exclude: &[String]) -> (usize, usize) {
+    eprintln!("Error: Checking a directory requires cosy to be built with the 'cli' feature.");
+    process::exit(1);
+}
+
+/// Starter configs for `cosy init`, by template name
+const TEMPLATES: &[(&str, &str)] = &[
+    ("basic", include_str!("templates/basic.cosy")),
+    ("server", include_str!("templates/server.cosy")),
+    ("database", include_str!("templates/database.cosy")),
+    ("all", include_str!("templates/all.cosy")),
+];
+
+fn init_config(template: &str, output: &str, force: bool) {
+    let Some((_, content)) = TEMPLATES.iter().find(|(name, _)| *name == template) else {
+        let names: Vec<_> = TEMPLATES.iter().map(|(name, _)| *name).collect();
+        eprintln!(
+            "Error: Unknown template '{}'; expected one of {}",
+            template,
+            names.join(", ")
+        );
+        process::exit(1);
+    };
+    if Path::new(output).exists() && !force {
+        eprintln!(
+            "❌ '{}' already exists; pass --force to overwrite it",
+            output
+        );
+        process::exit(1);
+    }
+    if let Err(e) = fs::write(output, content) {
+        eprintln!("❌ IO Error: Failed to write file '{}': {}", output, e);
+        process::exit(1);
+    }
+    println!("✅ Wrote the '{}' template to '{}'", template, output);
+}
+
+fn parse_file(path: &str) -> cosy::Value {
+    match fs::read_to_string(path) {
+        Ok(content) => match cosy::from_str(&content) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("❌ Parse Error in '{}': {}", path, e);
                 process::exit(1);
             }
         },
@@ -56,3 +272,81 @@ fn check_file(path: &str) {
         }
     }
 }
+
+fn diff_files(before_path: &str, after_path: &str) {
+    let before = parse_file(before_path);
+    let after = parse_file(after_path);
+
+    let changes: Vec<_> = cosy::diff::compute_diff(&before, &after)
+        .into_iter()
+        .filter(|c| !matches!(c, cosy::diff::Change::Unchanged(..)))
+        .collect();
+
+    if changes.is_empty() {
+        println!("No differences");
+        return;
+    }
+
+    if std::io::stdout().is_terminal() {
+        print!("{}", cosy::diff::render_diff_colored(&changes));
+    } else {
+        print!("{}", cosy::diff::render_diff(&changes));
+    }
+    // Like diff(1), signal "files differ" with exit status 1
+    process::exit(1);
+}
+
+fn validate_file(path: &str, schema_path: &str, show_info: bool) {
+    let instance = parse_file(path);
+    let schema = parse_file(schema_path);
+
+    let mut report = match cosy::schema::validate(&instance, &schema) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("❌ Invalid schema '{}': {}", schema_path, e);
+            process::exit(1);
+        }
+    };
+    cosy::schema::deduplicate_report(&mut report);
+
+    for item in &report {
+        if item.level == cosy::schema::ValidationLevel::Info && !show_info {
+            continue;
+        }
+        let level = match item.level {
+            cosy::schema::ValidationLevel::Error => "error",
+            cosy::schema::ValidationLevel::Warning => "warning",
+            cosy::schema::ValidationLevel::Info => "info",
+        };
+        match item.position {
+            Some(pos) => println!(
+                "{}:{}:{}: {}: {}",
+                path, pos.line, pos.column, level, item.message
+            ),
+            None => println!("{}: {}: {} (at {})", path, level, item.message, item.path),
+        }
+    }
+    if report.is_valid() {
+        println!("✅ '{}' matches the schema", path);
+    } else {
+        eprintln!("❌ {} error(s) in '{}'", report.errors().count(), path);
+        process::exit(1);
+    }
+}
+
+fn describe_schema(schema_path: &str, format: &str) {
+    let format = match format {
+        "markdown" | "md" => cosy::DescribeFormat::Markdown,
+        "text" => cosy::DescribeFormat::PlainText,
+        "html" => cosy::DescribeFormat::Html,
+        other => {
+            eprintln!(
+                "Error: Unknown format '{}' (expected markdown, text or html).",
+                other
+            );
+            process::exit(1);
+        }
+    };
+    let schema = parse_file(schema_path);
+    print!("{}", cosy::describe(&schema, format));
+}
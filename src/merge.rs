@@ -1,42 +1,559 @@
+use crate::path::{join_index, join_key};
 use crate::value::{Value, ValueKind};
+use std::fmt;
+
+/// How [`merge_with_options`] combines two arrays
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ArrayStrategy {
+    /// The override array replaces the base array (default)
+    #[default]
+    Replace,
+    /// The override's items are appended to the base array
+    Append,
+    /// Like `Append`, skipping items structurally equal to one already present
+    Unique,
+    /// Objects with the same scalar value at `key` are deep-merged in place;
+    /// other override items are appended, and unmatched base items are kept
+    MergeBy { key: String },
+}
+
+/// A per-key merge rule, written as a suffix between a key and its colon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeDirective {
+    /// `key!: ...` replaces the lower layer's value instead of deep-merging
+    Replace,
+    /// `key+: [...]` appends to the lower layer's array, whatever the
+    /// array strategy
+    Append,
+}
+
+/// What [`merge_with_options`] does when an override value has a different
+/// type than the base value it would replace.
+///
+/// Integers and floats are both numbers and never conflict, and neither
+/// does `null` with any type, so a layer can fill in a `null` default or
+/// clear a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypeConflict {
+    /// The override value replaces the base value (default)
+    #[default]
+    Override,
+    /// The merge fails with a [`MergeError`]
+    Error,
+    /// The base value is kept and the override value dropped
+    KeepBase,
+}
+
+/// Options for [`merge_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// How arrays present on both sides are combined (default: [`ArrayStrategy::Replace`])
+    pub array_strategy: ArrayStrategy,
+    /// What happens when a value would replace one of another type
+    /// (default: [`TypeConflict::Override`])
+    pub on_type_conflict: TypeConflict,
+}
+
+/// An override value whose type conflicts with the base value: the error
+/// with [`TypeConflict::Error`], and what [`merge_with_callback`] reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeError {
+    /// Path of the value, e.g. `server.port`, quoting keys as in
+    /// [`crate::path`]; empty for the top level
+    pub path: String,
+    pub base_type: &'static str,
+    pub override_type: &'static str,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Type conflict at the top level")?;
+        } else {
+            write!(f, "Type conflict at '{}'", self.path)?;
+        }
+        write!(
+            f,
+            ": cannot override {} with {}",
+            self.base_type, self.override_type
+        )
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// Something [`merge_with_options`] could not merge as asked, and how it
+/// went instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeWarning {
+    /// Path of the item, e.g. `listeners[2]` for the override's third listener
+    pub path: String,
+    pub message: String,
+}
 
 /// Deeply merges `override_val` into `base`.
 ///
 /// Use cases:
 /// - **Objects**: Keys in `override_val` replace keys in `base`. Nested objects are merged recursively.
-/// - **Arrays**: `override_val` replaces `base`. No array merging (concatenation) is performed.
+/// - **Arrays**: `override_val` replaces `base`. No array merging (concatenation) is performed;
+///   see [`merge_with_options`] for that.
 /// - **Primitives**: `override_val` replaces `base`.
+///
+/// A [`MergeDirective`] on an override value takes precedence and stays on
+/// the merged value, so it also applies to layers merged under it later;
+/// [`clear_directives`] removes them once every layer is merged.
 pub fn merge(base: &mut Value, override_val: Value) {
+    merge_with_options(base, override_val, &MergeOptions::default())
+        .expect("merging with TypeConflict::Override cannot fail");
+}
+
+/// Merges `layers` in order, each overriding the ones before it.
+///
+/// The first layer is the base; an empty `layers` gives an empty object.
+/// Merge directives are kept, as with [`merge`].
+///
+/// ```
+/// let layers = [
+///     cosy::from_str("{ host: \"localhost\", port: 80 }").unwrap(),
+///     cosy::from_str("{ port: 8080 }").unwrap(),
+/// ];
+/// let merged = cosy::merge_all(layers);
+/// assert_eq!(merged, cosy::from_str("{ host: \"localhost\", port: 8080 }").unwrap());
+/// ```
+pub fn merge_all(layers: impl IntoIterator<Item = Value>) -> Value {
+    let mut layers = layers.into_iter();
+    let mut merged = layers
+        .next()
+        .unwrap_or_else(|| Value::object(indexmap::IndexMap::new()));
+    for layer in layers {
+        merge(&mut merged, layer);
+    }
+    merged
+}
+
+/// Merges like [`merge`], combining arrays on both sides according to
+/// `options.array_strategy`, at any depth.
+///
+/// Returns a warning for each override item that [`ArrayStrategy::MergeBy`]
+/// appended because it had no scalar value at the key, or the first type
+/// conflict with [`TypeConflict::Error`]; `base` is then partly merged.
+///
+/// # Example
+///
+/// ```
+/// use cosy::merge::{ArrayStrategy, MergeOptions, merge_with_options};
+///
+/// let mut base = cosy::from_str("{ plugins: [\"auth\", \"log\"] }").unwrap();
+/// let local = cosy::from_str("{ plugins: [\"log\", \"trace\"] }").unwrap();
+/// let options = MergeOptions {
+///     array_strategy: ArrayStrategy::Unique,
+///     ..MergeOptions::default()
+/// };
+/// merge_with_options(&mut base, local, &options).unwrap();
+/// assert_eq!(base, cosy::from_str("{ plugins: [\"auth\", \"log\", \"trace\"] }").unwrap());
+/// ```
+pub fn merge_with_options(
+    base: &mut Value,
+    override_val: Value,
+    options: &MergeOptions,
+) -> Result<Vec<MergeWarning>, MergeError> {
+    merge_with_callback(base, override_val, options, &mut |_| {})
+}
+
+/// Merges like [`merge_with_options`], calling `on_type_change` for every
+/// value that the override would replace with one of another type, before
+/// `options.on_type_conflict` decides what happens to it.
+///
+/// # Example
+///
+/// ```
+/// use cosy::merge::{MergeOptions, merge_with_callback};
+///
+/// let mut base = cosy::from_str("{ port: 8080 }").unwrap();
+/// let local = cosy::from_str("{ port: \"auto\" }").unwrap();
+/// let mut changed = Vec::new();
+/// merge_with_callback(&mut base, local, &MergeOptions::default(), &mut |c| {
+///     changed.push(c.path.clone())
+/// })
+/// .unwrap();
+/// assert_eq!(changed, ["port"]);
+/// ```
+pub fn merge_with_callback(
+    base: &mut Value,
+    override_val: Value,
+    options: &MergeOptions,
+    on_type_change: &mut dyn FnMut(&MergeError),
+) -> Result<Vec<MergeWarning>, MergeError> {
+    let mut state = Merging {
+        options,
+        warnings: Vec::new(),
+        on_type_change,
+    };
+    merge_at(base, override_val, "", &mut state)?;
+    Ok(state.warnings)
+}
+
+/// What [`merge_at`] carries down the tree
+struct Merging<'a> {
+    options: &'a MergeOptions,
+    warnings: Vec<MergeWarning>,
+    on_type_change: &'a mut dyn FnMut(&MergeError),
+}
+
+fn merge_at(
+    base: &mut Value,
+    override_val: Value,
+    path: &str,
+    state: &mut Merging<'_>,
+) -> Result<(), MergeError> {
     let Value {
         kind: override_kind,
         comments: override_comments,
+        trailing_comment: override_trailing_comment,
+        closing_comments: override_closing_comments,
+        merge_directive: override_directive,
+        position: override_position,
     } = override_val;
+    let replace = override_directive == Some(MergeDirective::Replace);
+
+    match (&mut base.kind, override_kind) {
+        (ValueKind::Object(base_map), ValueKind::Object(override_map)) if !replace => {
+            for (k, v) in override_map {
+                if let Some(base_v) = base_map.get_mut(&k) {
+                    merge_at(base_v, v, &join_key(path, &k), state)?;
+                } else {
+                    base_map.insert(k, v);
+                }
+            }
+        }
+        (ValueKind::Array(base_items), ValueKind::Array(override_items))
+            if override_directive == Some(MergeDirective::Append) =>
+        {
+            base_items.extend(override_items);
+        }
+        (ValueKind::Array(base_items), ValueKind::Array(override_items))
+            if !replace && state.options.array_strategy != ArrayStrategy::Replace =>
+        {
+            for (i, item) in override_items.into_iter().enumerate() {
+                match &state.options.array_strategy {
+                    ArrayStrategy::Unique if base_items.iter().any(|b| same_data(b, &item)) => {}
+                    ArrayStrategy::MergeBy { key } => {
+                        let item_path = join_index(path, i);
+                        let Some(id) = scalar_at(&item, key) else {
+                            state.warnings.push(MergeWarning {
+                                message: format!("No scalar '{}' to merge by; appended", key),
+                                path: item_path,
+                            });
+                            base_items.push(item);
+                            continue;
+                        };
+                        match base_items
+                            .iter_mut()
+                            .find(|base_item| scalar_at(base_item, key) == Some(id))
+                        {
+                            Some(base_item) => merge_at(base_item, item, &item_path, state)?,
+                            None => base_items.push(item),
+                        }
+                    }
+                    _ => base_items.push(item),
+                }
+            }
+        }
+        (base_kind, override_kind) => {
+            if conflicts(base_kind, &override_kind) {
+                let conflict = MergeError {
+                    path: path.to_string(),
+                    base_type: base_kind.type_name(),
+                    override_type: override_kind.type_name(),
+                };
+                (state.on_type_change)(&conflict);
+                match state.options.on_type_conflict {
+                    TypeConflict::Override => {}
+                    TypeConflict::Error => return Err(conflict),
+                    TypeConflict::KeepBase => return Ok(()),
+                }
+            }
+            *base = Value {
+                kind: override_kind,
+                comments: override_comments,
+                trailing_comment: override_trailing_comment,
+                closing_comments: override_closing_comments,
+                merge_directive: override_directive,
+                position: override_position,
+            };
+        }
+    }
+    if override_directive.is_some() {
+        base.merge_directive = override_directive;
+    }
+    Ok(())
+}
+
+/// Whether replacing `base` with `override_kind` changes its type; `null`
+/// is compatible with every type
+fn conflicts(base: &ValueKind, override_kind: &ValueKind) -> bool {
+    let number = |kind: &ValueKind| matches!(kind, ValueKind::Integer(_) | ValueKind::Float(_));
+    let compatible = base.type_name() == override_kind.type_name()
+        || (number(base) && number(override_kind))
+        || matches!(base, ValueKind::Null)
+        || matches!(override_kind, ValueKind::Null);
+    !compatible
+}
+
+/// Whether two values hold the same data, ignoring comments at any depth
+fn same_data(a: &Value, b: &Value) -> bool {
+    match (&a.kind, &b.kind) {
+        (ValueKind::Array(a), ValueKind::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_data(a, b))
+        }
+        (ValueKind::Object(a), ValueKind::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, a)| b.get(k).is_some_and(|b| same_data(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Remove every [`MergeDirective`] in `value`, once there is nothing left
+/// to merge it over.
+pub fn clear_directives(value: &mut Value) {
+    value.merge_directive = None;
+    match &mut value.kind {
+        ValueKind::Array(items) => items.iter_mut().for_each(clear_directives),
+        ValueKind::Object(map) => map.values_mut().for_each(clear_directives),
+        _ => {}
+    }
+}
+
+/// The value at `key` of an object item, if it is a scalar
+fn scalar_at<'a>(item: &'a Value, key: &str) -> Option<&'a ValueKind> {
+    let ValueKind::Object(map) = &item.kind else {
+        return None;
+    };
+    map.get(key)
+        .map(|value| &value.kind)
+        .filter(|kind| !matches!(kind, ValueKind::Array(_) | ValueKind::Object(_)))
+}
+
+/// What a merge did at one path, as recorded by [`merge_traced`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeChange {
+    /// The path was not in the base
+    Added { new: Value },
+    /// The value changed, keeping its type
+    Overridden { old: Value, new: Value },
+    /// The value changed to one of another type
+    TypeChanged { old: Value, new: Value },
+    /// The path was dropped, by a `key!:` directive replacing its object
+    Removed { old: Value },
+}
+
+/// One path changed by a merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeEntry {
+    /// Dot-separated path of the key, e.g. `server.port`; empty when the
+    /// whole value was replaced
+    pub path: String,
+    pub change: MergeChange,
+}
+
+/// Every path a merge changed, in the merged value's key order.
+///
+/// Objects merged key by key are not entries themselves; a new object or a
+/// replaced array is a single entry. The `Display` form has one line per
+/// entry, for logs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    pub entries: Vec<MergeEntry>,
+}
+
+impl MergeReport {
+    /// The changes between `before` and `after`, the same value before and
+    /// after a merge.
+    pub(crate) fn between(before: &Value, after: &Value) -> Self {
+        let mut report = MergeReport::default();
+        report.record(Some(before), after, "");
+        report
+    }
 
-    // We can only merge if both are objects.
-    // However, we can't easily check `kind` without borrowing.
-    // But we need to update `*base` if they are NOT both objects.
-
-    let base_is_obj = matches!(base.kind, ValueKind::Object(_));
-    let override_is_obj = matches!(override_kind, ValueKind::Object(_));
-
-    if base_is_obj && override_is_obj {
-        // Both match, we must destructure both and merge.
-        if let ValueKind::Object(base_map) = &mut base.kind
-            && let ValueKind::Object(override_map) = override_kind {
-                for (k, v) in override_map {
-                    if let Some(base_v) = base_map.get_mut(&k) {
-                        merge(base_v, v);
-                    } else {
-                        base_map.insert(k, v);
+    fn record(&mut self, before: Option<&Value>, after: &Value, path: &str) {
+        let change = match (before, &after.kind) {
+            (
+                Some(Value {
+                    kind: ValueKind::Object(old_map),
+                    ..
+                }),
+                ValueKind::Object(new_map),
+            ) => {
+                for (key, value) in new_map {
+                    self.record(old_map.get(key), value, &join_key(path, key));
+                }
+                for (key, old) in old_map {
+                    if !new_map.contains_key(key) {
+                        self.push(
+                            &join_key(path, key),
+                            MergeChange::Removed { old: old.clone() },
+                        );
                     }
                 }
+                return;
+            }
+            (None, _) => MergeChange::Added { new: after.clone() },
+            (Some(old), new_kind) if old.kind == *new_kind => return,
+            (Some(old), new_kind) if old.kind.type_name() == new_kind.type_name() => {
+                MergeChange::Overridden {
+                    old: old.clone(),
+                    new: after.clone(),
+                }
             }
-    } else {
-        // Just replace
-        *base = Value {
-            kind: override_kind,
-            comments: override_comments,
+            (Some(old), _) => MergeChange::TypeChanged {
+                old: old.clone(),
+                new: after.clone(),
+            },
         };
+        self.push(path, change);
+    }
+
+    fn push(&mut self, path: &str, change: MergeChange) {
+        self.entries.push(MergeEntry {
+            path: path.to_string(),
+            change,
+        });
+    }
+
+    /// Whether the merge changed nothing.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The change at `path`, if any.
+    pub fn get(&self, path: &str) -> Option<&MergeChange> {
+        self.entries
+            .iter()
+            .find(|entry| entry.path == path)
+            .map(|entry| &entry.change)
+    }
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let path = if entry.path.is_empty() {
+                "(root)"
+            } else {
+                &entry.path
+            };
+            match &entry.change {
+                MergeChange::Added { new } => write!(f, "added {} = {}", path, new.kind)?,
+                MergeChange::Overridden { old, new } => {
+                    write!(f, "overrode {}: {} -> {}", path, old.kind, new.kind)?
+                }
+                MergeChange::TypeChanged { old, new } => write!(
+                    f,
+                    "changed type of {}: {} ({}) -> {} ({})",
+                    path,
+                    old.kind,
+                    old.kind.type_name(),
+                    new.kind,
+                    new.kind.type_name()
+                )?,
+                MergeChange::Removed { old } => write!(f, "removed {} (was {})", path, old.kind)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merges like [`merge`], also reporting every path `override_val` changed.
+///
+/// Keys whose value the override leaves as it was are not reported, so
+/// merging a value over an equal one gives an empty report.
+///
+/// # Example
+///
+/// ```
+/// use cosy::merge::{MergeChange, merge_traced};
+///
+/// let mut config = cosy::from_str("{ server: { host: \"a\", port: 80 } }").unwrap();
+/// let local = cosy::from_str("{ server: { host: \"a\", port: 8080 } }").unwrap();
+/// let report = merge_traced(&mut config, local);
+/// assert_eq!(report.entries.len(), 1);
+/// assert!(matches!(report.get("server.port"), Some(MergeChange::Overridden { .. })));
+/// println!("{}", report); // overrode server.port: 80 -> 8080
+/// ```
+pub fn merge_traced(base: &mut Value, override_val: Value) -> MergeReport {
+    let before = base.clone();
+    merge(base, override_val);
+    MergeReport::between(&before, base)
+}
+
+/// A key present in both values whose base value was replaced by a merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Dot-separated path of the key, e.g. `server.port`
+    pub path: String,
+    pub base_value: Value,
+    pub override_value: Value,
+}
+
+/// Merges like [`merge`], also reporting every key that `override_val`
+/// overrode.
+///
+/// A key in both values is a conflict unless both sides are objects, which
+/// are merged and checked key by key. The override counts even when it has
+/// the same value or type as the base. Keys only in `override_val` are
+/// added without a conflict, as is a whole value replaced at the top level.
+///
+/// # Example
+///
+/// ```
+/// use cosy::merge::merge_with_conflicts;
+///
+/// let base = cosy::from_str("{ server: { host: \"a\", port: 80 } }").unwrap();
+/// let local = cosy::from_str("{ server: { port: 8080 }, debug: true }").unwrap();
+/// let (_, conflicts) = merge_with_conflicts(&base, local);
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].path, "server.port");
+/// ```
+pub fn merge_with_conflicts(base: &Value, override_val: Value) -> (Value, Vec<MergeConflict>) {
+    let mut conflicts = Vec::new();
+    collect_conflicts(base, &override_val, "", &mut conflicts);
+    let mut merged = base.clone();
+    merge(&mut merged, override_val);
+    (merged, conflicts)
+}
+
+fn collect_conflicts(
+    base: &Value,
+    override_val: &Value,
+    path: &str,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let (ValueKind::Object(base_map), ValueKind::Object(override_map)) =
+        (&base.kind, &override_val.kind)
+    else {
+        return;
+    };
+    for (key, value) in override_map {
+        let Some(base_value) = base_map.get(key) else {
+            continue;
+        };
+        let key_path = join_key(path, key);
+        if matches!(base_value.kind, ValueKind::Object(_))
+            && matches!(value.kind, ValueKind::Object(_))
+        {
+            collect_conflicts(base_value, value, &key_path, conflicts);
+        } else {
+            conflicts.push(MergeConflict {
+                path: key_path,
+                base_value: base_value.clone(),
+                override_value: value.clone(),
+            });
+        }
     }
 }
 
@@ -67,6 +584,142 @@ mod tests {
         assert_eq!(base, from_str("[3]").unwrap());
     }
 
+    #[test]
+    fn test_merge_arrays_with_strategies() {
+        let base = from_str("{ server: { tags: [\"a\", \"b\"], ports: [[80, 443]] } }").unwrap();
+        let local =
+            from_str("{ server: { tags: [\"b\", \"c\"], ports: [[80, 443], [8080]] } }").unwrap();
+        let merged = |array_strategy| {
+            let mut merged = base.clone();
+            merge_with_options(
+                &mut merged,
+                local.clone(),
+                &MergeOptions {
+                    array_strategy,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            merged
+        };
+
+        assert_eq!(merged(ArrayStrategy::Replace), local);
+        assert_eq!(
+            merged(ArrayStrategy::Append),
+            from_str(
+                "{ server: { tags: [\"a\", \"b\", \"b\", \"c\"], ports: [[80, 443], [80, 443], [8080]] } }"
+            )
+            .unwrap()
+        );
+        // Nested arrays are compared structurally
+        assert_eq!(
+            merged(ArrayStrategy::Unique),
+            from_str("{ server: { tags: [\"a\", \"b\", \"c\"], ports: [[80, 443], [8080]] } }")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_unique_ignores_comments() {
+        let mut base =
+            from_str("{ sinks: [\n\"log\" // the logger\n{ kind: \"file\" // on disk\n}\n] }")
+                .unwrap();
+        let local = from_str(r#"{ sinks: ["log", { kind: "file" }] }"#).unwrap();
+        merge_with_options(
+            &mut base,
+            local,
+            &MergeOptions {
+                array_strategy: ArrayStrategy::Unique,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        match &base.pointer("sinks").unwrap().kind {
+            ValueKind::Array(items) => assert_eq!(items.len(), 2),
+            other => panic!("expected an array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merge_arrays_by_key() {
+        let options = MergeOptions {
+            array_strategy: ArrayStrategy::MergeBy {
+                key: "name".to_string(),
+            },
+            ..Default::default()
+        };
+        let mut base = from_str(
+            r#"{ listeners: [
+                { name: "http", port: 80, tls: false }
+                { name: "admin", port: 9000 }
+                { name: "grpc", port: 50051 }
+            ] }"#,
+        )
+        .unwrap();
+        // Listed in a different order, with one new listener in between
+        let local = from_str(
+            r#"{ listeners: [
+                { name: "grpc", port: 50052 }
+                { name: "metrics", port: 9100 }
+                { name: "http", tls: true }
+            ] }"#,
+        )
+        .unwrap();
+
+        let warnings = merge_with_options(&mut base, local, &options).unwrap();
+        assert!(warnings.is_empty());
+        // Base order is kept; unmatched override items follow in their order
+        assert_eq!(
+            base,
+            from_str(
+                r#"{ listeners: [
+                    { name: "http", port: 80, tls: true }
+                    { name: "admin", port: 9000 }
+                    { name: "grpc", port: 50052 }
+                    { name: "metrics", port: 9100 }
+                ] }"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_by_key_without_scalar_key_appends_with_warning() {
+        let options = MergeOptions {
+            array_strategy: ArrayStrategy::MergeBy {
+                key: "id".to_string(),
+            },
+            ..Default::default()
+        };
+        let mut base = from_str("{ jobs: [{ id: 1, every: 5 }, { every: 60 }] }").unwrap();
+        let local =
+            from_str("{ jobs: [{ every: 10 }, { id: [1], every: 1 }, { id: 1, every: 6 }] }")
+                .unwrap();
+
+        let warnings = merge_with_options(&mut base, local, &options).unwrap();
+        assert_eq!(
+            base,
+            from_str(
+                "{ jobs: [{ id: 1, every: 6 }, { every: 60 }, { every: 10 }, { id: [1], every: 1 }] }"
+            )
+            .unwrap()
+        );
+        let paths: Vec<_> = warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, ["jobs[0]", "jobs[1]"]);
+        assert_eq!(warnings[0].message, "No scalar 'id' to merge by; appended");
+    }
+
+    #[test]
+    fn test_merge_array_strategy_needs_arrays_on_both_sides() {
+        let options = MergeOptions {
+            array_strategy: ArrayStrategy::Append,
+            ..Default::default()
+        };
+        let mut base = from_str("{ tags: \"a\" }").unwrap();
+        merge_with_options(&mut base, from_str("{ tags: [\"b\"] }").unwrap(), &options).unwrap();
+        assert_eq!(base, from_str("{ tags: [\"b\"] }").unwrap());
+    }
+
     #[test]
     fn test_merge_objects_simple() {
         let mut base = from_str("{ a: 1, b: 2 }").unwrap();
@@ -128,4 +781,212 @@ mod tests {
             panic!("root not object");
         }
     }
+
+    #[test]
+    fn test_merge_with_conflicts() {
+        let base = from_str(
+            r#"{
+            name: "app"
+            server: { host: "localhost", port: 8080, tls: { enabled: false } }
+            tags: ["a"]
+        }"#,
+        )
+        .unwrap();
+        let override_val = from_str(
+            r#"{
+            server: { port: 9000, tls: { enabled: true, cert: "c.pem" }, host: "localhost" }
+            tags: { primary: "a" }
+            debug: true
+        }"#,
+        )
+        .unwrap();
+
+        let (merged, conflicts) = merge_with_conflicts(&base, override_val.clone());
+        let mut expected = base.clone();
+        merge(&mut expected, override_val);
+        assert_eq!(merged, expected);
+
+        let paths: Vec<_> = conflicts.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["server.port", "server.tls.enabled", "server.host", "tags"]
+        );
+        assert_eq!(
+            conflicts[0].base_value,
+            Value::from(ValueKind::Integer(8080))
+        );
+        assert_eq!(
+            conflicts[0].override_value,
+            Value::from(ValueKind::Integer(9000))
+        );
+        assert_eq!(conflicts[3].base_value, from_str(r#"["a"]"#).unwrap());
+    }
+
+    #[test]
+    fn test_merge_with_conflicts_only_additions() {
+        let base = from_str("{ a: 1, nested: { b: 2 } }").unwrap();
+        let override_val = from_str("{ c: 3, nested: { d: 4 } }").unwrap();
+        let (merged, conflicts) = merge_with_conflicts(&base, override_val);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merged,
+            from_str("{ a: 1, nested: { b: 2, d: 4 }, c: 3 }").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_traced_nested_objects() {
+        let mut base =
+            from_str(r#"{ server: { host: "a", port: 80, tls: { on: false } } }"#).unwrap();
+        let override_val =
+            from_str(r#"{ server: { port: 8080, tls: { on: "yes" }, debug: true } }"#).unwrap();
+        let report = merge_traced(&mut base, override_val);
+
+        let paths: Vec<_> = report.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["server.port", "server.tls.on", "server.debug"]);
+        assert_eq!(
+            report.get("server.port"),
+            Some(&MergeChange::Overridden {
+                old: Value::from(ValueKind::Integer(80)),
+                new: Value::from(ValueKind::Integer(8080)),
+            })
+        );
+        assert!(matches!(
+            report.get("server.tls.on"),
+            Some(MergeChange::TypeChanged { .. })
+        ));
+        assert_eq!(
+            report.get("server.debug"),
+            Some(&MergeChange::Added {
+                new: Value::from(ValueKind::Bool(true))
+            })
+        );
+        assert_eq!(
+            report.to_string(),
+            "overrode server.port: 80 -> 8080\n\
+             changed type of server.tls.on: false (boolean) -> \"yes\" (string)\n\
+             added server.debug = true"
+        );
+    }
+
+    #[test]
+    fn test_merge_traced_array_replacement() {
+        let mut base = from_str(r#"{ tags: ["a", "b"], keep: [1] }"#).unwrap();
+        let override_val = from_str(r#"{ tags: ["c"], keep: [1] }"#).unwrap();
+        let report = merge_traced(&mut base, override_val);
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(
+            report.get("tags"),
+            Some(&MergeChange::Overridden {
+                old: from_str(r#"["a", "b"]"#).unwrap(),
+                new: from_str(r#"["c"]"#).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_traced_no_op_and_replace_directive() {
+        let mut base = from_str("{ a: 1, nested: { b: 2 } }").unwrap();
+        let report = merge_traced(&mut base, from_str("{ a: 1, nested: { b: 2 } }").unwrap());
+        assert!(report.is_empty());
+        assert_eq!(report.to_string(), "");
+
+        let report = merge_traced(&mut base, from_str("{ nested!: { c: 3 } }").unwrap());
+        let paths: Vec<_> = report.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["nested.c", "nested.b"]);
+        assert!(matches!(
+            report.get("nested.b"),
+            Some(MergeChange::Removed { .. })
+        ));
+    }
+
+    fn strict(on_type_conflict: TypeConflict) -> MergeOptions {
+        MergeOptions {
+            on_type_conflict,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_type_conflict_object_vs_scalar() {
+        let mut base = from_str("{ server: { port: 80 } }").unwrap();
+        let err = merge_with_options(
+            &mut base,
+            from_str("{ server: \"localhost\" }").unwrap(),
+            &strict(TypeConflict::Error),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            MergeError {
+                path: "server".to_string(),
+                base_type: "object",
+                override_type: "string",
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Type conflict at 'server': cannot override object with string"
+        );
+
+        let mut kept = from_str("{ server: { port: 80 }, debug: false }").unwrap();
+        let local = from_str("{ server: \"localhost\", debug: true }").unwrap();
+        merge_with_options(&mut kept, local, &strict(TypeConflict::KeepBase)).unwrap();
+        assert_eq!(
+            kept,
+            from_str("{ server: { port: 80 }, debug: true }").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_type_conflict_scalar_vs_array() {
+        let mut base = from_str("{ hosts: \"a\", nested: { ports: [80] } }").unwrap();
+        let err = merge_with_options(
+            &mut base,
+            from_str("{ nested: { ports: 8080 } }").unwrap(),
+            &strict(TypeConflict::Error),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "nested.ports");
+        assert_eq!((err.base_type, err.override_type), ("array", "integer"));
+
+        let err = merge_with_options(
+            &mut base,
+            from_str("{ hosts: [\"a\", \"b\"] }").unwrap(),
+            &strict(TypeConflict::Error),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "hosts");
+        assert_eq!((err.base_type, err.override_type), ("string", "array"));
+    }
+
+    #[test]
+    fn test_type_conflict_allows_same_type() {
+        let mut base =
+            from_str("{ port: 80, ratio: 1, tags: [\"a\"], db: { host: \"x\" } }").unwrap();
+        let local = from_str("{ port: 8080, ratio: 0.5, tags: [1], db: { user: \"u\" } }").unwrap();
+        merge_with_options(&mut base, local, &strict(TypeConflict::Error)).unwrap();
+        assert_eq!(
+            base,
+            from_str("{ port: 8080, ratio: 0.5, tags: [1], db: { host: \"x\", user: \"u\" } }")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_type_conflict_allows_null() {
+        // Filling in a `null` default, and clearing a value, are not conflicts
+        let mut base = from_str("{ db: null, cache: { size: 10 } }").unwrap();
+        let local = from_str("{ db: { host: \"x\" }, cache: null }").unwrap();
+        let mut changes = 0;
+        merge_with_callback(&mut base, local, &strict(TypeConflict::Error), &mut |_| {
+            changes += 1
+        })
+        .unwrap();
+        assert_eq!(changes, 0);
+        assert_eq!(
+            base,
+            from_str("{ db: { host: \"x\" }, cache: null }").unwrap()
+        );
+    }
 }
@@ -0,0 +1,191 @@
+//! Paths to values, as reported by validation, and queries over them.
+//!
+//! A path starts at the root `$` and adds `.key` for an object key and `[2]`
+//! for an array index. Keys that are not plain identifiers (letters, digits,
+//! `_` and `-`) are quoted so the path stays unambiguous:
+//! `$["weird.key"].items[0]`, with `"` and `\` escaped by a backslash.
+//!
+//! [`Value::pointer`](crate::Value::pointer) looks a path up in a value;
+//! a [`PathQuery`] selects every value matching wildcards and filters.
+
+mod query;
+
+pub use query::{FilterExpr, PathQuery, QueryError, QueryStep};
+
+use crate::schema::suggest::find_best_match;
+use crate::value::{Value, ValueKind};
+use std::fmt;
+
+/// One step of a [`ValuePath`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A parsed path such as `$.server.ports[0]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ValuePath {
+    pub segments: Vec<PathSegment>,
+}
+
+impl ValuePath {
+    /// The root, `$`.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// This path followed by an object key.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.segments.push(PathSegment::Key(key.into()));
+        self
+    }
+
+    /// This path followed by an array index.
+    pub fn index(mut self, index: usize) -> Self {
+        self.segments.push(PathSegment::Index(index));
+        self
+    }
+
+    /// Parse a path as rendered by `Display`.
+    ///
+    /// The leading `$` may be left out, as may the dot before a first key,
+    /// so `server.port` is the same as `$.server.port`. Returns `None` for
+    /// a malformed path.
+    pub fn parse(path: &str) -> Option<Self> {
+        let mut rest = path.strip_prefix('$').unwrap_or(path);
+        let mut parsed = ValuePath::root();
+        if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            parsed = parsed.key(&rest[..end]);
+            rest = &rest[end..];
+        }
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end == 0 {
+                    return None;
+                }
+                parsed = parsed.key(&after[..end]);
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix("[\"") {
+                let (key, after) = unquote(after)?;
+                parsed = parsed.key(key);
+                rest = after.strip_prefix(']')?;
+            } else {
+                let after = rest.strip_prefix('[')?;
+                let end = after.find(']')?;
+                parsed = parsed.index(after[..end].parse().ok()?);
+                rest = &after[end + 1..];
+            }
+        }
+        Some(parsed)
+    }
+
+    /// The value at this path in `value`.
+    pub fn get<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match (segment, &current.kind) {
+                (PathSegment::Key(key), ValueKind::Object(map)) => map.get(key),
+                (PathSegment::Index(index), ValueKind::Array(items)) => items.get(*index),
+                _ => None,
+            })
+    }
+
+    /// The path in `value` closest to this one, with misspelled keys
+    /// corrected, e.g. `$.server.prot` for `$.server.port`.
+    ///
+    /// An existing path is returned unchanged. `None` if some key has no
+    /// close match or an index is out of range.
+    pub fn closest(&self, value: &Value) -> Option<ValuePath> {
+        let mut current = value;
+        let mut closest = ValuePath::root();
+        for segment in &self.segments {
+            match (segment, &current.kind) {
+                (PathSegment::Key(key), ValueKind::Object(map)) => {
+                    let key = if map.contains_key(key) {
+                        key.clone()
+                    } else {
+                        let keys: Vec<&String> = map.keys().collect();
+                        find_best_match(key, &keys, 2)?
+                    };
+                    current = &map[&key];
+                    closest = closest.key(key);
+                }
+                (PathSegment::Index(index), ValueKind::Array(items)) => {
+                    current = items.get(*index)?;
+                    closest = closest.index(*index);
+                }
+                _ => return None,
+            }
+        }
+        Some(closest)
+    }
+}
+
+impl fmt::Display for ValuePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) => f.write_str(&key_segment(key))?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `path` followed by `key`, e.g. `$.server` or `$["a.b"]`.
+///
+/// An empty `path` is the root of a path written without the `$`, as merge
+/// and diff paths are: the first key is then `server`, not `.server`.
+pub(crate) fn join_key(path: &str, key: &str) -> String {
+    let segment = key_segment(key);
+    match segment.strip_prefix('.') {
+        Some(plain) if path.is_empty() => plain.to_string(),
+        _ => format!("{}{}", path, segment),
+    }
+}
+
+/// Two paths written without the `$` joined into one, e.g. `server` and
+/// `tls.cert` into `server.tls.cert`. Either may be empty.
+pub(crate) fn join_path(prefix: &str, rest: &str) -> String {
+    if prefix.is_empty() || rest.is_empty() || rest.starts_with('[') {
+        format!("{}{}", prefix, rest)
+    } else {
+        format!("{}.{}", prefix, rest)
+    }
+}
+
+/// `path` followed by `[index]`.
+pub(crate) fn join_index(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+fn key_segment(key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if plain {
+        format!(".{}", key)
+    } else {
+        format!("[\"{}\"]", key.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Read a quoted key up to its closing `"`, returning it and what follows.
+fn unquote(input: &str) -> Option<(String, &str)> {
+    let mut key = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((key, &input[i + 1..])),
+            '\\' => key.push(chars.next()?.1),
+            c => key.push(c),
+        }
+    }
+    None
+}
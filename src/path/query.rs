@@ -0,0 +1,383 @@
+//! Queries selecting any number of values, with filters on array items.
+//!
+//! A query extends the [`ValuePath`](super::ValuePath) syntax with `*`
+//! (`$.servers[*].host`, or `.*`) for every child, and `[?(...)]` for the
+//! children matching a filter:
+//!
+//! ```text
+//! $.users[?(@.age > 18)].name
+//! $.users[?(@.role == "admin" || (@.age < 30 && @.active == true))]
+//! ```
+//!
+//! In a filter, `@.field` (or `@["field"]`, `@[0]`) names a child of the
+//! item and is compared with a literal using `>`, `<`, `==` or `!=`.
+//! Comparisons combine with `&&` and `||` (`&&` binds tighter) and may be
+//! parenthesized. An item without the field never matches.
+
+use super::{PathSegment, unquote};
+use crate::syntax::parser;
+use crate::value::{Value, ValueKind};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+/// A condition on an array item (or object value) in a [`PathQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    /// The field is greater than the value
+    Gt(PathSegment, Value),
+    /// The field is less than the value
+    Lt(PathSegment, Value),
+    /// The field equals the value
+    Eq(PathSegment, Value),
+    /// The field is present and does not equal the value
+    Ne(PathSegment, Value),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+impl FilterExpr {
+    /// Whether `item` satisfies the filter.
+    ///
+    /// Integers and floats compare as numbers, so `1 == 1.0`; strings
+    /// compare in lexicographic order. Other values are only equal or not.
+    pub fn matches(&self, item: &Value) -> bool {
+        let ordering = |segment: &PathSegment, value: &Value| {
+            field(item, segment).and_then(|found| compare(&found.kind, &value.kind))
+        };
+        match self {
+            FilterExpr::Gt(segment, value) => ordering(segment, value) == Some(Ordering::Greater),
+            FilterExpr::Lt(segment, value) => ordering(segment, value) == Some(Ordering::Less),
+            FilterExpr::Eq(segment, value) => {
+                field(item, segment).is_some_and(|found| equal(&found.kind, &value.kind))
+            }
+            FilterExpr::Ne(segment, value) => {
+                field(item, segment).is_some_and(|found| !equal(&found.kind, &value.kind))
+            }
+            FilterExpr::And(a, b) => a.matches(item) && b.matches(item),
+            FilterExpr::Or(a, b) => a.matches(item) || b.matches(item),
+        }
+    }
+}
+
+fn field<'a>(item: &'a Value, segment: &PathSegment) -> Option<&'a Value> {
+    match (segment, &item.kind) {
+        (PathSegment::Key(key), ValueKind::Object(map)) => map.get(key),
+        (PathSegment::Index(index), ValueKind::Array(items)) => items.get(*index),
+        _ => None,
+    }
+}
+
+fn compare(a: &ValueKind, b: &ValueKind) -> Option<Ordering> {
+    match (a, b) {
+        (ValueKind::Integer(a), ValueKind::Integer(b)) => Some(a.cmp(b)),
+        (ValueKind::Integer(a), ValueKind::Float(b)) => (*a as f64).partial_cmp(b),
+        (ValueKind::Float(a), ValueKind::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (ValueKind::Float(a), ValueKind::Float(b)) => a.partial_cmp(b),
+        (ValueKind::String(a), ValueKind::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn equal(a: &ValueKind, b: &ValueKind) -> bool {
+    match compare(a, b) {
+        Some(ordering) => ordering == Ordering::Equal,
+        None => a == b,
+    }
+}
+
+/// One step of a [`PathQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryStep {
+    Key(String),
+    Index(usize),
+    /// Every array item or object value
+    Wildcard,
+    /// The array items or object values matching a filter
+    Filter(FilterExpr),
+}
+
+/// A compiled query such as `$.users[?(@.age > 18)]`.
+///
+/// # Example
+///
+/// ```
+/// use cosy::path::PathQuery;
+///
+/// let config = cosy::from_str(r#"{ users: [
+///     { name: "ada", age: 36 }
+///     { name: "kit", age: 12 }
+/// ] }"#).unwrap();
+/// let adults = PathQuery::compile("$.users[?(@.age > 18)].name").unwrap();
+/// assert_eq!(adults.query(&config), [&cosy::Value::from("ada")]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathQuery {
+    pub steps: Vec<QueryStep>,
+}
+
+impl PathQuery {
+    /// Parse a query. The leading `$` may be left out, as in a
+    /// [`ValuePath`](super::ValuePath).
+    pub fn compile(query: &str) -> Result<Self, QueryError> {
+        let mut p = QueryParser {
+            input: query,
+            pos: 0,
+        };
+        p.eat("$");
+        let mut steps = Vec::new();
+        if !p.at_end() && !p.rest().starts_with(['.', '[']) {
+            steps.push(p.key_step()?);
+        }
+        while !p.at_end() {
+            if p.eat(".") {
+                steps.push(p.key_step()?);
+            } else if p.eat("[") {
+                p.skip_whitespace();
+                let step = if p.eat("?") {
+                    p.skip_whitespace();
+                    p.expect("(")?;
+                    let filter = p.or()?;
+                    p.skip_whitespace();
+                    p.expect(")")?;
+                    QueryStep::Filter(filter)
+                } else if p.eat("*") {
+                    QueryStep::Wildcard
+                } else {
+                    match p.bracketed()? {
+                        PathSegment::Key(key) => QueryStep::Key(key),
+                        PathSegment::Index(index) => QueryStep::Index(index),
+                    }
+                };
+                p.skip_whitespace();
+                p.expect("]")?;
+                steps.push(step);
+            } else {
+                return Err(p.error("expected '.' or '['"));
+            }
+        }
+        Ok(PathQuery { steps })
+    }
+
+    /// Every value in `value` the query selects, in document order.
+    pub fn query<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for value in current {
+                match (step, &value.kind) {
+                    (QueryStep::Key(key), ValueKind::Object(map)) => next.extend(map.get(key)),
+                    (QueryStep::Index(index), ValueKind::Array(items)) => {
+                        next.extend(items.get(*index))
+                    }
+                    (QueryStep::Wildcard, _) => next.extend(children(value)),
+                    (QueryStep::Filter(filter), _) => {
+                        next.extend(children(value).filter(|child| filter.matches(child)))
+                    }
+                    _ => {}
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+fn children(value: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match &value.kind {
+        ValueKind::Array(items) => Box::new(items.iter()),
+        ValueKind::Object(map) => Box::new(map.values()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// A query that could not be compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    /// Byte offset in the query where the problem was found
+    pub offset: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid path query at offset {}: {}",
+            self.offset, self.message
+        )
+    }
+}
+
+impl Error for QueryError {}
+
+/// Characters that end an unquoted key
+const KEY_END: &[char] = &[
+    '.', '[', ']', '(', ')', '<', '>', '=', '!', '&', '|', '"', ' ', '\t',
+];
+
+/// Characters that end an unquoted literal, which may contain a `.`
+const LITERAL_END: &[char] = &[')', ']', '&', '|', ' ', '\t'];
+
+struct QueryParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> QueryParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), QueryError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", token)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            message: message.to_string(),
+            offset: self.pos,
+        }
+    }
+
+    /// An unquoted run up to the first of `end`.
+    fn word(&mut self, end: &[char]) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(end).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    /// A key or `*` after a `.`, or at the start.
+    fn key_step(&mut self) -> Result<QueryStep, QueryError> {
+        if self.eat("*") {
+            return Ok(QueryStep::Wildcard);
+        }
+        match self.word(KEY_END) {
+            "" => Err(self.error("expected a key")),
+            key => Ok(QueryStep::Key(key.to_string())),
+        }
+    }
+
+    /// A `"quoted key"` or index inside brackets.
+    fn bracketed(&mut self) -> Result<PathSegment, QueryError> {
+        if self.eat("\"") {
+            return self.quoted().map(PathSegment::Key);
+        }
+        let start = self.pos;
+        self.word(KEY_END)
+            .parse()
+            .map(PathSegment::Index)
+            .map_err(|_| QueryError {
+                message: "expected an index, '*', '?(' or a quoted key".to_string(),
+                offset: start,
+            })
+    }
+
+    /// The rest of a string whose opening `"` was just read.
+    fn quoted(&mut self) -> Result<String, QueryError> {
+        let (text, after) =
+            unquote(self.rest()).ok_or_else(|| self.error("unterminated string"))?;
+        self.pos = self.input.len() - after.len();
+        Ok(text)
+    }
+
+    fn or(&mut self) -> Result<FilterExpr, QueryError> {
+        let mut left = self.and()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("||") {
+                return Ok(left);
+            }
+            left = FilterExpr::Or(Box::new(left), Box::new(self.and()?));
+        }
+    }
+
+    fn and(&mut self) -> Result<FilterExpr, QueryError> {
+        let mut left = self.comparison()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("&&") {
+                return Ok(left);
+            }
+            left = FilterExpr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+    }
+
+    fn comparison(&mut self) -> Result<FilterExpr, QueryError> {
+        self.skip_whitespace();
+        if self.eat("(") {
+            let inner = self.or()?;
+            self.skip_whitespace();
+            self.expect(")")?;
+            return Ok(inner);
+        }
+        self.expect("@")?;
+        let segment = if self.eat(".") {
+            match self.word(KEY_END) {
+                "" => return Err(self.error("expected a key")),
+                key => PathSegment::Key(key.to_string()),
+            }
+        } else if self.eat("[") {
+            let segment = self.bracketed()?;
+            self.expect("]")?;
+            segment
+        } else {
+            return Err(self.error("expected '.' or '[' after '@'"));
+        };
+
+        self.skip_whitespace();
+        let operator: fn(PathSegment, Value) -> FilterExpr = if self.eat("==") {
+            FilterExpr::Eq
+        } else if self.eat("!=") {
+            FilterExpr::Ne
+        } else if self.eat(">") {
+            FilterExpr::Gt
+        } else if self.eat("<") {
+            FilterExpr::Lt
+        } else {
+            return Err(self.error("expected '>', '<', '==' or '!='"));
+        };
+        self.skip_whitespace();
+        Ok(operator(segment, self.literal()?))
+    }
+
+    /// A string, number, `true`, `false` or `null`.
+    fn literal(&mut self) -> Result<Value, QueryError> {
+        if self.eat("\"") {
+            return self.quoted().map(Value::string);
+        }
+        let start = self.pos;
+        let word = self.word(LITERAL_END);
+        match parser::from_str(word) {
+            Ok(value) if !matches!(value.kind, ValueKind::Array(_) | ValueKind::Object(_)) => {
+                Ok(value)
+            }
+            _ => Err(QueryError {
+                message: "expected a string, number, true, false or null".to_string(),
+                offset: start,
+            }),
+        }
+    }
+}
@@ -1,19 +1,270 @@
+// `validate_strict` returns the offending `ValidationItem` by value as its error.
+#![allow(clippy::result_large_err)]
+
+use crate::path;
+use crate::syntax::lexer::Position;
 use crate::value::{Value, ValueKind};
+use compiled::SchemaCache;
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
+pub mod builder;
+pub mod coerce;
+pub mod compiled;
+pub mod compose;
+pub mod conditional;
+pub mod constraints;
+pub mod deprecation;
+pub mod describe;
+pub mod formats;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod patterns;
 pub mod suggest;
 
-#[derive(Debug, Clone, PartialEq)]
+pub use crate::path::{PathSegment, ValuePath};
+pub use builder::SchemaBuilder;
+pub use coerce::{
+    CoercionReport, validate_and_coerce, validate_and_coerce_with, validate_coercing,
+};
+pub use compiled::Schema;
+pub use compose::merge_schemas;
+pub use deprecation::{Deprecation, migrate_deprecated};
+pub use describe::{DescribeFormat, describe};
+#[cfg(feature = "json")]
+pub use json::{from_json_schema, from_json_schema_checked, to_json_schema};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ValidationLevel {
     Error,
     Warning,
+    /// Purely informational; never makes a report invalid.
+    Info,
+}
+
+/// The structured reason behind a `ValidationItem`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The value's type does not match the schema type.
+    TypeMismatch { expected: String, actual: String },
+    /// A required field is absent from an object.
+    MissingField { field: String },
+    /// An object contains a field the schema does not declare.
+    UnknownField {
+        field: String,
+        suggestion: Option<String>,
+    },
+    /// A string was converted to the schema type by `validate_coercing`.
+    Coerced { from: String, to: String },
+    /// A deprecated field is in use.
+    Deprecated {
+        message: String,
+        since: Option<String>,
+        replacement: Option<String>,
+    },
+    /// A schema `info` annotation on a field that is in use.
+    Info { message: String },
+    /// `migrate_deprecated` moved a deprecated field to its replacement.
+    Migrated { from: String, to: String },
+    /// A cross-field constraint requires `field` because `condition` holds.
+    RequiredBy { field: String, condition: String },
+    /// A cross-field constraint forbids `field` because `condition` holds.
+    ForbiddenBy { field: String, condition: String },
+    /// The value is not one of the schema's `enum` values.
+    NotInEnum {
+        value: String,
+        allowed: Vec<String>,
+        /// The closest allowed string, for a string value.
+        suggestion: Option<String>,
+    },
+    /// A number lies outside the schema's `min`/`max` bounds.
+    OutOfRange {
+        value: String,
+        min: Option<String>,
+        max: Option<String>,
+    },
+    /// A string's length (in characters) lies outside `minLength`/`maxLength`.
+    LengthOutOfRange {
+        length: usize,
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// A string does not match the schema's `format`.
+    InvalidFormat { format: String, value: String },
+    /// The schema itself is malformed. Only reports that don't return a
+    /// [`SchemaError`], such as [`validate_permissive`], contain this.
+    InvalidSchema { message: String },
+    /// `validate_with_options` dropped errors beyond `max_errors`.
+    Truncated { omitted: usize },
+    /// An include layer replaced a value with one of another type; see
+    /// [`include::resolve_with_warnings`](crate::include::resolve_with_warnings).
+    TypeChanged {
+        from: String,
+        to: String,
+        /// The file the new value came from, if it was not the document itself
+        file: Option<String>,
+    },
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationErrorKind::TypeMismatch { expected, actual } => {
+                write!(f, "Type mismatch: expected {}, found {}", expected, actual)
+            }
+            ValidationErrorKind::MissingField { field } => {
+                write!(f, "Missing required field '{}'", field)
+            }
+            ValidationErrorKind::UnknownField { field, suggestion } => {
+                write!(f, "Unknown field '{}'", field)?;
+                if let Some(s) = suggestion {
+                    write!(f, "; did you mean '{}'?", s)?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::Coerced { from, to } => {
+                write!(f, "Coerced string \"{}\" to {}", from, to)
+            }
+            ValidationErrorKind::Deprecated {
+                message,
+                since,
+                replacement,
+            } => {
+                write!(f, "Deprecated usage")?;
+                if !message.is_empty() {
+                    write!(f, ": {}", message)?;
+                }
+                if let Some(since) = since {
+                    write!(f, " (since {})", since)?;
+                }
+                if let Some(replacement) = replacement {
+                    write!(f, "; use '{}' instead", replacement)?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::Info { message } => write!(f, "{}", message),
+            ValidationErrorKind::Migrated { from, to } => {
+                write!(f, "Moved deprecated field '{}' to '{}'", from, to)
+            }
+            ValidationErrorKind::RequiredBy { field, condition } => {
+                write!(f, "Field '{}' is required when {}", field, condition)
+            }
+            ValidationErrorKind::ForbiddenBy { field, condition } => {
+                write!(f, "Field '{}' is not allowed when {}", field, condition)
+            }
+            ValidationErrorKind::NotInEnum {
+                value,
+                allowed,
+                suggestion,
+            } => {
+                write!(f, "Value {} is not one of [{}]", value, allowed.join(", "))?;
+                if let Some(s) = suggestion {
+                    write!(f, "; did you mean {}?", s)?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::OutOfRange { value, min, max } => {
+                write!(f, "Value {} is out of range", value)?;
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(f, " ({}..={})", min, max),
+                    (Some(min), None) => write!(f, " (min {})", min),
+                    (None, Some(max)) => write!(f, " (max {})", max),
+                    (None, None) => Ok(()),
+                }
+            }
+            ValidationErrorKind::LengthOutOfRange { length, min, max } => {
+                write!(f, "String length {} is out of range", length)?;
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(f, " ({}..={})", min, max),
+                    (Some(min), None) => write!(f, " (min {})", min),
+                    (None, Some(max)) => write!(f, " (max {})", max),
+                    (None, None) => Ok(()),
+                }
+            }
+            ValidationErrorKind::InvalidFormat { format, value } => {
+                write!(f, "Value \"{}\" is not a valid {}", value, format)
+            }
+            ValidationErrorKind::InvalidSchema { message } => write!(f, "{}", message),
+            ValidationErrorKind::TypeChanged { from, to, file } => {
+                write!(f, "Type changed from {} to {}", from, to)?;
+                if let Some(file) = file {
+                    write!(f, " (overridden in \"{}\")", file)?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::Truncated { omitted } => {
+                write!(f, "{} more error(s) not shown", format_count(*omitted))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ValidationItem {
     pub level: ValidationLevel,
     pub path: String,
+    pub kind: ValidationErrorKind,
+    /// Human-readable rendering of `kind`.
     pub message: String,
+    /// Source position of the offending value (the parent object for
+    /// missing fields), when the instance was parsed from text.
+    pub position: Option<Position>,
+}
+
+impl ValidationItem {
+    /// Create an item, deriving `message` from `kind`.
+    pub fn new(level: ValidationLevel, path: &str, kind: ValidationErrorKind) -> Self {
+        ValidationItem {
+            level,
+            path: path.to_string(),
+            message: kind.to_string(),
+            kind,
+            position: None,
+        }
+    }
+
+    /// Attach the source position of the value this item refers to.
+    pub fn with_position(mut self, position: Option<Position>) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn error(path: &str, kind: ValidationErrorKind) -> Self {
+        Self::new(ValidationLevel::Error, path, kind)
+    }
+
+    pub fn warning(path: &str, kind: ValidationErrorKind) -> Self {
+        Self::new(ValidationLevel::Warning, path, kind)
+    }
+
+    pub fn info(path: &str, kind: ValidationErrorKind) -> Self {
+        Self::new(ValidationLevel::Info, path, kind)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == ValidationLevel::Error
+    }
+}
+
+/// Items are equal when their level, path and message match; `kind` is
+/// implied by the message and `position` is ignored.
+impl PartialEq for ValidationItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level && self.path == other.path && self.message == other.message
+    }
+}
+
+impl Eq for ValidationItem {}
+
+impl Hash for ValidationItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.level.hash(state);
+        self.path.hash(state);
+        self.message.hash(state);
+    }
 }
 
 impl fmt::Display for ValidationItem {
@@ -21,198 +272,1049 @@ impl fmt::Display for ValidationItem {
         let level_str = match self.level {
             ValidationLevel::Error => "Error",
             ValidationLevel::Warning => "Warning",
+            ValidationLevel::Info => "Info",
         };
-        write!(f, "[{} at {}] {}", level_str, self.path, self.message)
+        match self.position {
+            Some(pos) => write!(
+                f,
+                "[{} at {}, line {}, column {}] {}",
+                level_str, self.path, pos.line, pos.column, self.message
+            ),
+            None => write!(f, "[{} at {}] {}", level_str, self.path, self.message),
+        }
+    }
+}
+
+/// A problem with a schema itself, as opposed to the instance being validated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub kind: SchemaErrorKind,
+    /// Human-readable description of the problem.
+    pub message: String,
+    /// Location of the problem within the schema.
+    pub schema_path: String,
+}
+
+/// The structured reason behind a `SchemaError`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaErrorKind {
+    /// The schema names a type the validator doesn't know.
+    UnknownType {
+        type_name: String,
+        suggestion: Option<String>,
+    },
+    /// Any other malformed schema; the message says what is wrong.
+    Malformed,
+}
+
+impl SchemaError {
+    /// A `Malformed` error at `schema_path`.
+    pub fn new(schema_path: &str, message: impl Into<String>) -> Self {
+        SchemaError {
+            kind: SchemaErrorKind::Malformed,
+            message: message.into(),
+            schema_path: schema_path.to_string(),
+        }
+    }
+
+    /// An `UnknownType` error, suggesting `suggestion` when there is one.
+    pub fn unknown_type(schema_path: &str, type_name: &str, suggestion: Option<String>) -> Self {
+        let mut message = format!("Unknown type '{}'", type_name);
+        if let Some(s) = &suggestion {
+            message.push_str(&format!("; did you mean '{}'?", s));
+        }
+        SchemaError {
+            kind: SchemaErrorKind::UnknownType {
+                type_name: type_name.to_string(),
+                suggestion,
+            },
+            message,
+            schema_path: schema_path.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[Schema error at {}] {}", self.schema_path, self.message)
     }
 }
 
-pub type ValidationReport = Vec<ValidationItem>;
+impl std::error::Error for SchemaError {}
+
+/// An `InvalidSchema` error at the schema path, for reporting a schema
+/// problem alongside instance errors.
+impl From<SchemaError> for ValidationItem {
+    fn from(e: SchemaError) -> Self {
+        ValidationItem::error(
+            &e.schema_path,
+            ValidationErrorKind::InvalidSchema { message: e.message },
+        )
+    }
+}
+
+/// The collected result of validating a value against a schema.
+///
+/// Dereferences to a slice of items, so it can be indexed and iterated
+/// directly.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub items: Vec<ValidationItem>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, item: ValidationItem) {
+        self.items.push(item);
+    }
+
+    /// Items with `ValidationLevel::Error`.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationItem> {
+        self.items
+            .iter()
+            .filter(|i| i.level == ValidationLevel::Error)
+    }
+
+    /// Items with `ValidationLevel::Warning`.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationItem> {
+        self.items
+            .iter()
+            .filter(|i| i.level == ValidationLevel::Warning)
+    }
+
+    /// Items with `ValidationLevel::Info`.
+    pub fn infos(&self) -> impl Iterator<Item = &ValidationItem> {
+        self.items
+            .iter()
+            .filter(|i| i.level == ValidationLevel::Info)
+    }
+
+    /// `true` if the report contains at least one error.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// `true` if the report contains no errors (warnings and info items are
+    /// allowed).
+    pub fn is_valid(&self) -> bool {
+        !self.has_errors()
+    }
+}
+
+impl std::ops::Deref for ValidationReport {
+    type Target = [ValidationItem];
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl IntoIterator for ValidationReport {
+    type Item = ValidationItem;
+    type IntoIter = std::vec::IntoIter<ValidationItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationReport {
+    type Item = &'a ValidationItem;
+    type IntoIter = std::slice::Iter<'a, ValidationItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Remove repeated items from `report`, keeping the first occurrence of each.
+pub fn deduplicate_report(report: &mut ValidationReport) {
+    let mut seen = HashSet::new();
+    report.items.retain(|item| seen.insert(item.clone()));
+}
+
+/// Concatenate two reports, dropping items that appear in both.
+pub fn merge_reports(a: ValidationReport, b: ValidationReport) -> ValidationReport {
+    let mut merged = a;
+    merged.items.extend(b.items);
+    deduplicate_report(&mut merged);
+    merged
+}
 
 /// Validate a COSY value against a schema definition.
-pub fn validate(instance: &Value, schema: &Value) -> Result<ValidationReport, ValidationItem> {
-    let mut report = Vec::new();
-    validate_recursive(instance, schema, "$", &mut report)?;
+///
+/// Returns a [`SchemaError`] only if the schema itself is invalid; problems
+/// with the instance are collected in the returned report.
+pub fn validate(instance: &Value, schema: &Value) -> Result<ValidationReport, SchemaError> {
+    let mut report = ValidationReport::new();
+    validate_recursive(instance, schema, "$", &SchemaCache::default(), &mut report)?;
+    Ok(report)
+}
+
+/// Options for [`validate_with_options`].
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    /// Keep at most this many errors, followed by a `Truncated` item
+    /// counting the rest (default: unlimited)
+    pub max_errors: usize,
+    /// Collapse items with the same message whose paths differ only in
+    /// array indices into the first one, with a count (default: false)
+    pub group_similar: bool,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions {
+            max_errors: usize::MAX,
+            group_similar: false,
+        }
+    }
+}
+
+/// Validate with control over how large reports are summarized.
+///
+/// Grouping happens before truncation, so `max_errors` counts groups.
+pub fn validate_with_options(
+    instance: &Value,
+    schema: &Value,
+    options: &ValidationOptions,
+) -> Result<ValidationReport, SchemaError> {
+    let mut report = validate(instance, schema)?;
+    if options.group_similar {
+        group_similar(&mut report);
+    }
+
+    let total = report.errors().count();
+    if total > options.max_errors {
+        let mut kept = 0;
+        report.items.retain(|item| {
+            if !item.is_error() {
+                return true;
+            }
+            kept += 1;
+            kept <= options.max_errors
+        });
+        report.push(ValidationItem::error(
+            "$",
+            ValidationErrorKind::Truncated {
+                omitted: total - options.max_errors,
+            },
+        ));
+    }
     Ok(report)
 }
 
+/// Merge items that share a level, message and index-free path.
+fn group_similar(report: &mut ValidationReport) {
+    // Key -> (index of the first item, number of items)
+    let mut groups: HashMap<(ValidationLevel, String, String), (usize, usize)> = HashMap::new();
+    let mut keep = Vec::with_capacity(report.items.len());
+
+    for (i, item) in report.items.iter().enumerate() {
+        let key = (
+            item.level.clone(),
+            wildcard_indices(&item.path),
+            item.message.clone(),
+        );
+        let group = groups.entry(key).or_insert((i, 0));
+        group.1 += 1;
+        keep.push(group.0 == i);
+    }
+
+    for ((_, path, _), (first, count)) in groups {
+        if count > 1 {
+            let item = &mut report.items[first];
+            item.message = format!(
+                "{} (and {} more at {})",
+                item.message,
+                format_count(count - 1),
+                path
+            );
+        }
+    }
+    let mut keep = keep.into_iter();
+    report.items.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// Replace every `[N]` in a report path with `[*]`, leaving quoted keys
+/// such as `["a[1]"]` alone.
+fn wildcard_indices(path: &str) -> String {
+    match ValuePath::parse(path) {
+        Some(parsed) => {
+            let mut out = "$".to_string();
+            for segment in parsed.segments {
+                out = match segment {
+                    PathSegment::Key(key) => path::join_key(&out, &key),
+                    PathSegment::Index(_) => format!("{}[*]", out),
+                };
+            }
+            out
+        }
+        None => path.to_string(),
+    }
+}
+
+/// Format a count with thousands separators (`9999` -> `9,999`).
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Validate, treating every error and warning as a failure.
+///
+/// Returns `Err` with the first error or warning, so a deprecation warning
+/// fails validation just like an unknown field or a type mismatch. `Info`
+/// items are ignored; see [`validate_strict_with_options`] to fail on them too.
+pub fn validate_strict(instance: &Value, schema: &Value) -> Result<(), ValidationItem> {
+    validate_strict_with_options(instance, schema, &StrictOptions::default())
+}
+
+/// Options for [`validate_strict_with_options`].
+#[derive(Debug, Clone)]
+pub struct StrictOptions {
+    /// Fail only on errors and warnings, ignoring `Info` items (default: true)
+    pub errors_and_warnings_only: bool,
+}
+
+impl Default for StrictOptions {
+    fn default() -> Self {
+        StrictOptions {
+            errors_and_warnings_only: true,
+        }
+    }
+}
+
+/// Validate strictly, choosing whether `Info` items also fail validation.
+pub fn validate_strict_with_options(
+    instance: &Value,
+    schema: &Value,
+    options: &StrictOptions,
+) -> Result<(), ValidationItem> {
+    let report = validate(instance, schema)?;
+    match report
+        .items
+        .into_iter()
+        .find(|item| !options.errors_and_warnings_only || item.level != ValidationLevel::Info)
+    {
+        Some(first) => Err(first),
+        None => Ok(()),
+    }
+}
+
+/// Validate, collecting everything and never failing.
+///
+/// A malformed schema does not abort validation: it ends the report with an
+/// `InvalidSchema` error whose message starts with "Schema error".
+pub fn validate_permissive(instance: &Value, schema: &Value) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    if let Err(e) = validate_recursive(instance, schema, "$", &SchemaCache::default(), &mut report)
+    {
+        report.push(ValidationItem::error(
+            &e.schema_path,
+            ValidationErrorKind::InvalidSchema {
+                message: format!("Schema error: {}", e.message),
+            },
+        ));
+    }
+    report
+}
+
 fn validate_recursive(
     instance: &Value,
     schema: &Value,
     path: &str,
+    cache: &SchemaCache,
     report: &mut ValidationReport,
-) -> Result<(), ValidationItem> {
+) -> Result<(), SchemaError> {
+    // 0. Resolve composition: { all_of: [...] } validates against the merged parts
+    if let Some(merged) = cache.merged(schema) {
+        return validate_recursive(instance, merged, path, cache, report);
+    }
+    if let Some(parts) = compose::all_of(schema) {
+        let merged = merge_schemas(parts).map_err(|e| all_of_error(path, e))?;
+        return validate_recursive(instance, &merged, path, cache, report);
+    }
+    // { if, then, else } validates against the branch the condition picks
+    if let Some(conditional) = conditional::conditional(schema) {
+        let mut scratch = ValidationReport::new();
+        validate_recursive(instance, conditional.condition, path, cache, &mut scratch)?;
+        let branch = conditional::branch(&conditional, conditional::matched(&scratch))
+            .map_err(|e| all_of_error(path, e))?;
+        return validate_recursive(instance, &branch, path, cache, report);
+    }
+
     // 1. Resolve Extended Schema Syntax: { type: "string", deprecated: "msg", optional: true }
     let (effective_type_schema, deprecation, _) = extract_metadata(schema);
 
-    // 2. Report Deprecation Warning if applicable
-    if let Some(msg) = deprecation {
-        report.push(ValidationItem {
-            level: ValidationLevel::Warning,
-            path: path.to_string(),
-            message: format!("Deprecated usage: {}", msg),
-        });
+    // 2. Report Deprecation Warning and Info annotation if applicable
+    if let Some(deprecation) = deprecation {
+        report.push(
+            ValidationItem::warning(path, deprecation.to_kind()).with_position(instance.position),
+        );
+    }
+    if let Some(message) = info_message(schema) {
+        report.push(
+            ValidationItem::info(path, ValidationErrorKind::Info { message })
+                .with_position(instance.position),
+        );
     }
 
     // 3. Validate Type / Structure
-    match &effective_type_schema.kind {
-        ValueKind::String(type_name) => validate_type(instance, type_name, path, report),
+    let nullable = is_nullable(schema);
+    let outcome = match &effective_type_schema.kind {
+        ValueKind::String(type_name) if type_name == TUPLE_TYPE => {
+            validate_tuple(instance, schema, nullable, path, cache, report)
+        }
+
+        ValueKind::String(type_name) => {
+            if validate_type(instance, type_name, nullable, path, report)? {
+                check_value_constraints(instance, schema, path, report)?;
+            }
+            Ok(())
+        }
+
+        _ if nullable && matches!(instance.kind, ValueKind::Null) => Ok(()),
 
         ValueKind::Object(schema_obj) => {
             if let ValueKind::Object(instance_obj) = &instance.kind {
                 // Check required fields
                 for (key, sub_schema) in schema_obj {
+                    if is_directive(key, sub_schema) {
+                        continue;
+                    }
                     if !instance_obj.contains_key(key) {
                         let (_, _, is_optional) = extract_metadata(sub_schema);
                         if !is_optional {
-                            report.push(ValidationItem {
-                                level: ValidationLevel::Error,
-                                path: path.to_string(),
-                                message: format!("Missing required field '{}'", key),
-                            });
+                            report.push(
+                                ValidationItem::error(
+                                    path,
+                                    ValidationErrorKind::MissingField { field: key.clone() },
+                                )
+                                .with_position(instance.position),
+                            );
                         }
                     } else {
                         validate_recursive(
                             &instance_obj[key],
                             sub_schema,
-                            &format!("{}.{}", path, key),
+                            &path::join_key(path, key),
+                            cache,
                             report,
                         )?;
                     }
                 }
 
                 // Check unknown fields and typos
-                let schema_keys: Vec<String> = schema_obj.keys().cloned().collect();
-                for key in instance_obj.keys() {
-                    if !schema_obj.contains_key(key) {
-                        let mut msg = format!("Unknown field '{}'", key);
-
-                        // Typo Suggestion
-                        if let Some(best_match) = suggest::find_best_match(key, &schema_keys, 2) {
-                            msg.push_str(&format!("; did you mean '{}'?", best_match));
+                let schema_keys: Vec<String> = schema_obj
+                    .iter()
+                    .filter(|(k, v)| !is_directive(k, v))
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                let additional = schema_obj.get(ADDITIONAL_PROPERTIES_KEY);
+                let pattern_schemas = match schema_obj.get(patterns::PATTERN_PROPERTIES_KEY) {
+                    Some(Value {
+                        kind: ValueKind::Object(patterns),
+                        ..
+                    }) => Some(patterns),
+                    _ => None,
+                };
+                let compiled;
+                let key_patterns = match pattern_schemas {
+                    Some(patterns) => match cache.patterns(patterns) {
+                        Some(key_patterns) => key_patterns,
+                        None => {
+                            compiled = patterns::compile(patterns, path)?;
+                            &compiled
                         }
+                    },
+                    None => &[][..],
+                };
+                let patterns = key_patterns
+                    .iter()
+                    .zip(pattern_schemas.into_iter().flat_map(|p| p.values()));
+                for (key, value) in instance_obj {
+                    if schema_keys.contains(key) {
+                        continue;
+                    }
+
+                    // Fields matching a key pattern are validated by its schema
+                    let field_path = path::join_key(path, key);
+                    let mut matched = false;
+                    for (_, pattern_schema) in patterns.clone().filter(|(p, _)| p.is_match(key)) {
+                        matched = true;
+                        validate_recursive(value, pattern_schema, &field_path, cache, report)?;
+                    }
+                    if matched {
+                        continue;
+                    }
 
-                        report.push(ValidationItem {
-                            level: ValidationLevel::Error,
-                            path: path.to_string(),
-                            message: msg,
-                        });
+                    match additional {
+                        // Open object: anything goes
+                        Some(Value {
+                            kind: ValueKind::Bool(true),
+                            ..
+                        }) => {}
+                        // Closed object (the default)
+                        None
+                        | Some(Value {
+                            kind: ValueKind::Bool(false),
+                            ..
+                        }) => {
+                            // Typo Suggestion
+                            let suggestion = suggest::find_best_match(key, &schema_keys, 2);
+
+                            report.push(
+                                ValidationItem::error(
+                                    path,
+                                    ValidationErrorKind::UnknownField {
+                                        field: key.clone(),
+                                        suggestion,
+                                    },
+                                )
+                                .with_position(value.position),
+                            );
+                        }
+                        // Extra fields must match the given schema
+                        Some(extra) => {
+                            validate_recursive(value, extra, &field_path, cache, report)?
+                        }
                     }
                 }
+
+                // Check cross-field constraints
+                if let Some(
+                    directive @ Value {
+                        kind: ValueKind::Array(rules),
+                        ..
+                    },
+                ) = schema_obj.get(constraints::CONSTRAINTS_KEY)
+                    && constraints::is_directive(constraints::CONSTRAINTS_KEY, directive)
+                {
+                    constraints::check(instance_obj, rules, path, instance.position, report)?;
+                }
                 Ok(())
             } else {
-                report.push(ValidationItem {
-                    level: ValidationLevel::Error,
-                    path: path.to_string(),
-                    message: format!("Expected object, found {}", instance.type_name()),
-                });
+                report.push(
+                    ValidationItem::error(
+                        path,
+                        ValidationErrorKind::TypeMismatch {
+                            expected: "object".to_string(),
+                            actual: instance.type_name().to_string(),
+                        },
+                    )
+                    .with_position(instance.position),
+                );
                 Ok(())
             }
         }
 
         ValueKind::Array(schema_arr) => {
             if schema_arr.len() != 1 {
-                return Err(ValidationItem {
-                    level: ValidationLevel::Error,
-                    path: path.to_string(),
-                    message: "Array schema must contain exactly one element specifier".to_string(),
-                });
+                return Err(invalid_schema(
+                    path,
+                    "Array schema must contain exactly one element specifier".to_string(),
+                ));
             }
 
             let item_schema = &schema_arr[0];
 
             if let ValueKind::Array(instance_arr) = &instance.kind {
                 for (i, item) in instance_arr.iter().enumerate() {
-                    validate_recursive(item, item_schema, &format!("{}[{}]", path, i), report)?;
+                    validate_recursive(
+                        item,
+                        item_schema,
+                        &path::join_index(path, i),
+                        cache,
+                        report,
+                    )?;
                 }
                 Ok(())
             } else {
-                report.push(ValidationItem {
-                    level: ValidationLevel::Error,
-                    path: path.to_string(),
-                    message: format!("Expected array, found {}", instance.type_name()),
-                });
+                report.push(
+                    ValidationItem::error(
+                        path,
+                        ValidationErrorKind::TypeMismatch {
+                            expected: "array".to_string(),
+                            actual: instance.type_name().to_string(),
+                        },
+                    )
+                    .with_position(instance.position),
+                );
                 Ok(())
             }
         }
 
-        _ => Err(ValidationItem {
-            level: ValidationLevel::Error,
-            path: path.to_string(),
-            message: format!(
+        _ => Err(invalid_schema(
+            path,
+            format!(
                 "Unsupported schema value type: {}",
                 effective_type_schema.type_name()
             ),
-        }),
+        )),
+    };
+    outcome?;
+
+    // 4. Check field dependencies declared in the extended form
+    if !std::ptr::eq(effective_type_schema, schema)
+        && let (ValueKind::Object(schema_obj), ValueKind::Object(instance_obj)) =
+            (&schema.kind, &instance.kind)
+    {
+        constraints::check_dependencies(instance_obj, schema_obj, path, instance.position, report)?;
+    }
+    Ok(())
+}
+
+/// The extended-form type of a positional array, `{ type: "tuple", items: [...] }`.
+pub(crate) const TUPLE_TYPE: &str = "tuple";
+
+/// The per-position schemas of a tuple schema.
+pub(crate) fn tuple_items(schema: &Value) -> Result<&[Value], String> {
+    match &schema.kind {
+        ValueKind::Object(schema_obj) => match schema_obj.get("items").map(|v| &v.kind) {
+            Some(ValueKind::Array(items)) => Ok(items),
+            Some(_) => Err("'items' must be an array".to_string()),
+            None => Err("'tuple' schema requires an 'items' array".to_string()),
+        },
+        _ => Err("'tuple' requires the extended form with 'items'".to_string()),
+    }
+}
+
+/// Check an array against a tuple schema, one item schema per position.
+fn validate_tuple(
+    instance: &Value,
+    schema: &Value,
+    nullable: bool,
+    path: &str,
+    cache: &SchemaCache,
+    report: &mut ValidationReport,
+) -> Result<(), SchemaError> {
+    let item_schemas = tuple_items(schema).map_err(|message| invalid_schema(path, message))?;
+    let expected = || format!("tuple of {} items", item_schemas.len());
+    match &instance.kind {
+        ValueKind::Null if nullable => {}
+        ValueKind::Array(items) if items.len() != item_schemas.len() => report.push(
+            ValidationItem::error(
+                path,
+                ValidationErrorKind::TypeMismatch {
+                    expected: expected(),
+                    actual: format!("array of {} items", items.len()),
+                },
+            )
+            .with_position(instance.position),
+        ),
+        ValueKind::Array(items) => {
+            for (i, (item, item_schema)) in items.iter().zip(item_schemas).enumerate() {
+                validate_recursive(item, item_schema, &path::join_index(path, i), cache, report)?;
+            }
+        }
+        _ => report.push(
+            ValidationItem::error(
+                path,
+                ValidationErrorKind::TypeMismatch {
+                    expected: expected(),
+                    actual: instance.type_name().to_string(),
+                },
+            )
+            .with_position(instance.position),
+        ),
     }
+    Ok(())
 }
 
+/// Type names accepted in a schema (`bool` is an alias of `boolean`).
+const KNOWN_TYPES: &[&str] = &[
+    "string",
+    "integer",
+    "float",
+    "boolean",
+    "null",
+    "number",
+    "any",
+    "object",
+    "array",
+    "u8",
+    "u16",
+    "u32",
+    "i32",
+    "port",
+    "positive_integer",
+    "non_negative_integer",
+];
+
+/// Integer type names that carry an implied range, as `(min, max)`.
+pub(crate) fn integer_alias(name: &str) -> Option<(i64, Option<i64>)> {
+    match name {
+        "u8" => Some((0, Some(u8::MAX as i64))),
+        "u16" => Some((0, Some(u16::MAX as i64))),
+        "u32" => Some((0, Some(u32::MAX as i64))),
+        "i32" => Some((i32::MIN as i64, Some(i32::MAX as i64))),
+        "port" => Some((1, Some(u16::MAX as i64))),
+        "positive_integer" => Some((1, None)),
+        "non_negative_integer" => Some((0, None)),
+        _ => None,
+    }
+}
+
+/// Check `instance` against a type name or a union such as `"string | integer"`,
+/// pushing a mismatch to `report`.
+///
+/// Returns whether one of the types matched, i.e. whether value constraints
+/// apply. A `null` accepted only because the type is nullable (a `?` suffix
+/// such as `"string?"`, or `nullable`) is not an error but returns `false`.
 fn validate_type(
     instance: &Value,
     type_name: &str,
+    mut nullable: bool,
     path: &str,
     report: &mut ValidationReport,
-) -> Result<(), ValidationItem> {
-    let actual_type = instance.type_name();
-    let is_valid = match type_name {
-        "any" => true,
-        "string" => matches!(instance.kind, ValueKind::String(_)),
-        "integer" => matches!(instance.kind, ValueKind::Integer(_)),
-        "float" => matches!(instance.kind, ValueKind::Float(_)),
-        "boolean" | "bool" => matches!(instance.kind, ValueKind::Bool(_)),
-        "null" => matches!(instance.kind, ValueKind::Null),
-        "number" => matches!(instance.kind, ValueKind::Integer(_) | ValueKind::Float(_)),
-        _ => {
-            return Err(ValidationItem {
-                level: ValidationLevel::Error,
-                path: path.to_string(),
-                message: format!("Unknown type '{}'", type_name),
-            });
+) -> Result<bool, SchemaError> {
+    let mut is_valid = false;
+    let mut has_alias = false;
+    for name in type_name.split('|').map(str::trim) {
+        let name = match name.strip_suffix('?') {
+            Some(base) => {
+                nullable = true;
+                base
+            }
+            None => name,
+        };
+        if let Some((min, max)) = integer_alias(name) {
+            has_alias = true;
+            is_valid |= matches!(
+                instance.kind,
+                ValueKind::Integer(i) if i >= min && max.is_none_or(|max| i <= max)
+            );
+            continue;
         }
+        is_valid |= match name {
+            "any" => true,
+            "string" => matches!(instance.kind, ValueKind::String(_)),
+            "integer" => matches!(instance.kind, ValueKind::Integer(_)),
+            "float" => matches!(instance.kind, ValueKind::Float(_)),
+            "boolean" | "bool" => matches!(instance.kind, ValueKind::Bool(_)),
+            "null" => matches!(instance.kind, ValueKind::Null),
+            "number" => matches!(instance.kind, ValueKind::Integer(_) | ValueKind::Float(_)),
+            "object" => matches!(instance.kind, ValueKind::Object(_)),
+            "array" => matches!(instance.kind, ValueKind::Array(_)),
+            _ => {
+                return Err(SchemaError::unknown_type(
+                    path,
+                    name,
+                    suggest::find_best_match(name, KNOWN_TYPES, 3),
+                ));
+            }
+        };
+    }
+
+    let accepted_null = nullable && matches!(instance.kind, ValueKind::Null);
+    if !is_valid && !accepted_null {
+        // Name the range of sugar types, and the offending number if it is one
+        let (expected, actual) = if has_alias {
+            let expected: Vec<String> = type_name
+                .split('|')
+                .map(str::trim)
+                .map(|name| match integer_alias(name.trim_end_matches('?')) {
+                    Some((min, Some(max))) => format!("{} ({}..={})", name, min, max),
+                    Some((min, None)) => format!("{} ({}..)", name, min),
+                    None => name.to_string(),
+                })
+                .collect();
+            let actual = match instance.kind {
+                ValueKind::Integer(i) => i.to_string(),
+                _ => instance.type_name().to_string(),
+            };
+            (expected.join(" | "), actual)
+        } else {
+            (type_name.to_string(), instance.type_name().to_string())
+        };
+        report.push(
+            ValidationItem::error(path, ValidationErrorKind::TypeMismatch { expected, actual })
+                .with_position(instance.position),
+        );
+    }
+    Ok(is_valid)
+}
+
+/// Check the `enum`, `min` and `max` keys of an extended schema.
+fn check_value_constraints(
+    instance: &Value,
+    schema: &Value,
+    path: &str,
+    report: &mut ValidationReport,
+) -> Result<(), SchemaError> {
+    let ValueKind::Object(schema_obj) = &schema.kind else {
+        return Ok(());
     };
 
-    if !is_valid {
-        report.push(ValidationItem {
-            level: ValidationLevel::Error,
-            path: path.to_string(),
-            message: format!(
-                "Type mismatch: expected {}, found {}",
-                type_name, actual_type
+    if let Some(allowed) = schema_obj.get("enum") {
+        let ValueKind::Array(allowed) = &allowed.kind else {
+            return Err(invalid_schema(path, "'enum' must be an array".to_string()));
+        };
+        if !allowed.iter().any(|v| v.kind == instance.kind) {
+            let suggestion = match &instance.kind {
+                ValueKind::String(s) => {
+                    let strings: Vec<&String> = allowed
+                        .iter()
+                        .filter_map(|v| match &v.kind {
+                            ValueKind::String(allowed) => Some(allowed),
+                            _ => None,
+                        })
+                        .collect();
+                    suggest::find_best_match(s, &strings, 2)
+                        .map(|best| ValueKind::String(best).to_string())
+                }
+                _ => None,
+            };
+            report.push(
+                ValidationItem::error(
+                    path,
+                    ValidationErrorKind::NotInEnum {
+                        value: instance.kind.to_string(),
+                        allowed: allowed.iter().map(|v| v.kind.to_string()).collect(),
+                        suggestion,
+                    },
+                )
+                .with_position(instance.position),
+            );
+        }
+    }
+
+    let min = bound(schema_obj.get("min"), "min", path)?;
+    let max = bound(schema_obj.get("max"), "max", path)?;
+    let below = min.is_some_and(|m| compare_numbers(&instance.kind, m) == Some(Ordering::Less));
+    let above = max.is_some_and(|m| compare_numbers(&instance.kind, m) == Some(Ordering::Greater));
+    if below || above {
+        report.push(
+            ValidationItem::error(
+                path,
+                ValidationErrorKind::OutOfRange {
+                    value: instance.kind.to_string(),
+                    min: min.map(|m| m.to_string()),
+                    max: max.map(|m| m.to_string()),
+                },
+            )
+            .with_position(instance.position),
+        );
+    }
+
+    if let ValueKind::String(s) = &instance.kind {
+        check_string(instance, s, schema_obj, path, report)?;
+    }
+    Ok(())
+}
+
+/// Check the `minLength`, `maxLength` and `format` keys against a string.
+fn check_string(
+    instance: &Value,
+    s: &str,
+    schema_obj: &IndexMap<String, Value>,
+    path: &str,
+    report: &mut ValidationReport,
+) -> Result<(), SchemaError> {
+    let min = length_bound(schema_obj.get("minLength"), "minLength", path)?;
+    let max = length_bound(schema_obj.get("maxLength"), "maxLength", path)?;
+    // Lengths count Unicode code points, not bytes
+    let length = s.chars().count();
+    if min.is_some_and(|m| length < m) || max.is_some_and(|m| length > m) {
+        report.push(
+            ValidationItem::error(
+                path,
+                ValidationErrorKind::LengthOutOfRange { length, min, max },
+            )
+            .with_position(instance.position),
+        );
+    }
+
+    if let Some(format) = schema_obj.get("format") {
+        let ValueKind::String(format) = &format.kind else {
+            return Err(invalid_schema(
+                path,
+                "'format' must be a string".to_string(),
+            ));
+        };
+        match formats::check(format, s) {
+            Some(true) => {}
+            Some(false) => report.push(
+                ValidationItem::error(
+                    path,
+                    ValidationErrorKind::InvalidFormat {
+                        format: format.clone(),
+                        value: s.to_string(),
+                    },
+                )
+                .with_position(instance.position),
             ),
-        });
+            None => {
+                let mut message = format!("Unknown format '{}'", format);
+                if let Some(s) = suggest::find_best_match(format, formats::FORMATS, 2) {
+                    message.push_str(&format!("; did you mean '{}'?", s));
+                }
+                return Err(invalid_schema(path, message));
+            }
+        }
     }
     Ok(())
 }
 
-fn extract_metadata(schema: &Value) -> (&Value, Option<String>, bool) {
-    if let ValueKind::Object(schema_obj) = &schema.kind {
-        if let Some(type_val) = schema_obj.get("type") {
-            if let ValueKind::String(_) = type_val.kind {
-                // Extended schema definition
-                let type_def = schema_obj.get("type").unwrap();
+fn length_bound(
+    value: Option<&Value>,
+    key: &str,
+    path: &str,
+) -> Result<Option<usize>, SchemaError> {
+    match value.map(|v| &v.kind) {
+        None => Ok(None),
+        Some(ValueKind::Integer(n)) if *n >= 0 => Ok(Some(*n as usize)),
+        Some(_) => Err(invalid_schema(
+            path,
+            format!("'{}' must be a non-negative integer", key),
+        )),
+    }
+}
+
+fn invalid_schema(path: &str, message: String) -> SchemaError {
+    SchemaError::new(path, message)
+}
 
-                let deprecated_msg = if let Some(dep_val) = schema_obj.get("deprecated") {
-                    if let ValueKind::String(msg) = &dep_val.kind {
-                        Some(msg.clone())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
+/// Report a failed `all_of` merge at the composed schema's `path`.
+fn all_of_error(path: &str, e: SchemaError) -> SchemaError {
+    SchemaError {
+        message: format!("{} (at {})", e.message, e.schema_path),
+        schema_path: path.to_string(),
+        ..e
+    }
+}
 
-                let optional = if let Some(opt_val) = schema_obj.get("optional") {
-                    if let ValueKind::Bool(b) = &opt_val.kind {
-                        *b
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
+fn bound<'a>(
+    value: Option<&'a Value>,
+    key: &str,
+    path: &str,
+) -> Result<Option<&'a ValueKind>, SchemaError> {
+    match value.map(|v| &v.kind) {
+        None => Ok(None),
+        Some(kind @ (ValueKind::Integer(_) | ValueKind::Float(_))) => Ok(Some(kind)),
+        Some(_) => Err(invalid_schema(path, format!("'{}' must be a number", key))),
+    }
+}
+
+/// Order two numeric values; `None` if either is not a number.
+fn compare_numbers(a: &ValueKind, b: &ValueKind) -> Option<Ordering> {
+    match (a, b) {
+        (ValueKind::Integer(a), ValueKind::Integer(b)) => Some(a.cmp(b)),
+        (ValueKind::Integer(a), ValueKind::Float(b)) => (*a as f64).partial_cmp(b),
+        (ValueKind::Float(a), ValueKind::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (ValueKind::Float(a), ValueKind::Float(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
 
-                return (type_def, deprecated_msg, optional);
+/// Keys recognised in the extended form `{ type: ..., optional: true, ... }`.
+const METADATA_KEYS: &[&str] = &[
+    "type",
+    "optional",
+    "deprecated",
+    "info",
+    "title",
+    "description",
+    "enum",
+    "min",
+    "max",
+    "minLength",
+    "maxLength",
+    "format",
+    "nullable",
+    "dependencies",
+    "exclusiveGroups",
+    "items",
+];
+
+/// Whether an extended schema sets `nullable: true`, allowing an explicit
+/// `null` in addition to its type. Unlike `optional`, the key must be present.
+fn is_nullable(schema: &Value) -> bool {
+    if let ValueKind::Object(schema_obj) = &schema.kind
+        && let Some(type_def) = schema_obj.get("type")
+        && is_extended(type_def, schema_obj)
+    {
+        return matches!(
+            schema_obj.get("nullable"),
+            Some(Value {
+                kind: ValueKind::Bool(true),
+                ..
+            })
+        );
+    }
+    false
+}
+
+/// The `info` annotation of an extended schema, reported as an `Info` item
+/// whenever the field is present.
+fn info_message(schema: &Value) -> Option<String> {
+    if let ValueKind::Object(schema_obj) = &schema.kind
+        && let Some(type_def) = schema_obj.get("type")
+        && is_extended(type_def, schema_obj)
+        && let Some(ValueKind::String(message)) = schema_obj.get("info").map(|v| &v.kind)
+    {
+        return Some(message.clone());
+    }
+    None
+}
+
+fn extract_metadata(schema: &Value) -> (&Value, Option<Deprecation>, bool) {
+    if let ValueKind::Object(schema_obj) = &schema.kind
+        && let Some(type_def) = schema_obj.get("type")
+        && is_extended(type_def, schema_obj)
+    {
+        // Extended schema definition
+        let deprecation = schema_obj.get("deprecated").and_then(Deprecation::parse);
+
+        let optional = if let Some(opt_val) = schema_obj.get("optional") {
+            if let ValueKind::Bool(b) = &opt_val.kind {
+                *b
+            } else {
+                false
             }
-        }
+        } else {
+            false
+        };
+
+        return (type_def, deprecation, optional);
     }
     (schema, None, false)
 }
+
+/// Whether an object schema with a `type` key is the extended form rather
+/// than an object with a field named `type`.
+///
+/// A string `type` always selects the extended form. An object or array
+/// `type` does so only when every other key is a metadata key.
+fn is_extended(type_def: &Value, schema_obj: &IndexMap<String, Value>) -> bool {
+    match type_def.kind {
+        ValueKind::String(_) => true,
+        ValueKind::Object(_) | ValueKind::Array(_) => schema_obj
+            .keys()
+            .all(|k| METADATA_KEYS.contains(&k.as_str())),
+        _ => false,
+    }
+}
+
+/// The reserved object-schema key controlling fields not listed in the schema.
+///
+/// `true` allows any extra field, `false` (the default) rejects them, and a
+/// schema validates each extra field against it.
+pub const ADDITIONAL_PROPERTIES_KEY: &str = "additionalProperties";
+
+/// Whether an object-schema entry is a directive rather than a field.
+fn is_directive(key: &str, schema: &Value) -> bool {
+    key == ADDITIONAL_PROPERTIES_KEY
+        || (key == patterns::PATTERN_PROPERTIES_KEY && matches!(schema.kind, ValueKind::Object(_)))
+        || constraints::is_directive(key, schema)
+}
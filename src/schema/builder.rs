@@ -0,0 +1,309 @@
+//! A fluent API for constructing schemas in code.
+//!
+//! ```
+//! use cosy::schema::SchemaBuilder;
+//!
+//! let schema = SchemaBuilder::object()
+//!     .field("host", SchemaBuilder::string())
+//!     .field("port", SchemaBuilder::integer().minimum(1024).maximum(65535))
+//!     .field("debug", SchemaBuilder::boolean().optional())
+//!     .build();
+//!
+//! let expected = cosy::from_str(
+//!     r#"{
+//!         host: "string"
+//!         port: { type: "integer", min: 1024, max: 65535 }
+//!         debug: { type: "boolean", optional: true }
+//!     }"#,
+//! )
+//! .unwrap();
+//! assert_eq!(schema, expected);
+//! ```
+
+use super::constraints::CONSTRAINTS_KEY;
+use super::{ADDITIONAL_PROPERTIES_KEY, METADATA_KEYS, SchemaError, is_extended};
+use crate::path;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// Builds a schema `Value` that [`validate`](super::validate) understands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaBuilder {
+    shape: Shape,
+    /// Keys of the extended form, other than `type`
+    metadata: IndexMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// A type name such as `"string"`, `"port"` or `"string|null"`
+    Type(String),
+    Object {
+        fields: IndexMap<String, SchemaBuilder>,
+        /// `additionalProperties` and `constraints`, kept as written
+        directives: IndexMap<String, Value>,
+    },
+    Array(Box<SchemaBuilder>),
+    /// A schema the builder has no structure for, such as `all_of`
+    Raw(Value),
+}
+
+impl SchemaBuilder {
+    fn new(shape: Shape) -> Self {
+        SchemaBuilder {
+            shape,
+            metadata: IndexMap::new(),
+        }
+    }
+
+    /// A schema for the named type, including aliases like `"port"` and
+    /// unions like `"string|null"`.
+    pub fn named(type_name: impl Into<String>) -> Self {
+        Self::new(Shape::Type(type_name.into()))
+    }
+    pub fn string() -> Self {
+        Self::named("string")
+    }
+    pub fn integer() -> Self {
+        Self::named("integer")
+    }
+    pub fn float() -> Self {
+        Self::named("float")
+    }
+    pub fn number() -> Self {
+        Self::named("number")
+    }
+    pub fn boolean() -> Self {
+        Self::named("boolean")
+    }
+    pub fn null() -> Self {
+        Self::named("null")
+    }
+    pub fn any() -> Self {
+        Self::named("any")
+    }
+
+    /// An object schema with no fields yet.
+    pub fn object() -> Self {
+        Self::new(Shape::Object {
+            fields: IndexMap::new(),
+            directives: IndexMap::new(),
+        })
+    }
+
+    /// An array schema whose items all match `items`.
+    pub fn array(items: SchemaBuilder) -> Self {
+        Self::new(Shape::Array(Box::new(items)))
+    }
+
+    /// Add a field to an object schema, replacing any field of that name.
+    ///
+    /// # Panics
+    ///
+    /// If this is not an object schema.
+    pub fn field(mut self, name: impl Into<String>, schema: SchemaBuilder) -> Self {
+        self.fields_mut().insert(name.into(), schema);
+        self
+    }
+
+    /// Remove a field from an object schema.
+    ///
+    /// # Panics
+    ///
+    /// If this is not an object schema.
+    pub fn without_field(mut self, name: &str) -> Self {
+        self.fields_mut().shift_remove(name);
+        self
+    }
+
+    /// Allow (`true`) or reject (`false`) fields not listed in an object schema.
+    pub fn additional_properties(self, allowed: bool) -> Self {
+        self.directive(ADDITIONAL_PROPERTIES_KEY, Value::boolean(allowed))
+    }
+
+    /// Validate fields not listed in an object schema against `schema`.
+    pub fn additional_properties_schema(self, schema: SchemaBuilder) -> Self {
+        self.directive(ADDITIONAL_PROPERTIES_KEY, schema.build())
+    }
+
+    /// Add a cross-field constraint such as `{ if_present: "a", require: "b" }`.
+    pub fn constraint(mut self, constraint: Value) -> Self {
+        let entry = self
+            .directives_mut()
+            .entry(CONSTRAINTS_KEY.to_string())
+            .or_insert_with(|| Value::array(Vec::new()));
+        if let ValueKind::Array(rules) = &mut entry.kind {
+            rules.push(constraint);
+        }
+        self
+    }
+
+    pub fn optional(self) -> Self {
+        self.meta("optional", Value::boolean(true))
+    }
+
+    /// Also accept an explicit `null`.
+    pub fn nullable(self) -> Self {
+        self.meta("nullable", Value::boolean(true))
+    }
+
+    /// Mark the field as deprecated with a message.
+    pub fn deprecated(self, message: impl Into<String>) -> Self {
+        self.meta("deprecated", Value::string(message.into()))
+    }
+
+    /// Attach an informational note, reported as an `Info` item when the
+    /// field is present.
+    pub fn info(self, message: impl Into<String>) -> Self {
+        self.meta("info", Value::string(message.into()))
+    }
+
+    /// A short name for the field, used by [`describe`](super::describe).
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.meta("title", Value::string(title.into()))
+    }
+
+    /// Explain the field, used by [`describe`](super::describe).
+    pub fn description(self, description: impl Into<String>) -> Self {
+        self.meta("description", Value::string(description.into()))
+    }
+
+    /// Restrict the value to one of `values` (the `enum` keyword).
+    pub fn allowed<V: Into<Value>>(self, values: impl IntoIterator<Item = V>) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.meta("enum", Value::array(values))
+    }
+
+    pub fn minimum(self, min: impl Into<Value>) -> Self {
+        self.meta("min", min.into())
+    }
+    pub fn maximum(self, max: impl Into<Value>) -> Self {
+        self.meta("max", max.into())
+    }
+    pub fn min_length(self, min: usize) -> Self {
+        self.meta("minLength", Value::integer(min as i64))
+    }
+    pub fn max_length(self, max: usize) -> Self {
+        self.meta("maxLength", Value::integer(max as i64))
+    }
+
+    /// Require a string format such as `"email"` or `"uuid"`.
+    pub fn format(self, format: impl Into<String>) -> Self {
+        self.meta("format", Value::string(format.into()))
+    }
+
+    /// Produce the schema `Value`.
+    ///
+    /// A schema with metadata uses the extended form
+    /// `{ type: ..., optional: true, ... }`; otherwise the plain form.
+    pub fn build(&self) -> Value {
+        let base = match &self.shape {
+            Shape::Type(name) => Value::string(name.clone()),
+            Shape::Object { fields, directives } => {
+                let mut obj: IndexMap<String, Value> = fields
+                    .iter()
+                    .map(|(name, schema)| (name.clone(), schema.build()))
+                    .collect();
+                obj.extend(directives.clone());
+                Value::object(obj)
+            }
+            Shape::Array(items) => Value::array(vec![items.build()]),
+            Shape::Raw(value) => value.clone(),
+        };
+
+        if self.metadata.is_empty() {
+            return base;
+        }
+        let mut obj = IndexMap::new();
+        obj.insert("type".to_string(), base);
+        obj.extend(self.metadata.clone());
+        Value::object(obj)
+    }
+
+    /// Parse an existing schema so it can be modified.
+    pub fn from_value(value: &Value) -> Result<SchemaBuilder, SchemaError> {
+        from_value_at(value, "$")
+    }
+
+    fn meta(mut self, key: &str, value: Value) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    fn directive(mut self, key: &str, value: Value) -> Self {
+        self.directives_mut().insert(key.to_string(), value);
+        self
+    }
+
+    fn fields_mut(&mut self) -> &mut IndexMap<String, SchemaBuilder> {
+        match &mut self.shape {
+            Shape::Object { fields, .. } => fields,
+            _ => panic!("SchemaBuilder: fields can only be set on an object schema"),
+        }
+    }
+
+    fn directives_mut(&mut self) -> &mut IndexMap<String, Value> {
+        match &mut self.shape {
+            Shape::Object { directives, .. } => directives,
+            _ => panic!("SchemaBuilder: directives can only be set on an object schema"),
+        }
+    }
+}
+
+fn from_value_at(value: &Value, path: &str) -> Result<SchemaBuilder, SchemaError> {
+    match &value.kind {
+        ValueKind::String(name) => Ok(SchemaBuilder::named(name.clone())),
+        ValueKind::Array(items) => match items.as_slice() {
+            [item] => Ok(SchemaBuilder::array(from_value_at(
+                item,
+                &format!("{}[*]", path),
+            )?)),
+            _ => Err(SchemaError::new(
+                path,
+                format!(
+                    "Array schema must have exactly one item type, found {}",
+                    items.len()
+                ),
+            )),
+        },
+        ValueKind::Object(obj) => {
+            if let Some(type_def) = obj.get("type")
+                && is_extended(type_def, obj)
+            {
+                let mut builder = from_value_at(type_def, path)?;
+                for (key, value) in obj.iter().filter(|(k, _)| *k != "type") {
+                    if !METADATA_KEYS.contains(&key.as_str()) {
+                        return Err(SchemaError::new(
+                            path,
+                            format!("Unknown schema keyword '{}'", key),
+                        ));
+                    }
+                    builder.metadata.insert(key.clone(), value.clone());
+                }
+                return Ok(builder);
+            }
+            if super::compose::all_of(value).is_some() {
+                return Ok(SchemaBuilder::new(Shape::Raw(value.clone())));
+            }
+
+            let mut fields = IndexMap::new();
+            let mut directives = IndexMap::new();
+            for (key, schema) in obj {
+                if super::is_directive(key, schema) {
+                    directives.insert(key.clone(), schema.clone());
+                } else {
+                    let field = from_value_at(schema, &path::join_key(path, key))?;
+                    fields.insert(key.clone(), field);
+                }
+            }
+            Ok(SchemaBuilder::new(Shape::Object { fields, directives }))
+        }
+        other => Err(SchemaError::new(
+            path,
+            format!(
+                "Expected a type name, object or array schema, found {}",
+                other.type_name()
+            ),
+        )),
+    }
+}
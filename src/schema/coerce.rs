@@ -0,0 +1,168 @@
+//! Opt-in type coercion for string values.
+//!
+//! Configs fed from environment variables often carry `"8080"` where an
+//! integer is expected. Coercion rewrites such strings in place when they
+//! parse cleanly as the schema type, and reports each rewrite.
+
+use super::{
+    SchemaError, ValidationErrorKind, ValidationItem, ValidationReport, compose, extract_metadata,
+    integer_alias, is_directive, validate,
+};
+use crate::path;
+use crate::value::{Value, ValueKind};
+
+/// How `validate_and_coerce_with` reports successful coercions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoercionReport {
+    /// Add a `Warning` item for each coerced value.
+    Warn,
+    /// Coerce without reporting.
+    Silent,
+}
+
+/// Validate `instance`, first converting strings that parse as the expected
+/// integer, float, number or boolean type.
+///
+/// Each coercion is reported as a `Warning`. Strings that don't parse are
+/// left alone and still fail validation as type mismatches. An invalid
+/// schema is returned as `Err`, as with [`validate`]. This is the entry point
+/// to use; [`validate_and_coerce_with`] adds control over the warnings.
+pub fn validate_coercing(
+    instance: &mut Value,
+    schema: &Value,
+) -> Result<ValidationReport, SchemaError> {
+    coerce_and_validate(instance, schema, CoercionReport::Warn)
+}
+
+/// [`validate_coercing`] with an invalid schema reported as the report's
+/// single item instead of `Err`.
+///
+/// Prefer [`validate_coercing`]; this form predates it.
+pub fn validate_and_coerce(instance: &mut Value, schema: &Value) -> ValidationReport {
+    validate_coercing(instance, schema).unwrap_or_else(into_report)
+}
+
+/// Like [`validate_and_coerce`], with control over how coercions are reported.
+pub fn validate_and_coerce_with(
+    instance: &mut Value,
+    schema: &Value,
+    reporting: CoercionReport,
+) -> ValidationReport {
+    coerce_and_validate(instance, schema, reporting).unwrap_or_else(into_report)
+}
+
+/// A report holding only the schema error.
+fn into_report(fatal: SchemaError) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    report.push(fatal.into());
+    report
+}
+
+/// Coerce, then validate, putting any coercion warnings ahead of the
+/// validation items.
+fn coerce_and_validate(
+    instance: &mut Value,
+    schema: &Value,
+    reporting: CoercionReport,
+) -> Result<ValidationReport, SchemaError> {
+    let mut coerced = ValidationReport::new();
+    coerce_recursive(instance, schema, "$", &mut coerced);
+
+    let mut report = validate(instance, schema)?;
+    if reporting == CoercionReport::Warn {
+        report.items.splice(0..0, coerced.items);
+    }
+    Ok(report)
+}
+
+fn coerce_recursive(
+    instance: &mut Value,
+    schema: &Value,
+    path: &str,
+    report: &mut ValidationReport,
+) {
+    // A conflicting composition is reported by validation
+    if let Some(parts) = compose::all_of(schema) {
+        if let Ok(merged) = compose::merge_schemas(parts) {
+            coerce_recursive(instance, &merged, path, report);
+        }
+        return;
+    }
+
+    let (effective, _, _) = extract_metadata(schema);
+
+    match (&effective.kind, &mut instance.kind) {
+        (ValueKind::String(type_name), ValueKind::String(s)) => {
+            if let Some((kind, to)) = coerce_string(s, type_name) {
+                let item = ValidationItem::warning(
+                    path,
+                    ValidationErrorKind::Coerced {
+                        from: s.clone(),
+                        to: to.to_string(),
+                    },
+                )
+                .with_position(instance.position);
+                instance.kind = kind;
+                report.push(item);
+            }
+        }
+        (ValueKind::Object(schema_obj), ValueKind::Object(instance_obj)) => {
+            for (key, sub_schema) in schema_obj {
+                if is_directive(key, sub_schema) {
+                    continue;
+                }
+                if let Some(child) = instance_obj.get_mut(key) {
+                    coerce_recursive(child, sub_schema, &path::join_key(path, key), report);
+                }
+            }
+        }
+        (ValueKind::Array(schema_arr), ValueKind::Array(instance_arr)) if schema_arr.len() == 1 => {
+            for (i, item) in instance_arr.iter_mut().enumerate() {
+                coerce_recursive(item, &schema_arr[0], &path::join_index(path, i), report);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Convert `s` to the first type of a (possibly `|`) union it parses as,
+/// returning the new value and that type's name.
+///
+/// Nothing is coerced if the union already accepts strings.
+fn coerce_string<'a>(s: &str, type_name: &'a str) -> Option<(ValueKind, &'a str)> {
+    let members: Vec<&str> = type_name
+        .split('|')
+        .map(|name| name.trim().trim_end_matches('?'))
+        .collect();
+    if members.iter().any(|name| matches!(*name, "string" | "any")) {
+        return None;
+    }
+    members
+        .into_iter()
+        .find_map(|name| coerce_to(s, name).map(|kind| (kind, name)))
+}
+
+fn coerce_to(s: &str, type_name: &str) -> Option<ValueKind> {
+    match type_name {
+        "integer" => s.parse::<i64>().ok().map(ValueKind::Integer),
+        // Range-checked by validation afterwards
+        name if integer_alias(name).is_some() => s.parse::<i64>().ok().map(ValueKind::Integer),
+        "float" => s
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(ValueKind::Float),
+        "number" => s.parse::<i64>().map(ValueKind::Integer).ok().or_else(|| {
+            s.parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(ValueKind::Float)
+        }),
+        "boolean" | "bool" => match s {
+            "true" => Some(ValueKind::Bool(true)),
+            "false" => Some(ValueKind::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
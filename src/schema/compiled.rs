@@ -0,0 +1,204 @@
+//! A schema checked and prepared once, for validating many instances.
+//!
+//! [`Schema::compile`] merges every `all_of` composition and compiles every
+//! `patternProperties` regex up front, so repeated validations skip that
+//! work. The cache is keyed by the address of the schema node it belongs to;
+//! the schema is boxed and never mutated, so those addresses stay valid.
+
+use super::patterns::{self, KeyPattern, PATTERN_PROPERTIES_KEY};
+use super::{
+    ADDITIONAL_PROPERTIES_KEY, KNOWN_TYPES, SchemaError, TUPLE_TYPE, ValidationReport, compose,
+    extract_metadata, integer_alias, is_directive, tuple_items, validate_recursive,
+};
+use crate::error::CosynError;
+use crate::path;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The key of a `$ref` cross-reference.
+pub const REF_KEY: &str = "$ref";
+
+/// A schema that has been checked for structural validity and prepared for
+/// repeated validation.
+///
+/// # Example
+///
+/// ```
+/// use cosy::Schema;
+///
+/// let schema = Schema::compile(cosy::from_str(r#"{ port: "port" }"#).unwrap()).unwrap();
+/// let report = schema.validate_str("{ port: 8080 }").unwrap();
+/// assert!(report.is_valid());
+/// ```
+pub struct Schema {
+    root: Box<Value>,
+    cache: SchemaCache,
+}
+
+impl Schema {
+    /// Check `schema_value` and prepare it for validation.
+    ///
+    /// Fails on unknown type names, malformed array schemas, conflicting
+    /// `all_of` parts, invalid key patterns and `$ref`, which is not
+    /// supported yet. Problems only visible with an instance, such as an
+    /// unknown `format`, are still reported by [`validate`](Self::validate).
+    pub fn compile(schema_value: Value) -> Result<Schema, SchemaError> {
+        let root = Box::new(schema_value);
+        let mut cache = SchemaCache::default();
+        compile_node(&root, "$", &mut cache)?;
+        Ok(Schema { root, cache })
+    }
+
+    /// The schema as written.
+    pub fn as_value(&self) -> &Value {
+        &self.root
+    }
+
+    /// Validate `instance`, like [`validate`](super::validate).
+    pub fn validate(&self, instance: &Value) -> Result<ValidationReport, SchemaError> {
+        let mut report = ValidationReport::new();
+        validate_recursive(instance, &self.root, "$", &self.cache, &mut report)?;
+        Ok(report)
+    }
+
+    /// Parse `input` and validate it.
+    ///
+    /// A schema problem found during validation is returned as
+    /// [`CosynError::Schema`].
+    pub fn validate_str(&self, input: &str) -> Result<ValidationReport, CosynError> {
+        let instance = crate::from_str(input)?;
+        self.validate(&instance)
+            .map_err(|e| CosynError::Schema(e.to_string()))
+    }
+}
+
+impl fmt::Debug for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Schema")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Work done once per schema: merged `all_of` compositions and compiled key
+/// patterns. Empty when validating an uncompiled schema.
+#[derive(Default)]
+pub(super) struct SchemaCache {
+    merged: HashMap<usize, Box<Value>>,
+    patterns: HashMap<usize, Vec<KeyPattern>>,
+}
+
+impl SchemaCache {
+    /// The merged form of an `all_of` schema.
+    pub(super) fn merged(&self, schema: &Value) -> Option<&Value> {
+        self.merged.get(&address(schema)).map(|merged| &**merged)
+    }
+
+    /// The compiled patterns of a `patternProperties` object.
+    pub(super) fn patterns(&self, patterns: &IndexMap<String, Value>) -> Option<&[KeyPattern]> {
+        self.patterns.get(&address(patterns)).map(Vec::as_slice)
+    }
+}
+
+fn address<T>(node: &T) -> usize {
+    node as *const T as usize
+}
+
+fn compile_node(schema: &Value, path: &str, cache: &mut SchemaCache) -> Result<(), SchemaError> {
+    if let Some(parts) = compose::all_of(schema) {
+        let merged = Box::new(compose::merge_schemas(parts).map_err(|e| {
+            SchemaError::new(&format!("{}{}", path, &e.schema_path[1..]), e.message)
+        })?);
+        compile_node(&merged, path, cache)?;
+        cache.merged.insert(address(schema), merged);
+        return Ok(());
+    }
+
+    let (effective, _, _) = extract_metadata(schema);
+    match &effective.kind {
+        ValueKind::String(type_name) if type_name == TUPLE_TYPE => {
+            let items = tuple_items(schema).map_err(|message| SchemaError::new(path, message))?;
+            for (i, item) in items.iter().enumerate() {
+                compile_node(item, &path::join_index(path, i), cache)?;
+            }
+            Ok(())
+        }
+        ValueKind::String(type_name) => check_type_name(type_name, path),
+        ValueKind::Object(schema_obj) => {
+            if let Some(reference) = schema_obj.get(REF_KEY) {
+                return resolve_ref(reference, path);
+            }
+            for (key, sub_schema) in schema_obj {
+                if !is_directive(key, sub_schema) {
+                    compile_node(sub_schema, &path::join_key(path, key), cache)?;
+                }
+            }
+            if let Some(extra) = schema_obj.get(ADDITIONAL_PROPERTIES_KEY)
+                && !matches!(extra.kind, ValueKind::Bool(_))
+            {
+                compile_node(
+                    extra,
+                    &path::join_key(path, ADDITIONAL_PROPERTIES_KEY),
+                    cache,
+                )?;
+            }
+            if let Some(Value {
+                kind: ValueKind::Object(pattern_schemas),
+                ..
+            }) = schema_obj.get(PATTERN_PROPERTIES_KEY)
+            {
+                let compiled = patterns::compile(pattern_schemas, path)?;
+                for (pattern, sub_schema) in pattern_schemas {
+                    let sub_path = format!("{}.{}[{:?}]", path, PATTERN_PROPERTIES_KEY, pattern);
+                    compile_node(sub_schema, &sub_path, cache)?;
+                }
+                cache.patterns.insert(address(pattern_schemas), compiled);
+            }
+            Ok(())
+        }
+        ValueKind::Array(items) if items.len() == 1 => {
+            compile_node(&items[0], &format!("{}[]", path), cache)
+        }
+        ValueKind::Array(_) => Err(SchemaError::new(
+            path,
+            "Array schema must contain exactly one element specifier",
+        )),
+        other => Err(SchemaError::new(
+            path,
+            format!("Unsupported schema value type: {}", other.type_name()),
+        )),
+    }
+}
+
+/// Check every name in a type or `|` union, allowing a `?` suffix.
+fn check_type_name(type_name: &str, path: &str) -> Result<(), SchemaError> {
+    for name in type_name.split('|').map(str::trim) {
+        let name = name.strip_suffix('?').unwrap_or(name);
+        if name != "bool" && !KNOWN_TYPES.contains(&name) && integer_alias(name).is_none() {
+            return Err(SchemaError::unknown_type(
+                path,
+                name,
+                super::suggest::find_best_match(name, KNOWN_TYPES, 3),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a `$ref` cross-reference. Not supported yet, so any reference is
+/// reported rather than silently validated as an object with a `$ref` field.
+fn resolve_ref(reference: &Value, path: &str) -> Result<(), SchemaError> {
+    let target = match &reference.kind {
+        ValueKind::String(target) => target.clone(),
+        other => other.to_string(),
+    };
+    Err(SchemaError::new(
+        path,
+        format!(
+            "'{}' cross-references are not supported yet ({})",
+            REF_KEY, target
+        ),
+    ))
+}
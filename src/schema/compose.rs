@@ -0,0 +1,152 @@
+//! Schema composition with `all_of`.
+//!
+//! `{ all_of: [common, service] }` validates against the structural merge of
+//! its sub-schemas: object fields are unioned (recursively for fields both
+//! define), so fields from one part are not flagged as unknown by another.
+//! For a field defined more than once, metadata such as `optional` from later
+//! schemas overrides earlier values.
+
+use super::constraints::CONSTRAINTS_KEY;
+use super::{SchemaError, extract_metadata, is_directive};
+use crate::path;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// The reserved key of a composed schema.
+pub const ALL_OF_KEY: &str = "all_of";
+
+/// The sub-schemas of `{ all_of: [...] }`, if `schema` has that form.
+pub fn all_of(schema: &Value) -> Option<&[Value]> {
+    match &schema.kind {
+        ValueKind::Object(obj) if obj.len() == 1 => match &obj.get(ALL_OF_KEY)?.kind {
+            ValueKind::Array(parts) => Some(parts),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Merge schemas into one that an instance satisfies only if it satisfies
+/// every part.
+///
+/// Returns an error when two parts declare incompatible types for the same
+/// field (e.g. `"string"` and `"integer"`).
+pub fn merge_schemas(schemas: &[Value]) -> Result<Value, SchemaError> {
+    let mut parts = schemas.iter();
+    let Some(first) = parts.next() else {
+        return Ok(Value::from("any"));
+    };
+    let mut merged = expand(first, "$")?;
+    for part in parts {
+        merged = merge_two(&merged, &expand(part, "$")?, "$")?;
+    }
+    Ok(merged)
+}
+
+/// Resolve a nested `all_of` so it can be merged like any other schema.
+fn expand(schema: &Value, path: &str) -> Result<Value, SchemaError> {
+    match all_of(schema) {
+        Some(parts) => merge_schemas(parts).map_err(|e| {
+            // The nested path starts at its own `$`
+            let nested = e.schema_path.strip_prefix('$').unwrap_or(&e.schema_path);
+            SchemaError::new(&format!("{}{}", path, nested), e.message)
+        }),
+        None => Ok(schema.clone()),
+    }
+}
+
+fn merge_two(a: &Value, b: &Value, path: &str) -> Result<Value, SchemaError> {
+    let a = expand(a, path)?;
+    let b = expand(b, path)?;
+    let (a_type, _, _) = extract_metadata(&a);
+    let (b_type, _, _) = extract_metadata(&b);
+
+    let merged_type = match (&a_type.kind, &b_type.kind) {
+        (ValueKind::Object(a_fields), ValueKind::Object(b_fields)) => {
+            Value::object(merge_fields(a_fields, b_fields, path)?)
+        }
+        (ValueKind::Array(a_items), ValueKind::Array(b_items))
+            if a_items.len() == 1 && b_items.len() == 1 =>
+        {
+            Value::array(vec![merge_two(
+                &a_items[0],
+                &b_items[0],
+                &format!("{}[*]", path),
+            )?])
+        }
+        (ValueKind::String(x), ValueKind::String(y)) if x == y => a_type.clone(),
+        _ => {
+            return Err(SchemaError::new(
+                path,
+                format!(
+                    "Conflicting types in all_of: {} and {}",
+                    describe(a_type),
+                    describe(b_type)
+                ),
+            ));
+        }
+    };
+
+    // Metadata of the extended form, later keys overriding earlier ones
+    let mut metadata = IndexMap::new();
+    for schema in [&a, &b] {
+        if !std::ptr::eq(extract_metadata(schema).0, schema)
+            && let ValueKind::Object(obj) = &schema.kind
+        {
+            for (key, value) in obj.iter().filter(|(k, _)| *k != "type") {
+                metadata.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    if metadata.is_empty() {
+        Ok(merged_type)
+    } else {
+        metadata.insert_before(0, "type".to_string(), merged_type);
+        Ok(Value::object(metadata))
+    }
+}
+
+fn merge_fields(
+    a: &IndexMap<String, Value>,
+    b: &IndexMap<String, Value>,
+    path: &str,
+) -> Result<IndexMap<String, Value>, SchemaError> {
+    let mut merged = a.clone();
+    for (key, schema) in b {
+        if is_directive(key, schema) {
+            match (key.as_str(), merged.get_mut(key)) {
+                // Every part's constraints apply
+                (
+                    CONSTRAINTS_KEY,
+                    Some(Value {
+                        kind: ValueKind::Array(rules),
+                        ..
+                    }),
+                ) => {
+                    if let ValueKind::Array(more) = &schema.kind {
+                        rules.extend(more.iter().cloned());
+                    }
+                }
+                // The later part decides, e.g. for `additionalProperties`
+                _ => {
+                    merged.insert(key.clone(), schema.clone());
+                }
+            }
+            continue;
+        }
+        let field = match merged.get(key) {
+            Some(existing) => merge_two(existing, schema, &path::join_key(path, key))?,
+            None => schema.clone(),
+        };
+        merged.insert(key.clone(), field);
+    }
+    Ok(merged)
+}
+
+fn describe(schema: &Value) -> String {
+    match &schema.kind {
+        ValueKind::String(name) => format!("'{}'", name),
+        other => other.type_name().to_string(),
+    }
+}
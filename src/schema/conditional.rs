@@ -0,0 +1,80 @@
+//! Conditional schemas with `if`, `then` and `else`.
+//!
+//! `{ if: { env: "string" }, then: { host: "string" }, else: { socket: "string" } }`
+//! checks the instance against `if` first. The condition is open: fields it
+//! does not mention are ignored, so it only asks whether the fields it names
+//! are present and valid. A matching instance is then validated against the
+//! rest of the schema merged with `if` and `then`, any other against the rest
+//! merged with `else`.
+//!
+//! `if` is only read as a condition alongside `then` or `else`, so a schema
+//! describing a config key named `if` keeps working.
+
+use super::compose::merge_schemas;
+use super::{SchemaError, ValidationErrorKind, ValidationReport};
+use crate::value::{Value, ValueKind};
+
+/// The key of the condition.
+pub const IF_KEY: &str = "if";
+
+/// The key of the schema applied when the condition matches.
+pub const THEN_KEY: &str = "then";
+
+/// The key of the schema applied when the condition does not match.
+pub const ELSE_KEY: &str = "else";
+
+/// The parts of a conditional schema.
+pub struct Conditional<'a> {
+    pub condition: &'a Value,
+    pub then: Option<&'a Value>,
+    pub otherwise: Option<&'a Value>,
+    /// The schema without its `if`, `then` and `else` keys
+    pub rest: Value,
+}
+
+/// The parts of `{ if: ..., then: ..., else: ... }`, if `schema` has that form.
+pub fn conditional(schema: &Value) -> Option<Conditional<'_>> {
+    let ValueKind::Object(obj) = &schema.kind else {
+        return None;
+    };
+    let condition = obj.get(IF_KEY)?;
+    let then = obj.get(THEN_KEY);
+    let otherwise = obj.get(ELSE_KEY);
+    if then.is_none() && otherwise.is_none() {
+        return None;
+    }
+    let mut rest = obj.clone();
+    for key in [IF_KEY, THEN_KEY, ELSE_KEY] {
+        rest.shift_remove(key);
+    }
+    Some(Conditional {
+        condition,
+        then,
+        otherwise,
+        rest: Value::object(rest),
+    })
+}
+
+/// Whether the scratch report of checking the condition counts as a match.
+/// Unknown fields are ignored, since the condition only names the fields it
+/// tests.
+pub(super) fn matched(scratch: &ValidationReport) -> bool {
+    !scratch
+        .errors()
+        .any(|item| !matches!(item.kind, ValidationErrorKind::UnknownField { .. }))
+}
+
+/// The schema to validate against once the condition has been checked.
+pub(super) fn branch(conditional: &Conditional, matched: bool) -> Result<Value, SchemaError> {
+    let mut parts = Vec::new();
+    if !matches!(&conditional.rest.kind, ValueKind::Object(rest) if rest.is_empty()) {
+        parts.push(conditional.rest.clone());
+    }
+    if matched {
+        parts.push(conditional.condition.clone());
+        parts.extend(conditional.then.cloned());
+    } else {
+        parts.extend(conditional.otherwise.cloned());
+    }
+    merge_schemas(&parts)
+}
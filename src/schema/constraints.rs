@@ -0,0 +1,262 @@
+//! Cross-field constraints declared with a `constraints` array in an object
+//! schema.
+//!
+//! Each entry pairs a condition with an action:
+//!
+//! - `{ if_present: "password", forbid: "password_file" }`
+//! - `{ if_present: "user", require: ["password"] }`
+//! - `{ if_equals: { key: "tls.enabled", value: true }, require: ["tls.cert_path"] }`
+//! - `{ all_or_none: ["user", "password"] }`
+//!
+//! `require` and `forbid` accept a single key or an array of keys. Keys are
+//! dotted paths resolved within the object being validated.
+//!
+//! The extended form also accepts two shorthands, checked after the object's
+//! fields:
+//!
+//! - `dependencies: { ssl: ["cert_path", "key_path"] }` requires the listed
+//!   keys whenever `ssl` is set to something other than `false` or `null`
+//! - `exclusiveGroups: [["host", "socket_path"]]` allows at most one key of
+//!   each group
+
+use super::{SchemaError, ValidationErrorKind, ValidationItem, ValidationReport};
+use crate::syntax::lexer::Position;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// The reserved object-schema key holding constraints.
+pub const CONSTRAINTS_KEY: &str = "constraints";
+
+/// Whether an object-schema entry is the constraints directive rather than a field.
+///
+/// Any array of objects under `constraints` counts, so a misspelled rule is
+/// reported instead of quietly becoming a required field. A field named
+/// `constraints` either holds a non-object array (e.g. `["string"]`) or uses
+/// the extended form, `constraints: { type: [{ column: "string" }] }`.
+pub fn is_directive(key: &str, schema: &Value) -> bool {
+    let ValueKind::Array(rules) = &schema.kind else {
+        return false;
+    };
+    key == CONSTRAINTS_KEY
+        && rules
+            .iter()
+            .all(|rule| matches!(rule.kind, ValueKind::Object(_)))
+}
+
+/// The extended-form key mapping a trigger key to the keys it requires.
+pub const DEPENDENCIES_KEY: &str = "dependencies";
+
+/// The extended-form key listing groups of mutually exclusive keys.
+pub const EXCLUSIVE_GROUPS_KEY: &str = "exclusiveGroups";
+
+/// Evaluate every constraint against `instance`, pushing violations to `report`.
+pub fn check(
+    instance: &IndexMap<String, Value>,
+    constraints: &[Value],
+    path: &str,
+    position: Option<Position>,
+    report: &mut ValidationReport,
+) -> Result<(), SchemaError> {
+    for constraint in constraints {
+        let ValueKind::Object(entry) = &constraint.kind else {
+            return Err(invalid(path, "Each constraint must be an object"));
+        };
+
+        if let Some(group) = entry.get("all_or_none") {
+            let keys = key_list(group, path, "all_or_none")?;
+            if let Some(present) = keys.iter().find(|k| lookup(instance, k).is_some()) {
+                for key in keys.iter().filter(|k| lookup(instance, k).is_none()) {
+                    report.push(
+                        ValidationItem::error(
+                            path,
+                            ValidationErrorKind::RequiredBy {
+                                field: key.clone(),
+                                condition: format!("'{}' is present", present),
+                            },
+                        )
+                        .with_position(position),
+                    );
+                }
+            }
+            continue;
+        }
+
+        let condition = if let Some(trigger) = entry.get("if_present") {
+            let ValueKind::String(key) = &trigger.kind else {
+                return Err(invalid(path, "'if_present' must be a string"));
+            };
+            lookup(instance, key).map(|_| format!("'{}' is present", key))
+        } else if let Some(trigger) = entry.get("if_equals") {
+            let (key, expected) = match &trigger.kind {
+                ValueKind::Object(obj) => match (obj.get("key"), obj.get("value")) {
+                    (
+                        Some(Value {
+                            kind: ValueKind::String(k),
+                            ..
+                        }),
+                        Some(v),
+                    ) => (k, v),
+                    _ => {
+                        return Err(invalid(
+                            path,
+                            "'if_equals' must be an object with 'key' and 'value'",
+                        ));
+                    }
+                },
+                _ => {
+                    return Err(invalid(
+                        path,
+                        "'if_equals' must be an object with 'key' and 'value'",
+                    ));
+                }
+            };
+            lookup(instance, key)
+                .filter(|actual| actual.kind == expected.kind)
+                .map(|_| format!("'{}' is {}", key, expected.kind))
+        } else {
+            return Err(invalid(
+                path,
+                "Constraint must have 'if_present', 'if_equals' or 'all_or_none'",
+            ));
+        };
+
+        let Some(condition) = condition else {
+            continue;
+        };
+
+        if let Some(required) = entry.get("require") {
+            for key in key_list(required, path, "require")? {
+                if lookup(instance, &key).is_none() {
+                    report.push(
+                        ValidationItem::error(
+                            path,
+                            ValidationErrorKind::RequiredBy {
+                                field: key,
+                                condition: condition.clone(),
+                            },
+                        )
+                        .with_position(position),
+                    );
+                }
+            }
+        }
+
+        if let Some(forbidden) = entry.get("forbid") {
+            for key in key_list(forbidden, path, "forbid")? {
+                if let Some(value) = lookup(instance, &key) {
+                    report.push(
+                        ValidationItem::error(
+                            path,
+                            ValidationErrorKind::ForbiddenBy {
+                                field: key,
+                                condition: condition.clone(),
+                            },
+                        )
+                        .with_position(value.position.or(position)),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check the `dependencies` and `exclusiveGroups` keys of an extended schema.
+pub fn check_dependencies(
+    instance: &IndexMap<String, Value>,
+    schema_obj: &IndexMap<String, Value>,
+    path: &str,
+    position: Option<Position>,
+    report: &mut ValidationReport,
+) -> Result<(), SchemaError> {
+    if let Some(dependencies) = schema_obj.get(DEPENDENCIES_KEY) {
+        let ValueKind::Object(dependencies) = &dependencies.kind else {
+            return Err(invalid(path, "'dependencies' must be an object"));
+        };
+        for (trigger, required) in dependencies {
+            let required = key_list(required, path, "dependencies")?;
+            let is_set = lookup(instance, trigger)
+                .is_some_and(|v| !matches!(v.kind, ValueKind::Null | ValueKind::Bool(false)));
+            if !is_set {
+                continue;
+            }
+            for key in required {
+                if lookup(instance, &key).is_none() {
+                    report.push(
+                        ValidationItem::error(
+                            path,
+                            ValidationErrorKind::RequiredBy {
+                                field: key,
+                                condition: format!("'{}' is set", trigger),
+                            },
+                        )
+                        .with_position(position),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(groups) = schema_obj.get(EXCLUSIVE_GROUPS_KEY) {
+        let ValueKind::Array(groups) = &groups.kind else {
+            return Err(invalid(
+                path,
+                "'exclusiveGroups' must be an array of arrays",
+            ));
+        };
+        for group in groups {
+            let keys = key_list(group, path, "exclusiveGroups")?;
+            let mut present = keys
+                .iter()
+                .filter_map(|k| lookup(instance, k).map(|v| (k, v)));
+            let Some((first, _)) = present.next() else {
+                continue;
+            };
+            for (key, value) in present {
+                report.push(
+                    ValidationItem::error(
+                        path,
+                        ValidationErrorKind::ForbiddenBy {
+                            field: key.clone(),
+                            condition: format!("'{}' is present", first),
+                        },
+                    )
+                    .with_position(value.position.or(position)),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a dotted path like `tls.enabled` within an object.
+fn lookup<'a>(obj: &'a IndexMap<String, Value>, dotted: &str) -> Option<&'a Value> {
+    let mut parts = dotted.split('.');
+    let mut current = obj.get(parts.next()?)?;
+    for part in parts {
+        match &current.kind {
+            ValueKind::Object(map) => current = map.get(part)?,
+            _ => return None,
+        }
+    }
+    Some(current)
+}
+
+fn key_list(value: &Value, path: &str, name: &str) -> Result<Vec<String>, SchemaError> {
+    let message = format!("'{}' must be a string or an array of strings", name);
+    match &value.kind {
+        ValueKind::String(s) => Ok(vec![s.clone()]),
+        ValueKind::Array(arr) => arr
+            .iter()
+            .map(|v| match &v.kind {
+                ValueKind::String(s) => Ok(s.clone()),
+                _ => Err(invalid(path, &message)),
+            })
+            .collect(),
+        _ => Err(invalid(path, &message)),
+    }
+}
+
+fn invalid(path: &str, message: &str) -> SchemaError {
+    SchemaError::new(path, message)
+}
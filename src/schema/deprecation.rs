@@ -0,0 +1,168 @@
+//! Deprecation metadata and migration of deprecated fields.
+//!
+//! A field's `deprecated` entry is either a plain message or an object with
+//! machine-readable details:
+//!
+//! - `{ type: "integer", deprecated: "Use 'port' instead" }`
+//! - `{ type: "boolean", deprecated: { since: "2.0", use: "tls.enabled", note: "..." } }`
+//!
+//! The `use` path is a dotted path resolved within the object that holds the
+//! deprecated field.
+
+use super::{
+    ValidationErrorKind, ValidationItem, ValidationReport, compose, extract_metadata, is_directive,
+};
+use crate::path;
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+/// Parsed `deprecated` metadata of a schema field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Deprecation {
+    /// Free-form explanation (the string form, or the object's `note`).
+    pub message: Option<String>,
+    /// Version in which the field was deprecated.
+    pub since: Option<String>,
+    /// Dotted path of the field that replaces this one.
+    pub replacement: Option<String>,
+}
+
+impl Deprecation {
+    /// Parse a `deprecated` schema entry. Returns `None` for unsupported forms.
+    pub fn parse(value: &Value) -> Option<Self> {
+        match &value.kind {
+            ValueKind::String(msg) => Some(Deprecation {
+                message: Some(msg.clone()),
+                ..Default::default()
+            }),
+            ValueKind::Object(obj) => {
+                let text = |key: &str| match obj.get(key).map(|v| &v.kind) {
+                    Some(ValueKind::String(s)) => Some(s.clone()),
+                    _ => None,
+                };
+                Some(Deprecation {
+                    message: text("note"),
+                    since: text("since"),
+                    replacement: text("use"),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The `ValidationErrorKind` reported when the field is in use.
+    pub fn to_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::Deprecated {
+            message: self.message.clone().unwrap_or_default(),
+            since: self.since.clone(),
+            replacement: self.replacement.clone(),
+        }
+    }
+}
+
+/// Move values of deprecated fields to their `use` replacement.
+///
+/// A value is moved only when the replacement path is absent; intermediate
+/// objects are created as needed. Each move is reported as a `Warning`.
+/// Fields whose replacement is already set are left for `validate` to flag.
+pub fn migrate_deprecated(instance: &mut Value, schema: &Value) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    migrate_recursive(instance, schema, "$", &mut report);
+    report
+}
+
+fn migrate_recursive(
+    instance: &mut Value,
+    schema: &Value,
+    path: &str,
+    report: &mut ValidationReport,
+) {
+    // A conflicting composition is reported by validation
+    if let Some(parts) = compose::all_of(schema) {
+        if let Ok(merged) = compose::merge_schemas(parts) {
+            migrate_recursive(instance, &merged, path, report);
+        }
+        return;
+    }
+
+    let (effective, _, _) = extract_metadata(schema);
+
+    match (&effective.kind, &mut instance.kind) {
+        (ValueKind::Object(schema_obj), ValueKind::Object(instance_obj)) => {
+            for (key, sub_schema) in schema_obj {
+                if is_directive(key, sub_schema) {
+                    continue;
+                }
+                if let (_, Some(deprecation), _) = extract_metadata(sub_schema)
+                    && let Some(target) = &deprecation.replacement
+                    && instance_obj.contains_key(key)
+                    && lookup(instance_obj, target).is_none()
+                    && can_insert(instance_obj, target)
+                {
+                    let value = instance_obj.shift_remove(key).unwrap();
+                    let position = value.position;
+                    insert(instance_obj, target, value);
+                    report.push(
+                        ValidationItem::warning(
+                            &path::join_key(path, key),
+                            ValidationErrorKind::Migrated {
+                                from: key.clone(),
+                                to: target.clone(),
+                            },
+                        )
+                        .with_position(position),
+                    );
+                } else if let Some(child) = instance_obj.get_mut(key) {
+                    migrate_recursive(child, sub_schema, &path::join_key(path, key), report);
+                }
+            }
+        }
+        (ValueKind::Array(schema_arr), ValueKind::Array(instance_arr)) if schema_arr.len() == 1 => {
+            for (i, item) in instance_arr.iter_mut().enumerate() {
+                migrate_recursive(item, &schema_arr[0], &path::join_index(path, i), report);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lookup<'a>(obj: &'a IndexMap<String, Value>, path: &str) -> Option<&'a Value> {
+    let (head, rest) = match path.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (path, None),
+    };
+    let value = obj.get(head)?;
+    match (rest, &value.kind) {
+        (None, _) => Some(value),
+        (Some(rest), ValueKind::Object(inner)) => lookup(inner, rest),
+        _ => None,
+    }
+}
+
+/// Whether every existing segment on the way to `path` is an object.
+fn can_insert(obj: &IndexMap<String, Value>, path: &str) -> bool {
+    match path.split_once('.') {
+        None => true,
+        Some((head, rest)) => match obj.get(head).map(|v| &v.kind) {
+            None => true,
+            Some(ValueKind::Object(inner)) => can_insert(inner, rest),
+            Some(_) => false,
+        },
+    }
+}
+
+fn insert(obj: &mut IndexMap<String, Value>, path: &str, value: Value) {
+    match path.split_once('.') {
+        None => {
+            obj.insert(path.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let child = obj
+                .entry(head.to_string())
+                .or_insert_with(|| Value::object(IndexMap::new()));
+            if let ValueKind::Object(inner) = &mut child.kind {
+                insert(inner, rest, value);
+            }
+        }
+    }
+}
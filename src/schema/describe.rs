@@ -0,0 +1,336 @@
+//! Reference documentation generated from a schema.
+//!
+//! [`describe`] lists every field of every object in the schema with its
+//! type, whether it is optional, and its `title`/`description`, `enum` and
+//! `deprecated` annotations:
+//!
+//! ```text
+//! ## server
+//!
+//! - **host**: string — Address to bind
+//! - **port**: port (optional) — Port to listen on
+//! ```
+//!
+//! Each object gets its own section, named by its dotted path (`[]` marks
+//! array items); the root section uses the root `title`, or `Configuration`.
+//! `allOf` parts are merged first, and a conditional schema is described by
+//! the fields outside its `if`/`then`/`else`.
+
+use super::deprecation::Deprecation;
+use super::{
+    ADDITIONAL_PROPERTIES_KEY, TUPLE_TYPE, compose, conditional, extract_metadata, is_directive,
+    is_nullable, tuple_items,
+};
+use crate::path::join_key;
+use crate::value::{Value, ValueKind};
+
+/// Output format of [`describe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescribeFormat {
+    /// `##` headings and `- **field**: type — description` items
+    Markdown,
+    /// Headings on their own line and indented fields
+    PlainText,
+    /// `<h2>` headings and `<ul>` lists
+    Html,
+}
+
+/// One object of the schema.
+struct Section {
+    heading: String,
+    title: Option<String>,
+    description: Option<String>,
+    fields: Vec<Field>,
+    /// What fields not listed in the schema may be
+    additional: Option<String>,
+}
+
+struct Field {
+    name: String,
+    type_name: String,
+    optional: bool,
+    description: Option<String>,
+    deprecation: Option<Deprecation>,
+    allowed: Vec<String>,
+}
+
+/// Document `schema` in the given format.
+pub fn describe(schema: &Value, format: DescribeFormat) -> String {
+    let root = annotation(schema, "title").unwrap_or_else(|| "Configuration".to_string());
+    let mut sections = Vec::new();
+    collect(schema, root, "", &mut sections);
+    match format {
+        DescribeFormat::Markdown => markdown(&sections),
+        DescribeFormat::PlainText => plain_text(&sections),
+        DescribeFormat::Html => html(&sections),
+    }
+}
+
+/// The schema to document once `allOf` and conditionals are flattened.
+fn flatten(schema: &Value) -> Value {
+    if let Some(parts) = compose::all_of(schema)
+        && let Ok(merged) = compose::merge_schemas(parts)
+    {
+        return flatten(&merged);
+    }
+    if let Some(conditional) = conditional::conditional(schema) {
+        return conditional.rest;
+    }
+    schema.clone()
+}
+
+/// Add a section for every object in `schema`, parents before children.
+fn collect(schema: &Value, heading: String, path: &str, sections: &mut Vec<Section>) {
+    let schema = flatten(schema);
+    let (effective, _, _) = extract_metadata(&schema);
+    match &effective.kind {
+        ValueKind::Object(obj) => {
+            let mut section = Section {
+                heading,
+                title: None,
+                description: annotation(&schema, "description"),
+                fields: Vec::new(),
+                additional: None,
+            };
+            // The root title is already the heading
+            if !path.is_empty() {
+                section.title = annotation(&schema, "title");
+            }
+            let mut children = Vec::new();
+            for (key, sub_schema) in obj {
+                if key == ADDITIONAL_PROPERTIES_KEY {
+                    section.additional = match &sub_schema.kind {
+                        ValueKind::Bool(false) => None,
+                        ValueKind::Bool(true) => Some("any".to_string()),
+                        _ => Some(type_name(sub_schema)),
+                    };
+                    continue;
+                }
+                if is_directive(key, sub_schema) {
+                    continue;
+                }
+                section.fields.push(field(key, sub_schema));
+                let child = join_key(path, key);
+                children.push((child, sub_schema));
+            }
+            sections.push(section);
+            for (child, sub_schema) in children {
+                collect(sub_schema, child.clone(), &child, sections);
+            }
+        }
+        ValueKind::Array(items) if items.len() == 1 && !path.is_empty() => {
+            let child = format!("{}[]", path);
+            collect(&items[0], child.clone(), &child, sections);
+        }
+        _ if path.is_empty() => sections.push(Section {
+            heading,
+            title: None,
+            description: annotation(&schema, "description"),
+            fields: Vec::new(),
+            additional: Some(type_name(&schema)),
+        }),
+        _ => {}
+    }
+}
+
+fn field(name: &str, schema: &Value) -> Field {
+    let schema = flatten(schema);
+    let (_, deprecation, optional) = extract_metadata(&schema);
+    let allowed = match extended(&schema)
+        .and_then(|obj| obj.get("enum"))
+        .map(|v| &v.kind)
+    {
+        Some(ValueKind::Array(values)) => values.iter().map(|v| v.kind.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    Field {
+        name: name.to_string(),
+        type_name: type_name(&schema),
+        optional,
+        description: annotation(&schema, "description").or_else(|| annotation(&schema, "title")),
+        deprecation,
+        allowed,
+    }
+}
+
+/// A short name for the type `schema` accepts.
+fn type_name(schema: &Value) -> String {
+    let (effective, _, _) = extract_metadata(schema);
+    let name = match &effective.kind {
+        ValueKind::String(name) if name == TUPLE_TYPE => match tuple_items(schema) {
+            Ok(items) => {
+                let items: Vec<String> = items.iter().map(|i| type_name(&flatten(i))).collect();
+                format!("tuple ({})", items.join(", "))
+            }
+            Err(_) => name.clone(),
+        },
+        ValueKind::String(name) => name.clone(),
+        ValueKind::Object(_) => "object".to_string(),
+        ValueKind::Array(items) if items.len() == 1 => {
+            format!("array of {}", type_name(&flatten(&items[0])))
+        }
+        ValueKind::Array(_) => "array".to_string(),
+        _ => "any".to_string(),
+    };
+    if is_nullable(schema) {
+        format!("{} | null", name)
+    } else {
+        name
+    }
+}
+
+/// The keys of an extended schema `{ type: ..., ... }`.
+fn extended(schema: &Value) -> Option<&indexmap::IndexMap<String, Value>> {
+    let (effective, _, _) = extract_metadata(schema);
+    match &schema.kind {
+        ValueKind::Object(obj) if !std::ptr::eq(effective, schema) => Some(obj),
+        _ => None,
+    }
+}
+
+/// A string annotation such as `description` on an extended schema.
+fn annotation(schema: &Value, key: &str) -> Option<String> {
+    match extended(schema)?.get(key).map(|v| &v.kind) {
+        Some(ValueKind::String(text)) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+fn deprecation_notice(deprecation: &Deprecation) -> String {
+    let mut notice = "Deprecated".to_string();
+    if let Some(since) = &deprecation.since {
+        notice.push_str(&format!(" since {}", since));
+    }
+    if let Some(message) = &deprecation.message {
+        notice.push_str(&format!(": {}", message));
+    }
+    if let Some(replacement) = &deprecation.replacement {
+        notice.push_str(&format!("; use '{}' instead", replacement));
+    }
+    notice
+}
+
+/// Everything after the field name and type, joined into one string.
+/// `text` formats prose and `code` formats allowed values.
+fn details(
+    field: &Field,
+    text: impl Fn(&str) -> String,
+    code: impl Fn(&str) -> String,
+) -> Option<String> {
+    let mut details = Vec::new();
+    if let Some(description) = &field.description {
+        details.push(text(description));
+    }
+    if !field.allowed.is_empty() {
+        let values: Vec<_> = field.allowed.iter().map(|v| code(v)).collect();
+        details.push(format!("Allowed values: {}", values.join(", ")));
+    }
+    if let Some(deprecation) = &field.deprecation {
+        details.push(text(&deprecation_notice(deprecation)));
+    }
+    (!details.is_empty()).then(|| details.join(". "))
+}
+
+fn optional_suffix(field: &Field) -> &'static str {
+    if field.optional { " (optional)" } else { "" }
+}
+
+fn markdown(sections: &[Section]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n", section.heading));
+        for text in [&section.title, &section.description].into_iter().flatten() {
+            out.push_str(&format!("{}\n\n", text));
+        }
+        for field in &section.fields {
+            out.push_str(&format!(
+                "- **{}**: {}{}",
+                field.name,
+                field.type_name,
+                optional_suffix(field)
+            ));
+            if let Some(details) = details(field, str::to_string, |v| format!("`{}`", v)) {
+                out.push_str(&format!(" — {}", details));
+            }
+            out.push('\n');
+        }
+        if let Some(additional) = &section.additional {
+            out.push_str(&format!("- *other fields*: {}\n", additional));
+        }
+    }
+    out
+}
+
+fn plain_text(sections: &[Section]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("{}\n", section.heading));
+        for text in [&section.title, &section.description].into_iter().flatten() {
+            out.push_str(&format!("  {}\n", text));
+        }
+        for field in &section.fields {
+            out.push_str(&format!(
+                "  {}: {}{}",
+                field.name,
+                field.type_name,
+                optional_suffix(field)
+            ));
+            if let Some(details) = details(field, str::to_string, str::to_string) {
+                out.push_str(&format!(" - {}", details));
+            }
+            out.push('\n');
+        }
+        if let Some(additional) = &section.additional {
+            out.push_str(&format!("  (other fields): {}\n", additional));
+        }
+    }
+    out
+}
+
+fn html(sections: &[Section]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        out.push_str(&format!("<h2>{}</h2>\n", escape(&section.heading)));
+        for text in [&section.title, &section.description].into_iter().flatten() {
+            out.push_str(&format!("<p>{}</p>\n", escape(text)));
+        }
+        if section.fields.is_empty() && section.additional.is_none() {
+            continue;
+        }
+        out.push_str("<ul>\n");
+        for field in &section.fields {
+            out.push_str(&format!(
+                "<li><strong>{}</strong>: {}{}",
+                escape(&field.name),
+                escape(&field.type_name),
+                optional_suffix(field)
+            ));
+            let code = |v: &str| format!("<code>{}</code>", escape(v));
+            if let Some(details) = details(field, escape, code) {
+                out.push_str(&format!(" — {}", details));
+            }
+            out.push_str("</li>\n");
+        }
+        if let Some(additional) = &section.additional {
+            out.push_str(&format!(
+                "<li><em>other fields</em>: {}</li>\n",
+                escape(additional)
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
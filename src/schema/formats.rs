@@ -0,0 +1,144 @@
+//! Built-in string formats for the `format` schema key.
+//!
+//! Each check is a lightweight syntactic test, not a full RFC parser: it
+//! catches typos and wrong kinds of values, not every malformed input.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Names accepted by `format`.
+pub const FORMATS: &[&str] = &["email", "url", "uuid", "ipv4", "ipv6", "date", "datetime"];
+
+/// Check `s` against a named format, or `None` if the format is unknown.
+pub fn check(format: &str, s: &str) -> Option<bool> {
+    Some(match format {
+        "email" => is_email(s),
+        "url" => is_url(s),
+        "uuid" => is_uuid(s),
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        "date" => is_date(s),
+        "datetime" => is_datetime(s),
+        _ => return None,
+    })
+}
+
+/// `local@domain.tld`, with no whitespace and a dotted domain.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !local.contains('@')
+        && !local.chars().any(char::is_whitespace)
+        && is_hostname(domain)
+        && domain.contains('.')
+}
+
+/// `scheme://host[...]`, where the scheme starts with a letter.
+fn is_url(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once("://") else {
+        return false;
+    };
+    let mut scheme_chars = scheme.chars();
+    let scheme_ok = scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    scheme_ok && !authority.is_empty() && !s.chars().any(char::is_whitespace)
+}
+
+fn is_hostname(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Five hex groups of 8-4-4-4-12 digits.
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `YYYY-MM-DD`, with the day valid for the month (leap years included).
+fn is_date(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) =
+        (number(&s[0..4]), number(&s[5..7]), number(&s[8..10]))
+    else {
+        return false;
+    };
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// RFC 3339 date-time: `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)`.
+fn is_datetime(s: &str) -> bool {
+    if s.len() < 20 || !s.is_char_boundary(10) || !s.is_char_boundary(11) {
+        return false;
+    }
+    let (date, rest) = s.split_at(10);
+    let (sep, time) = rest.split_at(1);
+    if !is_date(date) || !matches!(sep, "T" | "t" | " ") {
+        return false;
+    }
+
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return false,
+    };
+    let time = match time.split_once('.') {
+        Some((hms, frac)) if !frac.is_empty() && frac.chars().all(|c| c.is_ascii_digit()) => hms,
+        Some(_) => return false,
+        None => time,
+    };
+    is_clock(time) && (matches!(offset, "Z" | "z") || is_offset(offset))
+}
+
+/// `HH:MM:SS`; second 60 is allowed for leap seconds.
+fn is_clock(s: &str) -> bool {
+    let parts: Vec<&str> = s.split(':').collect();
+    parts.len() == 3
+        && parts.iter().all(|p| p.len() == 2)
+        && number(parts[0]).is_some_and(|h| h <= 23)
+        && number(parts[1]).is_some_and(|m| m <= 59)
+        && number(parts[2]).is_some_and(|sec| sec <= 60)
+}
+
+/// `+HH:MM` or `-HH:MM`.
+fn is_offset(s: &str) -> bool {
+    let Some(rest) = s.strip_prefix(['+', '-']) else {
+        return false;
+    };
+    let Some((h, m)) = rest.split_once(':') else {
+        return false;
+    };
+    h.len() == 2
+        && m.len() == 2
+        && number(h).is_some_and(|h| h <= 23)
+        && number(m).is_some_and(|m| m <= 59)
+}
+
+fn number(s: &str) -> Option<u32> {
+    if s.chars().all(|c| c.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
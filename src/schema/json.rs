@@ -0,0 +1,427 @@
+//! Conversion between COSY schemas and JSON Schema (requires the `json` feature).
+//!
+//! | COSY                                   | JSON Schema                                   |
+//! |----------------------------------------|-----------------------------------------------|
+//! | `"string"`, `"integer"`, `"boolean"`, `"null"` | `{"type": ...}`                       |
+//! | `"number"`, `"float"`                  | `{"type": "number"}`                          |
+//! | `"any"`                                | `{}`                                          |
+//! | `"u16"`, `"port"`, ...                 | `{"type": "integer"}` with `minimum`/`maximum` |
+//! | `"string \| null"`, `"string?"`         | `{"type": ["string", "null"]}`                |
+//! | `{ a: ..., b: ... }`                   | `properties`, `required`, `additionalProperties` |
+//! | `patternProperties`                    | `patternProperties`                           |
+//! | `[item]`                               | `{"type": "array", "items": ...}`             |
+//! | `optional: true`                       | field left out of `required`                  |
+//! | `nullable: true`                       | `"null"` added to `type`                      |
+//! | `enum`, `min`, `max`                   | `enum`, `minimum`, `maximum`                  |
+//! | `minLength`, `maxLength`, `format`     | the same keywords (`datetime` is `date-time`, `url` is `uri`) |
+//! | `deprecated`                           | `"deprecated": true`                          |
+//!
+//! `float` exports as `number`, deprecation details are reduced to a flag and
+//! cross-field `constraints` are not exported. Annotation keywords such as
+//! `title` and `description` are ignored on import; other keywords COSY cannot
+//! express are reported as warnings by [`from_json_schema_checked`].
+
+use super::patterns::PATTERN_PROPERTIES_KEY;
+use super::{
+    ADDITIONAL_PROPERTIES_KEY, SchemaError, extract_metadata, formats, integer_alias, is_directive,
+    is_nullable,
+};
+use crate::value::{Value, ValueKind};
+use indexmap::IndexMap;
+use serde_json::{Map, Value as Json, json};
+
+/// Keywords that only annotate a JSON Schema and are dropped without warning.
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// Convert a COSY schema to an equivalent JSON Schema.
+pub fn to_json_schema(schema: &Value) -> Json {
+    let (effective, deprecation, _) = extract_metadata(schema);
+
+    let mut out = match &effective.kind {
+        ValueKind::String(type_name) => type_to_json(type_name),
+        ValueKind::Object(obj) => object_to_json(obj),
+        ValueKind::Array(arr) if arr.len() == 1 => {
+            json!({ "type": "array", "items": to_json_schema(&arr[0]) })
+        }
+        _ => json!({}),
+    };
+
+    // Extended form: { type: ..., enum: [...], min: 0, max: 10 }
+    if !std::ptr::eq(effective, schema)
+        && let ValueKind::Object(schema_obj) = &schema.kind
+        && let Json::Object(out_obj) = &mut out
+    {
+        if let Some(allowed) = schema_obj.get("enum") {
+            out_obj.insert("enum".to_string(), Json::from(allowed));
+        }
+        if let Some(min) = schema_obj.get("min") {
+            out_obj.insert("minimum".to_string(), Json::from(min));
+        }
+        if let Some(max) = schema_obj.get("max") {
+            out_obj.insert("maximum".to_string(), Json::from(max));
+        }
+        for key in ["minLength", "maxLength"] {
+            if let Some(length) = schema_obj.get(key) {
+                out_obj.insert(key.to_string(), Json::from(length));
+            }
+        }
+        if let Some(Value {
+            kind: ValueKind::String(format),
+            ..
+        }) = schema_obj.get("format")
+        {
+            let format = match format.as_str() {
+                "datetime" => "date-time",
+                "url" => "uri",
+                other => other,
+            };
+            out_obj.insert("format".to_string(), json!(format));
+        }
+        if deprecation.is_some() {
+            out_obj.insert("deprecated".to_string(), Json::Bool(true));
+        }
+        if is_nullable(schema) {
+            match out_obj.get_mut("type") {
+                Some(Json::String(single)) if single != "null" => {
+                    let single = std::mem::take(single);
+                    out_obj.insert("type".to_string(), json!([single, "null"]));
+                }
+                Some(Json::Array(types)) if !types.contains(&json!("null")) => {
+                    types.push(json!("null"));
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn type_to_json(type_name: &str) -> Json {
+    if let Some((min, max)) = integer_alias(type_name.trim()) {
+        let mut out = json!({ "type": "integer", "minimum": min });
+        if let Some(max) = max {
+            out["maximum"] = json!(max);
+        }
+        return out;
+    }
+
+    let mut types: Vec<&str> = Vec::new();
+    for name in type_name.split('|').map(str::trim) {
+        let (name, nullable) = match name.strip_suffix('?') {
+            Some(base) => (base, true),
+            None => (name, false),
+        };
+        let mapped = match name {
+            "any" => return json!({}),
+            alias if integer_alias(alias).is_some() => "integer",
+            "float" | "number" => "number",
+            "boolean" | "bool" => "boolean",
+            other => other,
+        };
+        for mapped in std::iter::once(mapped).chain(nullable.then_some("null")) {
+            if !types.contains(&mapped) {
+                types.push(mapped);
+            }
+        }
+    }
+    match types.as_slice() {
+        [single] => json!({ "type": single }),
+        _ => json!({ "type": types }),
+    }
+}
+
+fn object_to_json(obj: &IndexMap<String, Value>) -> Json {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (key, sub_schema) in obj {
+        if is_directive(key, sub_schema) {
+            continue;
+        }
+        let (_, _, optional) = extract_metadata(sub_schema);
+        if !optional {
+            required.push(Json::String(key.clone()));
+        }
+        properties.insert(key.clone(), to_json_schema(sub_schema));
+    }
+
+    let additional = match obj.get(ADDITIONAL_PROPERTIES_KEY).map(|v| &v.kind) {
+        None | Some(ValueKind::Bool(false)) => Json::Bool(false),
+        Some(ValueKind::Bool(true)) => Json::Bool(true),
+        Some(_) => to_json_schema(&obj[ADDITIONAL_PROPERTIES_KEY]),
+    };
+
+    let mut out = Map::new();
+    out.insert("type".to_string(), json!("object"));
+    out.insert("properties".to_string(), Json::Object(properties));
+    if !required.is_empty() {
+        out.insert("required".to_string(), Json::Array(required));
+    }
+    if let Some(Value {
+        kind: ValueKind::Object(patterns),
+        ..
+    }) = obj.get(PATTERN_PROPERTIES_KEY)
+    {
+        let patterns = patterns
+            .iter()
+            .map(|(pattern, sub)| (pattern.clone(), to_json_schema(sub)))
+            .collect();
+        out.insert(PATTERN_PROPERTIES_KEY.to_string(), Json::Object(patterns));
+    }
+    out.insert("additionalProperties".to_string(), additional);
+    Json::Object(out)
+}
+
+/// Convert a JSON Schema to a COSY schema.
+///
+/// Keywords COSY cannot express are skipped; use
+/// [`from_json_schema_checked`] to find out which.
+pub fn from_json_schema(schema: &Json) -> Result<Value, SchemaError> {
+    from_json_schema_checked(schema).map(|(value, _)| value)
+}
+
+/// Like [`from_json_schema`], also returning a warning for each keyword
+/// that was skipped.
+pub fn from_json_schema_checked(schema: &Json) -> Result<(Value, Vec<String>), SchemaError> {
+    let mut warnings = Vec::new();
+    let value = import(schema, "#", &mut warnings)?;
+    Ok((value, warnings))
+}
+
+fn import(schema: &Json, path: &str, warnings: &mut Vec<String>) -> Result<Value, SchemaError> {
+    let obj = match schema {
+        Json::Bool(true) => return Ok(Value::from("any")),
+        Json::Object(obj) => obj,
+        _ => {
+            return Err(SchemaError::new(path, "Expected a schema object or `true`"));
+        }
+    };
+
+    for key in obj.keys() {
+        let handled = matches!(
+            key.as_str(),
+            "type"
+                | "properties"
+                | "required"
+                | "additionalProperties"
+                | "patternProperties"
+                | "items"
+                | "anyOf"
+                | "enum"
+                | "minimum"
+                | "maximum"
+                | "deprecated"
+                | "minLength"
+                | "maxLength"
+                | "format"
+        );
+        if !handled && !ANNOTATIONS.contains(&key.as_str()) {
+            warnings.push(format!("Unsupported keyword '{}' at {}", key, path));
+        }
+    }
+
+    let types = type_names(obj, path, warnings)?;
+    let base = match types.as_slice() {
+        [t] if t == "object" => import_object(obj, path, warnings)?,
+        [t] if t == "array" => import_array(obj, path, warnings)?,
+        [] if obj.contains_key("properties") => import_object(obj, path, warnings)?,
+        [] => Value::from("any"),
+        _ => {
+            if types.iter().any(|t| t == "object" || t == "array") {
+                warnings.push(format!(
+                    "Unions with object or array types are not supported at {}",
+                    path
+                ));
+                Value::from("any")
+            } else {
+                Value::from(types.join(" | "))
+            }
+        }
+    };
+
+    let mut extended = IndexMap::new();
+    if let Some(allowed) = obj.get("enum") {
+        extended.insert("enum".to_string(), Value::from(allowed));
+    }
+    if let Some(min) = obj.get("minimum") {
+        extended.insert("min".to_string(), Value::from(min));
+    }
+    if let Some(max) = obj.get("maximum") {
+        extended.insert("max".to_string(), Value::from(max));
+    }
+    for key in ["minLength", "maxLength"] {
+        if let Some(length) = obj.get(key) {
+            extended.insert(key.to_string(), Value::from(length));
+        }
+    }
+    if let Some(Json::String(format)) = obj.get("format") {
+        let format = match format.as_str() {
+            "date-time" => "datetime",
+            "uri" => "url",
+            other => other,
+        };
+        if formats::FORMATS.contains(&format) {
+            extended.insert("format".to_string(), Value::from(format));
+        } else {
+            warnings.push(format!("Unsupported format '{}' at {}", format, path));
+        }
+    }
+    if let Some(Json::Bool(true)) = obj.get("deprecated") {
+        extended.insert("deprecated".to_string(), Value::object(IndexMap::new()));
+    }
+
+    if extended.is_empty() {
+        Ok(base)
+    } else {
+        extended.insert_before(0, "type".to_string(), base);
+        Ok(Value::object(extended))
+    }
+}
+
+/// Scalar type names from `type` or a simple `anyOf`.
+fn type_names(
+    obj: &Map<String, Json>,
+    path: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<String>, SchemaError> {
+    let mut names = Vec::new();
+    match obj.get("type") {
+        None => {}
+        Some(Json::String(name)) => names.push(name.clone()),
+        Some(Json::Array(list)) => {
+            for item in list {
+                let Json::String(name) = item else {
+                    return Err(SchemaError::new(path, "'type' entries must be strings"));
+                };
+                names.push(name.clone());
+            }
+        }
+        Some(_) => return Err(SchemaError::new(path, "'type' must be a string or array")),
+    }
+
+    // anyOf: [{ type: "string" }, { type: "integer" }] is a plain union
+    if let Some(Json::Array(branches)) = obj.get("anyOf") {
+        let simple: Option<Vec<&str>> = branches
+            .iter()
+            .map(|b| match b {
+                Json::Object(b) if b.len() == 1 => b.get("type").and_then(Json::as_str),
+                _ => None,
+            })
+            .collect();
+        match simple {
+            Some(list) => names.extend(list.into_iter().map(str::to_string)),
+            None => warnings.push(format!(
+                "Only anyOf branches of the form {{\"type\": ...}} are supported at {}",
+                path
+            )),
+        }
+    }
+
+    for name in &names {
+        if !matches!(
+            name.as_str(),
+            "string" | "integer" | "number" | "boolean" | "null" | "object" | "array"
+        ) {
+            return Err(SchemaError::unknown_type(path, name, None));
+        }
+    }
+    Ok(names)
+}
+
+fn import_object(
+    obj: &Map<String, Json>,
+    path: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Value, SchemaError> {
+    let required: Vec<&str> = match obj.get("required") {
+        None => Vec::new(),
+        Some(Json::Array(list)) => list.iter().filter_map(Json::as_str).collect(),
+        Some(_) => return Err(SchemaError::new(path, "'required' must be an array")),
+    };
+
+    let mut fields = IndexMap::new();
+    if let Some(properties) = obj.get("properties") {
+        let Json::Object(properties) = properties else {
+            return Err(SchemaError::new(path, "'properties' must be an object"));
+        };
+        for (key, sub) in properties {
+            let sub_path = format!("{}/properties/{}", path, key);
+            let field = import(sub, &sub_path, warnings)?;
+            let field = if required.contains(&key.as_str()) {
+                field
+            } else {
+                make_optional(field)
+            };
+            fields.insert(key.clone(), field);
+        }
+    }
+
+    if let Some(patterns) = obj.get(PATTERN_PROPERTIES_KEY) {
+        let Json::Object(patterns) = patterns else {
+            return Err(SchemaError::new(
+                path,
+                "'patternProperties' must be an object",
+            ));
+        };
+        let mut imported = IndexMap::new();
+        for (pattern, sub) in patterns {
+            let sub_path = format!("{}/patternProperties/{}", path, pattern);
+            imported.insert(pattern.clone(), import(sub, &sub_path, warnings)?);
+        }
+        fields.insert(PATTERN_PROPERTIES_KEY.to_string(), Value::object(imported));
+    }
+
+    // JSON Schema objects are open unless stated otherwise; COSY's are closed
+    match obj.get("additionalProperties") {
+        Some(Json::Bool(false)) => {}
+        None | Some(Json::Bool(true)) => {
+            fields.insert(ADDITIONAL_PROPERTIES_KEY.to_string(), Value::boolean(true));
+        }
+        Some(extra) => {
+            let extra_path = format!("{}/additionalProperties", path);
+            fields.insert(
+                ADDITIONAL_PROPERTIES_KEY.to_string(),
+                import(extra, &extra_path, warnings)?,
+            );
+        }
+    }
+    Ok(Value::object(fields))
+}
+
+fn import_array(
+    obj: &Map<String, Json>,
+    path: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Value, SchemaError> {
+    let item = match obj.get("items") {
+        None => Value::from("any"),
+        Some(Json::Array(_)) => {
+            warnings.push(format!("Tuple 'items' is not supported at {}", path));
+            Value::from("any")
+        }
+        Some(items) => import(items, &format!("{}/items", path), warnings)?,
+    };
+    Ok(Value::array(vec![item]))
+}
+
+/// Mark a field schema `optional: true`, switching to the extended form if needed.
+fn make_optional(field: Value) -> Value {
+    let already_extended = !std::ptr::eq(extract_metadata(&field).0, &field);
+    let mut extended = match field.kind {
+        ValueKind::Object(obj) if already_extended => obj,
+        kind => {
+            let mut obj = IndexMap::new();
+            obj.insert("type".to_string(), Value::new(kind));
+            obj
+        }
+    };
+    extended.insert("optional".to_string(), Value::boolean(true));
+    Value::object(extended)
+}
@@ -0,0 +1,63 @@
+//! `patternProperties`: validating object fields by key regex.
+//!
+//! Regex support is behind the `regex` feature. Without it, a schema that
+//! uses `patternProperties` is reported as invalid.
+
+use super::{SchemaError, invalid_schema};
+use crate::value::Value;
+use indexmap::IndexMap;
+
+/// The reserved object-schema key mapping key patterns to value schemas.
+pub const PATTERN_PROPERTIES_KEY: &str = "patternProperties";
+
+/// A compiled key pattern.
+#[cfg(feature = "regex")]
+pub struct KeyPattern(regex::Regex);
+
+#[cfg(feature = "regex")]
+impl KeyPattern {
+    pub fn is_match(&self, key: &str) -> bool {
+        self.0.is_match(key)
+    }
+}
+
+/// Compile each pattern of a `patternProperties` object, in order.
+#[cfg(feature = "regex")]
+pub fn compile(
+    patterns: &IndexMap<String, Value>,
+    path: &str,
+) -> Result<Vec<KeyPattern>, SchemaError> {
+    patterns
+        .keys()
+        .map(|pattern| match regex::Regex::new(pattern) {
+            Ok(re) => Ok(KeyPattern(re)),
+            Err(e) => Err(invalid_schema(
+                path,
+                format!("Invalid key pattern '{}': {}", pattern, e),
+            )),
+        })
+        .collect()
+}
+
+/// A compiled key pattern (none can exist without the `regex` feature).
+#[cfg(not(feature = "regex"))]
+pub enum KeyPattern {}
+
+#[cfg(not(feature = "regex"))]
+impl KeyPattern {
+    pub fn is_match(&self, _key: &str) -> bool {
+        match *self {}
+    }
+}
+
+/// Without the `regex` feature, any `patternProperties` is a schema error.
+#[cfg(not(feature = "regex"))]
+pub fn compile(
+    _patterns: &IndexMap<String, Value>,
+    path: &str,
+) -> Result<Vec<KeyPattern>, SchemaError> {
+    Err(invalid_schema(
+        path,
+        format!("'{}' requires the `regex` feature", PATTERN_PROPERTIES_KEY),
+    ))
+}
@@ -15,11 +15,11 @@ pub fn levenshtein(a: &str, b: &str) -> usize {
 
     let mut dp = vec![vec![0; len_b + 1]; len_a + 1];
 
-    for i in 0..=len_a {
-        dp[i][0] = i;
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
     }
-    for j in 0..=len_b {
-        dp[0][j] = j;
+    for (j, cell) in dp[0].iter_mut().enumerate() {
+        *cell = j;
     }
 
     for (i, ca) in a.chars().enumerate() {
@@ -39,15 +39,20 @@ pub fn levenshtein(a: &str, b: &str) -> usize {
 }
 
 /// Find the best match for `target` in `candidates` within `max_dist`.
-pub fn find_best_match(target: &str, candidates: &[String], max_dist: usize) -> Option<String> {
+pub fn find_best_match<S: AsRef<str>>(
+    target: &str,
+    candidates: &[S],
+    max_dist: usize,
+) -> Option<String> {
     let mut best_dist = max_dist + 1;
     let mut best_match = None;
 
     for candidate in candidates {
+        let candidate = candidate.as_ref();
         let dist = levenshtein(target, candidate);
         if dist <= max_dist && dist < best_dist {
             best_dist = dist;
-            best_match = Some(candidate.clone());
+            best_match = Some(candidate.to_string());
         }
     }
 
@@ -2,14 +2,17 @@
 pub mod serializer;
 
 use crate::CosynError;
+use crate::path::{join_index, join_key};
 use crate::value::{Value, ValueKind};
 use indexmap::IndexMap;
 use serde::de::{self, Error as DeError, MapAccess, SeqAccess, Visitor};
 use serde::ser::{Error as SeError, SerializeMap};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 // Removed: use std::collections::HashMap;
+use std::cell::RefCell;
 use std::error::Error as StdError;
 use std::fmt;
+use std::rc::Rc;
 
 /// Deserialize any type that implements `Deserialize` from COSY text
 pub fn from_str<'a, T>(input: &'a str) -> Result<T, CosynError>
@@ -34,6 +37,43 @@ where
     })
 }
 
+/// Deserialize from COSY text, also reporting object keys that were never used.
+///
+/// Keys are returned as dotted paths (e.g. `server.prt`, `users[0].nick`).
+/// A key counts as unused when the target type ignored it, which is what
+/// happens to typos in structs that don't use `deny_unknown_fields`.
+pub fn from_str_checked<'a, T>(input: &'a str) -> Result<(T, Vec<String>), CosynError>
+where
+    T: Deserialize<'a>,
+{
+    let value = crate::from_str(input)?;
+    from_value_checked(value)
+}
+
+/// Deserialize from a COSY `Value`, also reporting unused object keys.
+///
+/// See [`from_str_checked`].
+pub fn from_value_checked<'a, T>(value: Value) -> Result<(T, Vec<String>), CosynError>
+where
+    T: Deserialize<'a>,
+{
+    let unused: UnusedKeys = Rc::new(RefCell::new(Vec::new()));
+    let deserializer = ValueDeserializer {
+        value,
+        path: String::new(),
+        unused: Some(Rc::clone(&unused)),
+    };
+    let result = T::deserialize(deserializer).map_err(|e| {
+        CosynError::Parse(crate::ParseError {
+            message: e.to_string(),
+            line: 0,
+            column: 0,
+        })
+    })?;
+    let keys = unused.take();
+    Ok((result, keys))
+}
+
 /// Serialize any type that implements `Serialize` to COSY text
 pub fn to_string<T>(value: &T) -> Result<String, SerializeError>
 where
@@ -87,14 +127,32 @@ impl serde::de::Error for DeserializeError {
 // DESERIALIZER IMPLEMENTATION
 // ============================================================================
 
+/// Shared list of paths that were skipped during deserialization.
+type UnusedKeys = Rc<RefCell<Vec<String>>>;
+
 /// A deserializer for COSY `Value` types
 pub struct ValueDeserializer {
     value: Value,
+    /// Location of `value` in the document, used for unused-key reporting
+    path: String,
+    unused: Option<UnusedKeys>,
 }
 
 impl ValueDeserializer {
     fn new(value: Value) -> Self {
-        ValueDeserializer { value }
+        ValueDeserializer {
+            value,
+            path: String::new(),
+            unused: None,
+        }
+    }
+
+    fn nested(value: Value, path: String, unused: &Option<UnusedKeys>) -> Self {
+        ValueDeserializer {
+            value,
+            path,
+            unused: unused.clone(),
+        }
     }
 }
 
@@ -111,8 +169,12 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
             ValueKind::Integer(i) => visitor.visit_i64(i),
             ValueKind::Float(f) => visitor.visit_f64(f),
             ValueKind::String(s) => visitor.visit_string(s),
-            ValueKind::Array(arr) => visitor.visit_seq(SeqDeserializer::new(arr)),
-            ValueKind::Object(obj) => visitor.visit_map(MapDeserializer::new(obj)),
+            ValueKind::Array(arr) => {
+                visitor.visit_seq(SeqDeserializer::new(arr, self.path, self.unused))
+            }
+            ValueKind::Object(obj) => {
+                visitor.visit_map(MapDeserializer::new(obj, self.path, self.unused))
+            }
         }
     }
 
@@ -188,17 +250,51 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
         V: Visitor<'de>,
     {
         match self.value.kind {
-            ValueKind::Array(arr) => visitor.visit_seq(SeqDeserializer::new(arr)),
+            ValueKind::Array(arr) => {
+                visitor.visit_seq(SeqDeserializer::new(arr, self.path, self.unused))
+            }
+            _ => Err(DeserializeError::custom("expected array")),
+        }
+    }
+
+    /// Tuples and fixed-size arrays need an array of exactly `len` items
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value.kind {
+            ValueKind::Array(arr) if arr.len() == len => {
+                visitor.visit_seq(SeqDeserializer::new(arr, self.path, self.unused))
+            }
+            ValueKind::Array(arr) => Err(DeserializeError::custom(format!(
+                "expected tuple of length {}, found {}",
+                len,
+                arr.len()
+            ))),
             _ => Err(DeserializeError::custom("expected array")),
         }
     }
 
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
         match self.value.kind {
-            ValueKind::Object(obj) => visitor.visit_map(MapDeserializer::new(obj)),
+            ValueKind::Object(obj) => {
+                visitor.visit_map(MapDeserializer::new(obj, self.path, self.unused))
+            }
             _ => Err(DeserializeError::custom("expected object")),
         }
     }
@@ -213,7 +309,9 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
         V: Visitor<'de>,
     {
         match self.value.kind {
-            ValueKind::Object(obj) => visitor.visit_map(MapDeserializer::new(obj)),
+            ValueKind::Object(obj) => {
+                visitor.visit_map(MapDeserializer::new(obj, self.path, self.unused))
+            }
             _ => Err(DeserializeError::custom("expected object")),
         }
     }
@@ -232,7 +330,12 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
             ValueKind::Object(obj) => {
                 if obj.len() == 1 {
                     let (key, val) = obj.into_iter().next().unwrap();
-                    visitor.visit_enum(NewtypeVariantDeserializer { key, value: val })
+                    visitor.visit_enum(NewtypeVariantDeserializer {
+                        key,
+                        value: val,
+                        path: self.path,
+                        unused: self.unused,
+                    })
                 } else {
                     Err(DeserializeError::custom(
                         "enum variants with multiple fields are not supported (use newtype or unit variants)",
@@ -251,7 +354,7 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
     {
         match self.value.kind {
             ValueKind::Null => visitor.visit_none(),
-            _ => visitor.visit_some(ValueDeserializer::new(self.value)),
+            _ => visitor.visit_some(self),
         }
     }
 
@@ -259,23 +362,32 @@ impl<'de> Deserializer<'de> for ValueDeserializer {
     where
         V: Visitor<'de>,
     {
+        // The target type skipped this value: record it when tracking is on.
+        if let Some(unused) = &self.unused {
+            unused.borrow_mut().push(self.path);
+            return visitor.visit_unit();
+        }
         self.deserialize_any(visitor)
     }
 
     serde::forward_to_deserialize_any! {
         u8 u16 u32 i8 i16 i32 f32 unit unit_struct newtype_struct
-        tuple tuple_struct bytes byte_buf char identifier
+        bytes byte_buf char identifier
     }
 }
 
 struct SeqDeserializer {
-    array: std::vec::IntoIter<Value>,
+    array: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    path: String,
+    unused: Option<UnusedKeys>,
 }
 
 impl SeqDeserializer {
-    fn new(array: Vec<Value>) -> Self {
+    fn new(array: Vec<Value>, path: String, unused: Option<UnusedKeys>) -> Self {
         SeqDeserializer {
-            array: array.into_iter(),
+            array: array.into_iter().enumerate(),
+            path,
+            unused,
         }
     }
 }
@@ -288,7 +400,11 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
         T: de::DeserializeSeed<'de>,
     {
         match self.array.next() {
-            Some(value) => seed.deserialize(ValueDeserializer::new(value)).map(Some),
+            Some((i, value)) => {
+                let path = join_index(&self.path, i);
+                seed.deserialize(ValueDeserializer::nested(value, path, &self.unused))
+                    .map(Some)
+            }
             None => Ok(None),
         }
     }
@@ -296,14 +412,18 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
 
 struct MapDeserializer {
     iter: indexmap::map::IntoIter<String, Value>,
-    value: Option<Value>,
+    value: Option<(String, Value)>,
+    path: String,
+    unused: Option<UnusedKeys>,
 }
 
 impl MapDeserializer {
-    fn new(object: IndexMap<String, Value>) -> Self {
+    fn new(object: IndexMap<String, Value>, path: String, unused: Option<UnusedKeys>) -> Self {
         MapDeserializer {
             iter: object.into_iter(),
             value: None,
+            path,
+            unused,
         }
     }
 }
@@ -317,7 +437,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
     {
         match self.iter.next() {
             Some((key, value)) => {
-                self.value = Some(value);
+                self.value = Some((join_key(&self.path, &key), value));
                 seed.deserialize(ValueDeserializer::new(Value::from(ValueKind::String(key))))
                     .map(Some)
             }
@@ -330,7 +450,9 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         V: de::DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(ValueDeserializer::new(value)),
+            Some((path, value)) => {
+                seed.deserialize(ValueDeserializer::nested(value, path, &self.unused))
+            }
             None => Err(DeserializeError::custom("value missing")),
         }
     }
@@ -395,6 +517,8 @@ impl<'de> de::VariantAccess<'de> for UnitVariantDeserializer {
 struct NewtypeVariantDeserializer {
     key: String,
     value: Value,
+    path: String,
+    unused: Option<UnusedKeys>,
 }
 
 impl<'de> de::EnumAccess<'de> for NewtypeVariantDeserializer {
@@ -422,7 +546,8 @@ impl<'de> de::VariantAccess<'de> for NewtypeVariantDeserializer {
     where
         T: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(ValueDeserializer::new(self.value))
+        let path = join_key(&self.path, &self.key);
+        seed.deserialize(ValueDeserializer::nested(self.value, path, &self.unused))
     }
 
     fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
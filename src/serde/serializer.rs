@@ -1,6 +1,48 @@
-use crate::value::{Value, ValueKind};
+use crate::merge::MergeDirective;
+use crate::value::{Comment, Value, ValueKind};
 use indexmap::IndexMap;
 
+/// The marker written in front of comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentMarker {
+    /// `//`, and `///` for doc comments (default)
+    #[default]
+    Slashes,
+    /// `#` for every comment, so doc comments read back as line comments.
+    /// Parse the output with `ParseOptions::hash_comments` enabled.
+    Hash,
+}
+
+/// The line break written between lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n` (default)
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r\n` on Windows, `\n` elsewhere
+    Native,
+}
+
+/// When floats are written in exponent form, such as `1e-7`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScientificPolicy {
+    /// Always fixed-point, e.g. `0.0000001` (default)
+    ///
+    /// The digits are the shortest that identify the `f64`, and integral
+    /// values end in `.0`, so the output reads back as the same float. Extreme
+    /// magnitudes get long, though: `1e300` is written with 301 digits before
+    /// the `.0` and `f64::MIN_POSITIVE` with over 300 after the point.
+    #[default]
+    Never,
+    /// Exponent form below `1e-5` and from `1e16` in magnitude, fixed-point
+    /// otherwise
+    Auto,
+    /// Always exponent form, e.g. `2.75e0`
+    Always,
+}
+
 /// Serialization options for controlling output format
 #[derive(Debug, Clone)]
 pub struct SerializeOptions {
@@ -10,6 +52,46 @@ pub struct SerializeOptions {
     pub use_newlines: bool,
     /// Add trailing commas (default: false)
     pub trailing_commas: bool,
+    /// Emit comments attached to values (default: true)
+    ///
+    /// When `use_newlines` is false, comments are hoisted in front of the
+    /// single-line value, since `//` comments can't appear inside it.
+    pub comments: bool,
+    /// Marker that introduces each comment (default: `Slashes`)
+    pub comment_marker: CommentMarker,
+    /// Put a space between the comment marker and its text (default: true)
+    pub space_after_comment_marker: bool,
+    /// Put a space between the colon and the value (default: true)
+    pub space_after_colon: bool,
+    /// Pad keys so the colons within each object line up (default: false)
+    ///
+    /// Only applies when `use_newlines` is true.
+    pub align_values: bool,
+    /// Break arrays and objects that would otherwise be written on one line
+    /// across several lines when they would run past this column (default: none)
+    ///
+    /// Values are never joined onto one line to fit; this only expands.
+    /// Strings are not split, so a long string can still exceed the width.
+    pub max_width: Option<usize>,
+    /// Start an object entry's array or object value on its own line below
+    /// the key, one level deeper, when it spans several lines (default: false)
+    ///
+    /// ```text
+    /// features:
+    ///     [
+    ///         "auth"
+    ///         "api_v2"
+    ///     ]
+    /// ```
+    pub brace_on_next_line: bool,
+    /// Leave out object entries whose value is `null` (default: false)
+    ///
+    /// `null` items in arrays are still written.
+    pub skip_nulls: bool,
+    /// Line break between lines, including after comments (default: `Lf`)
+    pub line_ending: LineEnding,
+    /// When floats use exponent form (default: `Never`)
+    pub scientific: ScientificPolicy,
 }
 
 impl Default for SerializeOptions {
@@ -18,6 +100,16 @@ impl Default for SerializeOptions {
             indent_size: 4,
             use_newlines: true,
             trailing_commas: false,
+            comments: true,
+            comment_marker: CommentMarker::Slashes,
+            space_after_comment_marker: true,
+            space_after_colon: true,
+            align_values: false,
+            max_width: None,
+            brace_on_next_line: false,
+            skip_nulls: false,
+            line_ending: LineEnding::Lf,
+            scientific: ScientificPolicy::Never,
         }
     }
 }
@@ -26,6 +118,8 @@ impl Default for SerializeOptions {
 pub struct Serializer {
     options: SerializeOptions,
     indent_level: usize,
+    /// Column at which the value being serialized starts, for `max_width`
+    column: usize,
 }
 
 impl Default for Serializer {
@@ -40,6 +134,7 @@ impl Serializer {
         Serializer {
             options: SerializeOptions::default(),
             indent_level: 0,
+            column: 0,
         }
     }
 
@@ -48,43 +143,136 @@ impl Serializer {
         Serializer {
             options,
             indent_level: 0,
+            column: 0,
         }
     }
 
     /// Serialize a value to a COSY string
     pub fn serialize(&mut self, value: &Value) -> String {
-        self.serialize_value(value)
+        self.column = 0;
+        if self.options.use_newlines {
+            return self.serialize_value(value);
+        }
+
+        // Compact output: line comments would swallow the rest of the line,
+        // so emit every comment in the tree before the value instead.
+        let mut result = String::new();
+        if self.options.comments {
+            let mut comments = Vec::new();
+            collect_comments(value, &mut comments);
+            for comment in comments {
+                result.push_str(&self.format_comment(comment));
+                result.push_str(self.newline());
+            }
+        }
+        result.push_str(&self.serialize_contents(value));
+        result
     }
 
     fn serialize_value(&mut self, value: &Value) -> String {
         let mut result = String::new();
         // Append comments first
-        for comment in &value.comments {
-            result.push_str(&self.indent());
-            result.push_str("// ");
-            result.push_str(comment);
-            result.push('\n');
-        }
+        self.push_leading_comments(&mut result, value);
 
         // Append value
-        result.push_str(&self.serialize_value_kind(&value.kind));
+        result.push_str(&self.serialize_contents(value));
+        self.push_trailing_comment(&mut result, value);
         result
     }
 
+    /// Emit the comments attached before `value`, one indented line each
+    ///
+    /// Compact output hoists comments instead, even when `max_width` breaks
+    /// a value across lines.
+    fn push_leading_comments(&self, result: &mut String, value: &Value) {
+        if self.options.comments && self.options.use_newlines {
+            self.push_comment_lines(result, &value.comments);
+        }
+    }
+
+    /// The comments to emit before a closing bracket: none for compact
+    /// output, which hoists them
+    fn closing_comments<'a>(&self, comments: &'a [Comment]) -> &'a [Comment] {
+        if self.options.comments && self.options.use_newlines {
+            comments
+        } else {
+            &[]
+        }
+    }
+
+    /// Emit `comments` one indented line each
+    fn push_comment_lines(&self, result: &mut String, comments: &[Comment]) {
+        for comment in comments {
+            result.push_str(&self.indent());
+            result.push_str(&self.format_comment(comment));
+            result.push_str(self.newline());
+        }
+    }
+
+    /// Emit the same-line comment after `value` (and its separator)
+    fn push_trailing_comment(&self, result: &mut String, value: &Value) {
+        if self.options.comments
+            && self.options.use_newlines
+            && let Some(comment) = &value.trailing_comment
+        {
+            result.push(' ');
+            result.push_str(&self.format_comment(comment));
+        }
+    }
+
+    /// Render a comment with the configured marker and spacing
+    fn format_comment(&self, comment: &Comment) -> String {
+        let marker = match (self.options.comment_marker, comment.kind) {
+            (CommentMarker::Hash, _) => "#",
+            (CommentMarker::Slashes, kind) => kind.marker(),
+        };
+        if self.options.space_after_comment_marker {
+            format!("{} {}", marker, comment.text)
+        } else {
+            format!("{}{}", marker, comment.text)
+        }
+    }
+
+    fn has_comments(&self, value: &Value) -> bool {
+        self.options.comments && (!value.comments.is_empty() || value.trailing_comment.is_some())
+    }
+
+    /// Render `value` without its leading and trailing comments
+    fn serialize_contents(&mut self, value: &Value) -> String {
+        match &value.kind {
+            ValueKind::Array(arr) => self.serialize_array(arr, &value.closing_comments),
+            ValueKind::Object(obj) => self.serialize_object(obj, &value.closing_comments),
+            kind => self.serialize_value_kind(kind),
+        }
+    }
+
     fn serialize_value_kind(&mut self, kind: &ValueKind) -> String {
         match kind {
             ValueKind::Null => "null".to_string(),
             ValueKind::Bool(b) => b.to_string(),
             ValueKind::Integer(i) => i.to_string(),
-            ValueKind::Float(f) => {
-                // Format floats nicely, avoiding unnecessary decimals
-                let s = f.to_string();
-                if s.ends_with(".0") { s } else { s }
-            }
+            ValueKind::Float(f) => self.serialize_float(*f),
             ValueKind::String(s) => self.serialize_string(s),
-            ValueKind::Array(arr) => self.serialize_array(arr),
-            ValueKind::Object(obj) => self.serialize_object(obj),
+            ValueKind::Array(arr) => self.serialize_array(arr, &[]),
+            ValueKind::Object(obj) => self.serialize_object(obj, &[]),
+        }
+    }
+
+    fn serialize_float(&self, f: f64) -> String {
+        let exponent = match self.options.scientific {
+            ScientificPolicy::Auto => f != 0.0 && !(1e-5..1e16).contains(&f.abs()),
+            ScientificPolicy::Never => false,
+            ScientificPolicy::Always => true,
+        };
+        if exponent {
+            return format!("{:e}", f);
+        }
+        let mut out = f.to_string();
+        // Without a `.`, integral values would read back as integers
+        if f.is_finite() && !out.contains('.') {
+            out.push_str(".0");
         }
+        out
     }
 
     fn serialize_string(&self, s: &str) -> String {
@@ -103,125 +291,215 @@ impl Serializer {
         result
     }
 
-    fn serialize_array(&mut self, arr: &[Value]) -> String {
-        if arr.is_empty() {
+    fn serialize_array(&mut self, arr: &[Value], closing: &[Comment]) -> String {
+        let closing = self.closing_comments(closing);
+        if arr.is_empty() && closing.is_empty() {
             return "[]".to_string();
         }
 
-        let mut result = String::from("[");
-
-        if self.options.use_newlines && arr.len() > 1 {
-            result.push('\n');
-            self.indent_level += 1;
-
-            for (i, item) in arr.iter().enumerate() {
-                result.push_str(&self.indent());
-                result.push_str(&self.serialize_value(item));
-
-                if i < arr.len() - 1 {
-                    result.push(',');
-                    result.push('\n');
-                } else if self.options.trailing_commas {
-                    result.push(',');
-                    result.push('\n');
-                } else {
-                    result.push('\n');
-                }
+        // Comments need their own lines, so commented arrays are never inlined
+        let multiline =
+            arr.len() > 1 || !closing.is_empty() || arr.iter().any(|item| self.has_comments(item));
+        if !(self.options.use_newlines && multiline) {
+            // Single line for short arrays or when use_newlines is false
+            // (in which case comments were hoisted by `serialize`)
+            let inline = self.without_width(|s| s.inline_array(arr));
+            if self.fits(&inline) {
+                return inline;
             }
+        }
 
-            self.indent_level -= 1;
+        let mut result = format!("[{}", self.newline());
+        self.indent_level += 1;
+        for (i, item) in arr.iter().enumerate() {
+            self.push_leading_comments(&mut result, item);
             result.push_str(&self.indent());
-        } else {
-            // Single line for short arrays or when use_newlines is false
-            for (i, item) in arr.iter().enumerate() {
-                result.push_str(&self.serialize_value(item));
-                if i < arr.len() - 1 {
-                    result.push_str(", ");
-                } else if self.options.trailing_commas {
-                    result.push(',');
-                }
+            self.column = self.indent().len();
+            result.push_str(&self.serialize_contents(item));
+
+            if i < arr.len() - 1 || self.options.trailing_commas {
+                result.push(',');
             }
+            self.push_trailing_comment(&mut result, item);
+            result.push_str(self.newline());
         }
+        self.push_comment_lines(&mut result, closing);
+        self.indent_level -= 1;
+        result.push_str(&self.indent());
+        result.push(']');
+        result
+    }
 
+    fn inline_array(&mut self, arr: &[Value]) -> String {
+        let mut result = String::from("[");
+        for (i, item) in arr.iter().enumerate() {
+            result.push_str(&self.serialize_contents(item));
+            if i < arr.len() - 1 {
+                result.push_str(", ");
+            } else if self.options.trailing_commas {
+                result.push(',');
+            }
+        }
         result.push(']');
         result
     }
 
-    fn serialize_object(&mut self, obj: &IndexMap<String, Value>) -> String {
-        if obj.is_empty() {
+    fn serialize_object(&mut self, obj: &IndexMap<String, Value>, closing: &[Comment]) -> String {
+        let closing = self.closing_comments(closing);
+        let entries: Vec<_> = obj
+            .iter()
+            .filter(|(_, value)| !(self.options.skip_nulls && value.kind == ValueKind::Null))
+            .collect();
+        if entries.is_empty() && closing.is_empty() {
             return "{}".to_string();
         }
 
-        let mut result = String::from("{");
+        if !self.options.use_newlines {
+            // Single line for compact output (comments were hoisted by `serialize`)
+            let inline = self.without_width(|s| s.inline_object(&entries));
+            if self.fits(&inline) {
+                return inline;
+            }
+        }
 
-        if self.options.use_newlines {
-            result.push('\n');
-            self.indent_level += 1;
+        let mut result = format!("{{{}", self.newline());
+        self.indent_level += 1;
 
-            let keys: Vec<_> = obj.keys().collect();
-            for (i, key) in keys.iter().enumerate() {
-                let value = &obj[*key];
+        let keys: Vec<_> = entries.iter().map(|(k, v)| key_text(k, v)).collect();
+        let key_width = if self.options.align_values {
+            keys.iter().map(|k| k.chars().count()).max().unwrap_or(0)
+        } else {
+            0
+        };
+        for (i, (key, (_, value))) in keys.iter().zip(&entries).enumerate() {
+            // Print comments before the key
+            self.push_leading_comments(&mut result, value);
+
+            let line_start = result.len();
+            result.push_str(&self.indent());
+            result.push_str(&format!("{:<width$}", key, width = key_width));
 
-                // Print comments before the key
-                for comment in &value.comments {
+            match self.value_on_next_line(value) {
+                Some(rendered) => {
+                    result.push(':');
+                    result.push_str(self.newline());
+                    self.indent_level += 1;
                     result.push_str(&self.indent());
-                    result.push_str("// ");
-                    result.push_str(comment);
-                    result.push('\n');
+                    self.indent_level -= 1;
+                    result.push_str(&rendered);
                 }
-
-                result.push_str(&self.indent());
-                result.push_str(key);
-                result.push_str(": ");
-
-                result.push_str(&self.serialize_value_kind(&value.kind));
-
-                if i < keys.len() - 1 {
-                    result.push(',');
-                    result.push('\n');
-                } else if self.options.trailing_commas {
-                    result.push(',');
-                    result.push('\n');
-                } else {
-                    result.push('\n');
+                None => {
+                    result.push_str(self.colon());
+                    self.column = result[line_start..].chars().count();
+                    result.push_str(&self.serialize_contents(value));
                 }
             }
 
-            self.indent_level -= 1;
-            result.push_str(&self.indent());
-        } else {
-            // Single line for compact output
-            let keys: Vec<_> = obj.keys().collect();
-            for (i, key) in keys.iter().enumerate() {
-                let value = &obj[*key];
-
-                for comment in &value.comments {
-                    result.push_str("// ");
-                    result.push_str(comment);
-                    result.push('\n'); // Forced newline for comment
-                }
+            if i < keys.len() - 1 || self.options.trailing_commas {
+                result.push(',');
+            }
+            self.push_trailing_comment(&mut result, value);
+            result.push_str(self.newline());
+        }
+        self.push_comment_lines(&mut result, closing);
+
+        self.indent_level -= 1;
+        result.push_str(&self.indent());
+        result.push('}');
+        result
+    }
 
-                result.push_str(key);
-                result.push_str(": ");
-                result.push_str(&self.serialize_value_kind(&value.kind));
+    /// With `brace_on_next_line`, render a compound object value one level
+    /// deeper, if it spans several lines
+    fn value_on_next_line(&mut self, value: &Value) -> Option<String> {
+        if !self.options.brace_on_next_line
+            || !matches!(value.kind, ValueKind::Array(_) | ValueKind::Object(_))
+        {
+            return None;
+        }
+        self.indent_level += 1;
+        self.column = self.indent().len();
+        let rendered = self.serialize_contents(value);
+        self.indent_level -= 1;
+        rendered.contains('\n').then_some(rendered)
+    }
 
-                if i < keys.len() - 1 {
-                    result.push_str(", ");
-                } else if self.options.trailing_commas {
-                    result.push(',');
-                }
+    fn inline_object(&mut self, entries: &[(&String, &Value)]) -> String {
+        let mut result = String::from("{");
+        for (i, (key, value)) in entries.iter().enumerate() {
+            result.push_str(&key_text(key, value));
+            result.push_str(self.colon());
+            result.push_str(&self.serialize_contents(value));
+
+            if i < entries.len() - 1 {
+                result.push_str(", ");
+            } else if self.options.trailing_commas {
+                result.push(',');
             }
         }
-
         result.push('}');
         result
     }
 
+    /// Whether `inline` fits within `max_width` starting at the current column
+    fn fits(&self, inline: &str) -> bool {
+        self.options
+            .max_width
+            .is_none_or(|width| self.column + inline.chars().count() <= width)
+    }
+
+    /// Run `f` with `max_width` disabled, for the parts of a line whose
+    /// enclosing value has already been checked against it as a whole
+    fn without_width<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let width = self.options.max_width.take();
+        let result = f(self);
+        self.options.max_width = width;
+        result
+    }
+
+    fn colon(&self) -> &'static str {
+        if self.options.space_after_colon {
+            ": "
+        } else {
+            ":"
+        }
+    }
+
+    fn newline(&self) -> &'static str {
+        match self.options.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+
     fn indent(&self) -> String {
         " ".repeat(self.indent_level * self.options.indent_size)
     }
 }
 
+/// An object key as written, with the value's merge directive suffix.
+fn key_text(key: &str, value: &Value) -> String {
+    match value.merge_directive {
+        Some(MergeDirective::Append) => format!("{}+", key),
+        Some(MergeDirective::Replace) => format!("{}!", key),
+        None => key.to_string(),
+    }
+}
+
+/// Collect comments from `value` and its descendants in document order.
+fn collect_comments<'a>(value: &'a Value, out: &mut Vec<&'a Comment>) {
+    out.extend(value.comments.iter());
+    match &value.kind {
+        ValueKind::Array(arr) => arr.iter().for_each(|v| collect_comments(v, out)),
+        ValueKind::Object(obj) => obj.values().for_each(|v| collect_comments(v, out)),
+        _ => {}
+    }
+    out.extend(value.closing_comments.iter());
+    out.extend(value.trailing_comment.iter());
+}
+
 /// Serialize a value to COSY format with default options
 pub fn to_string(value: &Value) -> String {
     let mut serializer = Serializer::new();
@@ -250,6 +528,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_serialize_numbers() {
         assert_eq!(to_string(&Value::from(ValueKind::Integer(42))), "42");
         assert_eq!(to_string(&Value::from(ValueKind::Integer(-10))), "-10");
@@ -390,4 +669,264 @@ mod tests {
         let reparsed = from_str(&serialized).unwrap();
         assert_eq!(parsed, reparsed);
     }
+
+    #[test]
+    fn test_compact_roundtrip_with_comments() {
+        use crate::from_str;
+
+        let input = r#"{
+            // the name
+            name: "Alice"
+            // scores so far
+            scores: [
+                // first
+                95
+                87
+            ]
+        }"#;
+        let parsed = from_str(input).unwrap();
+
+        let options = SerializeOptions {
+            use_newlines: false,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options);
+
+        // Everything after the hoisted comments stays on one line
+        let body = serialized.lines().last().unwrap();
+        assert_eq!(body, r#"{name: "Alice", scores: [95, 87]}"#);
+        assert!(serialized.starts_with("// the name\n// scores so far\n// first\n"));
+
+        let reparsed = from_str(&serialized).unwrap();
+        assert_eq!(reparsed.kind, from_str(body).unwrap().kind);
+        let texts: Vec<_> = reparsed.line_comments().collect();
+        assert_eq!(texts, vec!["the name", "scores so far", "first"]);
+    }
+
+    #[test]
+    fn test_compact_omit_comments() {
+        use crate::from_str;
+
+        let parsed = from_str("{\n // note\n a: 1\n}").unwrap();
+        let options = SerializeOptions {
+            use_newlines: false,
+            comments: false,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options);
+        assert_eq!(serialized, "{a: 1}");
+        assert!(from_str(&serialized).is_ok());
+    }
+
+    #[test]
+    fn test_no_space_after_colon() {
+        use crate::from_str;
+
+        let parsed = from_str("{ a: 1, b: { c: true } }").unwrap();
+        let options = SerializeOptions {
+            space_after_colon: false,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options.clone());
+        assert_eq!(serialized, "{\n    a:1,\n    b:{\n        c:true\n    }\n}");
+
+        let compact = to_string_with_options(
+            &parsed,
+            SerializeOptions {
+                use_newlines: false,
+                ..options
+            },
+        );
+        assert_eq!(compact, "{a:1, b:{c:true}}");
+    }
+
+    #[test]
+    fn test_align_values() {
+        use crate::from_str;
+
+        let parsed =
+            from_str("{ id: 1, hostname: \"h\", db: { user: \"u\", pw: \"p\" } }").unwrap();
+        let options = SerializeOptions {
+            align_values: true,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options);
+
+        // Each object is aligned to its own longest key
+        assert_eq!(
+            serialized,
+            "{\n    id      : 1,\n    hostname: \"h\",\n    db      : {\n        user: \"u\",\n        pw  : \"p\"\n    }\n}"
+        );
+        assert_eq!(from_str(&serialized).unwrap(), parsed);
+    }
+
+    #[test]
+    fn test_max_width_expands_inline_values() {
+        let value = crate::from_str(r#"{ tags: ["alpha"], ports: [80, 443] }"#).unwrap();
+        let compact = |max_width| SerializeOptions {
+            use_newlines: false,
+            max_width,
+            ..Default::default()
+        };
+
+        // Fits on one line as a whole
+        assert_eq!(
+            to_string_with_options(&value, compact(Some(40))),
+            r#"{tags: ["alpha"], ports: [80, 443]}"#
+        );
+
+        // Too wide: the object breaks, and each entry is checked on its own line
+        assert_eq!(
+            to_string_with_options(&value, compact(Some(20))),
+            "{\n    tags: [\"alpha\"],\n    ports: [80, 443]\n}"
+        );
+        assert_eq!(
+            to_string_with_options(&value, compact(Some(19))),
+            "{\n    tags: [\"alpha\"],\n    ports: [\n        80,\n        443\n    ]\n}"
+        );
+
+        // Single-item arrays are inline with newlines on, unless too wide
+        let options = SerializeOptions {
+            max_width: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with_options(&crate::from_str(r#"{ tags: ["alpha"] }"#).unwrap(), options),
+            "{\n    tags: [\n        \"alpha\"\n    ]\n}"
+        );
+    }
+
+    #[test]
+    fn test_brace_on_next_line() {
+        use crate::from_str;
+
+        let parsed = from_str(
+            r#"{ name: "api", tags: ["a"], features: ["auth", "api_v2"], db: { user: "u", pool: { max: 5, min: 1 } } }"#,
+        )
+        .unwrap();
+        let options = SerializeOptions {
+            brace_on_next_line: true,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options);
+
+        // Inline values stay on the key's line
+        assert_eq!(
+            serialized,
+            "{\n    name: \"api\",\n    tags: [\"a\"],\n    features:\n        [\n            \"auth\",\n            \"api_v2\"\n        ],\n    db:\n        {\n            user: \"u\",\n            pool:\n                {\n                    max: 5,\n                    min: 1\n                }\n        }\n}"
+        );
+        assert_eq!(from_str(&serialized).unwrap(), parsed);
+    }
+
+    #[test]
+    fn test_skip_nulls() {
+        use crate::serde::ValueSerializer;
+        use serde::Serialize;
+
+        #[derive(Serialize)]
+        struct Server {
+            host: String,
+            port: Option<u16>,
+            aliases: Vec<Option<String>>,
+        }
+
+        let server = Server {
+            host: "localhost".to_string(),
+            port: None,
+            aliases: vec![None, Some("www".to_string())],
+        }
+        .serialize(ValueSerializer)
+        .unwrap();
+        let options = SerializeOptions {
+            skip_nulls: true,
+            ..Default::default()
+        };
+
+        // Null items in arrays are kept
+        assert_eq!(
+            to_string_with_options(&server, options.clone()),
+            "{\n    host: \"localhost\",\n    aliases: [\n        null,\n        \"www\"\n    ]\n}"
+        );
+        let compact = SerializeOptions {
+            use_newlines: false,
+            ..options
+        };
+        assert_eq!(
+            to_string_with_options(&server, compact),
+            "{host: \"localhost\", aliases: [null, \"www\"]}"
+        );
+        assert!(to_string(&server).contains("port: null"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        use crate::from_str;
+
+        let parsed = from_str(
+            "{\n    // the name\n    name: \"api\"\n    ports: [80, 443]\n    db: { user: \"u\" }\n}",
+        )
+        .unwrap();
+        let options = SerializeOptions {
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+        let serialized = to_string_with_options(&parsed, options);
+
+        assert_eq!(
+            serialized,
+            "{\r\n    // the name\r\n    name: \"api\",\r\n    ports: [\r\n        80,\r\n        443\r\n    ],\r\n    db: {\r\n        user: \"u\"\r\n    }\r\n}"
+        );
+        assert!(!serialized.replace("\r\n", "").contains('\n'));
+        assert_eq!(from_str(&serialized).unwrap(), parsed);
+    }
+
+    #[test]
+    fn test_scientific_policy() {
+        use crate::from_str;
+
+        let tiny = Value::from(ValueKind::Float(0.0000001));
+        let with = |scientific| {
+            to_string_with_options(
+                &tiny,
+                SerializeOptions {
+                    scientific,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(to_string(&tiny), "0.0000001");
+        assert_eq!(with(ScientificPolicy::Never), "0.0000001");
+        assert_eq!(with(ScientificPolicy::Auto), "1e-7");
+        assert_eq!(with(ScientificPolicy::Always), "1e-7");
+        for policy in [ScientificPolicy::Never, ScientificPolicy::Always] {
+            assert_eq!(from_str(&with(policy)).unwrap(), tiny);
+        }
+
+        // `Auto` keeps ordinary magnitudes fixed-point; `Always` never does
+        let ratio = Value::from(ValueKind::Float(2.75));
+        let large = Value::from(ValueKind::Float(2.5e20));
+        let options = |scientific| SerializeOptions {
+            scientific,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with_options(&ratio, options(ScientificPolicy::Auto)),
+            "2.75"
+        );
+        assert_eq!(
+            to_string_with_options(&ratio, options(ScientificPolicy::Always)),
+            "2.75e0"
+        );
+        assert_eq!(
+            to_string_with_options(&large, options(ScientificPolicy::Auto)),
+            "2.5e20"
+        );
+        assert_eq!(to_string(&large), "250000000000000000000.0");
+
+        // Fixed-point output still reads back as the same float
+        for f in [2.5e20, 1e300, 3.0, -0.5] {
+            let value = Value::from(ValueKind::Float(f));
+            assert_eq!(from_str(&to_string(&value)).unwrap(), value, "{}", f);
+        }
+    }
 }
@@ -0,0 +1,159 @@
+//! Resolution of in-document anchors and references.
+//!
+//! With [`ParseOptions::enable_anchors`](super::parser::ParseOptions), a
+//! value written as `&name value` can be reused elsewhere in the same
+//! document: `*name` in value position stands for a copy of it, and `*name`
+//! as an object entry merges an anchored object into the enclosing one, whose
+//! own keys win. This is unrelated to file includes, which pull in other
+//! files through the `include` key.
+//!
+//! The parser leaves a `null` placeholder at every reference and records
+//! anchors and references by source position. [`resolve`] then substitutes
+//! them, resolving the references inside an anchored value before copying
+//! it, and reports a reference that needs its own result as a cycle.
+
+use super::lexer::Position;
+use super::parser::ParseError;
+use crate::merge::merge;
+use crate::value::{Value, ValueKind};
+
+/// A `*name` recorded by the parser
+#[derive(Debug, Clone)]
+pub(crate) struct Reference {
+    pub name: String,
+    /// The `*name` token, for error messages
+    pub position: Position,
+    /// The value to replace, or the object to merge into
+    pub target: Position,
+    /// Whether this is an object entry merging the anchor into its object
+    pub merge: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Pending,
+    InProgress,
+    Done,
+}
+
+/// Substitute every reference in `root`
+pub(crate) fn resolve(
+    root: &mut Value,
+    anchors: &[(String, Position)],
+    references: &[Reference],
+) -> Result<(), ParseError> {
+    let mut states = vec![State::Pending; references.len()];
+    for index in 0..references.len() {
+        resolve_one(root, anchors, references, &mut states, index)?;
+    }
+    Ok(())
+}
+
+fn resolve_one(
+    root: &mut Value,
+    anchors: &[(String, Position)],
+    references: &[Reference],
+    states: &mut [State],
+    index: usize,
+) -> Result<(), ParseError> {
+    if states[index] == State::Done {
+        return Ok(());
+    }
+    states[index] = State::InProgress;
+    let reference = &references[index];
+
+    let Some(&(_, anchor_pos)) = anchors.iter().find(|(name, _)| *name == reference.name) else {
+        return Err(error_at(
+            reference.position,
+            format!("Undefined anchor '{}'", reference.name),
+        ));
+    };
+
+    // References inside the anchored value must be substituted first
+    let mut inner = Vec::new();
+    if let Some(anchored) = find(root, anchor_pos) {
+        collect_positions(anchored, &mut inner);
+    }
+    for dependency in 0..references.len() {
+        if !inner.contains(&references[dependency].target) {
+            continue;
+        }
+        if states[dependency] == State::InProgress {
+            return Err(error_at(
+                reference.position,
+                format!("Anchor '{}' refers to itself", reference.name),
+            ));
+        }
+        resolve_one(root, anchors, references, states, dependency)?;
+    }
+
+    let Some(anchored) = find(root, anchor_pos).map(|value| value.kind.clone()) else {
+        // The anchored value was dropped by the duplicate key policy
+        return Err(error_at(
+            reference.position,
+            format!("Undefined anchor '{}'", reference.name),
+        ));
+    };
+    if let Some(target) = find_mut(root, reference.target) {
+        if reference.merge {
+            if !matches!(anchored, ValueKind::Object(_)) {
+                return Err(error_at(
+                    reference.position,
+                    format!(
+                        "Cannot merge anchor '{}' ({}) into an object",
+                        reference.name,
+                        anchored.type_name()
+                    ),
+                ));
+            }
+            let own = std::mem::replace(&mut target.kind, ValueKind::Null);
+            let mut merged = Value::from(anchored);
+            merge(&mut merged, Value::from(own));
+            target.kind = merged.kind;
+        } else {
+            target.kind = anchored;
+        }
+    }
+    states[index] = State::Done;
+    Ok(())
+}
+
+fn error_at(position: Position, message: String) -> ParseError {
+    ParseError {
+        message,
+        line: position.line,
+        column: position.column,
+    }
+}
+
+/// Positions of `value` and everything inside it
+fn collect_positions(value: &Value, positions: &mut Vec<Position>) {
+    positions.extend(value.position);
+    match &value.kind {
+        ValueKind::Array(items) => items.iter().for_each(|v| collect_positions(v, positions)),
+        ValueKind::Object(map) => map.values().for_each(|v| collect_positions(v, positions)),
+        _ => {}
+    }
+}
+
+fn find(value: &Value, position: Position) -> Option<&Value> {
+    if value.position == Some(position) {
+        return Some(value);
+    }
+    match &value.kind {
+        ValueKind::Array(items) => items.iter().find_map(|v| find(v, position)),
+        ValueKind::Object(map) => map.values().find_map(|v| find(v, position)),
+        _ => None,
+    }
+}
+
+fn find_mut(value: &mut Value, position: Position) -> Option<&mut Value> {
+    if value.position == Some(position) {
+        return Some(value);
+    }
+    match &mut value.kind {
+        ValueKind::Array(items) => items.iter_mut().find_map(|v| find_mut(v, position)),
+        ValueKind::Object(map) => map.values_mut().find_map(|v| find_mut(v, position)),
+        _ => None,
+    }
+}
@@ -1,5 +1,25 @@
+use crate::CosynError;
+use crate::value::ValueKind;
 use std::{env, error::Error, fmt};
 
+/// The value of an environment variable used outside a string: `true`,
+/// `false`, `null`, an integer or a float, or else the text as a string.
+pub(crate) fn infer_env_value(text: String) -> ValueKind {
+    if text == "true" {
+        ValueKind::Bool(true)
+    } else if text == "false" {
+        ValueKind::Bool(false)
+    } else if text == "null" {
+        ValueKind::Null
+    } else if let Ok(i) = text.parse::<i64>() {
+        ValueKind::Integer(i)
+    } else if let Ok(f) = text.parse::<f64>() {
+        ValueKind::Float(f)
+    } else {
+        ValueKind::String(text)
+    }
+}
+
 /// Position information for a token
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Position {
@@ -26,6 +46,25 @@ impl TokenWithPos {
     }
 }
 
+/// Whether a comment is an ordinary `//` comment or a `///` doc comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/// ...`, documentation for the following value
+    Doc,
+}
+
+impl CommentKind {
+    /// The marker that introduces this kind of comment in source text
+    pub fn marker(&self) -> &'static str {
+        match self {
+            CommentKind::Line => "//",
+            CommentKind::Doc => "///",
+        }
+    }
+}
+
 /// A token in the COSY format
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -39,7 +78,11 @@ pub enum Token {
     True,
     False,
     Null,
-    Comment(String),
+    Comment(String, CommentKind),
+    /// `&name`, naming the value that follows
+    Anchor(String),
+    /// `*name`, standing for an anchored value
+    Reference(String),
 
     // Symbols
     LeftBrace,    // {
@@ -49,6 +92,10 @@ pub enum Token {
     Colon,        // :
     Comma,        // ,
     Newline,      // \n
+    /// `+` between a key and its colon, appending to the merged value
+    Plus,
+    /// `!` between a key and its colon, replacing the merged value
+    Bang,
 
     // End of input
     Eof,
@@ -64,13 +111,17 @@ impl fmt::Display for Token {
             Token::True => write!(f, "true"),
             Token::False => write!(f, "false"),
             Token::Null => write!(f, "null"),
-            Token::Comment(s) => write!(f, "// {}", s),
+            Token::Comment(s, kind) => write!(f, "{} {}", kind.marker(), s),
+            Token::Anchor(name) => write!(f, "anchor '&{}'", name),
+            Token::Reference(name) => write!(f, "reference '*{}'", name),
             Token::LeftBrace => write!(f, "{{"),
             Token::RightBrace => write!(f, "}}"),
             Token::LeftBracket => write!(f, "["),
             Token::RightBracket => write!(f, "]"),
             Token::Colon => write!(f, ":"),
             Token::Comma => write!(f, ","),
+            Token::Plus => write!(f, "'+'"),
+            Token::Bang => write!(f, "'!'"),
             Token::Newline => write!(f, "newline"),
             Token::Eof => write!(f, "EOF"),
         }
@@ -85,6 +136,17 @@ pub struct LexError {
     pub column: usize,
 }
 
+impl LexError {
+    /// An error at `line` and `column`, both 1-based.
+    pub fn new(message: impl Into<String>, line: usize, column: usize) -> Self {
+        LexError {
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+}
+
 impl Error for LexError {}
 
 impl fmt::Display for LexError {
@@ -103,41 +165,60 @@ pub struct Lexer {
     position: usize,
     line: usize,
     column: usize,
+    /// Whether `#` starts a line comment
+    hash_comments: bool,
+    /// Whether `&name` and `*name` are anchors and references
+    anchors: bool,
+    /// Whether `Eof` or an error has been returned
+    finished: bool,
 }
 
 impl Lexer {
     /// Create a new lexer from input
+    ///
+    /// `\r\n` and lone `\r` line endings are normalized to `\n`, so every
+    /// line break advances the line counter and lexes as one `Newline`.
+    /// A leading UTF-8 byte order mark is skipped.
     pub fn new(input: &str) -> Self {
+        let input = input.strip_prefix('\u{FEFF}').unwrap_or(input);
+        let mut chars = Vec::with_capacity(input.len());
+        let mut iter = input.chars().peekable();
+        while let Some(ch) = iter.next() {
+            if ch == '\r' {
+                iter.next_if_eq(&'\n');
+                chars.push('\n');
+            } else {
+                chars.push(ch);
+            }
+        }
         Lexer {
-            input: input.chars().collect(),
+            input: chars,
             position: 0,
             line: 1,
             column: 1,
+            hash_comments: false,
+            anchors: false,
+            finished: false,
         }
     }
 
-    /// Tokenize the entire input, returning tokens with positions
-    pub fn tokenize(&mut self) -> Result<Vec<TokenWithPos>, LexError> {
-        let mut tokens = Vec::new();
-
-        loop {
-            self.skip_whitespace();
-
-            if self.is_at_end() {
-                tokens.push(TokenWithPos::new(
-                    Token::Eof,
-                    Position::new(self.line, self.column),
-                ));
-                break;
-            }
+    /// Also accept `#` line comments, lexed like `//` comments
+    pub fn with_hash_comments(mut self, enabled: bool) -> Self {
+        self.hash_comments = enabled;
+        self
+    }
 
-            // Capture position RIGHT before we start lexing the token
-            let pos = Position::new(self.line, self.column);
-            let token = self.next_token()?;
-            tokens.push(TokenWithPos::new(token, pos));
-        }
+    /// Also lex `&name` as an anchor and `*name` as a reference to it
+    pub fn with_anchors(mut self, enabled: bool) -> Self {
+        self.anchors = enabled;
+        self
+    }
 
-        Ok(tokens)
+    /// Tokenize the entire input, returning tokens with positions
+    ///
+    /// Equivalent to collecting the lexer as an iterator.
+    pub fn tokenize(&mut self) -> Result<Vec<TokenWithPos>, LexError> {
+        self.collect()
     }
 
     /// Get the next token
@@ -150,6 +231,10 @@ impl Lexer {
                 Ok(Token::Newline)
             }
             '/' if self.peek_next() == Some('/') => self.lex_comment(),
+            '#' if self.hash_comments => {
+                self.advance();
+                Ok(Token::Comment(self.read_comment_text(), CommentKind::Line))
+            }
             '{' => {
                 self.advance();
                 Ok(Token::LeftBrace)
@@ -174,6 +259,22 @@ impl Lexer {
                 self.advance();
                 Ok(Token::Comma)
             }
+            '+' if self.peek_next() == Some(':') => {
+                self.advance();
+                Ok(Token::Plus)
+            }
+            '!' if self.peek_next() == Some(':') => {
+                self.advance();
+                Ok(Token::Bang)
+            }
+            '&' if self.anchors => {
+                self.advance();
+                Ok(Token::Anchor(self.read_anchor_name()?))
+            }
+            '*' if self.anchors => {
+                self.advance();
+                Ok(Token::Reference(self.read_anchor_name()?))
+            }
             '"' => self.lex_string(),
             '$' => self.lex_standalone_env_var(), // Check for environment variable
             '-' | '0'..='9' => self.lex_number(),
@@ -343,6 +444,20 @@ impl Lexer {
         Ok(token)
     }
 
+    /// Read the name after `&` or `*`
+    fn read_anchor_name(&mut self) -> Result<String, LexError> {
+        let start = self.position;
+        while !self.is_at_end()
+            && (self.current_char().is_alphanumeric() || matches!(self.current_char(), '_' | '-'))
+        {
+            self.advance();
+        }
+        if self.position == start {
+            return Err(self.error("Expected a name after '&' or '*'".to_string()));
+        }
+        Ok(self.input[start..self.position].iter().collect())
+    }
+
     /// Read environment variable name (inside ${...})
     fn read_env_var_name(&mut self) -> Result<String, LexError> {
         let mut name = String::new();
@@ -384,27 +499,21 @@ impl Lexer {
             }
         };
 
-        // Type inference
-        if value_str == "true" {
-            Ok(Token::True)
-        } else if value_str == "false" {
-            Ok(Token::False)
-        } else if value_str == "null" {
-            Ok(Token::Null)
-        } else if let Ok(i) = value_str.parse::<i64>() {
-            Ok(Token::Integer(i))
-        } else if let Ok(f) = value_str.parse::<f64>() {
-            Ok(Token::Float(f))
-        } else {
-            Ok(Token::String(value_str))
-        }
+        Ok(match infer_env_value(value_str) {
+            ValueKind::Bool(true) => Token::True,
+            ValueKind::Bool(false) => Token::False,
+            ValueKind::Integer(i) => Token::Integer(i),
+            ValueKind::Float(f) => Token::Float(f),
+            ValueKind::String(s) => Token::String(s),
+            _ => Token::Null,
+        })
     }
 
     /// Skip whitespace only
     fn skip_whitespace(&mut self) {
         while !self.is_at_end() {
             match self.current_char() {
-                ' ' | '\t' | '\r' => self.advance(),
+                ' ' | '\t' => self.advance(),
                 _ => break,
             }
         }
@@ -434,13 +543,26 @@ impl Lexer {
         self.advance();
         self.advance();
 
+        // Exactly three slashes make a doc comment; `////` is ordinary
+        let kind = if self.current_char() == '/' && self.peek_next() != Some('/') {
+            self.advance();
+            CommentKind::Doc
+        } else {
+            CommentKind::Line
+        };
+
+        Ok(Token::Comment(self.read_comment_text(), kind))
+    }
+
+    /// Consume the rest of the line after a comment marker, trimmed
+    fn read_comment_text(&mut self) -> String {
         let start = self.position;
         while !self.is_at_end() && self.current_char() != '\n' {
             self.advance();
         }
 
         let comment: String = self.input[start..self.position].iter().collect();
-        Ok(Token::Comment(comment.trim().to_string()))
+        comment.trim().to_string()
     }
 
     /// Move to the next character - SINGLE SOURCE OF TRUTH for position tracking
@@ -471,6 +593,55 @@ impl Lexer {
     }
 }
 
+impl Iterator for Lexer {
+    type Item = Result<TokenWithPos, LexError>;
+
+    /// Lex the next token. The last item is `Token::Eof` or the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        self.skip_whitespace();
+
+        // Capture position RIGHT before we start lexing the token
+        let pos = Position::new(self.line, self.column);
+        if self.is_at_end() {
+            self.finished = true;
+            return Some(Ok(TokenWithPos::new(Token::Eof, pos)));
+        }
+        let token = self.next_token();
+        self.finished = token.is_err();
+        Some(token.map(|token| TokenWithPos::new(token, pos)))
+    }
+}
+
+impl std::iter::FusedIterator for Lexer {}
+
+/// A lexer over `input` that produces tokens lazily, as an iterator.
+///
+/// # Example
+///
+/// ```
+/// use cosy::syntax::lexer::{Token, lex};
+///
+/// let tokens = lex("{ a: 1 }").collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(tokens[0].token, Token::LeftBrace);
+/// assert_eq!(tokens.last().unwrap().token, Token::Eof);
+/// ```
+pub fn lex(input: &str) -> Lexer {
+    Lexer::new(input)
+}
+
+/// Tokenize COSY text into tokens with their source positions.
+///
+/// This is intended for tooling such as syntax highlighters and formatters.
+/// The token stream always ends with `Token::Eof`. Newlines and comments are
+/// kept as tokens. New `Token` variants may be added in minor releases, so
+/// match on it with a wildcard arm.
+pub fn tokenize(input: &str) -> Result<Vec<TokenWithPos>, CosynError> {
+    Ok(Lexer::new(input).tokenize()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -549,4 +720,87 @@ age: 30
         assert_eq!(tokens[0].pos, Position::new(1, 1)); // abc at line 1, col 1
         assert_eq!(tokens[2].pos, Position::new(2, 1)); // def at line 2, col 1 (index 2 because of newline at index 1)
     }
+
+    #[test]
+    fn test_crlf_and_cr_line_endings() {
+        for input in ["a\r\nb\r\n\r\nc", "a\rb\r\rc"] {
+            let tokens = Lexer::new(input).tokenize().unwrap();
+            let kinds: Vec<_> = tokens.iter().map(|t| t.token.clone()).collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    Token::Identifier("a".to_string()),
+                    Token::Newline,
+                    Token::Identifier("b".to_string()),
+                    Token::Newline,
+                    Token::Newline,
+                    Token::Identifier("c".to_string()),
+                    Token::Eof,
+                ]
+            );
+            assert_eq!(tokens[5].pos, Position::new(4, 1));
+        }
+    }
+
+    #[test]
+    fn test_leading_bom_is_skipped() {
+        let tokens = Lexer::new("\u{FEFF}a b").tokenize().unwrap();
+        assert_eq!(tokens[0].token, Token::Identifier("a".to_string()));
+        assert_eq!(tokens[0].pos, Position::new(1, 1));
+        assert_eq!(tokens[1].pos, Position::new(1, 3));
+
+        // Only a leading BOM is special
+        assert!(Lexer::new("a\u{FEFF}").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_hash_comments_opt_in() {
+        assert!(Lexer::new("a # note").tokenize().is_err());
+
+        let tokens = Lexer::new("a #  note \nb")
+            .with_hash_comments(true)
+            .tokenize()
+            .unwrap();
+        assert_eq!(
+            tokens[1].token,
+            Token::Comment("note".to_string(), CommentKind::Line)
+        );
+        assert_eq!(tokens[1].pos, Position::new(1, 3));
+        assert_eq!(tokens[3].token, Token::Identifier("b".to_string()));
+    }
+
+    #[test]
+    fn test_lex_iterator_matches_tokenize() {
+        let inputs = [
+            "{ a: 1, b: [true, null], c: \"s\" } // done",
+            "a\r\nb\r\rc",
+            "",
+            "// note\n{ x: 1.5e3 }",
+        ];
+        for input in inputs {
+            let lazy = lex(input).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(lazy, Lexer::new(input).tokenize().unwrap(), "{:?}", input);
+            assert_eq!(lazy.last().unwrap().token, Token::Eof);
+        }
+    }
+
+    #[test]
+    fn test_lex_iterator_is_lazy_and_stops_after_error() {
+        let mut lexer = lex("a b @ c");
+        assert_eq!(
+            lexer.next().unwrap().unwrap().token,
+            Token::Identifier("a".to_string())
+        );
+        assert_eq!(
+            lexer.next().unwrap().unwrap().token,
+            Token::Identifier("b".to_string())
+        );
+        let err = lexer.next().unwrap().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+        assert!(lexer.next().is_none());
+
+        let mut lexer = lex("a");
+        assert_eq!(lexer.by_ref().count(), 2);
+        assert!(lexer.next().is_none());
+    }
 }
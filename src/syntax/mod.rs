@@ -1,2 +1,3 @@
+pub(crate) mod anchors;
 pub mod lexer;
 pub mod parser;
@@ -1,6 +1,8 @@
 use crate::CosynError;
-use crate::syntax::lexer::{Lexer, Position, Token, TokenWithPos};
-use crate::value::{Value, ValueKind};
+use crate::merge::{MergeDirective, merge};
+use crate::syntax::anchors::{self, Reference};
+use crate::syntax::lexer::{LexError, Lexer, Position, Token, TokenWithPos};
+use crate::value::{Comment, Value, ValueKind};
 use indexmap::IndexMap;
 use std::error::Error;
 use std::fmt;
@@ -13,6 +15,17 @@ pub struct ParseError {
     pub column: usize,
 }
 
+impl ParseError {
+    /// An error at `line` and `column`, both 1-based.
+    pub fn new(message: impl Into<String>, line: usize, column: usize) -> Self {
+        ParseError {
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -25,26 +38,126 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
+/// How the parser treats a key that appears twice in the same object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// The later value replaces the earlier one (default)
+    #[default]
+    LastWins,
+    /// The earlier value is kept and the later one ignored
+    FirstWins,
+    /// Duplicate keys are a parse error
+    Error,
+    /// Deep-merge the values when both are objects, error otherwise
+    MergeObjects,
+}
+
+/// What the parser returns for input with no value: empty, or only
+/// whitespace and comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyPolicy {
+    /// Empty input is a parse error (default)
+    #[default]
+    Error,
+    /// Empty input is `null`
+    Null,
+    /// Empty input is an empty object
+    EmptyObject,
+}
+
+/// Parser options for controlling how input is interpreted
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Handling of duplicate object keys (default: `LastWins`)
+    pub duplicate_key: DuplicateKeyPolicy,
+    /// Handling of input with no value (default: `Error`)
+    pub empty_as: EmptyPolicy,
+    /// Accept `#` line comments in addition to `//` (default: false)
+    pub hash_comments: bool,
+    /// Accept `&name` anchors and `*name` references within the document
+    /// (default: false)
+    pub enable_anchors: bool,
+    /// Reject input longer than this many bytes before lexing it, so a huge
+    /// file fails cleanly instead of exhausting memory (default: no limit)
+    pub max_input_bytes: Option<usize>,
+}
+
+/// Counts of what a parse produced, for gauging how complex a config is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseStats {
+    pub objects: usize,
+    pub arrays: usize,
+    /// Strings, numbers, booleans and nulls
+    pub scalars: usize,
+    pub comments: usize,
+    /// Deepest nesting of objects and arrays (0 for a lone scalar)
+    pub max_depth: usize,
+}
+
 /// The COSY parser with position tracking
 pub struct Parser {
     tokens: Vec<TokenWithPos>,
     position: usize,
+    options: ParseOptions,
+    stats: ParseStats,
+    /// Objects and arrays currently open
+    depth: usize,
+    /// Anchor names with the position of the value they name
+    anchors: Vec<(String, Position)>,
+    references: Vec<Reference>,
 }
 
 impl Parser {
     /// Create a new parser from tokens
     pub fn new(tokens: Vec<TokenWithPos>) -> Self {
+        Self::with_options(tokens, ParseOptions::default())
+    }
+
+    /// Create a new parser with custom options
+    pub fn with_options(tokens: Vec<TokenWithPos>, options: ParseOptions) -> Self {
         Parser {
             tokens,
             position: 0,
+            options,
+            stats: ParseStats::default(),
+            depth: 0,
+            anchors: Vec::new(),
+            references: Vec::new(),
         }
     }
 
+    /// Create a parser from a token iterator such as [`lex`](super::lexer::lex),
+    /// collecting it eagerly. Returns the first lex error.
+    pub fn from_tokens<I>(tokens: I) -> Result<Self, LexError>
+    where
+        I: IntoIterator<Item = Result<TokenWithPos, LexError>>,
+    {
+        Ok(Self::new(tokens.into_iter().collect::<Result<_, _>>()?))
+    }
+
+    /// What has been parsed so far
+    pub fn stats(&self) -> ParseStats {
+        self.stats
+    }
+
     /// Parse a complete COSY document
     pub fn parse(&mut self) -> Result<Value, ParseError> {
         let (root_comments, _) = self.consume_newlines_and_comments_captured();
 
-        let value = self.parse_value(root_comments)?;
+        if matches!(self.current_token(), Token::Eof) {
+            match self.options.empty_as {
+                EmptyPolicy::Error => {}
+                EmptyPolicy::Null => {
+                    return Ok(Value::with_comments(ValueKind::Null, root_comments));
+                }
+                EmptyPolicy::EmptyObject => {
+                    let empty = ValueKind::Object(IndexMap::new());
+                    return Ok(Value::with_comments(empty, root_comments));
+                }
+            }
+        }
+
+        let mut value = self.parse_value(root_comments)?;
 
         self.consume_newlines_and_comments_captured(); // Allow trailing newlines/comments
 
@@ -53,14 +166,16 @@ impl Parser {
             return Err(self.error_at_current("Unexpected tokens after value".to_string()));
         }
 
+        anchors::resolve(&mut value, &self.anchors, &self.references)?;
         Ok(value)
     }
 
     /// Parse any value
-    fn parse_value(&mut self, mut leading_comments: Vec<String>) -> Result<Value, ParseError> {
+    fn parse_value(&mut self, mut leading_comments: Vec<Comment>) -> Result<Value, ParseError> {
         let (comments, _) = self.consume_newlines_and_comments_captured();
         leading_comments.extend(comments);
 
+        let pos = self.current_position();
         let val_kind = match &self.current_token() {
             Token::Null => {
                 self.advance();
@@ -89,12 +204,53 @@ impl Parser {
                 self.advance();
                 v
             }
-            Token::LeftBrace => return self.parse_object(leading_comments),
-            Token::LeftBracket => return self.parse_array(leading_comments),
+            Token::Anchor(name) => {
+                if self.anchors.iter().any(|(defined, _)| *defined == *name) {
+                    return Err(self.error_at_current(format!("Duplicate anchor '{}'", name)));
+                }
+                self.advance();
+                let value = self.parse_value(leading_comments)?;
+                if let Some(position) = value.position {
+                    self.anchors.push((name.clone(), position));
+                }
+                return Ok(value);
+            }
+            Token::Reference(name) => {
+                // A placeholder, replaced once parsing is done
+                self.references.push(Reference {
+                    name: name.clone(),
+                    position: pos,
+                    target: pos,
+                    merge: false,
+                });
+                self.advance();
+                return Ok(Value::with_comments(ValueKind::Null, leading_comments).at(pos));
+            }
+            Token::LeftBrace => {
+                self.enter();
+                let object = self.parse_object(leading_comments, pos)?;
+                self.depth -= 1;
+                self.stats.objects += 1;
+                return Ok(object.at(pos));
+            }
+            Token::LeftBracket => {
+                self.enter();
+                let array = self.parse_array(leading_comments)?;
+                self.depth -= 1;
+                self.stats.arrays += 1;
+                return Ok(array.at(pos));
+            }
             token => return Err(self.error_at_current(format!("Expected value, found {}", token))),
         };
 
-        Ok(Value::with_comments(val_kind, leading_comments))
+        self.stats.scalars += 1;
+        Ok(Value::with_comments(val_kind, leading_comments).at(pos))
+    }
+
+    /// Open an object or array
+    fn enter(&mut self) {
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
     }
 
     /// Expect a specific token, advance if found
@@ -158,7 +314,7 @@ impl Parser {
     }
 
     /// Consume newlines and comments, collecting comments and tracking if newline was seen
-    fn consume_newlines_and_comments_captured(&mut self) -> (Vec<String>, bool) {
+    fn consume_newlines_and_comments_captured(&mut self) -> (Vec<Comment>, bool) {
         let mut comments = Vec::new();
         let mut has_newline = false;
         loop {
@@ -167,8 +323,9 @@ impl Parser {
                     has_newline = true;
                     self.advance();
                 }
-                Token::Comment(c) => {
-                    comments.push(c);
+                Token::Comment(text, kind) => {
+                    comments.push(Comment { kind, text });
+                    self.stats.comments += 1;
                     self.advance();
                 }
                 _ => break,
@@ -177,8 +334,44 @@ impl Parser {
         (comments, has_newline)
     }
 
-    /// Parse an object with optional commas after newlines
-    fn parse_object(&mut self, leading_comments: Vec<String>) -> Result<Value, ParseError> {
+    /// Take a comment that directly follows a value on the same line
+    fn take_trailing_comment(&mut self) -> Option<Comment> {
+        if let Token::Comment(text, kind) = self.current_token() {
+            self.stats.comments += 1;
+            self.advance();
+            Some(Comment { kind, text })
+        } else {
+            None
+        }
+    }
+
+    /// Consume what follows an entry: a comment on the same line, then
+    /// newlines and/or a comma. Returns that comment, the comments before
+    /// the next entry, and whether a separator was found.
+    fn parse_separator(&mut self) -> (Option<Comment>, Vec<Comment>, bool) {
+        let mut trailing = self.take_trailing_comment();
+        let (mut comments, nl) = self.consume_newlines_and_comments_captured();
+        let mut has_sep = nl;
+
+        if matches!(self.current_token(), Token::Comma) {
+            self.advance();
+            has_sep = true;
+            if trailing.is_none() && !nl {
+                trailing = self.take_trailing_comment();
+            }
+            let (more, _) = self.consume_newlines_and_comments_captured();
+            comments.extend(more);
+        }
+        (trailing, comments, has_sep)
+    }
+
+    /// Parse an object with optional commas after newlines. `pos` is where
+    /// the object starts.
+    fn parse_object(
+        &mut self,
+        leading_comments: Vec<Comment>,
+        pos: Position,
+    ) -> Result<Value, ParseError> {
         self.expect(Token::LeftBrace, "Expected '{' to start object")?;
 
         let mut object = IndexMap::new();
@@ -191,19 +384,35 @@ impl Parser {
             // Handle empty object or end of object
             if matches!(self.current_token(), Token::RightBrace) {
                 self.advance();
-                // Note: pending_comments are trailing inside object.
-                // Currently discarding or attaching?
-                // Ideally shouldn't discard. But for now, returning object value.
-                // We could attach them? But object value is already created logic.
-                // For now, let's just return.
-                return Ok(Value::with_comments(
-                    ValueKind::Object(object),
-                    leading_comments,
-                ));
+                // Comments after the last entry belong to the object itself
+                return Ok(
+                    Value::with_comments(ValueKind::Object(object), leading_comments)
+                        .with_closing_comments(pending_comments),
+                );
             }
 
-            // Parse key (identifier or string)
+            // Parse key (identifier or string), or a `*name` entry merging
+            // an anchored object into this one
+            let key_pos = self.current_position();
             let key = match &self.current_token() {
+                Token::Reference(name) => {
+                    self.references.push(Reference {
+                        name: name.clone(),
+                        position: key_pos,
+                        target: pos,
+                        merge: true,
+                    });
+                    self.advance();
+                    let (_, comments, has_sep) = self.parse_separator();
+                    pending_comments.extend(comments);
+                    if !has_sep && !matches!(self.current_token(), Token::RightBrace) {
+                        return Err(self.error_at_current(format!(
+                            "Expected ',' or '}}' in object, found {}",
+                            self.current_token()
+                        )));
+                    }
+                    continue;
+                }
                 Token::Identifier(s) => {
                     let k = s.clone();
                     self.advance();
@@ -222,49 +431,75 @@ impl Parser {
                 }
             };
 
+            // A `+` or `!` before the colon says how the value merges
+            let directive = match self.current_token() {
+                Token::Plus => Some(MergeDirective::Append),
+                Token::Bang => Some(MergeDirective::Replace),
+                _ => None,
+            };
+            if directive.is_some() {
+                self.advance();
+            }
+
             // Parse colon
             self.expect(Token::Colon, "Expected ':' after object key")?;
 
             // Parse value
             // Pass pending_comments to the value
-            let value = self.parse_value(pending_comments)?;
+            let mut value = self.parse_value(pending_comments)?;
             // pending_comments is consumed by parse_value, so we reset it in the loop start
-
-            object.insert(key, value);
-
-            // Check for separator (comma or newline)
-            let (comments, nl) = self.consume_newlines_and_comments_captured();
+            let (trailing, comments, has_sep) = self.parse_separator();
             pending_comments = comments; // Save for next iteration or trailing
-            let mut has_sep = nl;
+            value.trailing_comment = trailing;
+            value.merge_directive = directive;
+            self.insert_entry(&mut object, key, value, key_pos)?;
 
-            if matches!(self.current_token(), Token::Comma) {
-                self.advance();
-                has_sep = true;
-                let (comments, _) = self.consume_newlines_and_comments_captured();
-                pending_comments.extend(comments);
-            }
-
-            if matches!(self.current_token(), Token::RightBrace) {
-                self.advance();
-                break;
-            }
-
-            if !has_sep {
+            if !has_sep && !matches!(self.current_token(), Token::RightBrace) {
                 return Err(self.error_at_current(format!(
                     "Expected ',' or '}}' in object, found {}",
                     self.current_token()
                 )));
             }
         }
+    }
+
+    /// Insert a parsed entry, applying the duplicate key policy
+    fn insert_entry(
+        &self,
+        object: &mut IndexMap<String, Value>,
+        key: String,
+        value: Value,
+        key_pos: Position,
+    ) -> Result<(), ParseError> {
+        let Some(existing) = object.get_mut(&key) else {
+            object.insert(key, value);
+            return Ok(());
+        };
 
-        Ok(Value::with_comments(
-            ValueKind::Object(object),
-            leading_comments,
-        ))
+        match self.options.duplicate_key {
+            DuplicateKeyPolicy::LastWins => {
+                object.insert(key, value);
+            }
+            DuplicateKeyPolicy::FirstWins => {}
+            DuplicateKeyPolicy::MergeObjects
+                if matches!(existing.kind, ValueKind::Object(_))
+                    && matches!(value.kind, ValueKind::Object(_)) =>
+            {
+                merge(existing, value);
+            }
+            DuplicateKeyPolicy::Error | DuplicateKeyPolicy::MergeObjects => {
+                return Err(ParseError {
+                    message: format!("Duplicate key '{}'", key),
+                    line: key_pos.line,
+                    column: key_pos.column,
+                });
+            }
+        }
+        Ok(())
     }
 
     /// Parse an array with optional commas after newlines
-    fn parse_array(&mut self, leading_comments: Vec<String>) -> Result<Value, ParseError> {
+    fn parse_array(&mut self, leading_comments: Vec<Comment>) -> Result<Value, ParseError> {
         self.expect(Token::LeftBracket, "Expected '[' to start array")?;
 
         let mut array = Vec::new();
@@ -277,59 +512,77 @@ impl Parser {
             // Handle empty array or end of array
             if matches!(self.current_token(), Token::RightBracket) {
                 self.advance();
-                return Ok(Value::with_comments(
-                    ValueKind::Array(array),
-                    leading_comments,
-                ));
+                return Ok(
+                    Value::with_comments(ValueKind::Array(array), leading_comments)
+                        .with_closing_comments(pending_comments),
+                );
             }
 
             // Parse value
-            let value = self.parse_value(pending_comments)?;
+            let mut value = self.parse_value(pending_comments)?;
             // pending_comments consumed
-
-            array.push(value);
-
-            // Check for separator
-            let (comments, nl) = self.consume_newlines_and_comments_captured();
+            let (trailing, comments, has_sep) = self.parse_separator();
             pending_comments = comments; // Save for next iteration
-            let mut has_sep = nl;
-
-            if matches!(self.current_token(), Token::Comma) {
-                self.advance();
-                has_sep = true;
-                let (comments, _) = self.consume_newlines_and_comments_captured();
-                pending_comments.extend(comments);
-            }
-
-            if matches!(self.current_token(), Token::RightBracket) {
-                self.advance();
-                break;
-            }
+            value.trailing_comment = trailing;
+            array.push(value);
 
-            if !has_sep {
+            if !has_sep && !matches!(self.current_token(), Token::RightBracket) {
                 return Err(self.error_at_current(format!(
                     "Expected ',' or ']' in array, found {}",
                     self.current_token()
                 )));
             }
         }
-
-        Ok(Value::with_comments(
-            ValueKind::Array(array),
-            leading_comments,
-        ))
     }
 }
 
 /// Parse COSY from a string
 pub fn from_str(input: &str) -> Result<Value, CosynError> {
-    let mut lexer = Lexer::new(input);
+    from_str_with_options(input, ParseOptions::default())
+}
+
+/// Parse COSY from a string with custom options
+pub fn from_str_with_options(input: &str, options: ParseOptions) -> Result<Value, CosynError> {
+    check_input_size(input.len() as u64, &options)?;
+    let mut lexer = Lexer::new(input)
+        .with_hash_comments(options.hash_comments)
+        .with_anchors(options.enable_anchors);
     let tokens = lexer.tokenize()?; // ? operator converts LexError to CosynError
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::with_options(tokens, options);
     let value = parser.parse()?; // ? operator converts ParseError to CosynError
     Ok(value)
 }
 
+/// Fail if `len` bytes of input exceed `options.max_input_bytes`. Callers
+/// reading a file can check its size before loading it.
+pub(crate) fn check_input_size(len: u64, options: &ParseOptions) -> Result<(), LexError> {
+    match options.max_input_bytes {
+        Some(limit) if len > limit as u64 => Err(LexError {
+            message: format!("Input is {} bytes, over the limit of {}", len, limit),
+            line: 1,
+            column: 1,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Parse COSY from a string, also counting what was parsed
+///
+/// # Example
+///
+/// ```
+/// let (_, stats) = cosy::from_str_with_stats("{ a: [1, 2], b: { c: true } }").unwrap();
+/// assert_eq!(stats.objects, 2);
+/// assert_eq!(stats.max_depth, 2);
+/// ```
+pub fn from_str_with_stats(input: &str) -> Result<(Value, ParseStats), CosynError> {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(tokens);
+    let value = parser.parse()?;
+    Ok((value, parser.stats()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,6 +614,18 @@ mod tests {
         assert_eq!(err.column, 13);
     }
 
+    #[test]
+    fn test_from_lexer_iterator() {
+        let input = "{ a: [1, 2], b: \"x\" }";
+        let mut parser = Parser::from_tokens(crate::syntax::lexer::lex(input)).unwrap();
+        assert_eq!(parser.parse().unwrap(), from_str(input).unwrap());
+
+        let err = Parser::from_tokens(crate::syntax::lexer::lex("{ a: @ }"))
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column), (1, 6));
+    }
+
     #[test]
     fn test_integration_with_lexer() {
         let input = r#"{
@@ -406,4 +671,32 @@ mod tests {
             _ => panic!("Expected object"),
         }
     }
+
+    #[test]
+    fn test_parse_stats() {
+        let input = r#"// service config
+{
+    name: "api" // display name
+    ports: [80, 443]
+    db: {
+        replicas: [{ host: "a" }, { host: "b" }]
+        pool: null
+    }
+}"#;
+        let (_, stats) = from_str_with_stats(input).unwrap();
+        assert_eq!(
+            stats,
+            ParseStats {
+                objects: 4,
+                arrays: 2,
+                scalars: 6,
+                comments: 2,
+                max_depth: 4,
+            }
+        );
+
+        let (_, stats) = from_str_with_stats("42").unwrap();
+        assert_eq!(stats.scalars, 1);
+        assert_eq!(stats.max_depth, 0);
+    }
 }
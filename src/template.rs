@@ -0,0 +1,130 @@
+//! Rendering `${...}` placeholders from a context value.
+//!
+//! Unlike environment variable interpolation, which the lexer applies while
+//! parsing, a template is any text whose `${path}` placeholders are looked up
+//! in a context [`Value`] with [`Value::pointer`]:
+//!
+//! ```
+//! use cosy::template::render;
+//!
+//! let context = cosy::from_str(r#"{ app: "cosy", server: { port: 8080 } }"#).unwrap();
+//! let text = render("${app} listens on ${server.port}", &context).unwrap();
+//! assert_eq!(text, "cosy listens on 8080");
+//! ```
+//!
+//! Strings are inserted as they are and other values in their inline COSY
+//! form. `$${` writes a literal `${`.
+
+use crate::error::CosynError;
+use crate::path::ValuePath;
+use crate::value::{Value, ValueKind};
+use std::error::Error;
+use std::fmt;
+
+/// An error rendering a template
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// A placeholder whose path is not in the context, with the closest
+    /// path that is
+    UndefinedVariable {
+        name: String,
+        suggestion: Option<String>,
+    },
+    /// A `${` without its closing `}`, at this byte offset
+    Unterminated { offset: usize },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UndefinedVariable { name, suggestion } => {
+                write!(f, "Undefined template variable '{}'", name)?;
+                if let Some(s) = suggestion {
+                    write!(f, "; did you mean '{}'?", s)?;
+                }
+                Ok(())
+            }
+            TemplateError::Unterminated { offset } => {
+                write!(f, "Unterminated '${{' at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+impl From<TemplateError> for CosynError {
+    fn from(e: TemplateError) -> Self {
+        CosynError::Template(e.to_string())
+    }
+}
+
+/// Replace every `${path}` in `template` with the value at `path` in
+/// `context`.
+pub fn render(template: &str, context: &Value) -> Result<String, TemplateError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let offset = template.len() - rest.len() + start;
+        if rest[..start].ends_with('$') {
+            // `$${` is an escaped `${`
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or(TemplateError::Unterminated { offset })?;
+        let name = after[..end].trim();
+        let value = Some(name)
+            .filter(|name| !name.is_empty())
+            .and_then(|name| context.pointer(name))
+            .ok_or_else(|| TemplateError::UndefinedVariable {
+                name: name.to_string(),
+                suggestion: closest_variable(name, context),
+            })?;
+        match &value.kind {
+            ValueKind::String(s) => out.push_str(s),
+            other => out.push_str(&other.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The existing path closest to `name`, written like a placeholder.
+fn closest_variable(name: &str, context: &Value) -> Option<String> {
+    let closest = ValuePath::parse(name)?.closest(context)?.to_string();
+    let closest = closest.strip_prefix('$').unwrap_or(&closest);
+    Some(closest.strip_prefix('.').unwrap_or(closest).to_string()).filter(|s| !s.is_empty())
+}
+
+/// Render every string in `value` with [`render`], at any depth.
+///
+/// Object keys are left as they are.
+pub fn render_value(mut value: Value, context: &Value) -> Result<Value, TemplateError> {
+    render_in_place(&mut value, context)?;
+    Ok(value)
+}
+
+fn render_in_place(value: &mut Value, context: &Value) -> Result<(), TemplateError> {
+    match &mut value.kind {
+        ValueKind::String(s) => *s = render(s, context)?,
+        ValueKind::Array(items) => {
+            for item in items {
+                render_in_place(item, context)?;
+            }
+        }
+        ValueKind::Object(map) => {
+            for item in map.values_mut() {
+                render_in_place(item, context)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
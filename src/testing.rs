@@ -0,0 +1,22 @@
+//! Helpers for asserting on COSY values in tests.
+
+use crate::diff;
+use crate::value::Value;
+
+/// Assert that two values are equal (ignoring comments).
+///
+/// On failure, panics with a rendered diff of the differing paths.
+#[track_caller]
+pub fn assert_value_eq(expected: &Value, actual: &Value) {
+    let changes = diff::compute_diff(expected, actual);
+    if !diff::diff_is_empty(&changes) {
+        let differing: Vec<_> = changes
+            .into_iter()
+            .filter(|c| !matches!(c, diff::Change::Unchanged(..)))
+            .collect();
+        panic!(
+            "values differ (- expected, + actual):\n{}",
+            diff::render_diff(&differing)
+        );
+    }
+}
@@ -1,11 +1,102 @@
+use crate::merge::MergeDirective;
+use crate::serde::serializer::{self, SerializeOptions};
+use crate::syntax::lexer::{CommentKind, Position};
 use indexmap::IndexMap;
 use std::fmt;
 
-/// COSY Value type - the core data structure representing any COSY value.
+/// A comment attached to a value, without its `//` or `///` marker.
 #[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    pub text: String,
+}
+
+impl Comment {
+    /// An ordinary `//` comment
+    pub fn line(text: impl Into<String>) -> Self {
+        Comment {
+            kind: CommentKind::Line,
+            text: text.into(),
+        }
+    }
+
+    /// A `///` doc comment
+    pub fn doc(text: impl Into<String>) -> Self {
+        Comment {
+            kind: CommentKind::Doc,
+            text: text.into(),
+        }
+    }
+
+    pub fn is_doc(&self) -> bool {
+        self.kind == CommentKind::Doc
+    }
+}
+
+impl fmt::Display for Comment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.kind.marker(), self.text)
+    }
+}
+
+impl From<String> for Comment {
+    fn from(text: String) -> Self {
+        Comment::line(text)
+    }
+}
+
+impl From<&str> for Comment {
+    fn from(text: &str) -> Self {
+        Comment::line(text)
+    }
+}
+
+/// COSY Value type - the core data structure representing any COSY value.
+///
+/// Equality compares `kind` and the comments; the source `position` is
+/// metadata and is ignored.
+#[derive(Debug, Clone)]
 pub struct Value {
     pub kind: ValueKind,
-    pub comments: Vec<String>,
+    pub comments: Vec<Comment>,
+    /// Comment on the same line after the value (`1, // one`)
+    pub trailing_comment: Option<Comment>,
+    /// Comments inside an object or array after its last entry, before the
+    /// closing bracket
+    pub closing_comments: Vec<Comment>,
+    /// How this value merges over a lower layer, from a `key!:` or `key+:`
+    /// suffix. Cleared once includes are resolved or files are loaded.
+    pub merge_directive: Option<MergeDirective>,
+    /// Where the value starts in the source text, if it was parsed
+    pub position: Option<Position>,
+}
+
+/// A child of an array or object, as yielded by [`Value::iter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueEntry<'a> {
+    /// An array element and its index
+    Index(usize, &'a Value),
+    /// An object entry
+    Key(&'a str, &'a Value),
+}
+
+/// A mutable child of an array or object, as yielded by [`Value::iter_mut`]
+#[derive(Debug, PartialEq)]
+pub enum ValueEntryMut<'a> {
+    /// An array element and its index
+    Index(usize, &'a mut Value),
+    /// An object entry
+    Key(&'a str, &'a mut Value),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.comments == other.comments
+            && self.trailing_comment == other.trailing_comment
+            && self.closing_comments == other.closing_comments
+            && self.merge_directive == other.merge_directive
+    }
 }
 
 impl Value {
@@ -13,11 +104,57 @@ impl Value {
         Value {
             kind,
             comments: Vec::new(),
+            trailing_comment: None,
+            closing_comments: Vec::new(),
+            merge_directive: None,
+            position: None,
+        }
+    }
+
+    pub fn with_comments(kind: ValueKind, comments: Vec<Comment>) -> Self {
+        Value {
+            kind,
+            comments,
+            trailing_comment: None,
+            closing_comments: Vec::new(),
+            merge_directive: None,
+            position: None,
         }
     }
 
-    pub fn with_comments(kind: ValueKind, comments: Vec<String>) -> Self {
-        Value { kind, comments }
+    /// Attach a comment to be printed after the value on the same line.
+    pub fn with_trailing_comment(mut self, comment: impl Into<Comment>) -> Self {
+        self.trailing_comment = Some(comment.into());
+        self
+    }
+
+    /// Attach comments to be printed before the closing bracket of an
+    /// object or array.
+    pub fn with_closing_comments(mut self, comments: Vec<Comment>) -> Self {
+        self.closing_comments = comments;
+        self
+    }
+
+    /// Attach a source position to this value.
+    pub fn at(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Text of the `///` doc comments attached to this value
+    pub fn doc_comments(&self) -> impl Iterator<Item = &str> {
+        self.comments
+            .iter()
+            .filter(|c| c.is_doc())
+            .map(|c| c.text.as_str())
+    }
+
+    /// Text of the ordinary `//` comments attached to this value
+    pub fn line_comments(&self) -> impl Iterator<Item = &str> {
+        self.comments
+            .iter()
+            .filter(|c| !c.is_doc())
+            .map(|c| c.text.as_str())
     }
 
     /// Get the string representation of the value's type
@@ -25,6 +162,148 @@ impl Value {
         self.kind.type_name()
     }
 
+    /// Number of entries in an array or object, or of characters (not bytes)
+    /// in a string. `None` for other scalars.
+    pub fn len(&self) -> Option<usize> {
+        match &self.kind {
+            ValueKind::Array(arr) => Some(arr.len()),
+            ValueKind::Object(map) => Some(map.len()),
+            ValueKind::String(s) => Some(s.chars().count()),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an empty array, object or string. Always `false` for
+    /// other scalars.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Display as COSY text with the default [`SerializeOptions`].
+    pub fn display(&self) -> PrettyDisplay<'_> {
+        PrettyDisplay(self, &DEFAULT_SERIALIZE_OPTIONS)
+    }
+
+    /// Display as COSY text with custom options.
+    pub fn display_with<'a>(&'a self, options: &'a SerializeOptions) -> PrettyDisplay<'a> {
+        PrettyDisplay(self, options)
+    }
+
+    /// The value at a validation path such as `$.server.ports[0]` or
+    /// `$["weird.key"]`; see [`ValuePath`](crate::path::ValuePath).
+    ///
+    /// `None` if the path is malformed or leads nowhere.
+    pub fn pointer(&self, path: &str) -> Option<&Value> {
+        crate::path::ValuePath::parse(path)?.get(self)
+    }
+
+    /// A copy of this value with `override_val` merged on top; see
+    /// [`merge`](crate::merge::merge).
+    pub fn merged(&self, override_val: &Value) -> Value {
+        let mut merged = self.clone();
+        crate::merge::merge(&mut merged, override_val.clone());
+        merged
+    }
+
+    /// Flatten a tree into its leaves, keyed by path.
+    ///
+    /// Object keys and array indices are joined with `separator`, so
+    /// `{ db: { hosts: ["a"] } }` becomes `db.hosts.0 = "a"` for `"."`.
+    /// Empty arrays and objects are kept as leaves. A scalar root is keyed by
+    /// the empty string.
+    pub fn flatten(&self, separator: &str) -> IndexMap<String, Value> {
+        let mut out = IndexMap::new();
+        self.flatten_into(String::new(), separator, &mut out);
+        out
+    }
+
+    fn flatten_into(&self, prefix: String, separator: &str, out: &mut IndexMap<String, Value>) {
+        let join = |key: &str| {
+            if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}{}{}", prefix, separator, key)
+            }
+        };
+        match &self.kind {
+            ValueKind::Array(arr) if !arr.is_empty() => {
+                for (i, item) in arr.iter().enumerate() {
+                    item.flatten_into(join(&i.to_string()), separator, out);
+                }
+            }
+            ValueKind::Object(map) if !map.is_empty() => {
+                for (key, item) in map {
+                    item.flatten_into(join(key), separator, out);
+                }
+            }
+            _ => {
+                out.insert(prefix, self.clone());
+            }
+        }
+    }
+
+    /// Call `f` on every node of the tree, depth-first and post-order.
+    ///
+    /// Children are visited before their parent, so `f` sees a container
+    /// only after all of its elements have been transformed.
+    pub fn apply_transform<F: FnMut(&mut Value)>(&mut self, f: &mut F) {
+        match &mut self.kind {
+            ValueKind::Array(arr) => {
+                for item in arr {
+                    item.apply_transform(f);
+                }
+            }
+            ValueKind::Object(map) => {
+                for item in map.values_mut() {
+                    item.apply_transform(f);
+                }
+            }
+            _ => {}
+        }
+        f(self);
+    }
+
+    /// The elements of an array or entries of an object, in order. Scalars
+    /// have none.
+    pub fn iter(&self) -> impl Iterator<Item = ValueEntry<'_>> {
+        let (items, entries) = match &self.kind {
+            ValueKind::Array(arr) => (Some(arr.iter()), None),
+            ValueKind::Object(map) => (None, Some(map.iter())),
+            _ => (None, None),
+        };
+        let items = items.into_iter().flatten().enumerate();
+        let entries = entries.into_iter().flatten();
+        items
+            .map(|(i, v)| ValueEntry::Index(i, v))
+            .chain(entries.map(|(k, v)| ValueEntry::Key(k.as_str(), v)))
+    }
+
+    /// Like [`iter`](Self::iter), with mutable access to the children.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ValueEntryMut<'_>> {
+        let (items, entries) = match &mut self.kind {
+            ValueKind::Array(arr) => (Some(arr.iter_mut()), None),
+            ValueKind::Object(map) => (None, Some(map.iter_mut())),
+            _ => (None, None),
+        };
+        let items = items.into_iter().flatten().enumerate();
+        let entries = entries.into_iter().flatten();
+        items
+            .map(|(i, v)| ValueEntryMut::Index(i, v))
+            .chain(entries.map(|(k, v)| ValueEntryMut::Key(k.as_str(), v)))
+    }
+
+    /// Sort the keys of every object in the tree, in place.
+    ///
+    /// Arrays keep their order, though objects inside them are sorted too.
+    /// Comments stay attached to their values.
+    pub fn sort_keys(&mut self) {
+        self.apply_transform(&mut |value| {
+            if let ValueKind::Object(map) = &mut value.kind {
+                map.sort_keys();
+            }
+        });
+    }
+
     // Helper constructors
     pub fn null() -> Self {
         Self::new(ValueKind::Null)
@@ -38,8 +317,8 @@ impl Value {
     pub fn float(f: f64) -> Self {
         Self::new(ValueKind::Float(f))
     }
-    pub fn string(s: String) -> Self {
-        Self::new(ValueKind::String(s))
+    pub fn string(s: impl Into<String>) -> Self {
+        Self::new(ValueKind::String(s.into()))
     }
     pub fn array(arr: Vec<Value>) -> Self {
         Self::new(ValueKind::Array(arr))
@@ -49,6 +328,107 @@ impl Value {
     }
 }
 
+/// Return a new tree with `f` applied to every string value.
+///
+/// Object keys are left untouched.
+///
+/// ```
+/// use cosy::value::map_strings;
+///
+/// let config = cosy::from_str(r#"{ name: "cosy", tags: ["a", "b"] }"#).unwrap();
+/// let upper = map_strings(config, str::to_uppercase);
+/// assert_eq!(upper, cosy::from_str(r#"{ name: "COSY", tags: ["A", "B"] }"#).unwrap());
+/// ```
+pub fn map_strings<F: Fn(&str) -> String>(mut value: Value, f: F) -> Value {
+    value.apply_transform(&mut |node: &mut Value| {
+        if let ValueKind::String(s) = &mut node.kind {
+            *s = f(s);
+        }
+    });
+    value
+}
+
+/// Return a new tree with `f` applied to every scalar, i.e. every value that
+/// is not an array or object.
+///
+/// ```
+/// use cosy::value::{Value, ValueKind, map};
+///
+/// let config = cosy::from_str("{ port: 80, hosts: [1, 2] }").unwrap();
+/// let doubled = map(config, |v| match v.kind {
+///     ValueKind::Integer(i) => Value::integer(i * 2),
+///     _ => v,
+/// });
+/// assert_eq!(doubled, cosy::from_str("{ port: 160, hosts: [2, 4] }").unwrap());
+/// ```
+pub fn map<F: Fn(Value) -> Value>(value: Value, f: F) -> Value {
+    filter_map(value, |v| Some(f(v)))
+}
+
+/// Like [`map`], except that `f` returning `None` removes the scalar: the
+/// element from its array, or the key from its object. A scalar root that is
+/// removed becomes `null`.
+///
+/// ```
+/// use cosy::value::{ValueKind, filter_map};
+///
+/// let config = cosy::from_str("{ a: 1, b: null, list: [null, 2] }").unwrap();
+/// let present = filter_map(config, |v| (v.kind != ValueKind::Null).then_some(v));
+/// assert_eq!(present, cosy::from_str("{ a: 1, list: [2] }").unwrap());
+/// ```
+pub fn filter_map<F: Fn(Value) -> Option<Value>>(value: Value, f: F) -> Value {
+    filter_map_node(value, &f).unwrap_or_else(Value::null)
+}
+
+fn filter_map_node<F: Fn(Value) -> Option<Value>>(mut value: Value, f: &F) -> Option<Value> {
+    match std::mem::replace(&mut value.kind, ValueKind::Null) {
+        ValueKind::Array(items) => {
+            let items = items
+                .into_iter()
+                .filter_map(|item| filter_map_node(item, f))
+                .collect();
+            value.kind = ValueKind::Array(items);
+            Some(value)
+        }
+        ValueKind::Object(map) => {
+            let map = map
+                .into_iter()
+                .filter_map(|(key, item)| Some((key, filter_map_node(item, f)?)))
+                .collect();
+            value.kind = ValueKind::Object(map);
+            Some(value)
+        }
+        scalar => {
+            value.kind = scalar;
+            f(value)
+        }
+    }
+}
+
+/// Return a new tree with `f` applied to every object key, at any depth.
+///
+/// Keys that map to the same string collapse into one entry, the last
+/// value winning, at the position of the first.
+///
+/// ```
+/// use cosy::value::map_keys;
+///
+/// let config = cosy::from_str("{ server: { port: 80 } }").unwrap();
+/// let upper = map_keys(config, str::to_uppercase);
+/// assert_eq!(upper, cosy::from_str("{ SERVER: { PORT: 80 } }").unwrap());
+/// ```
+pub fn map_keys<F: Fn(&str) -> String>(mut value: Value, f: F) -> Value {
+    value.apply_transform(&mut |node: &mut Value| {
+        if let ValueKind::Object(map) = &mut node.kind {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, item)| (f(&key), item))
+                .collect();
+        }
+    });
+    value
+}
+
 /// The actual data variant of a COSY value
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValueKind {
@@ -82,13 +462,84 @@ impl ValueKind {
     }
 }
 
+/// Formats a value as COSY text, comments included.
+///
+/// `format!("{}", value)` is the same as `cosy::to_string(&value)`. Use
+/// [`Value::display_with`] for other layouts, or format `value.kind` for a
+/// compact single line without comments.
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // For now, simple display doesn't show comments to keep debug output clean
-        write!(f, "{}", self.kind)
+        f.write_str(&serializer::to_string(self))
+    }
+}
+
+/// A value paired with serialization options, returned by [`Value::display`]
+/// and [`Value::display_with`].
+pub struct PrettyDisplay<'a>(&'a Value, &'a SerializeOptions);
+
+impl fmt::Display for PrettyDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&serializer::to_string_with_options(self.0, self.1.clone()))
     }
 }
 
+static DEFAULT_SERIALIZE_OPTIONS: std::sync::LazyLock<SerializeOptions> =
+    std::sync::LazyLock::new(SerializeOptions::default);
+
+/// Formats a value as compact JSON with `{:x}`, without comments.
+///
+/// Non-finite floats, which JSON can't represent, are written as `null`.
+impl fmt::LowerHex for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ValueKind::Null => f.write_str("null"),
+            ValueKind::Bool(b) => write!(f, "{}", b),
+            ValueKind::Integer(i) => write!(f, "{}", i),
+            ValueKind::Float(fl) if !fl.is_finite() => f.write_str("null"),
+            ValueKind::Float(fl) if fl.fract() == 0.0 && fl.abs() < 1e16 => write!(f, "{:.1}", fl),
+            ValueKind::Float(fl) => write!(f, "{}", fl),
+            ValueKind::String(s) => write_json_string(f, s),
+            ValueKind::Array(arr) => {
+                f.write_str("[")?;
+                for (i, v) in arr.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{:x}", v)?;
+                }
+                f.write_str("]")
+            }
+            ValueKind::Object(obj) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in obj.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_json_string(f, k)?;
+                    write!(f, ":{:x}", v)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
 impl fmt::Display for ValueKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -103,7 +554,7 @@ impl fmt::Display for ValueKind {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{}", v)?;
+                    write!(f, "{}", v.kind)?;
                 }
                 write!(f, "]")
             }
@@ -113,7 +564,7 @@ impl fmt::Display for ValueKind {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{}: {}", k, v)?;
+                    write!(f, "{}: {}", k, v.kind)?;
                 }
                 write!(f, "}}")
             }
@@ -150,6 +601,20 @@ impl From<String> for Value {
 }
 impl From<&str> for Value {
     fn from(v: &str) -> Self {
-        Self::string(v.to_string())
+        Self::string(v)
+    }
+}
+
+/// Build an object from `(key, value)` pairs, keeping their order.
+///
+/// ```
+/// use cosy::Value;
+///
+/// let server = Value::from_iter([("host", Value::from("localhost")), ("port", Value::from(8080))]);
+/// assert_eq!(server, cosy::from_str("{ host: \"localhost\", port: 8080 }").unwrap());
+/// ```
+impl<K: Into<String>> FromIterator<(K, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        Self::object(iter.into_iter().map(|(k, v)| (k.into(), v)).collect())
     }
 }
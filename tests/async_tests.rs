@@ -0,0 +1,204 @@
+#![cfg(feature = "async")]
+
+use cosy::from_str;
+use cosy::include::{self, IncludeError};
+use cosy::load::{load_and_merge_async, load_and_merge_resolved};
+use cosy::value::{Value, ValueKind};
+use std::fs;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_basic_inclusion() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("base.cosy"), "{ debug: false, port: 8000 }").unwrap();
+
+    let mut config = from_str(r#"{ include: "base.cosy", debug: true }"#).unwrap();
+    include::resolve_async(&mut config, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(config, from_str("{ debug: true, port: 8000 }").unwrap());
+}
+
+#[tokio::test]
+async fn test_nested_inclusion_and_sub_objects() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("base.cosy"), "{ a: 1 }").unwrap();
+    fs::write(
+        dir.path().join("mid.cosy"),
+        r#"{ include: "base.cosy", b: 2 }"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("db.cosy"), r#"{ host: "db" }"#).unwrap();
+
+    let mut config =
+        from_str(r#"{ include: "mid.cosy", c: 3, db: { include: "db.cosy", port: 5432 } }"#)
+            .unwrap();
+    include::resolve_async(&mut config, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ a: 1, b: 2, c: 3, db: { host: "db", port: 5432 } }"#).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_precedence_matches_sync_resolve() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("one.cosy"), "{ A: 1, B: 1, C: 1, D: 1, E: 1 }").unwrap();
+    fs::write(root.join("two.cosy"), "{ B: 2, C: 2, D: 2, E: 2 }").unwrap();
+    fs::write(root.join("mixin.cosy"), "{ C: 3, D: 3, E: 3 }").unwrap();
+    fs::write(root.join("later.cosy"), "{ D: 4, E: 4 }").unwrap();
+
+    let input = r#"{
+        extends: ["one.cosy", "two.cosy"]
+        include: ["mixin.cosy", "later.cosy"]
+        E: 5
+    }"#;
+    let mut sync = from_str(input).unwrap();
+    include::resolve(&mut sync, root).unwrap();
+    let mut config = from_str(input).unwrap();
+    include::resolve_async(&mut config, root).await.unwrap();
+
+    assert_eq!(config, sync);
+    assert_eq!(
+        config,
+        from_str("{ A: 1, B: 2, C: 3, D: 4, E: 5 }").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_glob_json_and_into() {
+    let dir = tempdir().unwrap();
+    let conf_d = dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(conf_d.join("10-a.cosy"), "{ a: 1, shared: \"a\" }").unwrap();
+    fs::write(conf_d.join("20-b.cosy"), "{ b: 2, shared: \"b\" }").unwrap();
+    fs::write(conf_d.join("notes.txt"), "not config").unwrap();
+    fs::write(dir.path().join("limits.json"), r#"{ "rps": 10 }"#).unwrap();
+    fs::write(dir.path().join("hosts.cosy"), r#"["a", "b"]"#).unwrap();
+
+    let mut config = from_str(
+        r#"{
+        include: [
+            "conf.d/*.cosy"
+            "limits.json"
+            { path: "hosts.cosy", into: "db.hosts" }
+        ]
+    }"#,
+    )
+    .unwrap();
+    include::resolve_async(&mut config, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ a: 1, shared: "b", b: 2, rps: 10, db: { hosts: ["a", "b"] } }"#).unwrap()
+    );
+
+    // A directory with no matches includes nothing
+    let mut config = from_str(r#"{ include: "missing/*.cosy", x: 1 }"#).unwrap();
+    include::resolve_async(&mut config, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(config, from_str("{ x: 1 }").unwrap());
+}
+
+/// Resolve `input` both ways, expecting the same error from each
+async fn resolve_error(input: &str, root: &std::path::Path) -> IncludeError {
+    let mut config = from_str(input).unwrap();
+    let err = include::resolve_async(&mut config, root).await.unwrap_err();
+
+    let mut config = from_str(input).unwrap();
+    let sync_err = include::resolve(&mut config, root).unwrap_err();
+    assert_eq!(err.to_string(), sync_err.to_string());
+    err
+}
+
+#[tokio::test]
+async fn test_errors_match_sync_resolve() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("a.cosy"), r#"{ include: "b.cosy" }"#).unwrap();
+    fs::write(root.join("b.cosy"), r#"{ include: "a.cosy" }"#).unwrap();
+    fs::write(root.join("list.cosy"), "[1, 2]").unwrap();
+    fs::write(root.join("app.cosy"), r#"{ include: "broken.cosy" }"#).unwrap();
+    fs::write(root.join("broken.cosy"), "{ port: }").unwrap();
+
+    let err = resolve_error(r#"{ include: "a.cosy" }"#, root).await;
+    assert!(matches!(err, IncludeError::CycleDetected { .. }));
+
+    let err = resolve_error(r#"{ include: "list.cosy" }"#, root).await;
+    assert!(matches!(err, IncludeError::IncludedNotObject { .. }));
+
+    let err = resolve_error(r#"{ include: "app.cosy" }"#, root).await;
+    assert!(matches!(err, IncludeError::ParseError { chain, .. } if chain.len() == 1));
+
+    let err = resolve_error(r#"{ include: "nope.cosy" }"#, root).await;
+    assert!(matches!(err, IncludeError::IoError(_)));
+}
+
+#[tokio::test]
+async fn test_load_and_merge_async() {
+    let dir = tempdir().unwrap();
+    let shared = dir.path().join("shared");
+    fs::create_dir(&shared).unwrap();
+    fs::write(shared.join("defaults.cosy"), "{ timeout: 30, port: 80 }").unwrap();
+    fs::write(
+        shared.join("base.cosy"),
+        r#"{ include: "defaults.cosy", name: "api" }"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("local.cosy"), "{ port: 8080 }").unwrap();
+
+    let paths = [shared.join("base.cosy"), dir.path().join("local.cosy")];
+    let config = load_and_merge_async(&paths).await.unwrap();
+    assert_eq!(config, load_and_merge_resolved(&paths).unwrap());
+    if let ValueKind::Object(map) = &config.kind {
+        assert_eq!(map.get("timeout"), Some(&Value::integer(30)));
+        assert_eq!(map.get("port"), Some(&Value::integer(8080)));
+    } else {
+        panic!("Expected object");
+    }
+
+    // The future can be moved to another task
+    let missing = dir.path().join("missing.cosy");
+    let handle = tokio::spawn(async move { load_and_merge_async(&[missing]).await });
+    assert!(matches!(
+        handle.await.unwrap(),
+        Err(cosy::CosynError::Io(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_long_include_chain() {
+    // Each file includes the next one, in a subdirectory of its own
+    let dir = tempdir().unwrap();
+    let mut current = dir.path().to_path_buf();
+    for i in 0..40 {
+        let next = current.join("next");
+        fs::create_dir(&next).unwrap();
+        fs::write(
+            current.join("layer.cosy"),
+            format!(
+                r#"{{ include: "next/layer.cosy", depth: {}, l{}: true }}"#,
+                i, i
+            ),
+        )
+        .unwrap();
+        current = next;
+    }
+    fs::write(current.join("layer.cosy"), "{ depth: 40, last: true }").unwrap();
+
+    let input = r#"{ include: "layer.cosy" }"#;
+    let mut sync = from_str(input).unwrap();
+    include::resolve(&mut sync, dir.path()).unwrap();
+    let mut config = from_str(input).unwrap();
+    include::resolve_async(&mut config, dir.path())
+        .await
+        .unwrap();
+    assert_eq!(config, sync);
+    assert_eq!(config.pointer("depth"), Some(&Value::integer(0)));
+    assert_eq!(config.pointer("last"), Some(&Value::from(true)));
+}
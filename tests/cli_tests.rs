@@ -0,0 +1,135 @@
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn cosy(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cosy"))
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.success(), stdout)
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_check_directory() {
+    let dir = tempdir().unwrap();
+    let nested = dir.path().join("services");
+    fs::create_dir(&nested).unwrap();
+    fs::write(dir.path().join("app.cosy"), "{ port: 8080 }").unwrap();
+    fs::write(nested.join("broken.cosy"), "{ port: }").unwrap();
+    fs::write(nested.join("notes.txt"), "{ not cosy").unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    // Without --recursive only the top level is checked
+    let (ok, stdout) = cosy(&["check", root]);
+    assert!(ok, "{}", stdout);
+    assert!(stdout.contains("Checked 1 files, 0 errors"), "{}", stdout);
+
+    let (ok, stdout) = cosy(&["check", "--recursive", root]);
+    assert!(!ok);
+    assert!(stdout.contains("Checked 2 files, 1 errors"), "{}", stdout);
+
+    let (ok, stdout) = cosy(&["check", root, "-r", "--exclude", "services/*"]);
+    assert!(ok, "{}", stdout);
+    assert!(stdout.contains("Checked 1 files, 0 errors"), "{}", stdout);
+}
+
+#[test]
+fn test_init_templates_parse() {
+    let dir = tempdir().unwrap();
+    for template in ["basic", "server", "database", "all"] {
+        let output = dir.path().join(format!("{}.cosy", template));
+        let output = output.to_str().unwrap();
+        let (ok, stdout) = cosy(&["init", "--template", template, "--output", output]);
+        assert!(ok, "{}", stdout);
+
+        let content = fs::read_to_string(output).unwrap();
+        assert!(content.contains("//"), "{} has no comments", template);
+        cosy::from_str(&content).unwrap();
+        let (ok, stdout) = cosy(&["check", output]);
+        assert!(ok, "{}", stdout);
+    }
+}
+
+#[test]
+fn test_init_defaults_and_overwrite() {
+    let dir = tempdir().unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cosy"))
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+            .status
+            .success()
+    };
+
+    // Basic template to ./config.cosy
+    assert!(run(&["init"]));
+    let path = dir.path().join("config.cosy");
+    let basic = fs::read_to_string(&path).unwrap();
+    assert!(basic.contains("logging"));
+
+    // Existing files are kept unless forced
+    fs::write(&path, "{ mine: true }").unwrap();
+    assert!(!run(&["init"]));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{ mine: true }");
+    assert!(run(&["init", "--force"]));
+    assert_eq!(fs::read_to_string(&path).unwrap(), basic);
+
+    assert!(!run(&["init", "--template", "nope", "--output", "x.cosy"]));
+    assert!(!dir.path().join("x.cosy").exists());
+}
+
+#[test]
+fn test_describe_schema() {
+    let dir = tempdir().unwrap();
+    let schema = dir.path().join("schema.cosy");
+    fs::write(
+        &schema,
+        r#"{ name: { type: "string", description: "Service name" } }"#,
+    )
+    .unwrap();
+    let schema = schema.to_str().unwrap();
+
+    let (ok, stdout) = cosy(&["describe", "--schema", schema, "--format", "markdown"]);
+    assert!(ok, "{}", stdout);
+    assert_eq!(
+        stdout,
+        "## Configuration\n\n- **name**: string — Service name\n"
+    );
+
+    let (ok, _) = cosy(&["describe", "--schema", schema, "--format", "pdf"]);
+    assert!(!ok);
+}
+
+#[test]
+fn test_validate_reports_source_positions() {
+    let dir = tempdir().unwrap();
+    let config = dir.path().join("config.cosy");
+    let schema = dir.path().join("schema.cosy");
+    fs::write(
+        &config,
+        "{\n    server: {\n        host: \"localhost\"\n        port: \"eighty\"\n    }\n}",
+    )
+    .unwrap();
+    fs::write(
+        &schema,
+        r#"{ server: { host: "string", port: "integer" } }"#,
+    )
+    .unwrap();
+
+    let (ok, stdout) = cosy(&[
+        "validate",
+        config.to_str().unwrap(),
+        schema.to_str().unwrap(),
+    ]);
+    assert!(!ok);
+    let expected = format!(
+        "{}:4:15: error: Type mismatch: expected integer, found string",
+        config.display()
+    );
+    assert!(stdout.contains(&expected), "{}", stdout);
+}
@@ -1,5 +1,8 @@
 use cosy::value::ValueKind;
-use cosy::{from_str, to_string};
+use cosy::{
+    CommentMarker, ParseOptions, SerializeOptions, from_str, from_str_with_options, to_string,
+    to_string_with_options,
+};
 
 #[test]
 fn test_roundtrip_comments_simple() {
@@ -32,8 +35,8 @@ fn test_roundtrip_comments_simple() {
         // So we expect " comment 1" (with space? Lexer `lex_comment` usually captures remainder of line).
         // Let's assume it captures " comment 1".
 
-        assert!(val_a.comments.iter().any(|c| c.contains("comment 1")));
-        assert!(val_b.comments.iter().any(|c| c.contains("comment 2")));
+        assert!(val_a.comments.iter().any(|c| c.text.contains("comment 1")));
+        assert!(val_b.comments.iter().any(|c| c.text.contains("comment 2")));
     } else {
         panic!("Expected object");
     }
@@ -86,7 +89,7 @@ fn test_comment_placement_in_object() {
 
     // Construct the "background" value with a comment
     let mut background_val = Value::from("oklch(0.985 0.005 240)");
-    background_val.comments = vec!["Hey!".to_string()];
+    background_val.comments = vec!["Hey!".into()];
 
     obj.insert("background".to_string(), background_val);
 
@@ -104,3 +107,172 @@ fn test_comment_placement_in_object() {
         "Comment should appear before the key"
     );
 }
+
+#[test]
+fn test_doc_comments_separate_from_line_comments() {
+    use cosy::value::Comment;
+
+    let input = r#"{
+        /// The port to listen on.
+        // TODO: change before release
+        /// Defaults to 8080.
+        port: 8080
+        //// not a doc comment
+        host: "localhost"
+    }"#;
+
+    let parsed = from_str(input).unwrap();
+    let ValueKind::Object(obj) = &parsed.kind else {
+        panic!("Expected object");
+    };
+
+    let port = &obj["port"];
+    let docs: Vec<&str> = port.doc_comments().collect();
+    let lines: Vec<&str> = port.line_comments().collect();
+    assert_eq!(docs, vec!["The port to listen on.", "Defaults to 8080."]);
+    assert_eq!(lines, vec!["TODO: change before release"]);
+    assert_eq!(port.comments[0], Comment::doc("The port to listen on."));
+
+    let host = &obj["host"];
+    assert_eq!(host.doc_comments().count(), 0);
+    assert_eq!(host.comments[0], Comment::line("// not a doc comment"));
+
+    // Kinds survive a round trip
+    let serialized = to_string(&parsed);
+    assert!(serialized.contains("/// The port to listen on."));
+    assert!(serialized.contains("// TODO: change before release"));
+    assert_eq!(from_str(&serialized).unwrap(), parsed);
+}
+
+#[test]
+fn test_roundtrip_array_trailing_comments() {
+    use cosy::value::Value;
+
+    let input = r#"[
+        1, // one
+        // leading
+        2, // two
+        3 // three
+    ]"#;
+
+    let parsed = from_str(input).unwrap();
+    let ValueKind::Array(items) = &parsed.kind else {
+        panic!("Expected array");
+    };
+    assert_eq!(items[0], Value::integer(1).with_trailing_comment("one"));
+    assert_eq!(
+        items[1].line_comments().collect::<Vec<_>>(),
+        vec!["leading"]
+    );
+    assert_eq!(items[2].trailing_comment, Some("three".into()));
+
+    let serialized = to_string(&parsed);
+    assert_eq!(
+        serialized,
+        "[\n    1, // one\n    // leading\n    2, // two\n    3 // three\n]"
+    );
+    assert_eq!(from_str(&serialized).unwrap(), parsed);
+}
+
+#[test]
+fn test_trailing_comment_in_object_and_single_item_array() {
+    let input = r#"{
+        port: 8080, // default
+        hosts: [
+            "a" // only host
+        ]
+    }"#;
+
+    let parsed = from_str(input).unwrap();
+    let serialized = to_string(&parsed);
+    assert!(serialized.contains("port: 8080, // default"));
+    assert!(serialized.contains("\"a\" // only host"));
+    assert_eq!(from_str(&serialized).unwrap(), parsed);
+}
+
+#[test]
+fn test_roundtrip_comments_before_closing_bracket() {
+    let input = r#"{
+        port: 8080
+        tags: [
+            "a"
+            // more tags later
+        ]
+        empty: {
+            // nothing yet
+        }
+        // keep this last
+    }"#;
+
+    let parsed = from_str(input).unwrap();
+    assert_eq!(parsed.closing_comments, vec!["keep this last".into()]);
+    let ValueKind::Object(obj) = &parsed.kind else {
+        panic!("Expected object");
+    };
+    assert_eq!(obj["tags"].closing_comments, vec!["more tags later".into()]);
+    assert_eq!(obj["empty"].closing_comments, vec!["nothing yet".into()]);
+
+    let serialized = to_string(&parsed);
+    assert_eq!(
+        serialized,
+        "{\n    port: 8080,\n    tags: [\n        \"a\"\n        // more tags later\n    ],\n    empty: {\n        // nothing yet\n    }\n    // keep this last\n}"
+    );
+    assert_eq!(from_str(&serialized).unwrap(), parsed);
+
+    // Compact output hoists them like any other comment
+    let compact = to_string_with_options(
+        &parsed,
+        SerializeOptions {
+            use_newlines: false,
+            ..Default::default()
+        },
+    );
+    assert!(compact.starts_with("// more tags later\n// nothing yet\n// keep this last\n"));
+}
+
+#[test]
+fn test_hash_comment_marker_roundtrip() {
+    let input = r#"{
+        // server config
+        port: 8080, // default
+        hosts: ["a", "b"]
+    }"#;
+    let parsed = from_str(input).unwrap();
+
+    let options = SerializeOptions {
+        comment_marker: CommentMarker::Hash,
+        ..Default::default()
+    };
+    let serialized = to_string_with_options(&parsed, options);
+    assert!(serialized.contains("    # server config\n"));
+    assert!(
+        serialized.contains("port: 8080, # default"),
+        "{}",
+        serialized
+    );
+    assert!(!serialized.contains("//"));
+
+    // `#` is only a comment when the lexer is told so
+    assert!(from_str(&serialized).is_err());
+    let hash_comments = ParseOptions {
+        hash_comments: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        from_str_with_options(&serialized, hash_comments).unwrap(),
+        parsed
+    );
+}
+
+#[test]
+fn test_comment_marker_without_space() {
+    let parsed = from_str("{\n    /// docs\n    a: 1 // note\n}").unwrap();
+    let options = SerializeOptions {
+        space_after_comment_marker: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        to_string_with_options(&parsed, options),
+        "{\n    ///docs\n    a: 1 //note\n}"
+    );
+}
@@ -0,0 +1,98 @@
+use cosy::diff::{Change, compute_diff, diff_is_empty, render_diff, render_diff_colored};
+use cosy::{Value, from_str};
+
+fn sample_pair() -> (Value, Value) {
+    let before = from_str(
+        r#"{
+        name: "app"
+        server: { host: "localhost", port: 8080 }
+        tags: ["a", "b", "c"]
+        legacy: true
+    }"#,
+    )
+    .unwrap();
+    let after = from_str(
+        r#"{
+        name: "app"
+        server: { host: "0.0.0.0", port: 8080 }
+        tags: ["a", "b"]
+        debug: false
+    }"#,
+    )
+    .unwrap();
+    (before, after)
+}
+
+#[test]
+fn test_compute_diff_all_kinds() {
+    let (before, after) = sample_pair();
+    let changes = compute_diff(&before, &after);
+
+    assert!(changes.contains(&Change::Unchanged("name".to_string(), Value::from("app"))));
+    assert!(changes.contains(&Change::Modified(
+        "server.host".to_string(),
+        Value::from("localhost"),
+        Value::from("0.0.0.0"),
+    )));
+    assert!(changes.contains(&Change::Unchanged(
+        "server.port".to_string(),
+        Value::integer(8080)
+    )));
+    assert!(changes.contains(&Change::Removed("tags[2]".to_string(), Value::from("c"))));
+    assert!(changes.contains(&Change::Removed("legacy".to_string(), Value::boolean(true))));
+    assert!(changes.contains(&Change::Added("debug".to_string(), Value::boolean(false))));
+    assert_eq!(changes.len(), 8);
+    assert!(!diff_is_empty(&changes));
+}
+
+#[test]
+fn test_diff_type_change_is_modification() {
+    let before = from_str(r#"{ port: "8080" }"#).unwrap();
+    let after = from_str(r#"{ port: 8080 }"#).unwrap();
+    let changes = compute_diff(&before, &after);
+
+    assert_eq!(
+        changes,
+        vec![Change::Modified(
+            "port".to_string(),
+            Value::from("8080"),
+            Value::integer(8080)
+        )]
+    );
+}
+
+#[test]
+fn test_diff_identical_ignores_comments() {
+    let before = from_str("{\n // note\n a: 1 }").unwrap();
+    let after = from_str("{ a: 1 }").unwrap();
+    assert!(diff_is_empty(&compute_diff(&before, &after)));
+}
+
+#[test]
+fn test_render_diff() {
+    let (before, after) = sample_pair();
+    let rendered = render_diff(&compute_diff(&before, &after));
+
+    assert!(rendered.contains("  name: \"app\"\n"));
+    assert!(rendered.contains("- server.host: \"localhost\"\n+ server.host: \"0.0.0.0\"\n"));
+    assert!(rendered.contains("- tags[2]: \"c\"\n"));
+    assert!(rendered.contains("+ debug: false\n"));
+}
+
+#[test]
+fn test_render_diff_colored() {
+    let (before, after) = sample_pair();
+    let rendered = render_diff_colored(&compute_diff(&before, &after));
+
+    assert!(rendered.contains("\x1b[32m+ debug: false\x1b[0m"));
+    assert!(rendered.contains("\x1b[31m- legacy: true\x1b[0m"));
+    assert!(rendered.contains("  name: \"app\"\n"));
+}
+
+#[test]
+#[should_panic(expected = "+ b: 3")]
+fn test_assert_value_eq_panics_with_diff() {
+    let expected = from_str("{ a: 1, b: 2 }").unwrap();
+    let actual = from_str("{ a: 1, b: 3 }").unwrap();
+    cosy::testing::assert_value_eq(&expected, &actual);
+}
@@ -0,0 +1,97 @@
+use cosy::convert::{ConvertError, EnvBlockOptions, from_env_block, to_env_block};
+use cosy::from_str;
+
+const CONFIG: &str = r#"{
+    server: { host: "0.0.0.0", port: 8080 }
+    db: { hosts: ["alpha", "beta"], password: "p@ss \"word\" $HOME" }
+    debug: false
+}"#;
+
+#[test]
+fn test_env_block_output() {
+    let config = from_str(CONFIG).unwrap();
+    let block = to_env_block(&config, "APP", &EnvBlockOptions::default()).unwrap();
+    assert_eq!(
+        block,
+        r#"export APP_SERVER_HOST="0.0.0.0"
+export APP_SERVER_PORT="8080"
+export APP_DB_HOSTS_0="alpha"
+export APP_DB_HOSTS_1="beta"
+export APP_DB_PASSWORD="p@ss \"word\" \$HOME"
+export APP_DEBUG="false"
+"#
+    );
+}
+
+#[test]
+fn test_env_block_round_trip() {
+    let config = from_str(CONFIG).unwrap();
+    for quote in [true, false] {
+        let options = EnvBlockOptions {
+            quote,
+            ..Default::default()
+        };
+        let block = to_env_block(&config, "APP", &options).unwrap();
+        let parsed = from_env_block(&block).unwrap();
+
+        // Values come back as strings, nested under the prefix
+        let expected = from_str(
+            r#"{
+            app: {
+                server: { host: "0.0.0.0", port: "8080" }
+                db: { hosts: ["alpha", "beta"], password: "p@ss \"word\" $HOME" }
+                debug: "false"
+            }
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, expected, "quote: {}", quote);
+        assert_eq!(to_env_block(&parsed, "", &options).unwrap(), block);
+    }
+}
+
+#[test]
+fn test_env_block_options() {
+    let config = from_str(r#"{ log: { level: "info", "file-name": "a b" } }"#).unwrap();
+    let options = EnvBlockOptions {
+        quote: false,
+        separator: '.',
+        header: true,
+    };
+    assert_eq!(
+        to_env_block(&config, "app", &options).unwrap(),
+        "#!/bin/sh\nexport APP.LOG.LEVEL=info\nexport APP.LOG.FILE_NAME=a\\ b\n"
+    );
+}
+
+#[test]
+fn test_from_env_block_forms() {
+    let block = r#"#!/bin/sh
+# a comment
+export A_X="double \"quoted\""
+A_Y='single $quoted'
+export A_Z=plain\ text
+"#;
+    let value = from_env_block(block).unwrap();
+    assert_eq!(
+        value,
+        from_str(r#"{ a: { x: "double \"quoted\"", y: "single $quoted", z: "plain text" } }"#)
+            .unwrap()
+    );
+
+    let err = from_env_block("export A=\"open\n").unwrap_err();
+    assert_eq!(err.line(), 1);
+}
+
+#[test]
+fn test_env_block_cannot_flatten() {
+    let config = from_str("{ tags: [] }").unwrap();
+    let err = to_env_block(&config, "APP", &EnvBlockOptions::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ConvertError::CannotFlatten {
+            key: "tags".to_string(),
+            found: "array"
+        }
+    );
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)]
+
 use cosy::from_str;
 use cosy::value::{Value, ValueKind};
 use std::env;
@@ -18,7 +20,7 @@ fn test_env_var_in_string() {
     if let ValueKind::Object(obj) = value.kind {
         assert_eq!(
             obj.get("key"),
-            Some(&Value::string("Value is interpolated".to_string()))
+            Some(&Value::string("Value is interpolated"))
         );
     } else {
         panic!("Expected object");
@@ -103,10 +105,7 @@ fn test_env_var_standalone_string_fallback() {
     let value = from_str(&input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(
-            obj.get("val"),
-            Some(&Value::string("not_a_number".to_string()))
-        );
+        assert_eq!(obj.get("val"), Some(&Value::string("not_a_number")));
     } else {
         panic!("Expected object");
     }
@@ -158,7 +157,7 @@ fn test_env_var_escaping() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("val"), Some(&Value::string("${VAR}".to_string())));
+        assert_eq!(obj.get("val"), Some(&Value::string("${VAR}")));
     } else {
         panic!("Expected object");
     }
@@ -1,4 +1,4 @@
-use cosy::include::resolve;
+use cosy::include::{resolve, resolve_collecting};
 use cosy::value::ValueKind;
 use std::fs;
 use tempfile::tempdir;
@@ -67,7 +67,14 @@ fn test_extends_and_include_order() {
     }"#;
 
     let mut val = cosy::from_str(input).unwrap();
-    resolve(&mut val, root).unwrap();
+    let loaded = resolve_collecting(&mut val, root).unwrap();
+    assert_eq!(
+        loaded,
+        vec![
+            fs::canonicalize(root.join("base.cosy")).unwrap(),
+            fs::canonicalize(root.join("mixin.cosy")).unwrap(),
+        ]
+    );
 
     if let ValueKind::Object(map) = val.kind {
         assert_eq!(map.get("A").unwrap().kind, ValueKind::Integer(1));
@@ -94,10 +101,21 @@ fn test_local_child_recursion_fix() {
         local_child: {
             include: "nested.cosy"
         }
+        other_child: {
+            include: "nested.cosy"
+        }
     }"#;
 
     let mut val = cosy::from_str(input).unwrap();
-    resolve(&mut val, root).unwrap();
+    let loaded = resolve_collecting(&mut val, root).unwrap();
+    // Local fields resolve before the bases; `nested.cosy` is listed once
+    assert_eq!(
+        loaded,
+        vec![
+            fs::canonicalize(root.join("nested.cosy")).unwrap(),
+            fs::canonicalize(root.join("base.cosy")).unwrap(),
+        ]
+    );
 
     if let ValueKind::Object(map) = val.kind {
         assert!(map.contains_key("base_val"));
@@ -134,9 +152,81 @@ fn test_recursive_extends() {
     }"#;
 
     let mut val = cosy::from_str(input).unwrap();
-    resolve(&mut val, root).unwrap();
+    let loaded = resolve_collecting(&mut val, root).unwrap();
+    assert_eq!(
+        loaded,
+        vec![
+            fs::canonicalize(root.join("parent.cosy")).unwrap(),
+            fs::canonicalize(root.join("gp.cosy")).unwrap(),
+        ]
+    );
 
     if let ValueKind::Object(map) = val.kind {
         assert_eq!(map.get("level").unwrap().kind, ValueKind::Integer(3));
     }
 }
+
+#[test]
+fn test_multiple_extends_four_layer_precedence() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    fs::write(
+        root.join("base.cosy"),
+        "{ A: \"base\", B: \"base\", C: \"base\", D: \"base\" }",
+    )
+    .unwrap();
+    fs::write(
+        root.join("dark.cosy"),
+        "{ B: \"dark\", C: \"dark\", D: \"dark\" }",
+    )
+    .unwrap();
+    fs::write(root.join("mixin.cosy"), "{ C: \"mixin\", D: \"mixin\" }").unwrap();
+
+    // Expected Precedence: Local > Mixin > later extends > earlier extends
+    let input = r#"{
+        extends: ["base.cosy", "dark.cosy"]
+        include: "mixin.cosy"
+        D: "local"
+    }"#;
+
+    let mut val = cosy::from_str(input).unwrap();
+    resolve(&mut val, root).unwrap();
+
+    assert_eq!(
+        val,
+        cosy::from_str(r#"{ A: "base", B: "dark", C: "mixin", D: "local" }"#).unwrap()
+    );
+}
+
+#[test]
+fn test_extends_array_of_three_bases() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+
+    fs::write(root.join("one.cosy"), "{ a: 1, b: 1, c: 1, d: 1 }").unwrap();
+    fs::write(root.join("two.cosy"), "{ b: 2, c: 2, d: 2 }").unwrap();
+    fs::write(root.join("three.cosy"), "{ c: 3, d: 3 }").unwrap();
+
+    // Each base overrides the ones before it; local keys override them all
+    let input = r#"{
+        extends: ["one.cosy", "two.cosy", "three.cosy"]
+        d: 4
+    }"#;
+
+    let mut val = cosy::from_str(input).unwrap();
+    let loaded = resolve_collecting(&mut val, root).unwrap();
+
+    assert_eq!(val, cosy::from_str("{ a: 1, b: 2, c: 3, d: 4 }").unwrap());
+    let names: Vec<_> = loaded.iter().map(|p| p.file_name().unwrap()).collect();
+    assert_eq!(names, ["one.cosy", "two.cosy", "three.cosy"]);
+}
+
+#[test]
+fn test_extends_empty_array_is_ignored() {
+    let dir = tempdir().unwrap();
+
+    let mut val = cosy::from_str("{ extends: [], a: 1 }").unwrap();
+    resolve(&mut val, dir.path()).unwrap();
+    assert_eq!(val, cosy::from_str("{ a: 1 }").unwrap());
+}
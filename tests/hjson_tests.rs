@@ -0,0 +1,109 @@
+#![cfg(feature = "hjson-compat")]
+
+use cosy::compat::from_hjson_str;
+use cosy::value::{Value, ValueKind};
+use indexmap::IndexMap;
+
+fn as_object(value: &Value) -> &IndexMap<String, Value> {
+    match &value.kind {
+        ValueKind::Object(map) => map,
+        other => panic!("Expected object, found {}", other.type_name()),
+    }
+}
+
+fn as_array(value: &Value) -> &Vec<Value> {
+    match &value.kind {
+        ValueKind::Array(arr) => arr,
+        other => panic!("Expected array, found {}", other.type_name()),
+    }
+}
+
+#[test]
+fn test_hjson_comments() {
+    let value = from_hjson_str(include_str!("fixtures/hjson/comments.hjson")).unwrap();
+    let obj = as_object(&value);
+
+    // A quoteless string keeps the trailing `#` text (HJSON semantics)
+    assert_eq!(
+        obj["x1"].kind,
+        Value::from("y1 # trailing hash comment").kind
+    );
+    assert_eq!(obj["x2"].kind, Value::integer(2).kind);
+    assert_eq!(obj["x3"].kind, Value::boolean(true).kind);
+    assert_eq!(obj["x4"].kind, Value::null().kind);
+    assert!(
+        obj["x1"]
+            .line_comments()
+            .any(|c| c == "the following are all valid:")
+    );
+}
+
+#[test]
+fn test_hjson_strings() {
+    let value = from_hjson_str(include_str!("fixtures/hjson/strings.hjson")).unwrap();
+    let obj = as_object(&value);
+
+    assert_eq!(
+        obj["text1"].kind,
+        Value::from("This is a valid string value.").kind
+    );
+    assert_eq!(obj["text2"].kind, Value::from("a \\ is just a \\").kind);
+    assert_eq!(
+        obj["multiline1"].kind,
+        Value::from("first line\n  indented line\nlast line").kind
+    );
+    assert_eq!(obj["multiline2"].kind, Value::from("single line").kind);
+    assert_eq!(obj["foo1a"].kind, Value::from("asdf\"'a\ts\nd\u{e9}f").kind);
+    assert_eq!(
+        obj["foo1b"].kind,
+        Value::from("single 'quoted' string").kind
+    );
+    assert_eq!(obj["notnum"].kind, Value::from("3 apples").kind);
+}
+
+#[test]
+fn test_hjson_numbers_and_literals() {
+    let value = from_hjson_str(include_str!("fixtures/hjson/kan.hjson")).unwrap();
+    let obj = as_object(&value);
+
+    let numbers = as_array(&obj["numbers"]);
+    assert_eq!(numbers.len(), 12);
+    assert_eq!(numbers[3], Value::integer(42));
+    assert_eq!(numbers[4], Value::float(42.1));
+    assert_eq!(numbers[6], Value::float(-5.1));
+    assert_eq!(numbers[11], Value::float(1e6));
+
+    let native = as_array(&obj["native"]);
+    assert_eq!(native.len(), 6);
+    assert_eq!(native[0], Value::boolean(true));
+    assert_eq!(native[3], Value::boolean(false));
+    assert_eq!(native[5], Value::null());
+}
+
+#[test]
+fn test_hjson_trailing_commas() {
+    let value = from_hjson_str(include_str!("fixtures/hjson/trail.hjson")).unwrap();
+    let obj = as_object(&value);
+
+    assert_eq!(as_array(&obj["arr"]).len(), 3);
+    assert_eq!(as_object(&obj["obj"]).len(), 2);
+    let nested = as_array(&obj["nested"]);
+    assert_eq!(as_object(&nested[1])["id"], Value::integer(2));
+}
+
+#[test]
+fn test_hjson_braceless_root() {
+    let value = from_hjson_str(include_str!("fixtures/hjson/root.hjson")).unwrap();
+    let obj = as_object(&value);
+
+    let db = as_object(&obj["database"]);
+    assert_eq!(db["host"], Value::from("127.0.0.1"));
+    assert_eq!(db["port"], Value::integer(5555));
+    assert_eq!(obj["name"].kind, Value::from("my app").kind);
+}
+
+#[test]
+fn test_hjson_error_position() {
+    let err = from_hjson_str("{\n  a: 1\n  b: 'open\n}").unwrap_err();
+    assert_eq!(err.line(), 4);
+}
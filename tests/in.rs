@@ -1,5 +1,6 @@
 // tests/integration_tests.rs
 // Place this file in tests/ directory at the root of your project
+#![allow(clippy::approx_constant)]
 
 use cosy::CosynError;
 use cosy::from_str;
@@ -39,13 +40,10 @@ fn test_floats() {
 
 #[test]
 fn test_strings() {
-    assert_eq!(
-        from_str(r#""hello""#).unwrap(),
-        Value::string("hello".to_string())
-    );
+    assert_eq!(from_str(r#""hello""#).unwrap(), Value::string("hello"));
     assert_eq!(
         from_str(r#""hello world""#).unwrap(),
-        Value::string("hello world".to_string())
+        Value::string("hello world")
     );
 }
 
@@ -53,19 +51,19 @@ fn test_strings() {
 fn test_string_escapes() {
     assert_eq!(
         from_str(r#""hello\nworld""#).unwrap(),
-        Value::string("hello\nworld".to_string())
+        Value::string("hello\nworld")
     );
     assert_eq!(
         from_str(r#""tab\there""#).unwrap(),
-        Value::string("tab\there".to_string())
+        Value::string("tab\there")
     );
     assert_eq!(
         from_str(r#""quote\"inside""#).unwrap(),
-        Value::string("quote\"inside".to_string())
+        Value::string("quote\"inside")
     );
     assert_eq!(
         from_str(r#""backslash\\here""#).unwrap(),
-        Value::string("backslash\\here".to_string())
+        Value::string("backslash\\here")
     );
 }
 
@@ -95,7 +93,7 @@ fn test_mixed_array() {
     let value = from_str(r#"[1, "hello", 3.14, true, null]"#).unwrap();
     let expected = Value::array(vec![
         Value::integer(1),
-        Value::string("hello".to_string()),
+        Value::string("hello"),
         Value::float(3.14),
         Value::boolean(true),
         Value::null(),
@@ -172,7 +170,7 @@ fn test_simple_object() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("name"), Some(&Value::string("Alice".to_string())));
+        assert_eq!(obj.get("name"), Some(&Value::string("Alice")));
         assert_eq!(obj.get("age"), Some(&Value::integer(30)));
     } else {
         panic!("Expected object");
@@ -185,7 +183,7 @@ fn test_object_with_string_keys() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("name"), Some(&Value::string("Bob".to_string())));
+        assert_eq!(obj.get("name"), Some(&Value::string("Bob")));
         assert_eq!(obj.get("age"), Some(&Value::integer(25)));
     } else {
         panic!("Expected object");
@@ -204,7 +202,7 @@ fn test_object_with_various_values() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("string"), Some(&Value::string("hello".to_string())));
+        assert_eq!(obj.get("string"), Some(&Value::string("hello")));
         assert_eq!(obj.get("number"), Some(&Value::integer(42)));
         assert_eq!(obj.get("float"), Some(&Value::float(3.14)));
         assert_eq!(obj.get("bool"), Some(&Value::boolean(true)));
@@ -227,10 +225,7 @@ fn test_nested_object() {
     if let ValueKind::Object(obj) = value.kind {
         if let Some(person_val) = obj.get("person") {
             if let ValueKind::Object(person) = &person_val.kind {
-                assert_eq!(
-                    person.get("name"),
-                    Some(&Value::string("Alice".to_string()))
-                );
+                assert_eq!(person.get("name"), Some(&Value::string("Alice")));
                 assert_eq!(person.get("age"), Some(&Value::integer(30)));
             } else {
                 panic!("Expected nested object");
@@ -277,7 +272,7 @@ fn test_object_with_newlines_as_separators() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("name"), Some(&Value::string("Alice".to_string())));
+        assert_eq!(obj.get("name"), Some(&Value::string("Alice")));
         assert_eq!(obj.get("age"), Some(&Value::integer(30)));
         assert_eq!(obj.get("active"), Some(&Value::boolean(true)));
     } else {
@@ -291,7 +286,7 @@ fn test_object_with_trailing_comma() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("name"), Some(&Value::string("Alice".to_string())));
+        assert_eq!(obj.get("name"), Some(&Value::string("Alice")));
         assert_eq!(obj.get("age"), Some(&Value::integer(30)));
     } else {
         panic!("Expected object");
@@ -331,7 +326,7 @@ fn test_complex_document() {
                 assert_eq!(users.len(), 2);
 
                 if let ValueKind::Object(alice) = &users[0].kind {
-                    assert_eq!(alice.get("name"), Some(&Value::string("Alice".to_string())));
+                    assert_eq!(alice.get("name"), Some(&Value::string("Alice")));
                     assert_eq!(alice.get("id"), Some(&Value::integer(1)));
                 } else {
                     panic!("Expected object for first user");
@@ -366,14 +361,14 @@ fn test_line_comments_in_object() {
             obj.get("name"),
             Some(&Value::with_comments(
                 ValueKind::String("Alice".to_string()),
-                vec!["This is a comment".to_string()]
+                vec!["This is a comment".into()]
             ))
         );
         assert_eq!(
             obj.get("age"),
             Some(&Value::with_comments(
                 ValueKind::Integer(30),
-                vec!["Another comment".to_string()]
+                vec!["Another comment".into()]
             ))
         );
     } else {
@@ -392,16 +387,74 @@ fn test_comments_in_array() {
     let value = from_str(input).unwrap();
     let expected = Value::array(vec![
         Value::integer(1),
-        Value::with_comments(ValueKind::Integer(2), vec!["comment here".to_string()]),
-        Value::integer(3), // inline comment is discarded by current parser logic if after value
+        Value::with_comments(ValueKind::Integer(2), vec!["comment here".into()]),
+        Value::integer(3).with_trailing_comment("inline comment"),
     ]);
     assert_eq!(value, expected);
 }
 
+// ============================================================================
+// TOKENIZER TESTS
+// ============================================================================
+
+#[test]
+fn test_tokenize_public_api() {
+    use cosy::{Position, Token, tokenize};
+
+    let tokens = tokenize("{a:1}").unwrap();
+    let kinds: Vec<(Token, Position)> = tokens.into_iter().map(|t| (t.token, t.pos)).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Token::LeftBrace, Position::new(1, 1)),
+            (Token::Identifier("a".to_string()), Position::new(1, 2)),
+            (Token::Colon, Position::new(1, 3)),
+            (Token::Integer(1), Position::new(1, 4)),
+            (Token::RightBrace, Position::new(1, 5)),
+            (Token::Eof, Position::new(1, 6)),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_error() {
+    let err = cosy::tokenize("{ a: @ }").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 6));
+}
+
 // ============================================================================
 // ERROR TESTS
 // ============================================================================
 
+#[test]
+fn test_error_categories() {
+    let lex = cosy::tokenize("@").unwrap_err();
+    let parse = from_str("{ a: }").unwrap_err();
+    let io = CosynError::from(std::io::Error::other("disk unplugged"));
+    let include = CosynError::Include("missing.cosy".to_string());
+    assert!(lex.is_syntax() && parse.is_syntax());
+    assert!(io.is_io() && io.is_recoverable());
+    assert!(include.is_include());
+
+    let others = [
+        CosynError::Schema(String::new()),
+        CosynError::Merge(String::new()),
+        CosynError::Template(String::new()),
+        CosynError::Validation(String::new()),
+    ];
+    for err in [lex, parse, io, include].iter().chain(&others) {
+        let categories = [err.is_io(), err.is_syntax(), err.is_include()];
+        assert!(categories.iter().filter(|c| **c).count() <= 1, "{:?}", err);
+        assert_eq!(err.is_recoverable(), err.is_io(), "{:?}", err);
+    }
+    assert!(
+        others
+            .iter()
+            .all(|err| !err.is_io() && !err.is_syntax() && !err.is_include())
+    );
+}
+
 #[test]
 fn test_error_unexpected_token() {
     let result = from_str("42 99");
@@ -470,6 +523,84 @@ fn test_lex_error_position() {
     }
 }
 
+#[test]
+fn test_custom_errors() {
+    use cosy::syntax::lexer::LexError;
+    use cosy::syntax::parser::ParseError;
+
+    // A loader reporting its own problem, positioned like COSY's errors
+    let err: CosynError = ParseError::new("Unknown section 'db'", 4, 3).into();
+    assert_eq!(
+        err.to_string(),
+        "Parse error at line 4, column 3: Unknown section 'db'"
+    );
+    assert_eq!((err.line(), err.column()), (4, 3));
+    assert_eq!(err.message(), "Unknown section 'db'");
+
+    let err = CosynError::parse(format!("Unknown section '{}'", "db"), 4, 3);
+    assert!(matches!(&err, CosynError::Parse(e) if e.line == 4 && e.column == 3));
+
+    let err: CosynError = LexError::new("Stray byte", 1, 9).into();
+    assert_eq!(err.to_string(), "Lex error at line 1, column 9: Stray byte");
+    let err = CosynError::lex("Stray byte", 1, 9);
+    assert_eq!((err.line(), err.column()), (1, 9));
+}
+
+#[test]
+fn test_parse_merge_directives() {
+    use cosy::merge::MergeDirective;
+
+    let value = from_str(r#"{ plugins+: ["x"], "my server"!: { port: 1 }, plain: 1 }"#).unwrap();
+    let ValueKind::Object(obj) = &value.kind else {
+        panic!("Expected object");
+    };
+    let keys: Vec<_> = obj.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["plugins", "my server", "plain"]);
+    assert_eq!(obj["plugins"].merge_directive, Some(MergeDirective::Append));
+    assert_eq!(
+        obj["my server"].merge_directive,
+        Some(MergeDirective::Replace)
+    );
+    assert_eq!(obj["plain"].merge_directive, None);
+
+    // Written back with their suffixes
+    let written = cosy::to_string(&from_str("{ a+: [1], b!: { c: 2 } }").unwrap());
+    assert_eq!(
+        written,
+        "{\n    a+: [1],\n    b!: {\n        c: 2\n    }\n}"
+    );
+
+    // Only directly before a colon
+    assert!(from_str("{ a: +1 }").is_err());
+    assert!(from_str("{ a!! : 1 }").is_err());
+}
+
+#[test]
+fn test_error_position_with_crlf_and_cr() {
+    for newline in ["\r\n", "\r"] {
+        let input = ["{", "  a: 1", "  b: 2", "  c: @", "}"].join(newline);
+        let err = from_str(&input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (4, 6), "newline {:?}", newline);
+
+        // Newlines still separate entries
+        let input = ["{", "  a: 1", "  b: 2", "}"].join(newline);
+        assert_eq!(
+            from_str(&input).unwrap(),
+            from_str("{ a: 1, b: 2 }").unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_parse_with_bom() {
+    let value = from_str("\u{FEFF}{\n  name: \"cosy\"\n}").unwrap();
+    assert_eq!(value, from_str(r#"{ name: "cosy" }"#).unwrap());
+    assert_eq!(value.position, Some(cosy::Position::new(1, 1)));
+
+    let err = from_str("\u{FEFF}{\n  name: @\n}").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 9));
+}
+
 #[test]
 fn test_parse_error_position() {
     let result = from_str("[1, 2, 3 4]");
@@ -495,3 +626,241 @@ fn test_string_with_newlines_deserialization() {
     let deserialized_value = from_str(input_for_parser).unwrap();
     assert_eq!(deserialized_value, Value::string(original_str.to_string()));
 }
+
+// ============================================================================
+// DUPLICATE KEY TESTS
+// ============================================================================
+
+const DUPLICATE_NAME: &str = r#"{
+    name: "first"
+    port: 80
+    name: "second"
+}"#;
+
+fn parse_with_policy(input: &str, policy: cosy::DuplicateKeyPolicy) -> Result<Value, CosynError> {
+    cosy::from_str_with_options(
+        input,
+        cosy::ParseOptions {
+            duplicate_key: policy,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn test_duplicate_key_last_wins() {
+    use cosy::DuplicateKeyPolicy;
+
+    let value = parse_with_policy(DUPLICATE_NAME, DuplicateKeyPolicy::LastWins).unwrap();
+    assert_eq!(value, from_str(r#"{ name: "second", port: 80 }"#).unwrap());
+    // The default matches `LastWins`
+    assert_eq!(from_str(DUPLICATE_NAME).unwrap(), value);
+}
+
+#[test]
+fn test_duplicate_key_first_wins() {
+    use cosy::DuplicateKeyPolicy;
+
+    let value = parse_with_policy(DUPLICATE_NAME, DuplicateKeyPolicy::FirstWins).unwrap();
+    assert_eq!(value, from_str(r#"{ name: "first", port: 80 }"#).unwrap());
+}
+
+#[test]
+fn test_duplicate_key_error() {
+    use cosy::DuplicateKeyPolicy;
+
+    let err = parse_with_policy(DUPLICATE_NAME, DuplicateKeyPolicy::Error).unwrap_err();
+    assert!(err.message().contains("Duplicate key 'name'"));
+    assert_eq!((err.line(), err.column()), (4, 5));
+}
+
+#[test]
+fn test_duplicate_key_merge_objects() {
+    use cosy::DuplicateKeyPolicy;
+
+    let input = r#"{
+        name: { first: "Ada", last: "Byron" }
+        name: { last: "Lovelace" }
+    }"#;
+    let value = parse_with_policy(input, DuplicateKeyPolicy::MergeObjects).unwrap();
+    assert_eq!(
+        value,
+        from_str(r#"{ name: { first: "Ada", last: "Lovelace" } }"#).unwrap()
+    );
+
+    // Non-object duplicates can't be merged
+    let err = parse_with_policy(DUPLICATE_NAME, DuplicateKeyPolicy::MergeObjects).unwrap_err();
+    assert!(err.message().contains("Duplicate key 'name'"));
+    assert_eq!(err.line(), 4);
+}
+
+// ============================================================================
+// EMPTY DOCUMENT TESTS
+// ============================================================================
+
+fn parse_with_empty_policy(input: &str, policy: cosy::EmptyPolicy) -> Result<Value, CosynError> {
+    cosy::from_str_with_options(
+        input,
+        cosy::ParseOptions {
+            empty_as: policy,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn test_empty_document_error_by_default() {
+    use cosy::EmptyPolicy;
+
+    for input in ["", "  \n\t\n", "// nothing here\n/// or here"] {
+        let err = parse_with_empty_policy(input, EmptyPolicy::Error).unwrap_err();
+        assert!(err.message().contains("Expected value, found EOF"));
+        assert!(from_str(input).is_err());
+    }
+}
+
+#[test]
+fn test_empty_document_as_null() {
+    use cosy::EmptyPolicy;
+
+    assert_eq!(
+        parse_with_empty_policy("  \n\t\n", EmptyPolicy::Null).unwrap(),
+        Value::null()
+    );
+    let value = parse_with_empty_policy("// nothing here\n", EmptyPolicy::Null).unwrap();
+    assert_eq!(value.kind, ValueKind::Null);
+    assert!(value.line_comments().any(|c| c == "nothing here"));
+}
+
+#[test]
+fn test_empty_document_as_empty_object() {
+    use cosy::EmptyPolicy;
+
+    let empty = from_str("{}").unwrap();
+    assert_eq!(
+        parse_with_empty_policy("  \n\t\n", EmptyPolicy::EmptyObject).unwrap(),
+        empty
+    );
+    let value = parse_with_empty_policy("// nothing here\n", EmptyPolicy::EmptyObject).unwrap();
+    assert_eq!(value.kind, empty.kind);
+
+    // Non-empty input is unaffected
+    let value = parse_with_empty_policy("{ a: 1 }", EmptyPolicy::EmptyObject).unwrap();
+    assert_eq!(value, from_str("{ a: 1 }").unwrap());
+}
+
+// ============================================================================
+// ANCHOR TESTS
+// ============================================================================
+
+fn parse_with_anchors(input: &str) -> Result<Value, CosynError> {
+    cosy::from_str_with_options(
+        input,
+        cosy::ParseOptions {
+            enable_anchors: true,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn test_scalar_anchor() {
+    let input = r#"{
+        default_port: &port 8080
+        api: { port: *port }
+        admin: { port: *port }
+    }"#;
+    let value = parse_with_anchors(input).unwrap();
+    assert_eq!(
+        value,
+        from_str("{ default_port: 8080, api: { port: 8080 }, admin: { port: 8080 } }").unwrap()
+    );
+}
+
+#[test]
+fn test_object_anchor_merged_into_two_places() {
+    let input = r#"{
+        defaults: &defaults { timeout: 30, retries: 3 }
+        api: {
+            *defaults
+            timeout: 5
+        }
+        worker: { *defaults, queue: "jobs" }
+    }"#;
+    let value = parse_with_anchors(input).unwrap();
+    let expected = from_str(
+        r#"{
+        defaults: { timeout: 30, retries: 3 }
+        api: { timeout: 5, retries: 3 }
+        worker: { timeout: 30, retries: 3, queue: "jobs" }
+    }"#,
+    )
+    .unwrap();
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn test_anchor_defined_after_use_and_nested() {
+    let input = r#"{
+        service: { *base, name: "api" }
+        base: &base { limits: &limits [1, 2], copy: *limits }
+    }"#;
+    let value = parse_with_anchors(input).unwrap();
+    assert_eq!(
+        value,
+        from_str(
+            r#"{
+            service: { limits: [1, 2], copy: [1, 2], name: "api" }
+            base: { limits: [1, 2], copy: [1, 2] }
+        }"#
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_anchor_errors() {
+    let err = parse_with_anchors("{ a: &a { b: *a } }").unwrap_err();
+    assert!(
+        err.message().contains("Anchor 'a' refers to itself"),
+        "{}",
+        err
+    );
+    assert_eq!((err.line(), err.column()), (1, 14));
+
+    let err = parse_with_anchors("{ a: &a { b: *b }, c: &b { d: *a } }").unwrap_err();
+    assert!(err.message().contains("refers to itself"), "{}", err);
+
+    let err = parse_with_anchors("{ a: *missing }").unwrap_err();
+    assert!(err.message().contains("Undefined anchor 'missing'"));
+
+    let err = parse_with_anchors("{ a: &x 1, b: &x 2 }").unwrap_err();
+    assert!(err.message().contains("Duplicate anchor 'x'"));
+
+    let err = parse_with_anchors("{ a: &x 1, b: { *x } }").unwrap_err();
+    assert!(err.message().contains("Cannot merge anchor 'x' (integer)"));
+
+    // Anchors are off by default
+    assert!(from_str("{ a: &x 1, b: *x }").is_err());
+}
+
+// ============================================================================
+// INPUT SIZE TESTS
+// ============================================================================
+
+#[test]
+fn test_max_input_bytes() {
+    let limited = |limit| cosy::ParseOptions {
+        max_input_bytes: Some(limit),
+        ..Default::default()
+    };
+
+    let err = cosy::from_str_with_options("{ name: \"too long\" }", limited(8)).unwrap_err();
+    assert!(matches!(err, CosynError::Lex(_)));
+    assert!(err.message().contains("over the limit of 8"), "{}", err);
+
+    // The limit is inclusive
+    let input = "{ a: 1 }";
+    let value = cosy::from_str_with_options(input, limited(input.len())).unwrap();
+    assert_eq!(value, from_str(input).unwrap());
+}
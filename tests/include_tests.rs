@@ -56,7 +56,9 @@ fn test_nested_inclusion() {
     let top_content = fs::read_to_string(&top_path).unwrap();
     let mut config = from_str(&top_content).unwrap();
 
-    include::resolve(&mut config, dir.path()).unwrap();
+    let loaded = include::resolve_collecting(&mut config, dir.path()).unwrap();
+    let canonical = |p: &std::path::Path| fs::canonicalize(p).unwrap();
+    assert_eq!(loaded, vec![canonical(&mid_path), canonical(&base_path)]);
 
     if let ValueKind::Object(map) = config.kind {
         assert_eq!(map.get("a"), Some(&Value::integer(1)));
@@ -88,12 +90,9 @@ fn test_include_in_sub_object() {
     if let ValueKind::Object(root) = config.kind {
         if let Some(db_val) = root.get("database") {
             if let ValueKind::Object(db) = &db_val.kind {
-                assert_eq!(
-                    db.get("host"),
-                    Some(&Value::string("localhost".to_string()))
-                );
+                assert_eq!(db.get("host"), Some(&Value::string("localhost")));
                 assert_eq!(db.get("port"), Some(&Value::integer(5432)));
-                assert_eq!(db.get("user"), Some(&Value::string("admin".to_string())));
+                assert_eq!(db.get("user"), Some(&Value::string("admin")));
             } else {
                 panic!("database should be an object");
             }
@@ -117,12 +116,770 @@ fn test_include_cycle_detection() {
     let a_content = fs::read_to_string(&a_path).unwrap();
     let mut config = from_str(&a_content).unwrap();
 
-    let result = include::resolve(&mut config, dir.path());
-    assert!(result.is_err());
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    let a_path = fs::canonicalize(&a_path).unwrap();
+    let b_path = fs::canonicalize(&b_path).unwrap();
+    match &err {
+        include::IncludeError::CycleDetected { chain } => {
+            assert_eq!(chain, &vec![b_path.clone(), a_path, b_path]);
+        }
+        other => panic!("Expected CycleDetected, got {:?}", other),
+    }
+    assert!(err.to_string().starts_with("Include cycle detected: "));
+}
+
+#[test]
+fn test_include_deep_chain_is_not_a_cycle() {
+    let dir = tempdir().unwrap();
+    for i in 0..12 {
+        let content = if i == 11 {
+            "{ last: true }".to_string()
+        } else {
+            format!(r#"{{ include: "{}.cosy", level{}: {} }}"#, i + 1, i, i)
+        };
+        fs::write(dir.path().join(format!("{}.cosy", i)), content).unwrap();
+    }
+
+    let mut config = from_str(r#"{ include: "0.cosy" }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    if let ValueKind::Object(map) = &config.kind {
+        assert_eq!(map.get("level10"), Some(&Value::integer(10)));
+        assert_eq!(map.get("last"), Some(&Value::boolean(true)));
+    } else {
+        panic!("root should be an object");
+    }
+
+    // The depth limit remains as a backstop
+    let mut config = from_str(r#"{ include: "0.cosy" }"#).unwrap();
+    let err = include::resolve_with_max_depth(&mut config, dir.path(), 5).unwrap_err();
+    assert!(matches!(
+        err,
+        include::IncludeError::RecursionLimitExceeded { max_depth: 5 }
+    ));
+}
+
+#[test]
+fn test_include_non_object_target() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("list.cosy"), "[1, 2, 3]").unwrap();
+
+    let mut config = from_str(r#"{ include: "list.cosy" }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+
+    match &err {
+        include::IncludeError::IncludedNotObject { path, found } => {
+            assert_eq!(path, std::path::Path::new("list.cosy"));
+            assert_eq!(*found, "array");
+        }
+        other => panic!("Expected IncludedNotObject, got {:?}", other),
+    }
+    assert!(err.to_string().contains("must be an Object, found array"));
+}
+
+#[test]
+fn test_include_list_precedence() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("logging.cosy"),
+        r#"{ level: "debug", format: "text", owner: "logging" }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("metrics.cosy"),
+        r#"{ format: "json", interval: 10, owner: "metrics" }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("overrides.cosy"),
+        r#"{ interval: 30, owner: "overrides" }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(
+        r#"{
+        include: ["logging.cosy", "metrics.cosy", "overrides.cosy"]
+        owner: "local"
+    }"#,
+    )
+    .unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+
+    let expected =
+        from_str(r#"{ level: "debug", format: "json", owner: "local", interval: 30 }"#).unwrap();
+    assert_eq!(config, expected);
+}
+
+#[test]
+fn test_extends_list_and_invalid_element() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.cosy"), "{ x: 1, y: 1 }").unwrap();
+    fs::write(dir.path().join("b.cosy"), "{ y: 2 }").unwrap();
+
+    let mut config = from_str(r#"{ extends: ["a.cosy", "b.cosy"] }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(config, from_str("{ x: 1, y: 2 }").unwrap());
+
+    let mut config = from_str(r#"{ include: ["a.cosy", 42] }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert!(matches!(
+        err,
+        include::IncludeError::InvalidIncludeTarget(_)
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Invalid include usage: Include value at index 1 must be a string or an object, found integer"
+    );
+}
+
+#[test]
+fn test_include_glob_merges_in_name_order() {
+    let dir = tempdir().unwrap();
+    let conf_d = dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    // Written out of order to make sure the result is sorted
+    fs::write(conf_d.join("30-c.cosy"), r#"{ level: "c" }"#).unwrap();
+    fs::write(
+        conf_d.join("10-a.cosy"),
+        r#"{ level: "a", a: true, shared: "a" }"#,
+    )
+    .unwrap();
+    fs::write(conf_d.join("20-b.cosy"), r#"{ level: "b", shared: "b" }"#).unwrap();
+    fs::write(conf_d.join("notes.txt"), "not a config").unwrap();
+    fs::write(conf_d.join(".99-hidden.cosy"), r#"{ level: "hidden" }"#).unwrap();
+
+    let mut config = from_str(r#"{ include: "conf.d/*.cosy" }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ level: "c", a: true, shared: "b" }"#).unwrap()
+    );
+
+    // Matching nothing is not an error
+    let mut config = from_str(r#"{ include: "conf.d/*.json", x: 1 }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(config, from_str("{ x: 1 }").unwrap());
+
+    // Wildcards in directories are rejected
+    let mut config = from_str(r#"{ include: "*/10-a.cosy" }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert!(matches!(
+        err,
+        include::IncludeError::InvalidIncludePath { .. }
+    ));
+}
+
+fn sandboxed(root: &std::path::Path) -> include::ResolveOptions {
+    include::ResolveOptions {
+        root: Some(root.to_path_buf()),
+        allow_absolute: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_include_sandbox_allows_files_inside_root() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("configs");
+    fs::create_dir_all(root.join("shared")).unwrap();
+    fs::write(root.join("shared/log.cosy"), r#"{ level: "info" }"#).unwrap();
+    // `..` is fine as long as the result stays inside the root
+    fs::write(
+        root.join("shared/app.cosy"),
+        r#"{ include: "../shared/log.cosy" }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(r#"{ include: "shared/app.cosy" }"#).unwrap();
+    include::resolve_with_options(&mut config, &root, &sandboxed(&root)).unwrap();
+    assert_eq!(config, from_str(r#"{ level: "info" }"#).unwrap());
+}
+
+#[test]
+fn test_include_sandbox_rejects_traversal() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("configs");
+    fs::create_dir(&root).unwrap();
+    fs::write(dir.path().join("secret.cosy"), "{ password: 1 }").unwrap();
+
+    let mut config = from_str(r#"{ include: "../secret.cosy" }"#).unwrap();
+    let err = include::resolve_with_options(&mut config, &root, &sandboxed(&root)).unwrap_err();
+    match &err {
+        include::IncludeError::PathEscapesRoot { path } => {
+            assert_eq!(path, &root.join("../secret.cosy"));
+        }
+        other => panic!("Expected PathEscapesRoot, got {:?}", other),
+    }
+
+    // The default resolver stays permissive
+    let mut config = from_str(r#"{ include: "../secret.cosy" }"#).unwrap();
+    include::resolve(&mut config, &root).unwrap();
+    assert_eq!(config, from_str("{ password: 1 }").unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_include_sandbox_rejects_symlink_outside_root() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("configs");
+    fs::create_dir(&root).unwrap();
+    fs::write(dir.path().join("secret.cosy"), "{ password: 1 }").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("secret.cosy"), root.join("link.cosy")).unwrap();
+
+    let mut config = from_str(r#"{ include: "link.cosy" }"#).unwrap();
+    let err = include::resolve_with_options(&mut config, &root, &sandboxed(&root)).unwrap_err();
+    assert!(matches!(err, include::IncludeError::PathEscapesRoot { .. }));
+}
+
+#[test]
+fn test_include_sandbox_absolute_paths() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("configs");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("inside.cosy"), "{ a: 1 }").unwrap();
+    fs::write(dir.path().join("outside.cosy"), "{ b: 1 }").unwrap();
+
+    let inside = root.join("inside.cosy");
+    let input = format!("{{ include: {:?} }}", inside.to_str().unwrap());
+
+    // Rejected outright when absolute paths are disallowed
+    let mut config = from_str(&input).unwrap();
+    let err = include::resolve_with_options(&mut config, &root, &sandboxed(&root)).unwrap_err();
+    assert!(matches!(
+        err,
+        include::IncludeError::InvalidIncludePath { .. }
+    ));
+
+    // Otherwise they are held to the root like any other path
+    let options = include::ResolveOptions {
+        allow_absolute: true,
+        ..sandboxed(&root)
+    };
+    let mut config = from_str(&input).unwrap();
+    include::resolve_with_options(&mut config, &root, &options).unwrap();
+    assert_eq!(config, from_str("{ a: 1 }").unwrap());
+
+    let outside = dir.path().join("outside.cosy");
+    let mut config = from_str(&format!("{{ include: {:?} }}", outside.to_str().unwrap())).unwrap();
+    let err = include::resolve_with_options(&mut config, &root, &options).unwrap_err();
+    assert!(matches!(err, include::IncludeError::PathEscapesRoot { .. }));
+}
+
+#[test]
+fn test_resolve_traced() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("db.cosy"), "{ host: \"db\", port: 5432 }").unwrap();
+
+    let mut config = from_str("{\n  db: { include: \"db.cosy\", port: 6432 }\n}").unwrap();
+    let trace = include::resolve_traced(&mut config, dir.path()).unwrap();
+
+    let db_file = fs::canonicalize(dir.path().join("db.cosy")).unwrap();
+    assert_eq!(trace["db.host"].file, Some(db_file.clone()));
+    assert_eq!(trace["db.host"].line, Some(1));
+    // Values written in the document itself have no file
+    assert_eq!(trace["db.port"].file, None);
+    assert_eq!(trace["db.port"].line, Some(2));
+    assert_eq!(
+        trace["db.host"].to_string(),
+        format!("{}:1", db_file.display())
+    );
+}
+
+#[test]
+fn test_include_json_base_with_cosy_override() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("limits.json"),
+        r#"{"limits": {"rps": 100, "burst": 20}, "motd": "${HOME} é"}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("override.cosy"),
+        "{ limits: { burst: 50 } }",
+    )
+    .unwrap();
+
+    let mut config =
+        from_str(r#"{ include: ["limits.json", "override.cosy"], name: "api" }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+
+    let ValueKind::Object(map) = &config.kind else {
+        panic!("Expected object");
+    };
+    assert_eq!(map["limits"], from_str("{ rps: 100, burst: 50 }").unwrap());
+    assert_eq!(map["name"], Value::from("api"));
+    // `${...}` in JSON strings is literal text
+    assert_eq!(map["motd"], Value::from("${HOME} é"));
+}
+
+#[test]
+fn test_include_object_form_overrides_format() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("limits.txt"), r#"{"rps": 100}"#).unwrap();
+
+    let mut config =
+        from_str(r#"{ extends: { path: "limits.txt", format: "json" }, rps: 5 }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(config, from_str("{ rps: 5 }").unwrap());
+
+    let mut config = from_str(r#"{ include: { path: "limits.txt", format: "xml" } }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid include usage: Unknown include format 'xml'; expected 'cosy' or 'json'"
+    );
+}
+
+#[test]
+fn test_include_json_parse_error_names_file() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("broken.json"), "{\"a\": 1,}").unwrap();
+
+    let mut config = from_str(r#"{ include: "broken.json" }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert!(matches!(err, include::IncludeError::ParseError { .. }));
+    assert!(err.to_string().contains("broken.json"), "{}", err);
+}
+
+#[test]
+fn test_include_parse_error_reports_chain() {
+    let dir = tempdir().unwrap();
+    let conf = dir.path().join("conf");
+    fs::create_dir(&conf).unwrap();
+    fs::write(conf.join("app.cosy"), r#"{ include: "db.cosy" }"#).unwrap();
+    fs::write(conf.join("db.cosy"), "{\n  host: \"db\"\n  port: }").unwrap();
+    fs::write(
+        dir.path().join("main.cosy"),
+        r#"{ include: "conf/app.cosy" }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(r#"{ include: "main.cosy" }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+
+    let app = fs::canonicalize(conf.join("app.cosy")).unwrap();
+    let main = fs::canonicalize(dir.path().join("main.cosy")).unwrap();
+    match &err {
+        include::IncludeError::ParseError { path, chain, .. } => {
+            assert!(path.ends_with("conf/db.cosy"));
+            assert_eq!(chain, &vec![app.clone(), main.clone()]);
+        }
+        other => panic!("Expected ParseError, got {:?}", other),
+    }
+    let message = err.to_string();
     assert!(
-        result
+        message.starts_with(&format!(
+            "error in \"{}\" (included from \"{}\" \u{2190} \"{}\"): Parse error at line 3",
+            conf.join("db.cosy").display(),
+            app.display(),
+            main.display()
+        )),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_include_into_mounts_object_under_path() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("queries.cosy"),
+        r#"{ users: "SELECT * FROM users", timeout: 5 }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(
+        r#"{
+        include: { path: "queries.cosy", into: "db.queries" }
+        db: { host: "localhost", queries: { timeout: 30 } }
+    }"#,
+    )
+    .unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+
+    // Local keys still override the mounted file
+    assert_eq!(
+        config,
+        from_str(
+            r#"{ db: { queries: { users: "SELECT * FROM users", timeout: 30 }, host: "localhost" } }"#
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_include_into_mounts_array_and_scalar() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("hosts.cosy"), r#"["a.local", "b.local"]"#).unwrap();
+    fs::write(dir.path().join("version.json"), "\"1.2.3\"").unwrap();
+
+    let mut config = from_str(
+        r#"{
+        include: [
+            { path: "hosts.cosy", into: "cluster.hosts" }
+            { path: "version.json", into: "version" }
+        ]
+    }"#,
+    )
+    .unwrap();
+    let trace = include::resolve_traced(&mut config, dir.path()).unwrap();
+
+    assert_eq!(
+        config,
+        from_str(r#"{ cluster: { hosts: ["a.local", "b.local"] }, version: "1.2.3" }"#).unwrap()
+    );
+    let hosts_file = fs::canonicalize(dir.path().join("hosts.cosy")).unwrap();
+    assert_eq!(trace["cluster.hosts"].file, Some(hosts_file));
+
+    // Without `into`, a non-object file is still an error
+    let mut config = from_str(r#"{ include: "hosts.cosy" }"#).unwrap();
+    assert!(matches!(
+        include::resolve(&mut config, dir.path()).unwrap_err(),
+        include::IncludeError::IncludedNotObject { .. }
+    ));
+
+    let mut config = from_str(r#"{ include: { path: "hosts.cosy", into: "a..b" } }"#).unwrap();
+    assert_eq!(
+        include::resolve(&mut config, dir.path())
             .unwrap_err()
-            .to_string()
-            .contains("Recursion limit exceeded")
+            .to_string(),
+        "Invalid include usage: Include 'into' must be a dotted key path, found 'a..b'"
+    );
+}
+
+#[test]
+fn test_include_path_interpolates_env_vars() {
+    let dir = tempdir().unwrap();
+    let staging = dir.path().join("staging");
+    fs::create_dir(&staging).unwrap();
+    fs::write(staging.join("overrides.cosy"), "{ replicas: 2 }").unwrap();
+    fs::write(staging.join("limits.cosy"), "{ rps: 50 }").unwrap();
+    fs::write(
+        dir.path().join("app.cosy"),
+        r#"{ include: "${COSY_TEST_INCLUDE_ENV}/limits.cosy" }"#,
+    )
+    .unwrap();
+    // An escaped `$` is taken literally, not interpolated later
+    let literal = dir.path().join("${COSY_TEST_INCLUDE_ENV}");
+    fs::create_dir(&literal).unwrap();
+    fs::write(literal.join("overrides.cosy"), "{ literal: true }").unwrap();
+
+    unsafe {
+        std::env::set_var("COSY_TEST_INCLUDE_ENV", "staging");
+    }
+
+    // In the root document and in included files alike
+    let mut config =
+        from_str(r#"{ include: ["${COSY_TEST_INCLUDE_ENV}/overrides.cosy", "app.cosy"] }"#)
+            .unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(config, from_str("{ replicas: 2, rps: 50 }").unwrap());
+
+    let mut config =
+        from_str(r#"{ include: "\${COSY_TEST_INCLUDE_ENV}/overrides.cosy" }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(config, from_str("{ literal: true }").unwrap());
+
+    unsafe {
+        std::env::remove_var("COSY_TEST_INCLUDE_ENV");
+    }
+}
+
+#[test]
+fn test_include_path_missing_env_var_names_variable_and_file() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("app.cosy"),
+        r#"{ include: "${COSY_TEST_INCLUDE_MISSING}/overrides.cosy" }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(r#"{ include: "app.cosy" }"#).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert!(matches!(err, include::IncludeError::ParseError { .. }));
+    let message = err.to_string();
+    assert!(message.contains("COSY_TEST_INCLUDE_MISSING"), "{}", message);
+    assert!(message.contains("app.cosy"), "{}", message);
+}
+
+#[test]
+fn test_include_respects_max_input_bytes() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("small.cosy"), "{ a: 1 }").unwrap();
+    fs::write(
+        dir.path().join("big.cosy"),
+        format!("{{ blob: \"{}\" }}", "x".repeat(1000)),
+    )
+    .unwrap();
+
+    let options = include::ResolveOptions {
+        parse_options: cosy::ParseOptions {
+            max_input_bytes: Some(100),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut config = from_str(r#"{ include: "small.cosy" }"#).unwrap();
+    include::resolve_with_options(&mut config, dir.path(), &options).unwrap();
+
+    let mut config = from_str(r#"{ include: "big.cosy" }"#).unwrap();
+    let err = include::resolve_with_options(&mut config, dir.path(), &options).unwrap_err();
+    assert!(matches!(err, include::IncludeError::ParseError { .. }));
+    let message = err.to_string();
+    assert!(message.contains("big.cosy"), "{}", message);
+    assert!(message.contains("over the limit of 100"), "{}", message);
+}
+
+#[test]
+fn test_include_forwards_merge_options() {
+    use cosy::merge::{ArrayStrategy, MergeOptions};
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        r#"{ server: { plugins: ["auth", "log"] } }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("extra.cosy"),
+        r#"{ server: { plugins: ["log", "metrics"] } }"#,
+    )
+    .unwrap();
+    let source = r#"{
+        extends: "base.cosy"
+        include: "extra.cosy"
+        server: { plugins: ["trace"] }
+    }"#;
+
+    let resolved = |array_strategy| {
+        let options = include::ResolveOptions {
+            merge_options: MergeOptions {
+                array_strategy,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut config = from_str(source).unwrap();
+        include::resolve_with_options(&mut config, dir.path(), &options).unwrap();
+        config
+    };
+
+    assert_eq!(
+        resolved(ArrayStrategy::Replace),
+        from_str(r#"{ server: { plugins: ["trace"] } }"#).unwrap()
+    );
+    assert_eq!(
+        resolved(ArrayStrategy::Append),
+        from_str(r#"{ server: { plugins: ["auth", "log", "log", "metrics", "trace"] } }"#).unwrap()
+    );
+    assert_eq!(
+        resolved(ArrayStrategy::Unique),
+        from_str(r#"{ server: { plugins: ["auth", "log", "metrics", "trace"] } }"#).unwrap()
+    );
+}
+
+#[test]
+fn test_included_file_is_interpolated_before_merge() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        r#"{ server: { port: ${COSY_TEST_INCLUDE_PORT}, url: "http://db:${COSY_TEST_INCLUDE_PORT}" } }"#,
+    )
+    .unwrap();
+
+    unsafe {
+        std::env::set_var("COSY_TEST_INCLUDE_PORT", "8080");
+    }
+
+    // The including file overrides nothing, so the base's values survive
+    let mut config = from_str(r#"{ extends: "base.cosy", debug: true }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ server: { port: 8080, url: "http://db:8080" }, debug: true }"#).unwrap()
+    );
+
+    // A higher layer still overrides an interpolated default
+    let mut config = from_str(r#"{ include: "base.cosy", server: { port: 9090 } }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ server: { port: 9090, url: "http://db:8080" } }"#).unwrap()
+    );
+
+    unsafe {
+        std::env::remove_var("COSY_TEST_INCLUDE_PORT");
+    }
+}
+
+#[test]
+fn test_merge_directives_through_includes() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        r#"{
+            plugins: ["auth", "log"]
+            server: { host: "0.0.0.0", port: 80, tls: { enabled: false } }
+            limits: { rps: 100, burst: 10 }
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("extra.cosy"),
+        r#"{ plugins+: ["metrics"] }"#,
+    )
+    .unwrap();
+
+    let mut config = from_str(
+        r#"{
+            extends: "base.cosy"
+            include: "extra.cosy"
+            plugins+: ["trace"]
+            server!: { port: 8080 }
+            limits: { rps: 50 }
+        }"#,
+    )
+    .unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+
+    // `+` appends, `!` replaces, and plain keys still deep-merge
+    assert_eq!(
+        config,
+        from_str(
+            r#"{
+                plugins: ["auth", "log", "metrics", "trace"]
+                server: { port: 8080 }
+                limits: { rps: 50, burst: 10 }
+            }"#
+        )
+        .unwrap()
+    );
+    // The directives are gone from the result
+    assert_eq!(
+        cosy::to_string(&config),
+        cosy::to_string(
+            &from_str(
+                r#"{ plugins: ["auth", "log", "metrics", "trace"], server: { port: 8080 }, limits: { rps: 50, burst: 10 } }"#
+            )
+            .unwrap()
+        )
+    );
+
+    // Directives with nothing under them just set the value
+    let mut config = from_str(r#"{ include: "extra.cosy", extra!: { a: 1 } }"#).unwrap();
+    include::resolve(&mut config, dir.path()).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ plugins: ["metrics"], extra: { a: 1 } }"#).unwrap()
+    );
+}
+
+#[test]
+fn test_type_conflict_names_the_overriding_file() {
+    use cosy::merge::{MergeOptions, TypeConflict};
+
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("base.cosy"), "{ port: 8080 }").unwrap();
+    fs::write(dir.path().join("mixin.cosy"), r#"{ port: "yes please" }"#).unwrap();
+
+    let options = include::ResolveOptions {
+        merge_options: MergeOptions {
+            on_type_conflict: TypeConflict::Error,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut config = from_str(r#"{ extends: "base.cosy", include: "mixin.cosy" }"#).unwrap();
+    let err = include::resolve_with_options(&mut config, dir.path(), &options).unwrap_err();
+    let include::IncludeError::MergeConflict { file, error } = &err else {
+        panic!("unexpected error: {}", err);
+    };
+    assert_eq!(*file, Some(dir.path().join("mixin.cosy")));
+    assert_eq!(error.path, "port");
+    assert!(err.to_string().contains("mixin.cosy"), "{}", err);
+
+    // The including document's own keys are a layer too
+    let mut config = from_str(r#"{ extends: "base.cosy", port: [1] }"#).unwrap();
+    let err = include::resolve_with_options(&mut config, dir.path(), &options).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Type conflict at 'port': cannot override integer with array"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_resolve_with_warnings_reports_type_changes() {
+    use cosy::schema::{ValidationErrorKind, ValidationLevel};
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        "{ port: 8080, tls: { enabled: false }, ratio: 1 }",
+    )
+    .unwrap();
+    fs::write(dir.path().join("mixin.cosy"), "{ tls: \"off\" }").unwrap();
+
+    let mut config = from_str(
+        r#"{
+        server: {
+            extends: "base.cosy"
+            include: "mixin.cosy"
+            port: "auto"
+            ratio: 0.5
+        }
+    }"#,
+    )
+    .unwrap();
+    let warnings = include::resolve_with_warnings(&mut config, dir.path()).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ server: { port: "auto", tls: "off", ratio: 0.5 } }"#).unwrap()
+    );
+
+    let paths: Vec<_> = warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.server.tls", "$.server.port"]);
+    assert!(warnings.iter().all(|w| w.level == ValidationLevel::Warning));
+    assert_eq!(
+        warnings[0].kind,
+        ValidationErrorKind::TypeChanged {
+            from: "object".to_string(),
+            to: "string".to_string(),
+            file: Some(dir.path().join("mixin.cosy").display().to_string()),
+        }
+    );
+    assert_eq!(warnings[1].message, "Type changed from integer to string");
+
+    // Nothing to report without type changes, and `null` changes no type
+    let mut config = from_str(r#"{ extends: "base.cosy", port: 9090, tls: null }"#).unwrap();
+    let warnings = include::resolve_with_warnings(&mut config, dir.path()).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_include_self_is_circular() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("self.cosy");
+    fs::write(&path, r#"{ include: "self.cosy", port: 80 }"#).unwrap();
+    let canonical = fs::canonicalize(&path).unwrap();
+
+    let err = cosy::load::load_and_merge(&[path.as_path()]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Include error: Circular include: \"{}\" includes itself (loading \"{}\")",
+            canonical.display(),
+            path.display()
+        )
+    );
+
+    // Without the including file's path, the loop is caught on its first turn
+    let mut config = from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let err = include::resolve(&mut config, dir.path()).unwrap_err();
+    assert!(
+        matches!(&err, include::IncludeError::CircularInclude { path } if *path == canonical),
+        "{:?}",
+        err
     );
 }
@@ -0,0 +1,53 @@
+#![cfg(feature = "json")]
+
+use cosy::{Value, from_str};
+use serde_json::json;
+
+#[test]
+fn test_json_value_round_trip() {
+    let value = from_str(
+        r#"{
+        // comments are dropped
+        name: "service"
+        port: 8080
+        ratio: 0.75
+        debug: false
+        owner: null
+        database: { host: "db.local", replicas: [1, 2, { weight: 0.5 }] }
+    }"#,
+    )
+    .unwrap();
+
+    let json = serde_json::Value::from(&value);
+    assert_eq!(
+        json,
+        json!({
+            "name": "service",
+            "port": 8080,
+            "ratio": 0.75,
+            "debug": false,
+            "owner": null,
+            "database": { "host": "db.local", "replicas": [1, 2, { "weight": 0.5 }] }
+        })
+    );
+    // Key order is preserved
+    let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(
+        keys,
+        ["name", "port", "ratio", "debug", "owner", "database"]
+    );
+
+    // Comments are dropped, so compare the JSON projections
+    let back = Value::from(json.clone());
+    assert!(back.comments.is_empty() && back.position.is_none());
+    assert_eq!(serde_json::Value::from(back), json);
+}
+
+#[test]
+fn test_json_numbers_keep_integer_and_float_kinds() {
+    let value = Value::from(json!([1, -2, 1.0, 1e300]));
+    assert_eq!(value, from_str("[1, -2, 1.0, 1e300]").unwrap());
+
+    // JSON has no NaN or infinity
+    assert_eq!(serde_json::Value::from(Value::float(f64::NAN)), json!(null));
+}
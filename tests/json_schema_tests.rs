@@ -0,0 +1,202 @@
+#![cfg(feature = "json")]
+
+use cosy::schema::{self, from_json_schema, from_json_schema_checked, to_json_schema};
+use cosy::{Value, from_str};
+use serde_json::json;
+
+const SERVICE_SCHEMA: &str = r#"{
+    name: "string"
+    port: { type: "integer", min: 1, max: 65535 }
+    mode: { type: "string", enum: ["dev", "prod"], optional: true }
+    timeout: "integer | null"
+    tags: ["string"]
+    database: {
+        host: "string"
+        replicas: { type: [{ host: "string", weight: "number" }], optional: true }
+    }
+    labels: { type: { additionalProperties: "string" }, optional: true }
+}"#;
+
+#[test]
+fn test_to_json_schema() {
+    let schema = from_str(SERVICE_SCHEMA).unwrap();
+    let json = to_json_schema(&schema);
+
+    assert_eq!(
+        json,
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                "mode": { "type": "string", "enum": ["dev", "prod"] },
+                "timeout": { "type": ["integer", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "database": {
+                    "type": "object",
+                    "properties": {
+                        "host": { "type": "string" },
+                        "replicas": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "host": { "type": "string" },
+                                    "weight": { "type": "number" }
+                                },
+                                "required": ["host", "weight"],
+                                "additionalProperties": false
+                            }
+                        }
+                    },
+                    "required": ["host"],
+                    "additionalProperties": false
+                },
+                "labels": {
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": { "type": "string" }
+                }
+            },
+            "required": ["name", "port", "timeout", "tags", "database"],
+            "additionalProperties": false
+        })
+    );
+}
+
+#[test]
+fn test_cosy_json_schema_round_trip() {
+    let schema = from_str(SERVICE_SCHEMA).unwrap();
+    let back = from_json_schema(&to_json_schema(&schema)).unwrap();
+    assert_eq!(back, schema);
+
+    // The converted schema validates exactly like the original
+    let instance = from_str(
+        r#"{
+        name: "api"
+        port: 70000
+        mode: "staging"
+        timeout: null
+        tags: ["a"]
+        database: { host: "db", replicas: [{ host: "r1", weight: 0.5 }] }
+        labels: { team: "core", tier: 1 }
+    }"#,
+    )
+    .unwrap();
+    let original = schema::validate(&instance, &schema).unwrap();
+    let converted = schema::validate(&instance, &back).unwrap();
+    let paths: Vec<&str> = original.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.port", "$.mode", "$.labels.tier"]);
+    assert_eq!(
+        converted.iter().map(|i| &i.message).collect::<Vec<_>>(),
+        original.iter().map(|i| &i.message).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_json_schema_round_trip() {
+    let json = json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "size": { "anyOf": [{ "type": "integer" }, { "type": "string" }] },
+            "points": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": { "x": { "type": "number" }, "y": { "type": "number" } },
+                    "required": ["x", "y"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["id"],
+        "additionalProperties": false
+    });
+
+    let schema = from_json_schema(&json).unwrap();
+    assert_eq!(
+        schema,
+        from_str(
+            r#"{
+            id: "string"
+            size: { type: "integer | string", optional: true }
+            points: { type: [{ x: "number", y: "number" }], optional: true }
+        }"#
+        )
+        .unwrap()
+    );
+
+    // anyOf comes back as a type list
+    let mut expected = json.clone();
+    expected["properties"]["size"] = json!({ "type": ["integer", "string"] });
+    assert_eq!(to_json_schema(&schema), expected);
+}
+
+#[test]
+fn test_from_json_schema_collects_unsupported_keywords() {
+    let json = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Config",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "pattern": "^[a-z]+$" },
+            "retries": { "type": "integer", "exclusiveMinimum": 0 }
+        },
+        "required": ["name"]
+    });
+
+    let (schema, warnings) = from_json_schema_checked(&json).unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            "Unsupported keyword 'pattern' at #/properties/name",
+            "Unsupported keyword 'exclusiveMinimum' at #/properties/retries",
+        ]
+    );
+
+    // No additionalProperties means an open object in JSON Schema
+    let instance = from_str(r#"{ name: "x", extra: true }"#).unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_valid());
+}
+
+#[test]
+fn test_from_json_schema_errors() {
+    let err = from_json_schema(&json!({ "properties": { "a": { "type": "text" } } })).unwrap_err();
+    assert_eq!(err.schema_path, "#/properties/a");
+    assert_eq!(err.message, "Unknown type 'text'");
+
+    assert!(from_json_schema(&json!(false)).is_err());
+    assert_eq!(from_json_schema(&json!(true)).unwrap(), Value::from("any"));
+}
+
+#[test]
+fn test_nullable_to_json_schema() {
+    let schema = from_str(r#"{ type: "string?", optional: true }"#).unwrap();
+    assert_eq!(
+        to_json_schema(&schema),
+        json!({ "type": ["string", "null"] })
+    );
+
+    let schema = from_str(r#"{ type: "integer", nullable: true, min: 0 }"#).unwrap();
+    assert_eq!(
+        to_json_schema(&schema),
+        json!({ "type": ["integer", "null"], "minimum": 0 })
+    );
+}
+
+#[test]
+fn test_pattern_properties_json_schema() {
+    let schema = from_str(r#"{ patternProperties: { "^feature_": "boolean" } }"#).unwrap();
+    let json = to_json_schema(&schema);
+    assert_eq!(
+        json,
+        json!({
+            "type": "object",
+            "properties": {},
+            "patternProperties": { "^feature_": { "type": "boolean" } },
+            "additionalProperties": false
+        })
+    );
+    assert_eq!(from_json_schema(&json).unwrap(), schema);
+}
@@ -213,7 +213,7 @@ fn test_large_object_key_order() {
 
     if let ValueKind::Object(obj) = value.kind {
         let parsed_keys: Vec<&String> = obj.keys().collect();
-        let expected_keys: Vec<&str> = keys_in_order.iter().copied().collect();
+        let expected_keys: Vec<&str> = keys_in_order.to_vec();
         assert_eq!(parsed_keys, expected_keys);
     } else {
         panic!("Expected object");
@@ -235,7 +235,7 @@ fn test_duplicate_keys_last_wins() {
     let value = from_str(input).unwrap();
 
     if let ValueKind::Object(obj) = value.kind {
-        assert_eq!(obj.get("name"), Some(&Value::string("Third".to_string())));
+        assert_eq!(obj.get("name"), Some(&Value::string("Third")));
         // Only one "name" key should exist
         assert_eq!(obj.len(), 1);
     } else {
@@ -1,6 +1,18 @@
+use cosy::include::ResolveOptions;
+use cosy::load::{
+    ConfigBuilder, EnvOverlayOptions, LoadConfig, env_overrides, load_and_merge_collecting,
+    load_and_merge_reported, load_and_merge_resolved, load_and_merge_traced,
+    load_and_merge_with_options, load_with_env_overlay, load_with_env_overlay_options,
+    watch_and_reload,
+};
 use cosy::load_and_merge;
+use cosy::merge::{ArrayStrategy, MergeChange, MergeOptions};
 use cosy::value::{Value, ValueKind};
+use cosy::{DuplicateKeyPolicy, EmptyPolicy, ParseOptions, from_str};
 use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
 use tempfile::tempdir;
 
 #[test]
@@ -38,16 +50,324 @@ fn test_load_and_merge_nested() {
 
     if let ValueKind::Object(root) = config.kind {
         let server = root.get("server").unwrap().as_object().unwrap();
-        assert_eq!(
-            server.get("host"),
-            Some(&Value::string("localhost".to_string()))
-        );
+        assert_eq!(server.get("host"), Some(&Value::string("localhost")));
         assert_eq!(server.get("port"), Some(&Value::integer(443)));
     } else {
         panic!("Expected object");
     }
 }
 
+#[test]
+fn test_load_and_merge_with_array_strategies() {
+    let dir = tempdir().unwrap();
+    let p1 = dir.path().join("1.cosy");
+    let p2 = dir.path().join("2.cosy");
+
+    fs::write(
+        &p1,
+        r#"{ app: { features: ["a", "b"], matrix: [[1, 2]] }, hosts: ["x"] }"#,
+    )
+    .unwrap();
+    fs::write(
+        &p2,
+        r#"{ app: { features: ["b", "c"], matrix: [[1, 2], [3]] } }"#,
+    )
+    .unwrap();
+
+    let paths = [p1.as_path(), p2.as_path()];
+    let merged = |array_strategy| {
+        load_and_merge_with_options(
+            &paths,
+            &MergeOptions {
+                array_strategy,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        merged(ArrayStrategy::Replace),
+        load_and_merge(&paths).unwrap()
+    );
+    assert_eq!(
+        merged(ArrayStrategy::Replace),
+        from_str(r#"{ app: { features: ["b", "c"], matrix: [[1, 2], [3]] }, hosts: ["x"] }"#)
+            .unwrap()
+    );
+    assert_eq!(
+        merged(ArrayStrategy::Append),
+        from_str(
+            r#"{ app: { features: ["a", "b", "b", "c"], matrix: [[1, 2], [1, 2], [3]] }, hosts: ["x"] }"#
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        merged(ArrayStrategy::Unique),
+        from_str(r#"{ app: { features: ["a", "b", "c"], matrix: [[1, 2], [3]] }, hosts: ["x"] }"#)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_load_and_merge_applies_merge_directives_across_files() {
+    let dir = tempdir().unwrap();
+    let base = dir.path().join("base.cosy");
+    let local = dir.path().join("local.cosy");
+    fs::write(
+        &base,
+        r#"{ features: ["a"], db: { host: "db", pool: 10 } }"#,
+    )
+    .unwrap();
+    // The directives also apply to files loaded before this one
+    fs::write(
+        &local,
+        r#"{ features+: ["b"], db!: { url: "sqlite::memory:" } }"#,
+    )
+    .unwrap();
+
+    let schema = from_str(r#"{ features: ["string"], db: { url: "string" } }"#).unwrap();
+    let (config, report) = LoadConfig::new()
+        .with_schema(schema)
+        .load_files(&[&base, &local])
+        .unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ features: ["a", "b"], db: { url: "sqlite::memory:" } }"#).unwrap()
+    );
+    // Keys are validated without their suffix
+    assert!(report.is_empty(), "{:?}", report);
+}
+
+#[test]
+fn test_load_and_merge_resolved_includes() {
+    let dir = tempdir().unwrap();
+    let shared = dir.path().join("shared");
+    fs::create_dir(&shared).unwrap();
+
+    // Included relative to base.cosy's own directory
+    fs::write(
+        shared.join("defaults.cosy"),
+        r#"{ timeout: 30, server: { host: "0.0.0.0" } }"#,
+    )
+    .unwrap();
+    fs::write(
+        shared.join("base.cosy"),
+        r#"{ include: "defaults.cosy", server: { port: 80 } }"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("env.cosy"), r#"{ server: { port: 8080 } }"#).unwrap();
+    fs::write(dir.path().join("local.cosy"), r#"{ debug: true }"#).unwrap();
+
+    let paths = [
+        shared.join("base.cosy"),
+        dir.path().join("env.cosy"),
+        dir.path().join("local.cosy"),
+    ];
+    let config = load_and_merge_resolved(&paths).unwrap();
+
+    let root = config.as_object().unwrap();
+    assert_eq!(root.get("timeout"), Some(&Value::integer(30))); // From include
+    assert_eq!(root.get("debug"), Some(&Value::boolean(true)));
+    assert!(!root.contains_key("include"));
+
+    let server = root.get("server").unwrap().as_object().unwrap();
+    assert_eq!(server.get("host"), Some(&Value::from("0.0.0.0"))); // From include
+    assert_eq!(server.get("port"), Some(&Value::integer(8080))); // Overridden
+
+    // Top-level files and their includes, in load order
+    let (collected, files) = load_and_merge_collecting(&paths).unwrap();
+    assert_eq!(collected, config);
+    let canonical = |p: PathBuf| fs::canonicalize(p).unwrap();
+    assert_eq!(
+        files,
+        vec![
+            canonical(shared.join("base.cosy")),
+            canonical(shared.join("defaults.cosy")),
+            canonical(dir.path().join("env.cosy")),
+            canonical(dir.path().join("local.cosy")),
+        ]
+    );
+}
+
+#[test]
+fn test_load_and_merge_traced() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        "{\n  server: { host: \"0.0.0.0\", port: 8080 }\n  cache: { size: 10 }\n}",
+    )
+    .unwrap();
+    fs::write(dir.path().join("mixin.cosy"), "{ server: { port: 9000 } }").unwrap();
+    fs::write(
+        dir.path().join("app.cosy"),
+        "{\n  extends: \"base.cosy\"\n  include: \"mixin.cosy\"\n  log: \"debug\"\n}",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("local.cosy"),
+        "{\n  server: { tls: true }\n  cache: \"off\"\n}",
+    )
+    .unwrap();
+
+    let paths = [dir.path().join("app.cosy"), dir.path().join("local.cosy")];
+    let (config, trace) = load_and_merge_traced(&paths).unwrap();
+    assert_eq!(config, load_and_merge_resolved(&paths).unwrap());
+
+    let file = |name: &str| Some(fs::canonicalize(dir.path().join(name)).unwrap());
+    assert_eq!(trace["server.host"].file, file("base.cosy"));
+    assert_eq!(trace["server.host"].line, Some(2));
+    assert_eq!(trace["server.port"].file, file("mixin.cosy"));
+    assert_eq!(trace["log"].file, file("app.cosy"));
+    assert_eq!(trace["log"].line, Some(4));
+    assert_eq!(trace["server.tls"].file, file("local.cosy"));
+
+    // `cache` was replaced wholesale, so base's `cache.size` is gone
+    assert_eq!(trace["cache"].file, file("local.cosy"));
+    assert!(!trace.contains_key("cache.size"));
+    assert_eq!(trace.len(), 5);
+}
+
+#[test]
+fn test_load_and_merge_reported() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("base.cosy"),
+        "{ server: { host: \"0.0.0.0\", port: 8080 } }",
+    )
+    .unwrap();
+    fs::write(dir.path().join("shared.cosy"), "{ log: \"info\" }").unwrap();
+    fs::write(
+        dir.path().join("local.cosy"),
+        "{\n  include: \"shared.cosy\"\n  server: { host: \"0.0.0.0\", port: 9000 }\n}",
+    )
+    .unwrap();
+
+    let paths = [dir.path().join("base.cosy"), dir.path().join("local.cosy")];
+    let (config, layers) = load_and_merge_reported(&paths).unwrap();
+    assert_eq!(config, load_and_merge_resolved(&paths).unwrap());
+
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].0, paths[0]);
+    assert!(matches!(
+        layers[0].1.get("server"),
+        Some(MergeChange::Added { .. })
+    ));
+
+    // The included file counts as part of the layer including it
+    let (path, report) = &layers[1];
+    assert_eq!(*path, paths[1]);
+    assert_eq!(
+        report.to_string(),
+        "overrode server.port: 8080 -> 9000\nadded log = \"info\""
+    );
+}
+
+#[test]
+fn test_load_config_type_conflicts() {
+    use cosy::merge::TypeConflict;
+
+    let dir = tempdir().unwrap();
+    let base = dir.path().join("base.cosy");
+    let local = dir.path().join("local.cosy");
+    fs::write(&base, "{ port: 8080, host: \"a\" }").unwrap();
+    fs::write(&local, "{ port: \"yes please\", host: \"b\" }").unwrap();
+
+    let load = |on_type_conflict| {
+        LoadConfig::new()
+            .with_merge_options(MergeOptions {
+                on_type_conflict,
+                ..Default::default()
+            })
+            .load_files(&[&base, &local])
+            .map(|(config, _)| config)
+    };
+
+    let err = load(TypeConflict::Error).unwrap_err();
+    assert!(matches!(err, cosy::CosynError::Merge(_)));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Merge error: Type conflict at 'port': cannot override integer with string (overridden in \"{}\")",
+            local.display()
+        )
+    );
+    assert_eq!(
+        load(TypeConflict::KeepBase).unwrap(),
+        from_str("{ port: 8080, host: \"b\" }").unwrap()
+    );
+    assert_eq!(
+        load(TypeConflict::Override).unwrap(),
+        load_and_merge(&[base.as_path(), local.as_path()]).unwrap()
+    );
+}
+
+#[test]
+fn test_load_config_pipeline() {
+    let dir = tempdir().unwrap();
+    let config_dir = dir.path().join("config");
+    fs::create_dir(&config_dir).unwrap();
+    fs::write(config_dir.join("shared.cosy"), "{ timeout: 30 }").unwrap();
+    fs::write(
+        config_dir.join("app.cosy"),
+        r#"{ include: "shared.cosy", port: "8080" }"#,
+    )
+    .unwrap();
+    fs::write(config_dir.join("local.cosy"), "// nothing yet\n").unwrap();
+    fs::write(config_dir.join("dup.cosy"), "{ a: 1, a: 2 }").unwrap();
+    fs::write(
+        config_dir.join("uses_dup.cosy"),
+        r#"{ include: "dup.cosy" }"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("secret.cosy"), "{ key: 1 }").unwrap();
+    fs::write(
+        config_dir.join("escape.cosy"),
+        r#"{ include: "../secret.cosy" }"#,
+    )
+    .unwrap();
+
+    let loader = LoadConfig::new()
+        .with_parse_options(ParseOptions {
+            duplicate_key: DuplicateKeyPolicy::Error,
+            empty_as: EmptyPolicy::EmptyObject,
+            ..Default::default()
+        })
+        .with_include_options(ResolveOptions {
+            root: Some(config_dir.clone()),
+            allow_absolute: false,
+            max_depth: 2,
+            ..ResolveOptions::default()
+        })
+        .with_schema(from_str(r#"{ timeout: "integer", port: "port" }"#).unwrap());
+
+    // Parse (empty file allowed), resolve, merge and validate
+    let paths = [config_dir.join("app.cosy"), config_dir.join("local.cosy")];
+    let (config, report) = loader.load_files(&paths).unwrap();
+    let root = config.as_object().unwrap();
+    assert_eq!(root.get("timeout"), Some(&Value::integer(30)));
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.port");
+
+    // Parse options also apply to included files
+    let err = loader
+        .load_file(config_dir.join("uses_dup.cosy"))
+        .unwrap_err();
+    assert!(err.message().contains("Duplicate key 'a'"), "{}", err);
+
+    // Include options are honored
+    let err = loader
+        .load_file(config_dir.join("escape.cosy"))
+        .unwrap_err();
+    assert!(matches!(err, cosy::CosynError::Include(_)));
+
+    // Without a schema the report is empty
+    let (_, report) = LoadConfig::new()
+        .load_file(config_dir.join("app.cosy"))
+        .unwrap();
+    assert!(report.is_empty());
+}
+
 pub trait ValueExt {
     fn as_object(&self) -> Option<&indexmap::IndexMap<String, Value>>;
 }
@@ -60,3 +380,313 @@ impl ValueExt for Value {
         }
     }
 }
+
+#[test]
+fn test_load_include_error_names_top_level_file() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.cosy");
+    fs::write(&main, r#"{ include: "db.cosy" }"#).unwrap();
+    fs::write(dir.path().join("db.cosy"), "{ port: }").unwrap();
+
+    let err = load_and_merge_resolved(&[&main]).unwrap_err();
+    let main = fs::canonicalize(&main).unwrap();
+    assert!(
+        err.to_string()
+            .contains(&format!("(included from \"{}\")", main.display())),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_watch_and_reload_calls_back_on_change() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("app.cosy");
+    fs::write(&path, "{ port: 8080 }").unwrap();
+
+    let interval = Duration::from_millis(100);
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = watch_and_reload(&[&path], interval, move |result| {
+        tx.send(result).unwrap();
+    });
+
+    // Nothing changed yet
+    assert!(rx.recv_timeout(interval * 2).is_err());
+
+    fs::write(&path, "{ port: 9090 }").unwrap();
+    let reloaded = rx.recv_timeout(interval * 2).unwrap().unwrap();
+    assert_eq!(reloaded, from_str("{ port: 9090 }").unwrap());
+
+    // A broken file is reported through the callback too. Pause so the new
+    // modification time differs even on coarse-grained filesystems.
+    std::thread::sleep(Duration::from_millis(20));
+    fs::write(&path, "{ port: }").unwrap();
+    assert!(rx.recv_timeout(interval * 2).unwrap().is_err());
+
+    watcher.stop();
+    std::thread::sleep(Duration::from_millis(20));
+    fs::write(&path, "{ port: 1 }").unwrap();
+    assert!(rx.recv_timeout(interval * 2).is_err());
+}
+
+#[test]
+fn test_load_and_merge_resolves_each_files_includes() {
+    let dir = tempdir().unwrap();
+    let base = dir.path().join("base");
+    let local = dir.path().join("local");
+    fs::create_dir_all(&base).unwrap();
+    fs::create_dir_all(&local).unwrap();
+    fs::write(
+        base.join("logging.cosy"),
+        r#"{ logging: { level: "info" } }"#,
+    )
+    .unwrap();
+    fs::write(
+        base.join("app.cosy"),
+        r#"{ include: "logging.cosy", port: 80 }"#,
+    )
+    .unwrap();
+    fs::write(
+        local.join("debug.cosy"),
+        r#"{ logging: { level: "debug" } }"#,
+    )
+    .unwrap();
+    fs::write(local.join("app.cosy"), r#"{ include: "debug.cosy" }"#).unwrap();
+
+    let paths = [base.join("app.cosy"), local.join("app.cosy")];
+    let paths: Vec<_> = paths.iter().map(PathBuf::as_path).collect();
+    let config = load_and_merge(&paths).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ logging: { level: "debug" }, port: 80 }"#).unwrap()
+    );
+
+    // Opting out keeps the directives as plain keys
+    let (config, _) = LoadConfig::new()
+        .with_resolve_includes(false)
+        .load_files(&paths)
+        .unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ include: "debug.cosy", port: 80 }"#).unwrap()
+    );
+
+    // Include errors name the listed file they came from
+    fs::write(local.join("app.cosy"), r#"{ include: "missing.cosy" }"#).unwrap();
+    let err = load_and_merge(&paths).unwrap_err();
+    assert!(matches!(err, cosy::CosynError::Include(_)));
+    assert!(
+        err.to_string().ends_with(&format!(
+            "(loading \"{}\")",
+            local.join("app.cosy").display()
+        )),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_load_with_env_overlay() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("app.cosy");
+    fs::write(
+        &path,
+        r#"{ server: { host: "localhost", port: 8080 }, debug: false }"#,
+    )
+    .unwrap();
+    unsafe {
+        std::env::set_var("COSY_OVERLAY_SERVER_PORT", "9000");
+        std::env::set_var("COSY_OVERLAY_DEBUG", "true");
+        std::env::set_var("COSY_OVERLAY_DB_URL", "postgres://db/app");
+        std::env::set_var("COSY_OVERLAYS_IGNORED", "1");
+    }
+
+    let config = load_with_env_overlay(&path, "COSY_OVERLAY").unwrap();
+    assert_eq!(
+        config,
+        from_str(
+            r#"{
+                server: { host: "localhost", port: 9000 }
+                debug: true
+                db: { url: "postgres://db/app" }
+            }"#
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_load_with_env_overlay_infers_like_env_overrides() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("app.cosy");
+    fs::write(&path, "{}").unwrap();
+    unsafe {
+        std::env::set_var("COSY_INFER_TABLE", "{a:1}");
+        std::env::set_var("COSY_INFER_HOME", "${HOME}");
+        std::env::set_var("COSY_INFER_RATIO", "0.5");
+    }
+
+    // Values are never parsed as COSY documents
+    let config = load_with_env_overlay(&path, "COSY_INFER").unwrap();
+    let expected: Value = [
+        ("home", Value::string("${HOME}")),
+        ("ratio", Value::float(0.5)),
+        ("table", Value::string("{a:1}")),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(config, expected);
+    assert_eq!(env_overrides("COSY_INFER_"), expected);
+}
+
+#[test]
+fn test_load_with_env_overlay_options() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("app.cosy");
+    fs::write(&path, r#"{ Server: { Port: 8080 } }"#).unwrap();
+    unsafe {
+        std::env::set_var("CosyCase.Server.Port", "8443");
+        std::env::set_var("COSYCASE.Server.Host", "example.com");
+    }
+
+    let options = EnvOverlayOptions {
+        case_sensitive: true,
+        separator: '.',
+    };
+    let config = load_with_env_overlay_options(&path, "CosyCase", &options).unwrap();
+    assert_eq!(config, from_str(r#"{ Server: { Port: 8443 } }"#).unwrap());
+
+    let options = EnvOverlayOptions {
+        separator: '.',
+        ..Default::default()
+    };
+    let config = load_with_env_overlay_options(&path, "cosycase", &options).unwrap();
+    assert_eq!(
+        config,
+        from_str(r#"{ Server: { Port: 8080 }, server: { port: 8443, host: "example.com" } }"#)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_config_builder_skips_missing_optional_file() {
+    let dir = tempdir().unwrap();
+    let system = dir.path().join("system.cosy");
+    fs::write(&system, "{ port: 81 }").unwrap();
+
+    let builder = ConfigBuilder::new()
+        .add_defaults_str(r#"{ host: "localhost", port: 80 }"#)
+        .add_file(&system)
+        .add_file_optional(dir.path().join("missing.cosy"));
+    assert_eq!(
+        builder.build().unwrap(),
+        from_str(r#"{ host: "localhost", port: 81 }"#).unwrap()
+    );
+
+    // A required file must exist
+    let err = builder
+        .add_file(dir.path().join("missing.cosy"))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, cosy::CosynError::Io(_)));
+}
+
+#[test]
+fn test_config_builder_precedence() {
+    let dir = tempdir().unwrap();
+    let user = dir.path().join("user.cosy");
+    fs::write(&user, "{ port: 81, level: \"warn\", name: \"user\" }").unwrap();
+    unsafe {
+        std::env::set_var("COSY_BUILDER_PORT", "82");
+        std::env::set_var("COSY_BUILDER_LEVEL", "debug");
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Config {
+        port: u16,
+        level: String,
+        name: String,
+    }
+
+    let builder = ConfigBuilder::new()
+        .add_defaults(from_str(r#"{ port: 80, level: "info", name: "app" }"#).unwrap())
+        .add_file(&user)
+        .add_env_prefix("COSY_BUILDER_")
+        .add_overrides(from_str(r#"{ level: "trace" }"#).unwrap());
+    // Files override defaults, the environment overrides files, and
+    // explicit overrides win
+    assert_eq!(
+        builder.build_into::<Config>().unwrap(),
+        Config {
+            port: 82,
+            level: "trace".to_string(),
+            name: "user".to_string(),
+        }
+    );
+
+    let err = builder
+        .with_schema(from_str(r#"{ port: "port", level: "integer", name: "string" }"#).unwrap())
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, cosy::CosynError::Validation(_)));
+    assert!(err.to_string().contains("$.level"), "{}", err);
+}
+
+#[test]
+fn test_env_overrides_nest_and_infer_types() {
+    unsafe {
+        std::env::set_var("COSY_ENVOVR_SERVER__PORT", "9000");
+        std::env::set_var("COSY_ENVOVR_SERVER__TLS__ENABLED", "true");
+        std::env::set_var("COSY_ENVOVR_LOG_LEVEL", "debug");
+        std::env::set_var("COSY_ENVOVR_RATIO", "0.5");
+        std::env::set_var("COSY_ENVOVR_PROXY", "null");
+        std::env::set_var("COSY_ENVOVR_LIST", "[1, 2]");
+    }
+
+    assert_eq!(
+        env_overrides("COSY_ENVOVR_"),
+        from_str(
+            r#"{
+                list: "[1, 2]"
+                log_level: "debug"
+                proxy: null
+                ratio: 0.5
+                server: { port: 9000, tls: { enabled: true } }
+            }"#
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        env_overrides("COSY_ENVOVR_NOTHING"),
+        from_str("{}").unwrap()
+    );
+}
+
+#[test]
+fn test_env_overrides_replace_non_object_values() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("app.cosy");
+    fs::write(
+        &path,
+        r#"{ server: "localhost:80", debug: { verbose: true } }"#,
+    )
+    .unwrap();
+    unsafe {
+        std::env::set_var("COSY_ENVCOLLIDE_SERVER__PORT", "9000");
+        std::env::set_var("COSY_ENVCOLLIDE_DEBUG", "false");
+    }
+
+    let expected = from_str(r#"{ server: { port: 9000 }, debug: false }"#).unwrap();
+    let (config, _) = LoadConfig::new()
+        .with_env_overrides("COSY_ENVCOLLIDE")
+        .load_file(&path)
+        .unwrap();
+    assert_eq!(config, expected);
+
+    let config = ConfigBuilder::new()
+        .add_file(&path)
+        .add_env_overrides("COSY_ENVCOLLIDE_")
+        .build()
+        .unwrap();
+    assert_eq!(config, expected);
+}
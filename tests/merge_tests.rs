@@ -53,7 +53,7 @@ fn test_repro_shallow_merge() {
                 // Deep Merge: Host MUST be present
                 assert_eq!(
                     host,
-                    Some(&Value::string("0.0.0.0".to_string())),
+                    Some(&Value::string("0.0.0.0")),
                     "Expected host to be preserved"
                 );
             } else {
@@ -120,10 +120,7 @@ fn test_deep_merge_complex() {
         let db = root.get("database").unwrap().as_object().unwrap();
 
         // Host preserved
-        assert_eq!(
-            db.get("host"),
-            Some(&Value::string("localhost".to_string()))
-        );
+        assert_eq!(db.get("host"), Some(&Value::string("localhost")));
         // Port overridden
         assert_eq!(db.get("port"), Some(&Value::integer(6000)));
 
@@ -135,15 +132,12 @@ fn test_deep_merge_complex() {
 
         let logging = root.get("logging").unwrap().as_object().unwrap();
         // Level preserved (from base)
-        assert_eq!(
-            logging.get("level"),
-            Some(&Value::string("info".to_string()))
-        );
+        assert_eq!(logging.get("level"), Some(&Value::string("info")));
 
         let outputs = logging.get("outputs").unwrap().as_array().unwrap();
         // Array REPLACED (not merged)
         assert_eq!(outputs.len(), 1);
-        assert_eq!(outputs[0], Value::string("file".to_string()));
+        assert_eq!(outputs[0], Value::string("file"));
     } else {
         panic!("Root not object");
     }
@@ -168,3 +162,29 @@ impl ValueExt for Value {
         }
     }
 }
+
+#[test]
+fn test_merge_all_matches_sequential_merges() {
+    let layers = [
+        r#"{ server: { host: "localhost", port: 80 }, tags: ["a"] }"#,
+        r#"{ server: { port: 8080 }, debug: true }"#,
+        r#"{ server: { host: "0.0.0.0" }, tags: ["b", "c"] }"#,
+    ]
+    .map(|text| from_str(text).unwrap());
+
+    let mut sequential = layers[0].clone();
+    cosy::merge(&mut sequential, layers[1].clone());
+    cosy::merge(&mut sequential, layers[2].clone());
+
+    assert_eq!(cosy::merge_all(layers.clone()), sequential);
+    assert_eq!(layers[0].merged(&layers[1]).merged(&layers[2]), sequential);
+    // merged() leaves the base alone
+    assert_eq!(
+        layers[0],
+        from_str(r#"{ server: { host: "localhost", port: 80 }, tags: ["a"] }"#).unwrap()
+    );
+    assert_eq!(
+        cosy::merge_all(Vec::new()),
+        Value::from(ValueKind::Object(Default::default()))
+    );
+}
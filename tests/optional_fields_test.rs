@@ -13,7 +13,7 @@ fn test_optional_field_support() {
     let config_missing = from_str(r#"{ required_field: "present" }"#).unwrap();
     let report = schema::validate(&config_missing, &schema).unwrap();
     assert!(
-        report.is_empty(),
+        report.is_valid(),
         "Optional field should be allowed to be missing"
     );
 
@@ -30,7 +30,7 @@ fn test_optional_field_support() {
     let config_invalid = from_str(r#"{ required_field: "present", optional_field: 123 }"#).unwrap();
     let report_3 = schema::validate(&config_invalid, &schema).unwrap();
     assert!(
-        !report_3.is_empty(),
+        !report_3.is_valid(),
         "Optional field should still check type when present"
     );
 }
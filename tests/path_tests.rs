@@ -0,0 +1,157 @@
+use cosy::Value;
+use cosy::path::{FilterExpr, PathQuery, PathSegment};
+use cosy::value::ValueKind;
+
+fn users() -> Value {
+    cosy::from_str(
+        r#"{
+            users: [
+                { name: "ada", age: 36, role: "admin" }
+                { name: "kit", age: 12, role: "guest" }
+                { name: "lin", age: 19.5, role: "user" }
+                { name: "sam", role: "user" }
+            ]
+        }"#,
+    )
+    .unwrap()
+}
+
+fn names(query: &str) -> Vec<String> {
+    let users = users();
+    PathQuery::compile(query)
+        .unwrap()
+        .query(&users)
+        .into_iter()
+        .filter_map(|v| match &v.kind {
+            ValueKind::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_query_filter_by_number() {
+    assert_eq!(names("$.users[?(@.age > 18)].name"), ["ada", "lin"]);
+    assert_eq!(names("$.users[?(@.age < 19.5)].name"), ["kit"]);
+    // Integers and floats compare as numbers; a missing field never matches
+    assert_eq!(names("$.users[?(@.age == 36.0)].name"), ["ada"]);
+    assert_eq!(names("$.users[?(@.age != 36)].name"), ["kit", "lin"]);
+}
+
+#[test]
+fn test_query_filter_by_string() {
+    assert_eq!(
+        names(r#"$.users[?(@.role == "user")].name"#),
+        ["lin", "sam"]
+    );
+    assert_eq!(names(r#"users[?(@["name"] > "kit")].name"#), ["lin", "sam"]);
+}
+
+#[test]
+fn test_query_and_or() {
+    assert_eq!(
+        names(r#"$.users[?(@.role == "user" && @.age > 18)].name"#),
+        ["lin"]
+    );
+    assert_eq!(
+        names(r#"$.users[?(@.role == "admin" || @.age < 18)].name"#),
+        ["ada", "kit"]
+    );
+    // && binds tighter than ||, unless parenthesized
+    assert_eq!(
+        names(r#"$.users[?(@.age < 18 || @.role == "user" && @.age > 18)].name"#),
+        ["kit", "lin"]
+    );
+    assert_eq!(
+        names(r#"$.users[?((@.age < 18 || @.role == "user") && @.age > 18)].name"#),
+        ["lin"]
+    );
+
+    let query = PathQuery::compile("$.users[?(@.age > 1 && @.age < 2)]").unwrap();
+    let expected = FilterExpr::And(
+        Box::new(FilterExpr::Gt(
+            PathSegment::Key("age".to_string()),
+            Value::from(1),
+        )),
+        Box::new(FilterExpr::Lt(
+            PathSegment::Key("age".to_string()),
+            Value::from(2),
+        )),
+    );
+    assert_eq!(
+        query.steps.last(),
+        Some(&cosy::path::QueryStep::Filter(expected))
+    );
+}
+
+#[test]
+fn test_query_wildcards() {
+    assert_eq!(names("$.users[*].name"), ["ada", "kit", "lin", "sam"]);
+    assert_eq!(names("$.users[0].*"), ["ada", "admin"]);
+    assert!(names("$.missing[*]").is_empty());
+}
+
+#[test]
+fn test_query_errors() {
+    for (query, offset) in [
+        ("$.users[?(@.age >> 18)]", 17),
+        ("$.users[?(@.age > 18]", 20),
+        ("$.users[?(age > 18)]", 10),
+        ("$.users[?(@.name == admin)]", 20),
+        ("$.users[x]", 8),
+    ] {
+        let error = PathQuery::compile(query).unwrap_err();
+        assert_eq!(error.offset, offset, "{}: {}", query, error);
+    }
+}
+
+#[test]
+fn test_diff_and_merge_paths_escape_keys() {
+    use cosy::merge::{MergeOptions, TypeConflict, merge_with_options};
+
+    let before = cosy::from_str(r#"{ "a.b": { "c[0]": 1 }, hosts: ["x"] }"#).unwrap();
+    let after = cosy::from_str(r#"{ "a.b": { "c[0]": 2 }, hosts: ["y"] }"#).unwrap();
+    let changed: Vec<String> = cosy::compute_diff(&before, &after)
+        .iter()
+        .filter(|c| !matches!(c, cosy::diff::Change::Unchanged(..)))
+        .map(|c| c.path().to_string())
+        .collect();
+    assert_eq!(changed, vec![r#"["a.b"]["c[0]"]"#, "hosts[0]"]);
+    for path in &changed {
+        assert!(after.pointer(path).is_some(), "{}", path);
+    }
+
+    let mut base = before.clone();
+    let options = MergeOptions {
+        on_type_conflict: TypeConflict::Error,
+        ..Default::default()
+    };
+    let err = merge_with_options(
+        &mut base,
+        cosy::from_str(r#"{ "a.b": { "c[0]": "two" } }"#).unwrap(),
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(err.path, r#"["a.b"]["c[0]"]"#);
+    assert!(before.pointer(&err.path).is_some());
+}
+
+#[test]
+fn test_closest_path_corrects_keys() {
+    use cosy::path::ValuePath;
+
+    let users = users();
+    let closest = |path: &str| {
+        ValuePath::parse(path)
+            .unwrap()
+            .closest(&users)
+            .map(|p| p.to_string())
+    };
+    assert_eq!(closest("$.usrs[1].rol").as_deref(), Some("$.users[1].role"));
+    assert_eq!(
+        closest("$.users[0].name").as_deref(),
+        Some("$.users[0].name")
+    );
+    assert_eq!(closest("$.users[9].name"), None);
+    assert_eq!(closest("$.accounts"), None);
+}
@@ -0,0 +1,76 @@
+use cosy::convert::{ConvertError, from_properties_str, to_properties_string};
+use cosy::from_str;
+
+const APP: &str = include_str!("fixtures/properties/app.properties");
+
+#[test]
+fn test_properties_round_trip() {
+    let value = from_properties_str(APP).unwrap();
+    assert_eq!(to_properties_string(&value).unwrap(), APP);
+}
+
+#[test]
+fn test_properties_nesting_and_escapes() {
+    let value = from_properties_str(APP).unwrap();
+    let expected = from_str(
+        r#"{
+        app: { name: "My App", version: "1.2.0" }
+        server: { host: "0.0.0.0", port: "8080", url: "http://example.com:8080/path?a=b" }
+        db: { hosts: ["alpha", "beta"], pool: { max: "20" } }
+        paths: { windows: "C:\\Users\\cosy" }
+        message: "line one\nline two"
+    }"#,
+    )
+    .unwrap();
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn test_properties_separators_comments_and_continuations() {
+    let loose = from_properties_str(include_str!("fixtures/properties/loose.properties")).unwrap();
+    assert_eq!(loose, from_properties_str(APP).unwrap());
+}
+
+#[test]
+fn test_to_properties_scalars() {
+    let value = from_str(
+        r#"{
+        // Comments are dropped
+        enabled: true
+        ratio: 0.5
+        count: 3
+        missing: null
+        " key": " padded"
+    }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        to_properties_string(&value).unwrap(),
+        "enabled=true\nratio=0.5\ncount=3\nmissing=\n\\ key=\\ padded\n"
+    );
+}
+
+#[test]
+fn test_to_properties_cannot_flatten() {
+    let err = to_properties_string(&from_str("{ a: { b: [] } }").unwrap()).unwrap_err();
+    assert_eq!(
+        err,
+        ConvertError::CannotFlatten {
+            key: "a.b".to_string(),
+            found: "array"
+        }
+    );
+
+    let err = to_properties_string(&from_str("[1, 2]").unwrap()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot flatten a root array into properties"
+    );
+}
+
+#[test]
+fn test_properties_conflicting_keys() {
+    let err = from_properties_str("a=1\na.b=2\n").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert!(err.message().contains("'a.b'"));
+}
@@ -1,5 +1,6 @@
-use cosy::schema::ValidationLevel;
-use cosy::{Value, from_str, schema};
+use cosy::schema::{SchemaErrorKind, ValidationErrorKind, ValidationLevel};
+use cosy::{CosynError, Schema, Value, from_str, schema};
+use std::time::Instant;
 
 #[test]
 fn test_validate_basic_types() {
@@ -18,6 +19,14 @@ fn test_validate_basic_types() {
     assert_eq!(report_invalid.len(), 1);
     assert_eq!(report_invalid[0].level, ValidationLevel::Error);
     assert!(report_invalid[0].message.contains("Type mismatch"));
+    assert_eq!(
+        report_invalid[0].kind,
+        ValidationErrorKind::TypeMismatch {
+            expected: "integer".to_string(),
+            actual: "string".to_string(),
+        }
+    );
+    assert!(!report_invalid.is_valid());
 }
 
 #[test]
@@ -59,6 +68,10 @@ fn test_validate_missing_field() {
             .any(|i| i.message.contains("Missing required field"))
     );
     assert!(report.iter().any(|i| i.message.contains("Unknown field")));
+    assert!(report.iter().any(|i| i.kind
+        == ValidationErrorKind::MissingField {
+            field: "required".to_string()
+        }));
 }
 
 #[test]
@@ -123,6 +136,13 @@ fn test_typo_suggestion() {
         .find(|i| i.message.contains("Unknown field"))
         .unwrap();
     assert!(unknown_err.message.contains("did you mean 'port'?"));
+    assert_eq!(
+        unknown_err.kind,
+        ValidationErrorKind::UnknownField {
+            field: "prt".to_string(),
+            suggestion: Some("port".to_string()),
+        }
+    );
 }
 
 #[test]
@@ -151,4 +171,1520 @@ fn test_deprecation_warning() {
             .message
             .contains("Deprecated usage: Use 'port' instead")
     );
+    assert!(report.is_valid());
+    assert_eq!(report.warnings().count(), 1);
+    assert_eq!(report.errors().count(), 0);
+}
+
+#[test]
+fn test_info_annotation() {
+    let schema: Value = from_str(
+        r#"{
+        host: "string"
+        debug: { type: "boolean", optional: true, info: "consider disabling in production" }
+    }"#,
+    )
+    .unwrap();
+
+    let instance = from_str(r#"{ host: "localhost", debug: true }"#).unwrap();
+    let report = schema::validate(&instance, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].level, ValidationLevel::Info);
+    assert_eq!(report[0].path, "$.debug");
+    assert_eq!(
+        report[0].to_string(),
+        "[Info at $.debug, line 1, column 29] consider disabling in production"
+    );
+    assert!(!report.has_errors());
+    assert!(report.is_valid());
+    assert_eq!(report.infos().count(), 1);
+    assert_eq!(report.warnings().count(), 0);
+
+    // Absent fields are not annotated
+    let instance = from_str(r#"{ host: "localhost" }"#).unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_empty());
+}
+
+#[test]
+fn test_validate_strict_ignores_info_by_default() {
+    let schema: Value =
+        from_str(r#"{ debug: { type: "boolean", info: "disable in production" } }"#).unwrap();
+    let instance = from_str("{ debug: true }").unwrap();
+
+    assert!(schema::validate_strict(&instance, &schema).is_ok());
+
+    let options = schema::StrictOptions {
+        errors_and_warnings_only: false,
+    };
+    let err = schema::validate_strict_with_options(&instance, &schema, &options).unwrap_err();
+    assert_eq!(err.level, ValidationLevel::Info);
+    assert_eq!(err.path, "$.debug");
+}
+
+#[test]
+fn test_structured_deprecation_warning() {
+    let schema: Value = from_str(
+        r#"{
+        ssl: { type: "boolean", deprecated: { since: "2.0", use: "tls.enabled", note: "TLS settings moved" } }
+        tls: { type: "any", optional: true }
+    }"#,
+    )
+    .unwrap();
+    let instance = from_str("{ ssl: true }").unwrap();
+
+    let report = schema::validate(&instance, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(
+        report[0].kind,
+        ValidationErrorKind::Deprecated {
+            message: "TLS settings moved".to_string(),
+            since: Some("2.0".to_string()),
+            replacement: Some("tls.enabled".to_string()),
+        }
+    );
+    assert_eq!(
+        report[0].message,
+        "Deprecated usage: TLS settings moved (since 2.0); use 'tls.enabled' instead"
+    );
+}
+
+#[test]
+fn test_migrate_deprecated_into_new_nested_path() {
+    let schema: Value = from_str(
+        r#"{
+        name: "string"
+        ssl: { type: "boolean", deprecated: { use: "tls.enabled" }, optional: true }
+        tls: { type: "any", optional: true }
+    }"#,
+    )
+    .unwrap();
+    let mut instance = from_str(r#"{ name: "app", ssl: true }"#).unwrap();
+
+    let migrations = schema::migrate_deprecated(&mut instance, &schema);
+    assert_eq!(migrations.len(), 1);
+    assert_eq!(migrations[0].path, "$.ssl");
+    assert_eq!(
+        migrations[0].kind,
+        ValidationErrorKind::Migrated {
+            from: "ssl".to_string(),
+            to: "tls.enabled".to_string(),
+        }
+    );
+    assert_eq!(
+        instance,
+        from_str(r#"{ name: "app", tls: { enabled: true } }"#).unwrap()
+    );
+
+    let report = schema::validate(&instance, &schema).unwrap();
+    assert!(report.is_empty());
+}
+
+#[test]
+fn test_migrate_deprecated_keeps_existing_replacement() {
+    let schema: Value = from_str(
+        r#"{
+        old_port: { type: "integer", deprecated: { use: "port" }, optional: true }
+        port: "integer"
+    }"#,
+    )
+    .unwrap();
+    let mut instance = from_str("{ old_port: 80, port: 8080 }").unwrap();
+
+    let migrations = schema::migrate_deprecated(&mut instance, &schema);
+    assert!(migrations.is_empty());
+    assert_eq!(instance, from_str("{ old_port: 80, port: 8080 }").unwrap());
+}
+
+#[test]
+fn test_validate_strict_fails_fast() {
+    let schema: Value = from_str(r#"{ name: "string", age: "integer" }"#).unwrap();
+
+    let valid = from_str(r#"{ name: "Alice", age: 30 }"#).unwrap();
+    assert!(schema::validate_strict(&valid, &schema).is_ok());
+
+    let invalid = from_str(r#"{ name: 1, age: "30" }"#).unwrap();
+    let err = schema::validate_strict(&invalid, &schema).unwrap_err();
+    assert_eq!(err.path, "$.name");
+    assert!(matches!(err.kind, ValidationErrorKind::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_validate_strict_and_permissive_modes() {
+    let schema: Value =
+        from_str(r#"{ name: "string", old: { type: "integer", deprecated: "gone" } }"#).unwrap();
+    let instance = from_str(r#"{ name: "a", old: 1 }"#).unwrap();
+
+    // Only a warning: `validate` reports it, strict mode fails on it
+    let report = schema::validate(&instance, &schema).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.len(), 1);
+    let err = schema::validate_strict(&instance, &schema).unwrap_err();
+    assert_eq!(err.level, ValidationLevel::Warning);
+    assert_eq!(err.path, "$.old");
+    assert_eq!(schema::validate_permissive(&instance, &schema).len(), 1);
+
+    // A malformed schema: `validate` and strict mode fail, permissive mode
+    // keeps what it found before the problem
+    let schema: Value = from_str(r#"{ name: "integer", tag: "strnig" }"#).unwrap();
+    let instance = from_str(r#"{ name: "a", tag: "b" }"#).unwrap();
+    assert!(schema::validate(&instance, &schema).is_err());
+    assert!(schema::validate_strict(&instance, &schema).is_err());
+    let report = schema::validate_permissive(&instance, &schema);
+    assert_eq!(report.len(), 2);
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+    assert_eq!(report[1].path, "$.tag");
+    assert!(
+        report[1]
+            .message
+            .starts_with("Schema error: Unknown type 'strnig'")
+    );
+}
+
+#[test]
+fn test_validation_item_position() {
+    let schema: Value = from_str(r#"{ server: { host: "string", port: "integer" } }"#).unwrap();
+    let config = from_str(
+        r#"{
+    server: {
+        host: "localhost"
+        port: "8080"
+    }
+}"#,
+    )
+    .unwrap();
+
+    let report = schema::validate(&config, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    let pos = report[0].position.expect("parsed values carry positions");
+    assert_eq!(pos.line, 4);
+    assert_eq!(pos.column, 15);
+    assert!(report[0].to_string().contains("line 4, column 15"));
+}
+
+#[test]
+fn test_missing_field_position_is_parent_object() {
+    let schema: Value = from_str(r#"{ server: { host: "string", port: "integer" } }"#).unwrap();
+    let config = from_str("{\n    server: { host: \"localhost\" }\n}").unwrap();
+
+    let report = schema::validate(&config, &schema).unwrap();
+    let missing = report
+        .iter()
+        .find(|i| matches!(i.kind, ValidationErrorKind::MissingField { .. }))
+        .unwrap();
+    let pos = missing.position.unwrap();
+    assert_eq!((pos.line, pos.column), (2, 13));
+}
+
+#[test]
+fn test_constraint_conflict() {
+    let schema: Value = from_str(
+        r#"{
+        password: { type: "string", optional: true }
+        password_file: { type: "string", optional: true }
+        constraints: [
+            { if_present: "password", forbid: "password_file" }
+        ]
+    }"#,
+    )
+    .unwrap();
+
+    let ok = from_str(r#"{ password: "secret" }"#).unwrap();
+    assert!(schema::validate(&ok, &schema).unwrap().is_valid());
+
+    let both = from_str(r#"{ password: "secret", password_file: "/run/pw" }"#).unwrap();
+    let report = schema::validate(&both, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$");
+    assert_eq!(
+        report[0].kind,
+        ValidationErrorKind::ForbiddenBy {
+            field: "password_file".to_string(),
+            condition: "'password' is present".to_string(),
+        }
+    );
+    assert!(report[0].message.contains("password_file"));
+    assert!(report[0].message.contains("'password'"));
+}
+
+#[test]
+fn test_constraint_if_equals_requires_nested() {
+    let schema: Value = from_str(
+        r#"{
+        tls: {
+            enabled: "boolean"
+            cert_path: { type: "string", optional: true }
+            key_path: { type: "string", optional: true }
+        }
+        constraints: [
+            {
+                if_equals: { key: "tls.enabled", value: true }
+                require: ["tls.cert_path", "tls.key_path"]
+            }
+        ]
+    }"#,
+    )
+    .unwrap();
+
+    let disabled = from_str(r#"{ tls: { enabled: false } }"#).unwrap();
+    assert!(schema::validate(&disabled, &schema).unwrap().is_valid());
+
+    let enabled = from_str(r#"{ tls: { enabled: true, cert_path: "c.pem" } }"#).unwrap();
+    let report = schema::validate(&enabled, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(
+        report[0].message,
+        "Field 'tls.key_path' is required when 'tls.enabled' is true"
+    );
+}
+
+#[test]
+fn test_constraint_all_or_none() {
+    let schema: Value = from_str(
+        r#"{
+        user: { type: "string", optional: true }
+        password: { type: "string", optional: true }
+        constraints: [{ all_or_none: ["user", "password"] }]
+    }"#,
+    )
+    .unwrap();
+
+    let none = from_str("{}").unwrap();
+    assert!(schema::validate(&none, &schema).unwrap().is_valid());
+
+    let partial = from_str(r#"{ user: "admin" }"#).unwrap();
+    let report = schema::validate(&partial, &schema).unwrap();
+    assert_eq!(report.errors().count(), 1);
+    assert!(matches!(
+        &report[0].kind,
+        ValidationErrorKind::RequiredBy { field, .. } if field == "password"
+    ));
+}
+
+#[test]
+fn test_constraint_invalid_definition() {
+    let schema: Value =
+        from_str(r#"{ a: "string", constraints: [{ if_present: 1, forbid: "a" }] }"#).unwrap();
+    let config = from_str(r#"{ a: "x" }"#).unwrap();
+    let err = schema::validate(&config, &schema).unwrap_err();
+    assert_eq!(err.schema_path, "$");
+    assert_eq!(err.message, "'if_present' must be a string");
+
+    // A rule without a condition, or with a misspelled one, is still a rule
+    for rules in [
+        r#"[{ forbid: "a" }]"#,
+        r#"[{ if_presnt: "a", forbid: "b" }]"#,
+    ] {
+        let schema: Value =
+            from_str(&format!(r#"{{ a: "string", constraints: {} }}"#, rules)).unwrap();
+        let err = schema::validate(&config, &schema).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Constraint must have 'if_present', 'if_equals' or 'all_or_none'"
+        );
+    }
+}
+
+#[test]
+fn test_constraints_field_is_not_a_directive() {
+    // An array of non-objects under `constraints` is an ordinary field
+    let schema: Value = from_str(r#"{ name: "string", constraints: ["string"] }"#).unwrap();
+    let config = from_str(r#"{ name: "x", constraints: ["unique", "not_null"] }"#).unwrap();
+    assert!(schema::validate(&config, &schema).unwrap().is_empty());
+    assert!(Schema::compile(schema.clone()).is_ok());
+
+    let config = from_str(r#"{ name: "x", constraints: [1] }"#).unwrap();
+    let report = schema::validate(&config, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.constraints[0]");
+
+    let report = schema::validate(&from_str(r#"{ name: "x" }"#).unwrap(), &schema).unwrap();
+    assert!(matches!(
+        &report[0].kind,
+        ValidationErrorKind::MissingField { field } if field == "constraints"
+    ));
+
+    // The extended form opts an array of objects out of the directive
+    let schema: Value =
+        from_str(r#"{ name: "string", constraints: { type: [{ column: "string" }] } }"#).unwrap();
+    let config = from_str(r#"{ name: "x", constraints: [{ column: "id" }] }"#).unwrap();
+    assert!(schema::validate(&config, &schema).unwrap().is_empty());
+    let config = from_str(r#"{ name: "x", constraints: [{ column: 1 }] }"#).unwrap();
+    let report = schema::validate(&config, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.constraints[0].column");
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn test_validate_and_coerce() {
+    #[derive(serde::Deserialize)]
+    struct Server {
+        port: i64,
+        ratio: f64,
+        enabled: bool,
+    }
+
+    let schema: Value =
+        from_str(r#"{ port: "integer", ratio: "float", enabled: "boolean" }"#).unwrap();
+    let mut config = from_str(r#"{ port: "8080", ratio: "3.14", enabled: "true" }"#).unwrap();
+
+    // Without coercion these are type mismatches
+    assert_eq!(
+        schema::validate(&config, &schema).unwrap().errors().count(),
+        3
+    );
+
+    let report = schema::validate_and_coerce(&mut config, &schema);
+    assert!(report.is_valid());
+    assert_eq!(report.warnings().count(), 3);
+    assert_eq!(
+        report[1].kind,
+        ValidationErrorKind::Coerced {
+            from: "3.14".to_string(),
+            to: "float".to_string(),
+        }
+    );
+
+    let server: Server = cosy::from_value(config).unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.ratio, 3.14);
+    assert!(server.enabled);
+}
+
+#[test]
+fn test_validate_and_coerce_unparseable_still_fails() {
+    let schema: Value = from_str(r#"{ port: "integer", enabled: "boolean" }"#).unwrap();
+    let mut config = from_str(r#"{ port: "eighty", enabled: "yes" }"#).unwrap();
+
+    let report =
+        schema::validate_and_coerce_with(&mut config, &schema, schema::CoercionReport::Silent);
+    assert_eq!(report.errors().count(), 2);
+    assert_eq!(report.warnings().count(), 0);
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_validate_coercing() {
+    let schema: Value = from_str(
+        r#"{ port: "port", debug: "boolean", retries: "integer | null", label: "string | integer" }"#,
+    )
+    .unwrap();
+    let mut config =
+        from_str(r#"{ port: "8080", debug: "true", retries: "3", label: "42" }"#).unwrap();
+
+    let report = schema::validate_coercing(&mut config, &schema).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.len(), 3);
+    assert_eq!(
+        report[2].kind,
+        ValidationErrorKind::Coerced {
+            from: "3".to_string(),
+            to: "integer".to_string(),
+        }
+    );
+    // A union that accepts strings leaves the value alone
+    assert_eq!(
+        config,
+        from_str(r#"{ port: 8080, debug: true, retries: 3, label: "42" }"#).unwrap()
+    );
+
+    let mut config =
+        from_str(r#"{ port: "abc", debug: "false", retries: null, label: 1 }"#).unwrap();
+    let report = schema::validate_coercing(&mut config, &schema).unwrap();
+    assert_eq!(report.errors().count(), 1);
+    let error = report.errors().next().unwrap();
+    assert_eq!(error.path, "$.port");
+    assert!(matches!(
+        error.kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+
+    let bad_schema: Value = from_str(r#"{ port: "integr" }"#).unwrap();
+    let mut config = from_str(r#"{ port: "1" }"#).unwrap();
+    assert!(schema::validate_coercing(&mut config, &bad_schema).is_err());
+}
+
+#[test]
+fn test_unknown_type_suggestion() {
+    let schema: Value = from_str(r#"{ name: "str" }"#).unwrap();
+    let instance = from_str(r#"{ name: "Alice" }"#).unwrap();
+
+    let err = schema::validate(&instance, &schema).unwrap_err();
+    assert_eq!(err.schema_path, "$.name");
+    assert_eq!(err.message, "Unknown type 'str'; did you mean 'string'?");
+    assert_eq!(
+        err.kind,
+        SchemaErrorKind::UnknownType {
+            type_name: "str".to_string(),
+            suggestion: Some("string".to_string()),
+        }
+    );
+
+    let schema: Value = from_str(r#"{ name: "widget" }"#).unwrap();
+    let err = schema::validate(&instance, &schema).unwrap_err();
+    assert_eq!(err.message, "Unknown type 'widget'");
+
+    // Other schema problems carry only a message
+    let schema: Value = from_str(r#"{ name: { type: "string", min: "a" } }"#).unwrap();
+    let err = schema::validate(&instance, &schema).unwrap_err();
+    assert_eq!(err.kind, SchemaErrorKind::Malformed);
+}
+
+#[test]
+fn test_enum_and_range() {
+    let schema: Value = from_str(
+        r#"{
+        level: { type: "string", enum: ["debug", "info", "warn"] }
+        port: { type: "integer", min: 1, max: 65535 }
+        ratio: { type: "number", min: 0, max: 1 }
+    }"#,
+    )
+    .unwrap();
+
+    let valid = from_str(r#"{ level: "info", port: 65535, ratio: 0.5 }"#).unwrap();
+    assert!(schema::validate(&valid, &schema).unwrap().is_empty());
+
+    let invalid = from_str(r#"{ level: "trace", port: 0, ratio: 1.5 }"#).unwrap();
+    let report = schema::validate(&invalid, &schema).unwrap();
+    let messages: Vec<&str> = report.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Value \"trace\" is not one of [\"debug\", \"info\", \"warn\"]",
+            "Value 0 is out of range (1..=65535)",
+            "Value 1.5 is out of range (0..=1)",
+        ]
+    );
+
+    // A wrong type is reported once, not also as out of range
+    let wrong_type = from_str(r#"{ level: "info", port: "80", ratio: 0 }"#).unwrap();
+    let report = schema::validate(&wrong_type, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+
+    // A near miss suggests the closest allowed string
+    let typo = from_str(r#"{ level: "wran", port: 80, ratio: 0 }"#).unwrap();
+    let report = schema::validate(&typo, &schema).unwrap();
+    assert_eq!(
+        report[0].message,
+        "Value \"wran\" is not one of [\"debug\", \"info\", \"warn\"]; did you mean \"warn\"?"
+    );
+}
+
+#[test]
+fn test_union_types() {
+    let schema: Value = from_str(r#"{ timeout: "integer | null" }"#).unwrap();
+
+    assert!(
+        schema::validate(&from_str("{ timeout: 5 }").unwrap(), &schema)
+            .unwrap()
+            .is_valid()
+    );
+    assert!(
+        schema::validate(&from_str("{ timeout: null }").unwrap(), &schema)
+            .unwrap()
+            .is_valid()
+    );
+
+    let report = schema::validate(&from_str("{ timeout: \"5\" }").unwrap(), &schema).unwrap();
+    assert_eq!(
+        report[0].message,
+        "Type mismatch: expected integer | null, found string"
+    );
+
+    let bad: Value = from_str(r#"{ timeout: "integer | nul" }"#).unwrap();
+    let err = schema::validate(&from_str("{ timeout: 5 }").unwrap(), &bad).unwrap_err();
+    assert_eq!(err.message, "Unknown type 'nul'; did you mean 'null'?");
+}
+
+#[test]
+fn test_additional_properties() {
+    let open: Value = from_str(r#"{ name: "string", additionalProperties: true }"#).unwrap();
+    let typed: Value = from_str(r#"{ name: "string", additionalProperties: "integer" }"#).unwrap();
+    let instance = from_str(r#"{ name: "x", a: 1, b: "two" }"#).unwrap();
+
+    assert!(schema::validate(&instance, &open).unwrap().is_empty());
+
+    let report = schema::validate(&instance, &typed).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.b");
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_pattern_properties() {
+    let schema: Value = from_str(
+        r#"{
+            name: "string"
+            patternProperties: { "^feature_": "boolean" }
+            additionalProperties: "string"
+        }"#,
+    )
+    .unwrap();
+
+    let ok = from_str(r#"{ name: "x", feature_auth: true, region: "eu" }"#).unwrap();
+    assert!(schema::validate(&ok, &schema).unwrap().is_empty());
+
+    let report = schema::validate(
+        &from_str(r#"{ name: "x", feature_auth: "yes" }"#).unwrap(),
+        &schema,
+    )
+    .unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.feature_auth");
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+
+    // Keys matching no pattern fall through to additionalProperties
+    let report =
+        schema::validate(&from_str(r#"{ name: "x", region: 1 }"#).unwrap(), &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.region");
+
+    let closed: Value = from_str(r#"{ patternProperties: { "^feature_": "boolean" } }"#).unwrap();
+    let report = schema::validate(&from_str("{ region: \"eu\" }").unwrap(), &closed).unwrap();
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::UnknownField { .. }
+    ));
+
+    let bad: Value = from_str(r#"{ patternProperties: { "(": "boolean" } }"#).unwrap();
+    let err = schema::validate(&from_str("{ a: 1 }").unwrap(), &bad).unwrap_err();
+    assert!(err.message.starts_with("Invalid key pattern '('"));
+}
+
+#[cfg(not(feature = "regex"))]
+#[test]
+fn test_pattern_properties_requires_regex_feature() {
+    let schema: Value = from_str(r#"{ patternProperties: { "^feature_": "boolean" } }"#).unwrap();
+    let err = schema::validate(&from_str("{ feature_auth: true }").unwrap(), &schema).unwrap_err();
+    assert_eq!(
+        err.message,
+        "'patternProperties' requires the `regex` feature"
+    );
+}
+
+#[test]
+fn test_deduplicate_report() {
+    let schema: Value = from_str(r#"{ name: "string", age: "integer" }"#).unwrap();
+    let instance = from_str(r#"{ name: 1, age: "x" }"#).unwrap();
+
+    // Validating twice (e.g. on reload) accumulates the same items
+    let first = schema::validate(&instance, &schema).unwrap();
+    let mut report = first.clone();
+    report.items.extend(first.items.clone());
+    assert_eq!(report.len(), 4);
+
+    schema::deduplicate_report(&mut report);
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].path, "$.name");
+    assert_eq!(report[1].path, "$.age");
+
+    // Items are equal by level, path and message
+    assert_eq!(report[0], first[0]);
+    assert_ne!(report[0], report[1]);
+}
+
+#[test]
+fn test_merge_reports() {
+    let schema_a: Value = from_str(r#"{ name: "string", port: "integer" }"#).unwrap();
+    let schema_b: Value = from_str(r#"{ name: "string", port: "string" }"#).unwrap();
+    let instance = from_str(r#"{ name: 1, port: 80 }"#).unwrap();
+
+    let merged = schema::merge_reports(
+        schema::validate(&instance, &schema_a).unwrap(),
+        schema::validate(&instance, &schema_b).unwrap(),
+    );
+    let paths: Vec<&str> = merged.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.name", "$.port"]);
+}
+
+#[test]
+fn test_ranged_integer_aliases() {
+    let cases: &[(&str, i64, Option<i64>)] = &[
+        ("u8", 0, Some(255)),
+        ("u16", 0, Some(65535)),
+        ("u32", 0, Some(4294967295)),
+        ("i32", -2147483648, Some(2147483647)),
+        ("port", 1, Some(65535)),
+        ("positive_integer", 1, None),
+        ("non_negative_integer", 0, None),
+    ];
+
+    for &(alias, min, max) in cases {
+        let schema: Value = from_str(&format!(r#"{{ v: "{}" }}"#, alias)).unwrap();
+        let check = |v: i64| {
+            let instance = from_str(&format!("{{ v: {} }}", v)).unwrap();
+            schema::validate(&instance, &schema).unwrap()
+        };
+
+        assert!(check(min).is_valid(), "{} should accept {}", alias, min);
+        assert!(
+            !check(min - 1).is_valid(),
+            "{} should reject {}",
+            alias,
+            min - 1
+        );
+        match max {
+            Some(max) => {
+                assert!(check(max).is_valid(), "{} should accept {}", alias, max);
+                assert!(
+                    !check(max + 1).is_valid(),
+                    "{} should reject {}",
+                    alias,
+                    max + 1
+                );
+            }
+            None => assert!(check(i64::MAX).is_valid()),
+        }
+    }
+
+    // The canonical name is unbounded
+    let schema: Value = from_str(r#"{ v: "integer" }"#).unwrap();
+    let instance = from_str(&format!("{{ v: {} }}", i64::MIN)).unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_valid());
+}
+
+#[test]
+fn test_ranged_integer_alias_messages() {
+    let schema: Value = from_str(r#"{ port: "u16", id: "positive_integer" }"#).unwrap();
+    let instance = from_str(r#"{ port: 70000, id: "7" }"#).unwrap();
+
+    let report = schema::validate(&instance, &schema).unwrap();
+    let messages: Vec<&str> = report.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Type mismatch: expected u16 (0..=65535), found 70000",
+            "Type mismatch: expected positive_integer (1..), found string",
+        ]
+    );
+
+    // A nullable alias still names its range
+    let schema: Value = from_str(r#"{ port: "u16?" }"#).unwrap();
+    let instance = from_str(r#"{ port: -1 }"#).unwrap();
+    let report = schema::validate(&instance, &schema).unwrap();
+    assert_eq!(
+        report[0].message,
+        "Type mismatch: expected u16? (0..=65535), found -1"
+    );
+    let instance = from_str(r#"{ port: null }"#).unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_empty());
+}
+
+fn many_wrong_items(n: usize) -> Value {
+    let items = vec![Value::from("x"); n];
+    let mut root = indexmap::IndexMap::new();
+    root.insert("items".to_string(), Value::array(items));
+    root.insert("name".to_string(), Value::integer(1));
+    Value::object(root)
+}
+
+#[test]
+fn test_validate_with_options_groups_similar() {
+    let schema: Value = from_str(r#"{ items: ["integer"], name: "string" }"#).unwrap();
+    let instance = many_wrong_items(10_000);
+
+    // The default keeps every item
+    assert_eq!(schema::validate(&instance, &schema).unwrap().len(), 10_001);
+
+    let options = schema::ValidationOptions {
+        group_similar: true,
+        ..Default::default()
+    };
+    let report = schema::validate_with_options(&instance, &schema, &options).unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].path, "$.items[0]");
+    assert_eq!(
+        report[0].message,
+        "Type mismatch: expected integer, found string (and 9,999 more at $.items[*])"
+    );
+    assert_eq!(report[1].path, "$.name");
+}
+
+#[test]
+fn test_validate_with_options_truncates() {
+    let schema: Value = from_str(r#"{ items: ["integer"], name: "string" }"#).unwrap();
+    let instance = many_wrong_items(5);
+
+    let options = schema::ValidationOptions {
+        max_errors: 3,
+        ..Default::default()
+    };
+    let report = schema::validate_with_options(&instance, &schema, &options).unwrap();
+    let paths: Vec<&str> = report.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.items[0]", "$.items[1]", "$.items[2]", "$"]);
+    assert_eq!(
+        report[3].kind,
+        ValidationErrorKind::Truncated { omitted: 3 }
+    );
+    assert_eq!(report[3].message, "3 more error(s) not shown");
+}
+
+#[test]
+fn test_string_length_counts_characters() {
+    let schema: Value =
+        from_str(r#"{ name: { type: "string", minLength: 2, maxLength: 4 } }"#).unwrap();
+    let check = |name: &str| {
+        let instance = from_str(&format!(r#"{{ name: "{}" }}"#, name)).unwrap();
+        schema::validate(&instance, &schema).unwrap()
+    };
+
+    // 4 characters but 8 bytes
+    assert!(check("ñäöü").is_valid());
+    assert!(check("日本").is_valid());
+    let report = check("日");
+    assert_eq!(
+        report[0].kind,
+        ValidationErrorKind::LengthOutOfRange {
+            length: 1,
+            min: Some(2),
+            max: Some(4),
+        }
+    );
+    assert_eq!(report[0].message, "String length 1 is out of range (2..=4)");
+    assert!(!check("abcde").is_valid());
+}
+
+#[test]
+fn test_string_formats() {
+    let cases: &[(&str, &[&str], &[&str])] = &[
+        (
+            "email",
+            &["user@example.com", "first.last+tag@mail.example.org"],
+            &[
+                "user.example.com",
+                "user@localhost",
+                "a b@example.com",
+                "@example.com",
+            ],
+        ),
+        (
+            "url",
+            &["https://example.com", "http://localhost:8080/path?q=1"],
+            &[
+                "example.com",
+                "https://",
+                "1http://x.com",
+                "http://exa mple.com",
+            ],
+        ),
+        (
+            "uuid",
+            &[
+                "123e4567-e89b-12d3-a456-426614174000",
+                "ABCDEF00-0000-0000-0000-000000000000",
+            ],
+            &[
+                "123e4567e89b12d3a456426614174000",
+                "123e4567-e89b-12d3-a456-42661417400g",
+            ],
+        ),
+        (
+            "ipv4",
+            &["127.0.0.1", "255.255.255.255"],
+            &["256.0.0.1", "1.2.3"],
+        ),
+        (
+            "ipv6",
+            &["::1", "2001:db8::ff00:42:8329"],
+            &["2001:::1", "12345::"],
+        ),
+        (
+            "date",
+            &["2024-02-29", "1999-12-31"],
+            &["2023-02-29", "2024-13-01", "2024-1-01", "24-01-01"],
+        ),
+        (
+            "datetime",
+            &[
+                "2024-05-01T12:30:00Z",
+                "2024-05-01T12:30:00.123+02:00",
+                "2016-12-31t23:59:60z",
+            ],
+            &[
+                "2024-05-01",
+                "2024-05-01T25:00:00Z",
+                "2024-05-01T12:30:00",
+                "2024-05-01T12:30:00+2:00",
+            ],
+        ),
+    ];
+
+    for (format, valid, invalid) in cases {
+        let schema: Value = from_str(&format!(
+            r#"{{ v: {{ type: "string", format: "{}" }} }}"#,
+            format
+        ))
+        .unwrap();
+        let check = |s: &str| {
+            let mut obj = indexmap::IndexMap::new();
+            obj.insert("v".to_string(), Value::from(s));
+            schema::validate(&Value::object(obj), &schema).unwrap()
+        };
+        for s in *valid {
+            assert!(check(s).is_valid(), "{} should accept {:?}", format, s);
+        }
+        for s in *invalid {
+            let report = check(s);
+            assert_eq!(
+                report[0].kind,
+                ValidationErrorKind::InvalidFormat {
+                    format: format.to_string(),
+                    value: s.to_string(),
+                },
+                "{} should reject {:?}",
+                format,
+                s
+            );
+        }
+    }
+}
+
+#[test]
+fn test_unknown_format_is_schema_error() {
+    let schema: Value = from_str(r#"{ v: { type: "string", format: "emial" } }"#).unwrap();
+    let err = schema::validate(&from_str(r#"{ v: "x" }"#).unwrap(), &schema).unwrap_err();
+    assert_eq!(err.message, "Unknown format 'emial'; did you mean 'email'?");
+}
+
+#[test]
+fn test_all_of_merges_object_schemas() {
+    let schema: Value = from_str(
+        r#"{
+            all_of: [
+                { logging: { level: "string" }, metrics: "boolean" }
+                {
+                    name: "string"
+                    logging: { file: { type: "string", optional: true } }
+                    metrics: { type: "boolean", optional: true }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    // Fields from both parts are known, and `metrics` became optional
+    let valid = from_str(r#"{ name: "api", logging: { level: "info" } }"#).unwrap();
+    assert!(schema::validate(&valid, &schema).unwrap().is_empty());
+
+    let invalid = from_str(r#"{ name: "api", logging: { level: 1 }, extra: 1 }"#).unwrap();
+    let report = schema::validate(&invalid, &schema).unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].path, "$.logging.level");
+    assert!(matches!(
+        &report[1].kind,
+        ValidationErrorKind::UnknownField { field, .. } if field == "extra"
+    ));
+}
+
+#[test]
+fn test_merge_schemas() {
+    let common: Value =
+        from_str(r#"{ port: { type: "integer", min: 1 }, tags: ["string"] }"#).unwrap();
+    let specific: Value =
+        from_str(r#"{ port: { type: "integer", max: 9000 }, name: "string" }"#).unwrap();
+    let merged = schema::merge_schemas(&[common, specific]).unwrap();
+    let expected: Value = from_str(
+        r#"{ port: { type: "integer", min: 1, max: 9000 }, tags: ["string"], name: "string" }"#,
+    )
+    .unwrap();
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn test_all_of_conflicting_types_is_schema_error() {
+    let a: Value = from_str(r#"{ db: { port: "integer" } }"#).unwrap();
+    let b: Value = from_str(r#"{ db: { port: "string" } }"#).unwrap();
+    let err = schema::merge_schemas(&[a.clone(), b.clone()]).unwrap_err();
+    assert_eq!(err.schema_path, "$.db.port");
+    assert_eq!(
+        err.message,
+        "Conflicting types in all_of: 'integer' and 'string'"
+    );
+
+    let mut obj = indexmap::IndexMap::new();
+    obj.insert("all_of".to_string(), Value::array(vec![a, b]));
+    let instance = from_str(r#"{ db: { port: 1 } }"#).unwrap();
+    let err = schema::validate(&instance, &Value::object(obj)).unwrap_err();
+    assert_eq!(err.schema_path, "$");
+    assert!(err.message.starts_with("Conflicting types in all_of"));
+}
+
+#[test]
+fn test_conditional_schema() {
+    let schema: Value = from_str(
+        r#"{ if: { env: "string" }, then: { host: "string" }, else: { socket: "string" } }"#,
+    )
+    .unwrap();
+
+    // Matches `if`, so `then` applies
+    let remote = from_str(r#"{ env: "prod", host: "db.internal" }"#).unwrap();
+    assert!(schema::validate(&remote, &schema).unwrap().is_empty());
+
+    let report = schema::validate(
+        &from_str(r#"{ env: "prod", socket: "/tmp/db" }"#).unwrap(),
+        &schema,
+    )
+    .unwrap();
+    assert_eq!(report.len(), 2);
+    assert!(matches!(
+        &report[0].kind,
+        ValidationErrorKind::MissingField { field } if field == "host"
+    ));
+    assert!(matches!(
+        &report[1].kind,
+        ValidationErrorKind::UnknownField { field, .. } if field == "socket"
+    ));
+
+    // Does not match `if`, so `else` applies
+    let local = from_str(r#"{ socket: "/tmp/db" }"#).unwrap();
+    assert!(schema::validate(&local, &schema).unwrap().is_empty());
+
+    let report =
+        schema::validate(&from_str(r#"{ host: "db.internal" }"#).unwrap(), &schema).unwrap();
+    assert!(report.iter().any(|item| matches!(
+        &item.kind,
+        ValidationErrorKind::MissingField { field } if field == "socket"
+    )));
+
+    // Compiled schemas pick the same branch
+    let compiled = Schema::compile(schema).unwrap();
+    assert!(compiled.validate(&remote).unwrap().is_empty());
+    assert!(compiled.validate(&local).unwrap().is_empty());
+}
+
+#[test]
+fn test_conditional_keeps_other_fields_and_plain_if_field() {
+    let schema: Value = from_str(
+        r#"{
+            name: "string"
+            if: { tls: "boolean" }
+            then: { cert: "string" }
+        }"#,
+    )
+    .unwrap();
+    let valid = from_str(r#"{ name: "api", tls: true, cert: "a.pem" }"#).unwrap();
+    assert!(schema::validate(&valid, &schema).unwrap().is_empty());
+    let plain = from_str(r#"{ name: "api" }"#).unwrap();
+    assert!(schema::validate(&plain, &schema).unwrap().is_empty());
+    let missing = from_str(r#"{ tls: false }"#).unwrap();
+    let report = schema::validate(&missing, &schema).unwrap();
+    assert!(report.iter().any(|item| matches!(
+        &item.kind,
+        ValidationErrorKind::MissingField { field } if field == "name"
+    )));
+
+    // Without `then` or `else`, `if` is an ordinary field
+    let schema: Value = from_str(r#"{ if: "string" }"#).unwrap();
+    let instance = from_str(r#"{ if: "x" }"#).unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_empty());
+}
+
+#[test]
+fn test_schema_builder_matches_hand_written() {
+    use cosy::schema::SchemaBuilder;
+
+    let built = SchemaBuilder::object()
+        .field("host", SchemaBuilder::string().format("url"))
+        .field(
+            "port",
+            SchemaBuilder::integer().minimum(1024).maximum(65535),
+        )
+        .field("debug", SchemaBuilder::boolean().optional())
+        .field(
+            "mode",
+            SchemaBuilder::string()
+                .allowed(["dev", "prod"])
+                .deprecated("use profile"),
+        )
+        .field(
+            "tags",
+            SchemaBuilder::array(SchemaBuilder::string().min_length(1)),
+        )
+        .field(
+            "tls",
+            SchemaBuilder::object()
+                .field("cert", SchemaBuilder::named("string|null"))
+                .additional_properties(true)
+                .optional(),
+        )
+        .build();
+
+    let expected: Value = from_str(
+        r#"{
+            host: { type: "string", format: "url" }
+            port: { type: "integer", min: 1024, max: 65535 }
+            debug: { type: "boolean", optional: true }
+            mode: { type: "string", enum: ["dev", "prod"], deprecated: "use profile" }
+            tags: [{ type: "string", minLength: 1 }]
+            tls: { type: { cert: "string|null", additionalProperties: true }, optional: true }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(built, expected);
+
+    let config =
+        from_str(r#"{ host: "https://x.io", port: 8080, mode: "dev", tags: ["a"] }"#).unwrap();
+    let report = schema::validate(&config, &built).unwrap();
+    assert!(report.is_valid());
+}
+
+#[test]
+fn test_schema_builder_from_value() {
+    use cosy::schema::SchemaBuilder;
+
+    let schema: Value = from_str(
+        r#"{
+            name: "string"
+            port: { type: "port", optional: true }
+            constraints: [{ if_present: "port", require: "name" }]
+        }"#,
+    )
+    .unwrap();
+    let builder = SchemaBuilder::from_value(&schema).unwrap();
+    assert_eq!(builder.build(), schema);
+
+    let modified = builder
+        .without_field("name")
+        .field("host", SchemaBuilder::string())
+        .build();
+    let expected: Value = from_str(
+        r#"{
+            port: { type: "port", optional: true }
+            constraints: [{ if_present: "port", require: "name" }]
+            host: "string"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(modified, expected);
+
+    let err = SchemaBuilder::from_value(&from_str("{ a: [1] }").unwrap()).unwrap_err();
+    assert_eq!(err.schema_path, "$.a[*]");
+}
+
+#[test]
+fn test_errors_and_deprecation_warnings_together() {
+    let schema: Value = from_str(
+        r#"{
+            port: "integer"
+            ssl: { type: "boolean", deprecated: "use tls" }
+        }"#,
+    )
+    .unwrap();
+    let config = from_str("{ prot: 8080, ssl: true }").unwrap();
+
+    let report = schema::validate(&config, &schema).unwrap();
+    assert!(report.has_errors());
+    assert!(!report.is_valid());
+
+    let errors: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(errors, ["$", "$"]); // missing `port`, unknown `prot`
+    assert!(report.errors().any(|i| matches!(
+        &i.kind,
+        ValidationErrorKind::UnknownField { field, suggestion: Some(s) } if field == "prot" && s == "port"
+    )));
+
+    let warnings: Vec<_> = report.warnings().collect();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].path, "$.ssl");
+    assert!(matches!(
+        warnings[0].kind,
+        ValidationErrorKind::Deprecated { .. }
+    ));
+}
+
+#[test]
+fn test_nullable_types() {
+    let schema: Value = from_str(
+        r#"{
+            proxy: "string?"
+            timeout: { type: "integer", nullable: true, min: 1 }
+            replicas: { type: [{ host: "string" }], nullable: true, optional: true }
+            name: { type: "string", optional: true }
+        }"#,
+    )
+    .unwrap();
+
+    // Present null passes when nullable
+    let config = from_str("{ proxy: null, timeout: null, replicas: null }").unwrap();
+    assert!(schema::validate(&config, &schema).unwrap().is_empty());
+
+    // Non-null values are still checked against the type
+    let config = from_str(r#"{ proxy: 1, timeout: 0 }"#).unwrap();
+    let report = schema::validate(&config, &schema).unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].path, "$.proxy");
+    assert!(matches!(
+        report[1].kind,
+        ValidationErrorKind::OutOfRange { .. }
+    ));
+
+    // Nullable does not imply optional
+    let report = schema::validate(&from_str("{ proxy: null }").unwrap(), &schema).unwrap();
+    assert!(matches!(
+        &report[0].kind,
+        ValidationErrorKind::MissingField { field } if field == "timeout"
+    ));
+
+    // Optional does not imply nullable
+    let config = from_str("{ proxy: null, timeout: 1, name: null }").unwrap();
+    let report = schema::validate(&config, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.name");
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_dependencies() {
+    let schema: Value = from_str(
+        r#"{
+            type: "object"
+            dependencies: { ssl: ["cert_path", "key_path"], proxy: "proxy_port" }
+        }"#,
+    )
+    .unwrap();
+    let check = |config: &str| schema::validate(&from_str(config).unwrap(), &schema).unwrap();
+
+    assert!(check(r#"{ ssl: true, cert_path: "c", key_path: "k" }"#).is_empty());
+    // A trigger set to false or null doesn't require anything
+    assert!(check("{ ssl: false, proxy: null }").is_empty());
+
+    let report = check(r#"{ ssl: true, cert_path: "c", proxy: "p" }"#);
+    let messages: Vec<_> = report.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Field 'key_path' is required when 'ssl' is set",
+            "Field 'proxy_port' is required when 'proxy' is set",
+        ]
+    );
+}
+
+#[test]
+fn test_exclusive_groups() {
+    let schema: Value = from_str(
+        r#"{
+            type: {
+                host: { type: "string", optional: true }
+                socket_path: { type: "string", optional: true }
+                port: { type: "integer", optional: true }
+            }
+            exclusiveGroups: [["host", "socket_path"]]
+            dependencies: { port: "host" }
+        }"#,
+    )
+    .unwrap();
+    let check = |config: &str| schema::validate(&from_str(config).unwrap(), &schema).unwrap();
+
+    assert!(check(r#"{ host: "db", port: 5432 }"#).is_empty());
+    assert!(check(r#"{ socket_path: "/tmp/db.sock" }"#).is_empty());
+    assert!(check("{}").is_empty());
+
+    let report = check(r#"{ host: "db", socket_path: "/tmp/db.sock" }"#);
+    assert_eq!(report.len(), 1);
+    assert_eq!(
+        report[0].kind,
+        ValidationErrorKind::ForbiddenBy {
+            field: "socket_path".to_string(),
+            condition: "'host' is present".to_string(),
+        }
+    );
+
+    // Field checks still run before the dependency checks
+    let report = check(r#"{ port: "5432" }"#);
+    assert_eq!(report.len(), 2);
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+    assert!(matches!(
+        report[1].kind,
+        ValidationErrorKind::RequiredBy { .. }
+    ));
+}
+
+#[test]
+fn test_compiled_schema_repeated_validation() {
+    let schema_value: Value = from_str(
+        r#"{
+            all_of: [
+                { name: "string", port: "port" }
+                { tags: ["string"], limits: { rps: "positive_integer", burst: "u16" } }
+                { debug: { type: "boolean", optional: true } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let instance = from_str(
+        r#"{ name: "api", port: 8080, tags: ["a", "b"], limits: { rps: 100, burst: 20 } }"#,
+    )
+    .unwrap();
+
+    let compiled = Schema::compile(schema_value.clone()).unwrap();
+    let start = Instant::now();
+    for _ in 0..1000 {
+        assert!(compiled.validate(&instance).unwrap().is_empty());
+    }
+    let compiled_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..1000 {
+        assert!(
+            schema::validate(&instance, &schema_value)
+                .unwrap()
+                .is_empty()
+        );
+    }
+    let direct_time = start.elapsed();
+
+    // The compiled schema merges `all_of` once instead of on every call
+    assert!(
+        compiled_time < direct_time,
+        "compiled {:?}, direct {:?}",
+        compiled_time,
+        direct_time
+    );
+
+    let report = compiled
+        .validate_str(r#"{ name: "api", port: 0, tags: [], limits: { rps: 1, burst: 1 } }"#)
+        .unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.port");
+}
+
+#[test]
+fn test_compile_rejects_invalid_schemas() {
+    let compile = |s: &str| Schema::compile(from_str(s).unwrap()).unwrap_err();
+
+    let err = compile(r#"{ server: { port: "integr" } }"#);
+    assert_eq!(err.schema_path, "$.server.port");
+    assert_eq!(
+        err.message,
+        "Unknown type 'integr'; did you mean 'integer'?"
+    );
+
+    let err = compile(r#"{ tags: ["string", "integer"] }"#);
+    assert_eq!(err.schema_path, "$.tags");
+
+    let err = compile(r#"{ db: { "$ref": "defs.cosy#/db" } }"#);
+    assert_eq!(err.schema_path, "$.db");
+    assert!(err.message.contains("not supported yet"), "{}", err);
+
+    // Valid schemas with unions, nullable types and aliases compile
+    assert!(Schema::compile(from_str(r#"{ a: "string | integer?", b: "bool" }"#).unwrap()).is_ok());
+}
+
+#[test]
+fn test_compiled_validate_str_errors() {
+    let schema =
+        Schema::compile(from_str(r#"{ email: { type: "string", format: "emial" } }"#).unwrap())
+            .unwrap();
+    assert!(matches!(
+        schema.validate_str("{ email: ").unwrap_err(),
+        CosynError::Parse(_)
+    ));
+    // Unknown formats are only found while validating
+    let err = schema.validate_str(r#"{ email: "a@b.c" }"#).unwrap_err();
+    assert!(matches!(err, CosynError::Schema(_)));
+    assert!(
+        err.to_string().contains("Unknown format 'emial'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_describe_markdown() {
+    let schema: Value = from_str(
+        r#"{
+            type: {
+                server: {
+                    type: {
+                        host: { type: "string", description: "Address to bind" }
+                        port: { type: "port", optional: true, description: "Port to listen on" }
+                    }
+                    description: "HTTP server settings"
+                }
+                level: { type: "string", enum: ["debug", "info"] }
+                port_num: { type: "integer", optional: true, deprecated: "Use 'server.port' instead" }
+                jobs: [{ name: "string" }]
+            }
+            title: "App Config"
+        }"#,
+    )
+    .unwrap();
+    // Annotations are accepted by validation
+    let instance: Value =
+        from_str(r#"{ server: { host: "localhost" }, level: "info", jobs: [{ name: "backup" }] }"#)
+            .unwrap();
+    assert!(schema::validate(&instance, &schema).unwrap().is_valid());
+
+    let doc = cosy::describe(&schema, cosy::DescribeFormat::Markdown);
+    assert!(doc.starts_with("## App Config\n"), "{}", doc);
+    assert!(
+        doc.contains("\n## server\n\nHTTP server settings\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("- **host**: string — Address to bind\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("- **port**: port (optional) — Port to listen on\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains(r#"- **level**: string — Allowed values: `"debug"`, `"info"`"#),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("- **port_num**: integer (optional) — Deprecated: Use 'server.port' instead"),
+        "{}",
+        doc
+    );
+    assert!(doc.contains("- **jobs**: array of object\n"), "{}", doc);
+    assert!(
+        doc.contains("\n## jobs[]\n\n- **name**: string\n"),
+        "{}",
+        doc
+    );
+
+    let html = cosy::describe(&schema, cosy::DescribeFormat::Html);
+    assert!(html.contains("<h2>server</h2>"), "{}", html);
+    assert!(html.contains("<code>&quot;debug&quot;</code>"), "{}", html);
+    let text = cosy::describe(&schema, cosy::DescribeFormat::PlainText);
+    assert!(
+        text.contains("\nserver\n  HTTP server settings\n  host: string - Address to bind\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn test_report_paths_quote_special_keys() {
+    let schema: Value = from_str(r#"{ "weird.key": "integer", list: ["string"] }"#).unwrap();
+    let instance: Value = from_str(r#"{ "weird.key": "x", list: ["a", 1] }"#).unwrap();
+    let report = schema::validate(&instance, &schema).unwrap();
+
+    let paths: Vec<_> = report.errors().map(|item| item.path.as_str()).collect();
+    assert_eq!(paths, vec![r#"$["weird.key"]"#, "$.list[1]"]);
+    for path in paths {
+        assert!(instance.pointer(path).is_some(), "{}", path);
+    }
+    assert_eq!(
+        instance.pointer(r#"$["weird.key"]"#),
+        Some(&Value::from("x"))
+    );
+    assert!(instance.pointer("$.weird.key").is_none());
+}
+
+#[test]
+fn test_value_path_round_trip() {
+    use cosy::schema::ValuePath;
+
+    let path = ValuePath::root()
+        .key("server")
+        .key(r#"say "hi"\now"#)
+        .index(2)
+        .key("");
+    let rendered = path.to_string();
+    assert_eq!(rendered, r#"$.server["say \"hi\"\\now"][2][""]"#);
+    assert_eq!(ValuePath::parse(&rendered), Some(path));
+    assert_eq!(
+        ValuePath::parse("server.port"),
+        ValuePath::parse("$.server.port")
+    );
+    assert_eq!(ValuePath::parse("$.list[x]"), None);
+    assert_eq!(ValuePath::parse(r#"$["open"#), None);
+}
+
+#[test]
+fn test_tuple_schema() {
+    let schema: Value = from_str(
+        r#"{
+        endpoint: { type: "tuple", items: ["string", "port"] }
+        tags: ["string"]
+    }"#,
+    )
+    .unwrap();
+    let valid: Value = from_str(r#"{ endpoint: ["localhost", 8080], tags: ["a", "b"] }"#).unwrap();
+    assert!(schema::validate(&valid, &schema).unwrap().is_empty());
+    assert!(Schema::compile(schema.clone()).is_ok());
+
+    // Each slot is checked against its own schema
+    let wrong_slot: Value = from_str(r#"{ endpoint: [8080, "localhost"], tags: [] }"#).unwrap();
+    let report = schema::validate(&wrong_slot, &schema).unwrap();
+    let paths: Vec<_> = report.errors().map(|item| item.path.as_str()).collect();
+    assert_eq!(paths, vec!["$.endpoint[0]", "$.endpoint[1]"]);
+
+    // The length must match exactly
+    let wrong_length: Value = from_str(r#"{ endpoint: ["localhost"], tags: [] }"#).unwrap();
+    let report = schema::validate(&wrong_length, &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].path, "$.endpoint");
+    assert_eq!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch {
+            expected: "tuple of 2 items".to_string(),
+            actual: "array of 1 items".to_string(),
+        }
+    );
+
+    // A tuple without `items` is a schema error
+    let bad: Value = from_str(r#"{ endpoint: { type: "tuple" } }"#).unwrap();
+    assert!(Schema::compile(bad.clone()).is_err());
+    assert!(schema::validate(&valid, &bad).is_err());
+}
+
+#[test]
+fn test_schema_errors_are_separate_from_instance_errors() {
+    // Instance problems are report items
+    let schema: Value = from_str(r#"{ port: "integer" }"#).unwrap();
+    let report = schema::validate(&from_str(r#"{ port: "x" }"#).unwrap(), &schema).unwrap();
+    assert_eq!(report.len(), 1);
+    assert!(matches!(
+        report[0].kind,
+        ValidationErrorKind::TypeMismatch { .. }
+    ));
+
+    // Schema problems are a `SchemaError`, whatever the instance
+    let bad: Value = from_str(r#"{ server: { port: { type: "integer", min: "low" } } }"#).unwrap();
+    let err: schema::SchemaError =
+        schema::validate(&from_str("{ server: { port: 1 } }").unwrap(), &bad).unwrap_err();
+    assert_eq!(err.schema_path, "$.server.port");
+    assert_eq!(err.message, "'min' must be a number");
+    assert_eq!(
+        err.to_string(),
+        "[Schema error at $.server.port] 'min' must be a number"
+    );
+
+    // `validate_permissive` reports it as a final item instead
+    let report = schema::validate_permissive(&from_str("{ server: { port: 1 } }").unwrap(), &bad);
+    assert!(matches!(
+        report.items.last().map(|item| &item.kind),
+        Some(ValidationErrorKind::InvalidSchema { .. })
+    ));
 }
@@ -314,6 +314,44 @@ fn test_serde_newtype_enum() {
     assert_eq!(original, deserialized);
 }
 
+#[test]
+fn test_serde_tuple_length_is_checked() {
+    let pair: (i32, i32) = serde_support::from_str("[1, 2]").unwrap();
+    assert_eq!(pair, (1, 2));
+
+    let err = serde_support::from_str::<(i32, i32, i32)>("[1, 2]").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected tuple of length 3, found 2"),
+        "{}",
+        err
+    );
+    let err = serde_support::from_str::<(i32,)>("[1, 2]").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected tuple of length 1, found 2"),
+        "{}",
+        err
+    );
+
+    // Fixed-size arrays and tuple structs too
+    let rgb: [u8; 3] = serde_support::from_str("[255, 128, 0]").unwrap();
+    assert_eq!(rgb, [255, 128, 0]);
+    assert!(serde_support::from_str::<[u8; 3]>("[255, 128]").is_err());
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point(f64, f64);
+    let point: Point = serde_support::from_str("[1.5, 2.5]").unwrap();
+    assert_eq!(point, Point(1.5, 2.5));
+    let err = serde_support::from_str::<Point>("[1.5, 2.5, 3.5]").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected tuple of length 2, found 3"),
+        "{}",
+        err
+    );
+}
+
 #[test]
 fn test_serde_enum_error_on_tuple_variant() {
     #[derive(Debug, Serialize)]
@@ -621,3 +659,57 @@ fn test_serde_error_message_helpful() {
         assert!(!msg.is_empty());
     }
 }
+
+// ============================================================================
+// UNUSED KEY TESTS
+// ============================================================================
+
+#[test]
+fn test_serde_checked_reports_unused_keys() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        #[serde(default)]
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        server: Server,
+    }
+
+    let cosy_text = r#"{
+        name: "app"
+        prt: 8080
+        server: {
+            host: "localhost"
+            prot: 9000
+        }
+    }"#;
+
+    let (config, unused): (Config, Vec<String>) =
+        serde_support::from_str_checked(cosy_text).unwrap();
+
+    assert_eq!(config.name, "app");
+    assert_eq!(config.server.port, 0);
+    assert_eq!(unused, vec!["prt".to_string(), "server.prot".to_string()]);
+}
+
+#[test]
+fn test_serde_checked_reports_unused_keys_in_arrays() {
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        _id: i32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        _items: Vec<Item>,
+    }
+
+    let cosy_text = r#"{ _items: [{ _id: 1 }, { _id: 2, extra: true }] }"#;
+    let (_, unused): (Config, Vec<String>) = serde_support::from_str_checked(cosy_text).unwrap();
+
+    assert_eq!(unused, vec!["_items[1].extra".to_string()]);
+}
@@ -0,0 +1,88 @@
+use cosy::template::{TemplateError, render, render_value};
+use cosy::{CosynError, Value, from_str};
+
+fn context() -> Value {
+    from_str(
+        r#"{
+            app: "cosy"
+            server: { host: "localhost", port: 8080, tls: false }
+            replicas: ["a", "b"]
+            "odd.key": "quoted"
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_render_nested_paths() {
+    let text = render(
+        "${app} on ${server.host}:${server.port} (tls: ${ server.tls }), first ${replicas[0]}",
+        &context(),
+    )
+    .unwrap();
+    assert_eq!(text, "cosy on localhost:8080 (tls: false), first a");
+    assert_eq!(render(r#"${$["odd.key"]}"#, &context()).unwrap(), "quoted");
+    assert_eq!(render("${replicas}", &context()).unwrap(), r#"["a", "b"]"#);
+}
+
+#[test]
+fn test_render_escapes_and_plain_text() {
+    assert_eq!(
+        render("no placeholders", &context()).unwrap(),
+        "no placeholders"
+    );
+    assert_eq!(
+        render("$${app} is ${app}", &context()).unwrap(),
+        "${app} is cosy"
+    );
+    assert_eq!(render("costs $5", &context()).unwrap(), "costs $5");
+}
+
+#[test]
+fn test_render_errors() {
+    assert_eq!(
+        render("${server.user}", &context()),
+        Err(TemplateError::UndefinedVariable {
+            name: "server.user".to_string(),
+            suggestion: None,
+        })
+    );
+    assert_eq!(
+        render("${}", &context()),
+        Err(TemplateError::UndefinedVariable {
+            name: String::new(),
+            suggestion: None,
+        })
+    );
+    let err = render("${server.prot}", &context()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Undefined template variable 'server.prot'; did you mean 'server.port'?"
+    );
+    assert_eq!(
+        render("ok ${app", &context()),
+        Err(TemplateError::Unterminated { offset: 3 })
+    );
+
+    let error: CosynError = render("${nope}", &context()).unwrap_err().into();
+    assert_eq!(
+        error.to_string(),
+        "Template error: Undefined template variable 'nope'"
+    );
+}
+
+#[test]
+fn test_render_value_tree() {
+    let template = from_str(
+        r#"{
+            url: "http://\${server.host}:\${server.port}"
+            names: [ "\${app}-\${replicas[1]}", 3 ]
+        }"#,
+    )
+    .unwrap();
+    let rendered = render_value(template, &context()).unwrap();
+    assert_eq!(
+        rendered,
+        from_str(r#"{ url: "http://localhost:8080", names: ["cosy-b", 3] }"#).unwrap()
+    );
+}
@@ -0,0 +1,112 @@
+use cosy::from_str;
+use cosy::value::{Value, ValueKind, filter_map, map, map_keys, map_strings};
+
+const NESTED: &str = r#"{
+    name: "  app  "
+    servers: [
+        { host: " alpha ", tags: [" a ", " b "] }
+        { host: " beta ", ports: [80, 443] }
+    ]
+    deep: { deeper: { deepest: [[" x "]] } }
+    enabled: true
+}"#;
+
+#[test]
+fn test_map_strings_reaches_every_leaf() {
+    let value = from_str(NESTED).unwrap();
+    let trimmed = map_strings(value, |s| s.trim().to_string());
+
+    let expected = from_str(
+        r#"{
+        name: "app"
+        servers: [
+            { host: "alpha", tags: ["a", "b"] }
+            { host: "beta", ports: [80, 443] }
+        ]
+        deep: { deeper: { deepest: [["x"]] } }
+        enabled: true
+    }"#,
+    )
+    .unwrap();
+    assert_eq!(trimmed, expected);
+}
+
+#[test]
+fn test_map_strings_with_function_path() {
+    let value = from_str(r#"{ a: "x", b: ["y", { c: "z" }] }"#).unwrap();
+    let upper = map_strings(value, str::to_uppercase);
+    assert_eq!(
+        upper,
+        from_str(r#"{ a: "X", b: ["Y", { c: "Z" }] }"#).unwrap()
+    );
+}
+
+#[test]
+fn test_apply_transform_is_post_order() {
+    let mut value = from_str(r#"{ a: [1, 2], b: { c: "s" } }"#).unwrap();
+    let mut visited = Vec::new();
+    value.apply_transform(&mut |node: &mut Value| visited.push(node.type_name()));
+
+    assert_eq!(
+        visited,
+        vec!["integer", "integer", "array", "string", "object", "object"]
+    );
+}
+
+#[test]
+fn test_apply_transform_mutates_in_place() {
+    let mut value = from_str("{ a: 1, b: [2, 3], c: { d: 4 } }").unwrap();
+    value.apply_transform(&mut |node: &mut Value| {
+        if let ValueKind::Integer(i) = &mut node.kind {
+            *i *= 10;
+        }
+    });
+    assert_eq!(
+        value,
+        from_str("{ a: 10, b: [20, 30], c: { d: 40 } }").unwrap()
+    );
+}
+
+#[test]
+fn test_map_integers_to_strings() {
+    let value = from_str(NESTED).unwrap();
+    let mapped = map(value, |v| match v.kind {
+        ValueKind::Integer(i) => Value::string(i.to_string()),
+        _ => v,
+    });
+    let ports = mapped.pointer("$.servers[1].ports").unwrap();
+    assert_eq!(*ports, from_str(r#"["80", "443"]"#).unwrap());
+    assert_eq!(mapped.pointer("$.enabled"), Some(&Value::boolean(true)));
+}
+
+#[test]
+fn test_filter_map_removes_nulls() {
+    let value = from_str(
+        r#"{
+        a: null
+        b: 1
+        list: [null, "x", null, { c: null, d: [null] }]
+    }"#,
+    )
+    .unwrap();
+    let filtered = filter_map(value, |v| (v.kind != ValueKind::Null).then_some(v));
+    assert_eq!(
+        filtered,
+        from_str(r#"{ b: 1, list: ["x", { d: [] }] }"#).unwrap()
+    );
+
+    // Only scalars are offered to `f`
+    let root = filter_map(Value::integer(1), |_| None);
+    assert_eq!(root, Value::null());
+}
+
+#[test]
+fn test_map_keys_to_uppercase() {
+    let value = from_str(NESTED).unwrap();
+    let upper = map_keys(value, str::to_uppercase);
+    assert!(upper.pointer("$.SERVERS[0].TAGS").is_some());
+    assert!(upper.pointer("$.DEEP.DEEPER.DEEPEST[0][0]").is_some());
+    assert!(upper.pointer("$.name").is_none());
+    // Values are untouched
+    assert_eq!(upper.pointer("$.NAME"), Some(&Value::from("  app  ")));
+}
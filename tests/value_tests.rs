@@ -0,0 +1,190 @@
+use cosy::value::{Value, ValueEntry, ValueEntryMut, ValueKind};
+use cosy::{SerializeOptions, from_str, to_string};
+
+#[test]
+fn test_len_of_containers() {
+    let empty = from_str("{}").unwrap();
+    assert_eq!(empty.len(), Some(0));
+    assert!(empty.is_empty());
+
+    let array = from_str("[1, 2, 3]").unwrap();
+    assert_eq!(array.len(), Some(3));
+    assert!(!array.is_empty());
+}
+
+#[test]
+fn test_len_of_strings_counts_characters() {
+    assert_eq!(Value::from("héllo").len(), Some(5));
+    assert!(Value::from("").is_empty());
+}
+
+#[test]
+fn test_len_of_scalars_is_none() {
+    assert_eq!(Value::integer(42).len(), None);
+    assert_eq!(Value::null().len(), None);
+    assert!(!Value::null().is_empty());
+}
+
+const CONFIG: &str = r#"{
+    // The service name
+    name: "api"
+    ports: [80, 443]
+    tls: { enabled: true, ratio: 1.0 }
+}"#;
+
+#[test]
+fn test_display_matches_to_string() {
+    let value = from_str(CONFIG).unwrap();
+    assert_eq!(format!("{}", value), to_string(&value));
+    assert_eq!(value.display().to_string(), to_string(&value));
+    assert!(format!("{}", value).contains("// The service name"));
+
+    // The kind alone stays on one line
+    assert_eq!(
+        value.kind.to_string(),
+        r#"{name: "api", ports: [80, 443], tls: {enabled: true, ratio: 1}}"#
+    );
+}
+
+#[test]
+fn test_display_with_options() {
+    let value = from_str(CONFIG).unwrap();
+    let options = SerializeOptions {
+        use_newlines: false,
+        comments: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        value.display_with(&options).to_string(),
+        cosy::to_string_with_options(&value, options.clone())
+    );
+}
+
+#[test]
+fn test_lower_hex_is_json() {
+    let value = from_str(CONFIG).unwrap();
+    assert_eq!(
+        format!("{:x}", value),
+        r#"{"name":"api","ports":[80,443],"tls":{"enabled":true,"ratio":1.0}}"#
+    );
+    let escaped = Value::from("say \"hi\"\n\u{1}");
+    assert_eq!(format!("{:x}", escaped), r#""say \"hi\"\n\u0001""#);
+    assert_eq!(format!("{:x}", Value::float(f64::NAN)), "null");
+}
+
+#[test]
+fn test_sort_keys_recursively() {
+    let mut value = from_str(
+        r#"{
+        zeta: 1
+        // about alpha
+        alpha: { c: 3, a: 1, b: [{ y: 2, x: 1 }, 3, 1] }
+        mid: "m"
+    }"#,
+    )
+    .unwrap();
+    value.sort_keys();
+
+    let keys = |value: &Value| match &value.kind {
+        ValueKind::Object(map) => map.keys().cloned().collect::<Vec<_>>(),
+        _ => panic!("Expected object"),
+    };
+    assert_eq!(keys(&value), ["alpha", "mid", "zeta"]);
+
+    let ValueKind::Object(root) = &value.kind else {
+        unreachable!()
+    };
+    let alpha = &root["alpha"];
+    assert_eq!(keys(alpha), ["a", "b", "c"]);
+    assert!(alpha.line_comments().any(|c| c == "about alpha"));
+
+    // Arrays keep their order; objects inside them are sorted
+    let ValueKind::Object(alpha_map) = &alpha.kind else {
+        unreachable!()
+    };
+    let ValueKind::Array(items) = &alpha_map["b"].kind else {
+        panic!("Expected array")
+    };
+    assert_eq!(keys(&items[0]), ["x", "y"]);
+    assert_eq!(items[1..], [Value::integer(3), Value::integer(1)]);
+}
+
+#[test]
+fn test_iter_arrays_and_objects() {
+    let array = from_str("[10, \"a\"]").unwrap();
+    assert_eq!(
+        array.iter().collect::<Vec<_>>(),
+        [
+            ValueEntry::Index(0, &Value::integer(10)),
+            ValueEntry::Index(1, &Value::from("a")),
+        ]
+    );
+
+    let object = from_str("{ b: 1, a: [true] }").unwrap();
+    let nested = from_str("[true]").unwrap();
+    assert_eq!(
+        object.iter().collect::<Vec<_>>(),
+        [
+            ValueEntry::Key("b", &Value::integer(1)),
+            ValueEntry::Key("a", &nested),
+        ]
+    );
+
+    // Nested containers are yielded whole and can be iterated in turn
+    let ValueEntry::Key(_, inner) = object.iter().nth(1).unwrap() else {
+        panic!("Expected key entry")
+    };
+    assert_eq!(
+        inner.iter().collect::<Vec<_>>(),
+        [ValueEntry::Index(0, &Value::boolean(true))]
+    );
+
+    for empty in ["[]", "{}", "null", "42", "\"text\""] {
+        assert_eq!(from_str(empty).unwrap().iter().count(), 0, "{}", empty);
+    }
+}
+
+#[test]
+fn test_iter_mut() {
+    let mut value = from_str("{ a: 1, b: [2, 3] }").unwrap();
+    for entry in value.iter_mut() {
+        match entry {
+            ValueEntryMut::Key("a", v) => *v = Value::integer(10),
+            ValueEntryMut::Key(_, v) => {
+                for item in v.iter_mut() {
+                    if let ValueEntryMut::Index(i, v) = item {
+                        *v = Value::integer(i as i64);
+                    }
+                }
+            }
+            ValueEntryMut::Index(..) => panic!("Expected key entries"),
+        }
+    }
+    assert_eq!(value, from_str("{ a: 10, b: [0, 1] }").unwrap());
+    assert_eq!(Value::null().iter_mut().count(), 0);
+}
+
+#[test]
+fn test_constructors_accept_str() {
+    assert_eq!(Value::string("cosy"), Value::string(String::from("cosy")));
+    assert_eq!(Value::string("cosy").kind, ValueKind::String("cosy".into()));
+
+    let server = Value::from_iter([
+        ("host", Value::string("localhost")),
+        ("port", Value::integer(8080)),
+    ]);
+    assert_eq!(
+        server,
+        from_str(r#"{ host: "localhost", port: 8080 }"#).unwrap()
+    );
+
+    // Keys keep their order and may be owned
+    let keys: Value = ["b", "a"]
+        .into_iter()
+        .map(|k| (k.to_string(), Value::null()))
+        .collect();
+    let ValueKind::Object(map) = &keys.kind else {
+        panic!("expected an object");
+    };
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["b", "a"]);
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "yaml-compat")]
+
+use cosy::convert::{from_yaml_str, to_yaml_string};
+use cosy::from_str;
+
+const DOCUMENT: &str = r#"{
+    // comments are dropped
+    name: "service"
+    port: 8080
+    ratio: 0.75
+    debug: false
+    owner: null
+    tags: ["a", "b"]
+    database: { host: "db.local", replicas: [1, 2] }
+}"#;
+
+#[test]
+fn test_to_yaml_values_match() {
+    let value = from_str(DOCUMENT).unwrap();
+    let yaml = to_yaml_string(&value).unwrap();
+    assert!(!yaml.contains("comments"));
+
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(parsed["name"], serde_yaml::Value::from("service"));
+    assert_eq!(parsed["port"].as_i64(), Some(8080));
+    assert_eq!(parsed["ratio"].as_f64(), Some(0.75));
+    assert_eq!(parsed["debug"], serde_yaml::Value::Bool(false));
+    assert!(parsed["owner"].is_null());
+    assert_eq!(parsed["tags"][1], serde_yaml::Value::from("b"));
+    assert_eq!(
+        parsed["database"]["host"],
+        serde_yaml::Value::from("db.local")
+    );
+    assert_eq!(parsed["database"]["replicas"][0].as_i64(), Some(1));
+}
+
+#[test]
+fn test_yaml_round_trip() {
+    let mut value = from_str(DOCUMENT).unwrap();
+    let back = from_yaml_str(&to_yaml_string(&value).unwrap()).unwrap();
+
+    value.apply_transform(&mut |v: &mut cosy::Value| v.comments.clear());
+    assert_eq!(back, value);
+}
+
+#[test]
+fn test_from_yaml_ignores_tags() {
+    let value = from_yaml_str("count: !custom 5\n1: one\nitems: !list [true]\n").unwrap();
+    assert_eq!(
+        value,
+        from_str(r#"{ count: 5, "1": "one", items: [true] }"#).unwrap()
+    );
+}
+
+#[test]
+fn test_from_yaml_error_position() {
+    let err = from_yaml_str("a: 1\nb: [1, 2\n").unwrap_err();
+    assert!(err.line() > 0);
+}